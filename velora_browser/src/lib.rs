@@ -0,0 +1,238 @@
+//! Page loading pipeline of the Velora browser
+//! 
+//! Turns HTML into laid out boxes and paint commands without a window, so the
+//! browser, benchmarks and tests share one path from markup to rects.
+
+use std::collections::{HashMap, HashSet};
+use velora_core::{Color, CssUnit, CssValue, NodeId, Overflow, Position, Rect, Size, VeloraResult};
+use velora_parser::{cascade, CssParser, CssRule, HtmlParser, StyleEngine};
+use velora_layout::{BoxModel, BoxOffsets, LayoutNode, LayoutTree, SpecifiedLengths};
+use velora_dom::{Document, StylesheetRef, ViewportConfig};
+use velora_paint::{DisplayList, DisplayListBuilder, ImageRenderer};
+
+/// Elements that never generate boxes, so their subtrees are left out of layout
+const NON_RENDERED_TAGS: &[&str] = &["base", "head", "link", "meta", "script", "style", "template", "title"];
+
+/// Background color of a laid out page: the body's computed `background-color`, or None if it is unset or transparent
+pub fn page_background(document: &Document, layout: &PageLayout) -> Option<Color> {
+    let body = document.body()?;
+    match layout.properties.get(&body.id)?.get("background-color") {
+        Some(CssValue::Color(color)) if color.a > 0 => Some(*color),
+        _ => None,
+    }
+}
+
+/// Parse, style and lay out an HTML document, returning the rect of every node
+///
+/// `viewport` is the window size; a viewport meta tag may ask for a different layout viewport.
+/// Images are sized from `images`, with a placeholder box for any not loaded there. Only the
+/// document's `<style>` elements apply, since linked stylesheets are not fetched.
+pub fn render_pipeline(html: &str, viewport: Size, images: &ImageRenderer) -> VeloraResult<HashMap<NodeId, Rect>> {
+    let document = HtmlParser::new().parse_html(html)?;
+    let parser = CssParser::new();
+    let rules: Vec<CssRule> = document.collect_stylesheets()
+        .into_iter()
+        .filter_map(|stylesheet| match stylesheet {
+            StylesheetRef::Inline(css) => parser.parse_css(&css).ok(),
+            StylesheetRef::Linked(_) => None,
+        })
+        .flatten()
+        .collect();
+    layout_document(&document, &rules, viewport, images)
+}
+
+/// Style and lay out a parsed document, returning the rect of every node
+///
+/// `rules` are the page's stylesheet rules; the user agent stylesheet applies beneath them.
+/// The page is laid out as a desktop browser would.
+pub fn layout_document(document: &Document, rules: &[CssRule], viewport: Size, images: &ImageRenderer) -> VeloraResult<HashMap<NodeId, Rect>> {
+    Ok(layout_page(document, rules, viewport, false, images)?.rects)
+}
+
+/// A styled and laid out document
+pub struct PageLayout {
+    /// Layout nodes of every box on the page
+    pub tree: LayoutTree,
+    /// Rect of every node in the layout tree
+    pub rects: HashMap<NodeId, Rect>,
+    /// Computed properties of every node reachable from the root
+    pub properties: HashMap<NodeId, HashMap<String, CssValue>>,
+}
+
+/// Paint commands for a laid out page: each box with a background color, in stacking order
+pub fn page_display_list(layout: &PageLayout) -> DisplayList {
+    let mut builder = DisplayListBuilder::new();
+    for node_id in layout.rects.keys() {
+        if let Some(CssValue::Color(color)) = layout.properties.get(node_id).and_then(|properties| properties.get("background-color")) {
+            if color.a > 0 {
+                builder.set_background(*node_id, *color);
+            }
+        }
+    }
+    builder.build(&layout.tree, &layout.rects)
+}
+
+/// Style and lay out a parsed document, keeping the layout tree and computed properties
+///
+/// A `mobile` browser lays out pages without a viewport meta tag wider than the window.
+pub fn layout_page(document: &Document, rules: &[CssRule], viewport: Size, mobile: bool, images: &ImageRenderer) -> VeloraResult<PageLayout> {
+    let viewport = document.viewport_config()
+        .unwrap_or_else(|| ViewportConfig::fallback(mobile))
+        .layout_size(viewport);
+    let engine = StyleEngine::new(viewport);
+    let mut cascade_rules = cascade::user_agent_rules();
+    cascade_rules.extend(rules.iter().cloned());
+    let properties = engine.computed_properties_all(document, &cascade_rules);
+    
+    // Rendered elements and text reached by the style pass take part in layout; comments
+    // and anything under `display: none` do not
+    let tree = document.get_dom_tree();
+    let mut boxes = HashSet::new();
+    tree.traverse_dfs(|node| {
+        let hidden = properties.get(&node.id)
+            .and_then(|properties| properties.get("display"))
+            .is_some_and(|display| *display == CssValue::Keyword("none".to_string()));
+        let rendered = !hidden && (node.is_text()
+            || (node.is_element() && !NON_RENDERED_TAGS.contains(&node.node_name.as_str())));
+        if rendered && properties.contains_key(&node.id) {
+            boxes.insert(node.id);
+        }
+        Ok(rendered)
+    })?;
+    let in_layout = |node_id: &NodeId| boxes.contains(node_id);
+    
+    let mut layout_tree = LayoutTree::new();
+    if let Some(root) = tree.get_root() {
+        layout_tree.set_root(root.id);
+    }
+    for node_id in properties.keys().copied().filter(in_layout) {
+        let node = tree.get_node(node_id)?;
+        // Percentages stay unresolved until the containing block is known
+        let mut lengths = match (node.is_element(), properties.get(&node_id)) {
+            (true, Some(properties)) => SpecifiedLengths::from_properties(properties),
+            _ => SpecifiedLengths::default(),
+        };
+        
+        // Images keep their natural aspect ratio; `width` and `height` attributes are pixel sizes
+        let element = node.element_id.and_then(|element_id| tree.get_element(element_id).ok());
+        let intrinsic_size = match element {
+            Some(element) if node.node_name.eq_ignore_ascii_case("img") => {
+                for (name, length) in [("width", &mut lengths.width), ("height", &mut lengths.height)] {
+                    if length.is_none() {
+                        *length = element.get_attribute(name)
+                            .and_then(|value| value.trim().parse::<f32>().ok())
+                            .map(|pixels| CssValue::Length(pixels, CssUnit::Px));
+                    }
+                }
+                let src = element.get_attribute("src").unwrap_or_default();
+                let src = document.resolve_url(src).unwrap_or_else(|| src.to_string());
+                Some(images.intrinsic_size(&src))
+            }
+            _ => None,
+        };
+        layout_tree.add_node(LayoutNode {
+            node_id,
+            box_model: BoxModel::new(Rect::zero()),
+            flexbox: None,
+            grid: None,
+            parent_id: node.parent_id,
+            child_ids: node.child_ids.iter().copied().filter(in_layout).collect(),
+            position: Position::Static,
+            offsets: BoxOffsets::default(),
+            z_index: None,
+            overflow: Overflow::Visible,
+            lengths,
+            intrinsic_size,
+        })?;
+    }
+    
+    let rects = layout_tree.calculate_layout(viewport)?;
+    Ok(PageLayout { tree: layout_tree, rects, properties })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use velora_dom::ViewportWidth;
+    use velora_paint::{DecodedImage, PixelBuffer};
+    
+    #[test]
+    fn test_render_pipeline_sizes_root_to_viewport() {
+        let viewport = Size::new(800.0, 600.0);
+        let rects = render_pipeline("", viewport, &ImageRenderer::new().unwrap()).unwrap();
+        
+        // The implied html and body get boxes; head does not
+        let document = HtmlParser::new().parse_html("").unwrap();
+        let root = document.get_dom_tree().get_root().unwrap().id;
+        assert_eq!(rects.len(), 2);
+        assert_eq!(rects[&root], Rect::new(0.0, 0.0, 800.0, 600.0));
+        assert!(!rects.contains_key(&document.head().unwrap().id));
+    }
+    
+    #[test]
+    fn test_render_pipeline_applies_viewport_meta() {
+        let html = "<!DOCTYPE html><html><head><meta name=\"viewport\" content=\"width=device-width,initial-scale=1\"></head><body></body></html>";
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        let config = document.viewport_config().unwrap();
+        assert_eq!(config, ViewportConfig { width: ViewportWidth::DeviceWidth, initial_scale: 1.0 });
+        
+        // device-width follows the window, shrunk by the initial scale
+        let viewport = Size::new(800.0, 600.0);
+        let root = document.get_dom_tree().get_root().unwrap().id;
+        let images = ImageRenderer::new().unwrap();
+        assert_eq!(render_pipeline(html, viewport, &images).unwrap()[&root], Rect::new(0.0, 0.0, 800.0, 600.0));
+        
+        let zoomed = html.replace("initial-scale=1", "initial-scale=2");
+        assert_eq!(render_pipeline(&zoomed, viewport, &images).unwrap()[&root], Rect::new(0.0, 0.0, 400.0, 300.0));
+    }
+    
+    #[test]
+    fn test_render_pipeline_resolves_percentages() {
+        let html = r#"<div style="width: 400px"><p style="width: 50%; padding: 10%">Hi</p></div>"#;
+        let rects = render_pipeline(html, Size::new(800.0, 600.0), &ImageRenderer::new().unwrap()).unwrap();
+        
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        let paragraph = document.get_elements_by_tag_name("p")[0].id;
+        assert_eq!(rects[&paragraph], Rect::new(40.0, 40.0, 200.0, rects[&paragraph].height));
+    }
+    
+    #[test]
+    fn test_render_pipeline_skips_display_none() {
+        let html = r#"<style>.hidden { display: none }</style><div class="hidden"><p>Gone</p></div><p>Shown</p>"#;
+        let rects = render_pipeline(html, Size::new(800.0, 600.0), &ImageRenderer::new().unwrap()).unwrap();
+        
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        let paragraphs = document.get_elements_by_tag_name("p");
+        assert!(!rects.contains_key(&document.get_elements_by_tag_name("div")[0].id));
+        assert!(!rects.contains_key(&paragraphs[0].id));
+        assert!(rects.contains_key(&paragraphs[1].id));
+    }
+    
+    #[test]
+    fn test_render_pipeline_applies_style_elements() {
+        let html = r#"<style>.box { width: 400px } .box p { width: 50%; padding: 10% }</style><div class="box"><p>Hi</p></div>"#;
+        let rects = render_pipeline(html, Size::new(800.0, 600.0), &ImageRenderer::new().unwrap()).unwrap();
+        
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        let paragraph = document.get_elements_by_tag_name("p")[0].id;
+        assert_eq!(rects[&paragraph], Rect::new(40.0, 40.0, 200.0, rects[&paragraph].height));
+    }
+    
+    #[test]
+    fn test_render_pipeline_keeps_image_aspect_ratio() {
+        let mut images = ImageRenderer::new().unwrap();
+        let data = PixelBuffer::new(200, 100).encode_png().unwrap();
+        let decoded = DecodedImage::decode(&data).unwrap();
+        images.insert_image("https://example.com/a.png", data, decoded);
+        images.set_placeholder_size(Size::new(40.0, 20.0));
+        
+        let html = r#"<img src="https://example.com/a.png" style="width: 100px"><img src="missing.png" height="12">"#;
+        let rects = render_pipeline(html, Size::new(800.0, 600.0), &images).unwrap();
+        
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        let sizes: Vec<(f32, f32)> = document.get_elements_by_tag_name("img").iter()
+            .map(|image| (rects[&image.id].width, rects[&image.id].height))
+            .collect();
+        assert_eq!(sizes, vec![(100.0, 50.0), (24.0, 12.0)]);
+    }
+}
@@ -2,36 +2,862 @@ use eframe::egui;
 use tokio::runtime::Runtime;
 use anyhow::Result;
 use log::{info, error};
-use std::sync::mpsc;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::Notify;
 
 // Velora engine imports
-use velora_parser::HtmlParser;
-use velora_dom::{Document, Node, NodeType};
-use velora_net::HttpClient;
+use velora_core::{Color, CssValue, Event, EventType, NodeId, Rect, Size, VeloraError, VeloraResult};
+use velora_parser::{cascade, CssParser, CssRule, HtmlParser, StyleEngine};
+use velora_dom::{Document, InteractionState, Node, NodeType, StylesheetRef};
+use velora_net::{DownloadEvent, HttpClient, PendingResponse, ResourceKind, ResourceLoader, UserAgentPreset};
+use velora_net::client::HttpClientConfig;
+use velora_paint::{DecodedImage, ImageRenderer, PixelBuffer, Renderer};
+use velora_jsrt::JsRuntime;
+use velora_browser::{layout_page, page_background, page_display_list, render_pipeline};
+
+/// Smallest allowed page zoom factor
+const MIN_ZOOM: f32 = 0.25;
+
+/// Largest allowed page zoom factor
+const MAX_ZOOM: f32 = 5.0;
+
+/// Zoom change applied by a single Ctrl+Plus/Ctrl+Minus press
+const ZOOM_STEP: f32 = 0.1;
+
+/// Distance the page scrolls for an arrow key press
+const SCROLL_STEP: f32 = 40.0;
+
+/// Part of the old screenful kept in view when scrolling by a page
+const PAGE_SCROLL_OVERLAP: f32 = 40.0;
+
+/// Longest a page load may take before it is abandoned
+const NAVIGATION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest wait for one linked stylesheet before the page is shown without it
+const STYLESHEET_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often the UI checks for finished loads while a tab is loading
+const LOADING_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Default number of back/forward entries a tab keeps
+const DEFAULT_MAX_HISTORY: usize = 50;
+
+/// File name used when neither the response nor the URL suggests one
+const DEFAULT_DOWNLOAD_NAME: &str = "download";
+
+/// Characters of a text node shown in a DOM dump before it is cut off
+const DUMP_TEXT_LIMIT: usize = 40;
+
+/// Initial window size, also used for headless screenshots
+const DEFAULT_WINDOW_SIZE: [f32; 2] = [1200.0, 800.0];
+
+/// Edge length of a tab favicon in pixels
+const FAVICON_SIZE: u32 = 16;
+
+/// Glyph shown in place of a favicon that is missing or failed to load
+const DEFAULT_FAVICON_GLYPH: &str = "🌐";
+
+/// Most history suggestions shown under the URL field
+const MAX_URL_SUGGESTIONS: usize = 8;
+
+/// Tallest the network panel's request list grows before it scrolls
+const NETWORK_PANEL_HEIGHT: f32 = 200.0;
+
+/// Opacity of the UI panels when the window background is transparent
+const TRANSPARENT_PANEL_OPACITY: f32 = 0.85;
+
+/// Whether the platform can blur what is behind a transparent window
+const BLUR_SUPPORTED: bool = cfg!(any(target_os = "macos", target_os = "windows"));
+
+/// Native window options chosen at startup
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct WindowConfig {
+    /// Let the desktop show through the window background
+    transparent: bool,
+    /// Ask the system to blur what shows through; only used with `transparent`
+    blur: bool,
+}
+
+impl WindowConfig {
+    /// Make the window background transparent
+    fn with_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+        self
+    }
+    
+    /// Request a blurred (vibrancy) background behind a transparent window
+    fn with_blur(mut self, blur: bool) -> Self {
+        self.blur = blur;
+        self
+    }
+    
+    /// Whether blur applies to this window on this platform
+    ///
+    /// eframe does not forward winit's blur attribute yet, so this is only a hint
+    /// until the viewport builder grows one.
+    fn blur_enabled(&self) -> bool {
+        self.transparent && self.blur && BLUR_SUPPORTED
+    }
+    
+    /// Build the viewport for the main window
+    ///
+    /// A transparent viewport makes eframe pick an alpha-capable surface format.
+    fn viewport(&self) -> egui::ViewportBuilder {
+        egui::ViewportBuilder::default()
+            .with_inner_size(DEFAULT_WINDOW_SIZE)
+            .with_min_inner_size([800.0, 600.0])
+            .with_transparent(self.transparent)
+    }
+    
+    /// Color the surface is cleared to before the UI is drawn
+    fn clear_color(&self, visuals: &egui::Visuals) -> [f32; 4] {
+        if self.transparent {
+            egui::Rgba::TRANSPARENT.to_array()
+        } else {
+            visuals.panel_fill.to_normalized_gamma_f32()
+        }
+    }
+}
+
+/// The monitor the window is on
+///
+/// eframe only reports the window's current monitor, not the full list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MonitorInfo {
+    /// Monitor size in logical points
+    size: egui::Vec2,
+    /// Physical pixels per logical point
+    scale_factor: f32,
+}
+
+impl MonitorInfo {
+    /// Read the current monitor from the viewport state, if the platform reports it
+    fn from_viewport(info: &egui::ViewportInfo) -> Option<Self> {
+        Some(Self {
+            size: info.monitor_size?,
+            scale_factor: info.native_pixels_per_point?,
+        })
+    }
+    
+    /// Monitor size in physical pixels
+    fn physical_size(&self) -> [u32; 2] {
+        let size = self.size * self.scale_factor;
+        [size.x.round() as u32, size.y.round() as u32]
+    }
+}
+
+/// A change in the native window's visibility
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WindowEvent {
+    /// The window was minimized
+    Minimized,
+    /// The window came back from being minimized
+    Restored,
+}
+
+/// Turn the viewport state egui reports each frame into a window event
+///
+/// `was_minimized` is the state seen on the previous frame. Platforms that cannot
+/// report minimization leave `minimized` unset, which never produces an event.
+fn convert_viewport_event(was_minimized: bool, info: &egui::ViewportInfo) -> Option<WindowEvent> {
+    match info.minimized {
+        Some(true) if !was_minimized => Some(WindowEvent::Minimized),
+        Some(false) if was_minimized => Some(WindowEvent::Restored),
+        _ => None,
+    }
+}
+
+/// Storage behind the browser's clipboard
+trait ClipboardBackend {
+    /// Read the clipboard text, if there is any
+    fn get_text(&mut self) -> Option<String>;
+    
+    /// Replace the clipboard text, returning false if the clipboard refused it
+    fn set_text(&mut self, text: &str) -> bool;
+}
+
+impl ClipboardBackend for arboard::Clipboard {
+    fn get_text(&mut self) -> Option<String> {
+        arboard::Clipboard::get_text(self).ok()
+    }
+    
+    fn set_text(&mut self, text: &str) -> bool {
+        arboard::Clipboard::set_text(self, text).is_ok()
+    }
+}
+
+/// A clipboard shortcut aimed at a text field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClipboardCommand {
+    Copy,
+    Cut,
+    Paste,
+}
+
+impl ClipboardCommand {
+    /// Get the command egui produced for Ctrl+C, Ctrl+X or Ctrl+V
+    fn from_event(event: &egui::Event) -> Option<Self> {
+        match event {
+            egui::Event::Copy => Some(Self::Copy),
+            egui::Event::Cut => Some(Self::Cut),
+            egui::Event::Paste(_) => Some(Self::Paste),
+            _ => None,
+        }
+    }
+}
+
+/// Clipboard shared by the browser's text fields
+///
+/// Copied text goes to the system clipboard and is also kept in an internal buffer,
+/// which stands in for the system clipboard when it is unavailable.
+#[derive(Default)]
+struct Clipboard {
+    backend: Option<Box<dyn ClipboardBackend>>,
+    buffer: String,
+}
+
+impl Clipboard {
+    /// Use the system clipboard, falling back to the internal buffer alone
+    fn system() -> Self {
+        match arboard::Clipboard::new() {
+            Ok(clipboard) => Self::with_backend(Box::new(clipboard)),
+            Err(e) => {
+                info!("System clipboard unavailable, using an internal one: {}", e);
+                Self::default()
+            }
+        }
+    }
+    
+    /// Use the given clipboard storage
+    fn with_backend(backend: Box<dyn ClipboardBackend>) -> Self {
+        Self {
+            backend: Some(backend),
+            buffer: String::new(),
+        }
+    }
+    
+    /// Put text on the clipboard
+    fn copy(&mut self, text: &str) {
+        self.buffer = text.to_string();
+        if let Some(backend) = &mut self.backend {
+            if !backend.set_text(text) {
+                error!("Failed to write to the system clipboard");
+            }
+        }
+    }
+    
+    /// Read the clipboard text
+    fn paste(&mut self) -> String {
+        self.backend
+            .as_mut()
+            .and_then(|backend| backend.get_text())
+            .unwrap_or_else(|| self.buffer.clone())
+    }
+    
+    /// Apply a clipboard command to a single-line field, returning the new selection
+    ///
+    /// Selections are char ranges, as egui's text cursors use. Copy and cut with
+    /// nothing selected leave the clipboard alone; pasted line breaks are dropped.
+    fn apply(&mut self, command: ClipboardCommand, text: &mut String, selection: Range<usize>) -> Range<usize> {
+        let len = text.chars().count();
+        let selection = selection.start.min(len)..selection.end.min(len);
+        let bytes = byte_offset(text, selection.start)..byte_offset(text, selection.end);
+        
+        match command {
+            ClipboardCommand::Copy | ClipboardCommand::Cut if selection.is_empty() => selection,
+            ClipboardCommand::Copy => {
+                self.copy(&text[bytes]);
+                selection
+            }
+            ClipboardCommand::Cut => {
+                self.copy(&text[bytes.clone()]);
+                text.replace_range(bytes, "");
+                selection.start..selection.start
+            }
+            ClipboardCommand::Paste => {
+                let pasted: String = self.paste().chars().filter(|c| !matches!(c, '\n' | '\r')).collect();
+                text.replace_range(bytes, &pasted);
+                let cursor = selection.start + pasted.chars().count();
+                cursor..cursor
+            }
+        }
+    }
+}
+
+/// Byte offset of a char index, or the end of the text if it is past the last char
+fn byte_offset(text: &str, char_index: usize) -> usize {
+    text.char_indices().nth(char_index).map_or(text.len(), |(offset, _)| offset)
+}
+
+/// Widget ID of the URL field
+fn url_input_id() -> egui::Id {
+    egui::Id::new("url_input")
+}
+
+/// Select the whole URL field text, so typing or pasting replaces it
+fn select_url_text(ctx: &egui::Context, url: &str) {
+    let id = url_input_id();
+    let mut state = egui::TextEdit::load_state(ctx, id).unwrap_or_default();
+    state.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+        egui::text::CCursor::new(0),
+        egui::text::CCursor::new(url.chars().count()),
+    )));
+    state.store(ctx, id);
+}
+
+/// How often and how recently a URL was visited
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct UrlVisits {
+    count: u32,
+    /// Position of the latest visit in the order of all visits
+    last_visit: u64,
+}
+
+/// URLs visited in any tab, used to complete what is typed in the URL field
+#[derive(Debug, Default)]
+struct UrlHistory {
+    entries: HashMap<String, UrlVisits>,
+    visits: u64,
+}
+
+impl UrlHistory {
+    /// Record a visit to a URL
+    fn record_visit(&mut self, url: &str) {
+        self.visits += 1;
+        let entry = self.entries.entry(url.to_string()).or_insert(UrlVisits { count: 0, last_visit: 0 });
+        entry.count += 1;
+        entry.last_visit = self.visits;
+    }
+    
+    /// Visited URLs starting with what was typed, most visited first, then most recent
+    ///
+    /// The scheme and a leading `www.` may be left out, and case is ignored.
+    fn suggest(&self, prefix: &str) -> Vec<String> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+        
+        let mut matches: Vec<(&String, &UrlVisits)> = self.entries.iter()
+            .filter(|(url, _)| {
+                let url = url.to_lowercase();
+                url.starts_with(&prefix) || url_without_scheme(&url).starts_with(&prefix)
+            })
+            .collect();
+        matches.sort_by(|(_, a), (_, b)| b.count.cmp(&a.count).then(b.last_visit.cmp(&a.last_visit)));
+        matches.into_iter().take(MAX_URL_SUGGESTIONS).map(|(url, _)| url.clone()).collect()
+    }
+}
+
+/// A page visit in the global history
+#[derive(Debug, Clone, PartialEq)]
+struct HistoryEntry {
+    url: String,
+    title: String,
+    timestamp: SystemTime,
+}
+
+/// Every navigation in any tab, oldest first
+///
+/// Unlike a tab's own history this is never truncated by going back, so it can be browsed
+/// and searched as a log.
+#[derive(Debug, Default)]
+struct NavigationHistory {
+    entries: Vec<HistoryEntry>,
+}
+
+impl NavigationHistory {
+    /// Log a navigation; the URL stands in for the title until the page loads
+    fn record(&mut self, url: &str) {
+        self.entries.push(HistoryEntry {
+            url: url.to_string(),
+            title: url.to_string(),
+            timestamp: SystemTime::now(),
+        });
+    }
+    
+    /// Give the latest visit to a URL the title of the page that loaded
+    fn set_title(&mut self, url: &str, title: &str) {
+        if let Some(entry) = self.entries.iter_mut().rev().find(|entry| entry.url == url) {
+            entry.title = title.to_string();
+        }
+    }
+    
+    /// Get the logged visits, oldest first
+    fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+    
+    /// Forget every logged visit
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+    
+    /// Visits whose URL or title contains the query, ignoring case
+    fn search(&self, query: &str) -> Vec<&HistoryEntry> {
+        let query = query.trim().to_lowercase();
+        self.entries.iter()
+            .filter(|entry| entry.url.to_lowercase().contains(&query) || entry.title.to_lowercase().contains(&query))
+            .collect()
+    }
+}
+
+/// How long ago a moment was, roughly, e.g. "5 minutes ago"
+fn time_ago(timestamp: SystemTime) -> String {
+    let seconds = timestamp.elapsed().unwrap_or_default().as_secs();
+    match seconds {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{} minutes ago", seconds / 60),
+        3600..86400 => format!("{} hours ago", seconds / 3600),
+        _ => format!("{} days ago", seconds / 86400),
+    }
+}
+
+/// A URL without its scheme and `www.`, as people usually type it
+fn url_without_scheme(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.strip_prefix("www.").unwrap_or(rest)
+}
 
 #[derive(Clone)]
 struct Tab {
-    #[allow(dead_code)]
     id: usize,
     title: String,
     url: String,
     content: Option<String>,
     dom: Option<Document>,
     loading: bool,
+    history: Vec<String>,
+    history_index: usize,
+    /// Most entries kept in `history`; the oldest are dropped first
+    max_history: usize,
+    zoom: f32,
+    favicon: Option<DecodedImage>,
+    favicon_texture: Option<egui::TextureHandle>,
+    cancel_token: Option<CancellationToken>,
+    /// URL of the page currently shown, restored when a load is stopped
+    committed_url: Option<String>,
+    /// Hover, focus and active state of the page's nodes
+    interaction: InteractionState,
+    /// Response that could not be displayed and can be saved instead
+    download_offer: Option<DownloadOffer>,
+    /// Rules from the page's `<style>` elements and linked stylesheets
+    style_rules: Vec<CssRule>,
+    /// User-Agent sent for this tab's navigations instead of the default
+    user_agent: Option<UserAgentPreset>,
+    /// How far the page is scrolled down, in pixels
+    scroll_offset: f32,
+    /// Where the page's images are laid out, and which are being fetched
+    lazy_images: LazyImages,
+    /// Viewport the page was last laid out for, in page pixels
+    layout_size: Option<Size>,
+    /// Height of the laid out page, in page pixels
+    page_height: f32,
+    /// Body background of the laid out page, if it sets an opaque one
+    page_background: Option<Color>,
+    /// Textures of the page's images currently on screen, by source URL
+    image_textures: HashMap<String, egui::TextureHandle>,
+}
+
+impl Tab {
+    /// Set the page zoom, clamped to the supported range
+    fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+    
+    /// Record a navigation, discarding any forward history
+    fn navigate_to(&mut self, url: String) {
+        if !self.history.is_empty() {
+            self.history.truncate(self.history_index + 1);
+        }
+        self.history.push(url.clone());
+        self.history_index = self.history.len() - 1;
+        self.url = url;
+        self.trim_history();
+    }
+    
+    /// Change how many history entries the tab keeps, trimming the oldest if needed
+    fn set_max_history(&mut self, max_history: usize) {
+        self.max_history = max_history.max(1);
+        self.trim_history();
+    }
+    
+    /// Drop entries beyond the cap, oldest first and then the furthest forward, never the current page
+    fn trim_history(&mut self) {
+        let excess = self.history.len().saturating_sub(self.max_history);
+        let behind = excess.min(self.history_index);
+        self.history.drain(..behind);
+        self.history_index -= behind;
+        self.history.truncate(self.max_history);
+    }
+    
+    /// Whether the tab poses as a phone, which changes how pages without a viewport meta tag are laid out
+    fn is_mobile(&self) -> bool {
+        self.user_agent.is_some_and(|preset| preset.is_mobile())
+    }
+    
+    /// Size the page is laid out at for a viewport, in page pixels before zoom
+    fn layout_viewport(&self, viewport: Size) -> Size {
+        Size::new(viewport.width / self.zoom, viewport.height / self.zoom)
+    }
+    
+    /// Part of the page shown in a viewport at the current scroll offset, in page pixels
+    fn visible_rect(&self, viewport: Size) -> Rect {
+        let size = self.layout_viewport(viewport);
+        Rect::new(0.0, self.scroll_offset / self.zoom, size.width, size.height)
+    }
+    
+    fn can_go_back(&self) -> bool {
+        self.history_index > 0
+    }
+    
+    fn can_go_forward(&self) -> bool {
+        self.history_index + 1 < self.history.len()
+    }
+    
+    /// Step back in history, returning the URL to load
+    fn go_back(&mut self) -> Option<String> {
+        if !self.can_go_back() {
+            return None;
+        }
+        self.history_index -= 1;
+        self.url = self.history[self.history_index].clone();
+        Some(self.url.clone())
+    }
+    
+    /// Step forward in history, returning the URL to load
+    fn go_forward(&mut self) -> Option<String> {
+        if !self.can_go_forward() {
+            return None;
+        }
+        self.history_index += 1;
+        self.url = self.history[self.history_index].clone();
+        Some(self.url.clone())
+    }
 }
 
 #[derive(Clone)]
 enum Action {
     Switch(usize),
     Close(usize),
+    Move(usize, usize),
     New,
     Navigate(String),
+    Back,
+    Forward,
+    Stop,
+    Download(DownloadOffer),
+}
+
+/// Persisted state of a single tab
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct SessionTab {
+    url: String,
+    title: String,
+    history: Vec<String>,
+    history_index: usize,
+}
+
+/// Persisted browser session used to restore tabs after a restart or crash
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Session {
+    tabs: Vec<SessionTab>,
+    active_tab_index: usize,
+}
+
+/// A saved page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Bookmark {
+    url: String,
+    title: String,
+}
+
+/// Saved pages, in the order they were added
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Bookmarks {
+    entries: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    /// Bookmark a page, returning false if it was already bookmarked
+    ///
+    /// Bookmarking a URL again only updates its title.
+    fn add(&mut self, url: &str, title: &str) -> bool {
+        if let Some(existing) = self.entries.iter_mut().find(|bookmark| bookmark.url == url) {
+            existing.title = title.to_string();
+            return false;
+        }
+        
+        self.entries.push(Bookmark {
+            url: url.to_string(),
+            title: title.to_string(),
+        });
+        true
+    }
+    
+    /// Remove a bookmark, returning false if the URL was not bookmarked
+    fn remove(&mut self, url: &str) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|bookmark| bookmark.url != url);
+        self.entries.len() != len
+    }
+    
+    /// Get the bookmarks in the order they were added
+    fn list(&self) -> &[Bookmark] {
+        &self.entries
+    }
+    
+    /// Read bookmarks saved by `save`
+    fn load(path: &Path) -> VeloraResult<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+    
+    /// Write the bookmarks to a file
+    fn save(&self, path: &Path) -> VeloraResult<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+/// A response the browser cannot display, offered for saving
+#[derive(Debug, Clone, PartialEq)]
+struct DownloadOffer {
+    url: String,
+    suggested_name: String,
+    /// The response itself, with its body still unread
+    response: HeldResponse,
+}
+
+/// A response kept open until the user decides whether to save it
+///
+/// The first save takes it; saving the same offer again fetches the URL anew.
+#[derive(Debug, Clone, Default)]
+struct HeldResponse(Arc<Mutex<Option<PendingResponse>>>);
+
+impl HeldResponse {
+    fn new(response: PendingResponse) -> Self {
+        Self(Arc::new(Mutex::new(Some(response))))
+    }
+    
+    /// Take the response, if no save has taken it yet
+    fn take(&self) -> Option<PendingResponse> {
+        self.0.lock().ok().and_then(|mut response| response.take())
+    }
+}
+
+impl PartialEq for HeldResponse {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Progress of a download
+#[derive(Debug, Clone, PartialEq)]
+enum DownloadState {
+    InProgress,
+    Completed,
+    Failed(String),
+}
+
+/// A file being saved, or saved, from the network
+#[derive(Debug, Clone, PartialEq)]
+struct Download {
+    id: usize,
+    url: String,
+    path: PathBuf,
+    /// Bytes written so far
+    received: u64,
+    /// Size from the Content-Length header, if the server sent one
+    total: Option<u64>,
+    state: DownloadState,
+}
+
+impl Download {
+    /// Share of the file received, if its size is known
+    fn fraction(&self) -> Option<f32> {
+        match self.total {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.received as f32 / total as f32).min(1.0)),
+            None => None,
+        }
+    }
+}
+
+/// Progress sent back from a download running in the background
+#[derive(Debug, Clone, PartialEq)]
+enum DownloadUpdate {
+    Progress(DownloadEvent),
+    Finished(Result<(), String>),
+}
+
+/// Downloads started in this session
+struct Downloads {
+    entries: Vec<Download>,
+    next_id: usize,
+    sender: mpsc::Sender<(usize, DownloadUpdate)>,
+    receiver: mpsc::Receiver<(usize, DownloadUpdate)>,
+}
+
+impl Default for Downloads {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            entries: Vec::new(),
+            next_id: 0,
+            sender,
+            receiver,
+        }
+    }
+}
+
+impl Downloads {
+    /// Save an offered response into the downloads directory, streaming it on the runtime
+    ///
+    /// The offer's own response is used if it is still unread, so the URL is fetched only once.
+    fn start(&mut self, runtime: &Runtime, client: &HttpClient, offer: &DownloadOffer) -> usize {
+        let dir = downloads_dir();
+        let (path, file) = match create_download_file(&dir, &offer.suggested_name) {
+            Ok(created) => created,
+            Err(e) => {
+                let id = self.add(&offer.url, dir.join(&offer.suggested_name));
+                self.apply(id, DownloadUpdate::Finished(Err(e.to_string())));
+                return id;
+            }
+        };
+        let id = self.add(&offer.url, path.clone());
+        
+        let sender = self.sender.clone();
+        let client = client.clone();
+        let url = offer.url.clone();
+        let response = offer.response.take();
+        runtime.spawn(async move {
+            let progress = |event| {
+                let _ = sender.send((id, DownloadUpdate::Progress(event)));
+            };
+            let response = match response {
+                Some(response) => Ok(response),
+                None => client.open(&url, None).await,
+            };
+            let result = match response.and_then(PendingResponse::error_for_status) {
+                Ok(response) => response.save(tokio::fs::File::from_std(file), progress).await,
+                Err(e) => Err(e),
+            };
+            let result = result
+                .map(|_| ())
+                .map_err(|e| e.to_string());
+            if let Err(e) = &result {
+                error!("Failed to download {} to {}: {}", url, path.display(), e);
+            }
+            let _ = sender.send((id, DownloadUpdate::Finished(result)));
+        });
+        id
+    }
+    
+    /// Track a new download, returning its id
+    fn add(&mut self, url: &str, path: PathBuf) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push(Download {
+            id,
+            url: url.to_string(),
+            path,
+            received: 0,
+            total: None,
+            state: DownloadState::InProgress,
+        });
+        id
+    }
+    
+    /// Record progress for a download; updates for finished downloads are ignored
+    fn apply(&mut self, id: usize, update: DownloadUpdate) {
+        let Some(download) = self.entries.iter_mut().find(|download| download.id == id) else {
+            return;
+        };
+        if download.state != DownloadState::InProgress {
+            return;
+        }
+        
+        match update {
+            DownloadUpdate::Progress(DownloadEvent::Started { total }) => download.total = total,
+            DownloadUpdate::Progress(DownloadEvent::Received(bytes)) => download.received += bytes,
+            DownloadUpdate::Finished(Ok(())) => download.state = DownloadState::Completed,
+            DownloadUpdate::Finished(Err(e)) => download.state = DownloadState::Failed(e),
+        }
+    }
+    
+    /// Apply the progress reported by background downloads
+    fn poll(&mut self) {
+        while let Ok((id, update)) = self.receiver.try_recv() {
+            self.apply(id, update);
+        }
+    }
+    
+    /// Whether any download is still running
+    fn is_active(&self) -> bool {
+        self.entries.iter().any(|download| download.state == DownloadState::InProgress)
+    }
+    
+    /// Get the downloads in the order they were started
+    fn list(&self) -> &[Download] {
+        &self.entries
+    }
+}
+
+/// File name to save a response under, from Content-Disposition or the URL path
+fn suggested_file_name(url: &str, headers: &HashMap<String, String>) -> String {
+    let disposition = headers.iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-disposition"))
+        .and_then(|(_, value)| value.split(';').find_map(|part| part.trim().strip_prefix("filename=")))
+        .map(|name| name.trim_matches('"').to_string());
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let from_url = path.split_once("://")
+        .and_then(|(_, rest)| rest.split_once('/'))
+        .and_then(|(_, path)| path.rsplit('/').next())
+        .map(str::to_string);
+    
+    // Never let a server pick the directory the file lands in
+    disposition.into_iter().chain(from_url)
+        .filter_map(|name| Path::new(&name).file_name().map(|name| name.to_string_lossy().into_owned()))
+        .find(|name| !name.is_empty())
+        .unwrap_or_else(|| DEFAULT_DOWNLOAD_NAME.to_string())
+}
+
+/// Create a new file in `dir` for `name`, numbering the name if it is taken
+///
+/// Each name is claimed with `create_new`, so two downloads of the same name can never end
+/// up writing to one file.
+fn create_download_file(dir: &Path, name: &str) -> std::io::Result<(PathBuf, std::fs::File)> {
+    let file_name = Path::new(name);
+    let stem = file_name.file_stem().map_or_else(|| DEFAULT_DOWNLOAD_NAME.into(), |stem| stem.to_string_lossy());
+    let extension = file_name.extension().map(|extension| format!(".{}", extension.to_string_lossy())).unwrap_or_default();
+    let candidates = std::iter::once(dir.join(name))
+        .chain((1..).map(|n| dir.join(format!("{} ({}){}", stem, n, extension))));
+    
+    for path in candidates {
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("numbered download names are unbounded")
 }
 
 #[derive(Clone)]
 struct NavigationRequest {
     url: String,
-    tab_index: usize,
+    /// Stable `Tab::id` of the tab to load into, which survives closing and reordering tabs
+    tab_id: usize,
 }
 
 #[derive(Default)]
@@ -45,48 +871,289 @@ struct BrowserApp {
     navigation_queue: Vec<NavigationRequest>,
     result_sender: Option<mpsc::Sender<NavigationResult>>,
     result_receiver: Option<mpsc::Receiver<NavigationResult>>,
+    /// Images fetched in the background, sent back to the UI thread
+    image_sender: Option<mpsc::Sender<LoadedImage>>,
+    image_receiver: Option<mpsc::Receiver<LoadedImage>>,
+    /// Images fetched by any tab, kept once under one memory budget
+    images: ImageRenderer,
+    find_active: bool,
+    find_query: String,
+    find_matches: Vec<NodeId>,
+    find_match_index: usize,
+    theme_override: Option<egui::Theme>,
+    high_contrast: bool,
+    applied_theme: Option<(egui::Theme, bool)>,
+    /// Node under the pointer, recorded while the page is drawn
+    hovered_node: Cell<Option<NodeId>>,
+    /// Node clicked this frame, recorded while the page is drawn
+    clicked_node: Cell<Option<NodeId>>,
+    /// Node middle-clicked this frame, recorded while the page is drawn
+    middle_clicked_node: Cell<Option<NodeId>>,
+    /// Node picked with "Inspect", shown in the inspector panel
+    inspected_node: Cell<Option<NodeId>>,
+    /// Scroll offset of the page, recorded while the page is drawn
+    scrolled_to: Cell<Option<f32>>,
+    /// Content and viewport height of the page, recorded while the page is drawn
+    page_extent: Cell<Option<ScrollExtent>>,
+    /// Scroll offset to move the page to on the next frame
+    scroll_request: Option<f32>,
+    /// Screen rect of the page content area, recorded while the page is drawn
+    content_rect: Cell<Option<Rect>>,
+    /// Native window options
+    window_config: WindowConfig,
+    /// Whether the window was minimized as of the last frame
+    minimized: bool,
+    /// JavaScript runtime for page scripts; its timers are paused while the window is minimized
+    script_runtime: JsRuntime,
+    /// Monitor the window was last seen on
+    current_monitor: Option<MonitorInfo>,
+    /// Clipboard used by the URL field
+    clipboard: Clipboard,
+    /// URLs visited in any tab
+    url_history: UrlHistory,
+    /// History matches for what is typed in the URL field
+    url_suggestions: Vec<String>,
+    /// Chronological log of navigations in all tabs
+    navigation_history: NavigationHistory,
+    /// Filter typed into the history panel
+    history_query: String,
+    /// Saved pages
+    bookmarks: Bookmarks,
+    /// Files saved from the network
+    downloads: Downloads,
+    /// History cap for tabs, when not the default
+    max_history: Option<usize>,
 }
 
 #[derive(Clone)]
 struct NavigationResult {
-    tab_index: usize,
+    tab_id: usize,
     success: bool,
+    cancelled: bool,
     content: Option<String>,
     dom: Option<Document>,
     title: Option<String>,
+    favicon: Option<DecodedImage>,
+    style_rules: Vec<CssRule>,
     error: Option<String>,
+    download_offer: Option<DownloadOffer>,
 }
 
-impl BrowserApp {
-    fn new() -> Self {
-        let (sender, receiver) = mpsc::channel();
-        let mut app = Self {
-            tabs: Vec::new(),
-            next_tab_id: 0,
-            active_tab_index: 0,
-            runtime: None,
-            http_client: None,
-            _html_parser: HtmlParser::new(), // Mark as intentionally unused for now
-            navigation_queue: Vec::new(),
-            result_sender: Some(sender),
-            result_receiver: Some(receiver),
-        };
-        
-        // Initialize async runtime
-        app.runtime = Some(Runtime::new().unwrap());
+impl NavigationResult {
+    /// A failed load, with the message shown in the page and the one logged
+    fn failure(tab_id: usize, content: String, error: String) -> Self {
+        Self {
+            tab_id,
+            success: false,
+            cancelled: false,
+            content: Some(content),
+            dom: None,
+            title: None,
+            favicon: None,
+            style_rules: Vec::new(),
+            error: Some(error),
+            download_offer: None,
+        }
+    }
+    
+    /// A load that was aborted before it finished
+    fn cancelled(tab_id: usize) -> Self {
+        Self {
+            tab_id,
+            success: false,
+            cancelled: true,
+            content: None,
+            dom: None,
+            title: None,
+            favicon: None,
+            style_rules: Vec::new(),
+            error: None,
+            download_offer: None,
+        }
+    }
+}
+
+/// Signal shared between the UI and a pending navigation to abort it
+#[derive(Debug, Clone, Default)]
+struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl CancellationToken {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+    
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+    
+    /// Resolve once the token is cancelled
+    async fn cancelled(&self) {
+        let notified = self.notify.notified();
+        tokio::pin!(notified);
+        
+        // Register before checking the flag so a concurrent cancel is not missed
+        notified.as_mut().enable();
+        if self.is_cancelled() {
+            return;
+        }
+        notified.await;
+    }
+}
+
+/// Marker for work that was aborted through a CancellationToken
+#[derive(Debug, PartialEq)]
+struct Cancelled;
+
+/// Run a future to completion unless the token is cancelled first
+async fn run_cancellable<F: Future>(token: &CancellationToken, future: F) -> Result<F::Output, Cancelled> {
+    tokio::select! {
+        biased;
+        _ = token.cancelled() => Err(Cancelled),
+        output = future => Ok(output),
+    }
+}
+
+impl BrowserApp {
+    fn new() -> Self {
+        let mut app = Self::with_runtime();
+        
+        // Restore the previous session, falling back to a fresh tab
+        let session_path = session_file_path();
+        if let Ok(json) = std::fs::read_to_string(&session_path) {
+            if let Err(e) = app.restore_session(&json) {
+                error!("Failed to restore session from {}: {}", session_path.display(), e);
+            }
+        }
+        
+        if app.tabs.is_empty() {
+            app.add_new_tab();
+        }
+        
+        let bookmarks_path = bookmarks_file_path();
+        if bookmarks_path.exists() {
+            match Bookmarks::load(&bookmarks_path) {
+                Ok(bookmarks) => app.bookmarks = bookmarks,
+                Err(e) => error!("Failed to load bookmarks from {}: {}", bookmarks_path.display(), e),
+            }
+        }
+        app
+    }
+    
+    /// Create an app with no tabs that can load pages
+    fn with_runtime() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let (image_sender, image_receiver) = mpsc::channel();
+        let mut app = Self {
+            tabs: Vec::new(),
+            next_tab_id: 0,
+            active_tab_index: 0,
+            runtime: None,
+            http_client: None,
+            _html_parser: HtmlParser::new(), // Mark as intentionally unused for now
+            navigation_queue: Vec::new(),
+            result_sender: Some(sender),
+            result_receiver: Some(receiver),
+            image_sender: Some(image_sender),
+            image_receiver: Some(image_receiver),
+            images: ImageRenderer::default(),
+            find_active: false,
+            find_query: String::new(),
+            find_matches: Vec::new(),
+            find_match_index: 0,
+            theme_override: None,
+            high_contrast: false,
+            applied_theme: None,
+            hovered_node: Cell::new(None),
+            clicked_node: Cell::new(None),
+            middle_clicked_node: Cell::new(None),
+            inspected_node: Cell::new(None),
+            scrolled_to: Cell::new(None),
+            page_extent: Cell::new(None),
+            scroll_request: None,
+            content_rect: Cell::new(None),
+            window_config: WindowConfig::default(),
+            minimized: false,
+            script_runtime: JsRuntime::new().unwrap(),
+            current_monitor: None,
+            clipboard: Clipboard::system(),
+            url_history: UrlHistory::default(),
+            url_suggestions: Vec::new(),
+            navigation_history: NavigationHistory::default(),
+            history_query: String::new(),
+            bookmarks: Bookmarks::default(),
+            downloads: Downloads::default(),
+            max_history: None,
+        };
+        
+        // Initialize async runtime
+        app.runtime = Some(Runtime::new().unwrap());
         
         // Initialize HTTP client
         if let Some(rt) = &app.runtime {
+            // Requests are logged for the network panel
+            let config = HttpClientConfig { network_log: true, ..HttpClientConfig::default() };
             app.http_client = rt.block_on(async {
-                HttpClient::new()
+                HttpClient::with_config(config)
             }).ok();
         }
-        
-        // Create initial tab
-        app.add_new_tab();
         app
     }
     
+    /// Serialize the open tabs and their history to JSON
+    fn serialize_session(&self) -> String {
+        let session = Session {
+            tabs: self.tabs.iter().map(|tab| SessionTab {
+                url: tab.url.clone(),
+                title: tab.title.clone(),
+                history: tab.history.clone(),
+                history_index: tab.history_index,
+            }).collect(),
+            active_tab_index: self.active_tab_index,
+        };
+        
+        serde_json::to_string(&session).unwrap_or_default()
+    }
+    
+    /// Replace the open tabs with a session produced by `serialize_session`
+    fn restore_session(&mut self, json: &str) -> VeloraResult<()> {
+        let session: Session = serde_json::from_str(json)?;
+        
+        if session.tabs.is_empty() {
+            return Err(VeloraError::InvalidState("Session contains no tabs".to_string()));
+        }
+        
+        self.tabs.clear();
+        for saved in session.tabs {
+            self.add_new_tab();
+            if let Some(tab) = self.tabs.last_mut() {
+                let history_index = saved.history_index.min(saved.history.len().saturating_sub(1));
+                tab.url = saved.url;
+                tab.title = saved.title;
+                tab.history = saved.history;
+                tab.history_index = history_index;
+                tab.trim_history();
+                for url in &tab.history {
+                    self.url_history.record_visit(url);
+                }
+            }
+        }
+        
+        self.active_tab_index = session.active_tab_index.min(self.tabs.len() - 1);
+        Ok(())
+    }
+    
+    /// Write the current session to disk so it survives a crash
+    fn save_session(&self) {
+        let session_path = session_file_path();
+        if let Err(e) = std::fs::write(&session_path, self.serialize_session()) {
+            error!("Failed to save session to {}: {}", session_path.display(), e);
+        }
+    }
+    
     fn add_new_tab(&mut self) {
         let tab_id = self.next_tab_id;
         let new_tab = Tab {
@@ -96,6 +1163,24 @@ impl BrowserApp {
             content: None,
             dom: None,
             loading: false,
+            history: Vec::new(),
+            history_index: 0,
+            max_history: self.max_history.unwrap_or(DEFAULT_MAX_HISTORY).max(1),
+            zoom: 1.0,
+            favicon: None,
+            favicon_texture: None,
+            cancel_token: None,
+            committed_url: None,
+            interaction: InteractionState::new(),
+            download_offer: None,
+            style_rules: Vec::new(),
+            user_agent: None,
+            scroll_offset: 0.0,
+            lazy_images: LazyImages::default(),
+            layout_size: None,
+            page_background: None,
+            page_height: 0.0,
+            image_textures: HashMap::new(),
         };
         
         self.tabs.push(new_tab);
@@ -103,11 +1188,20 @@ impl BrowserApp {
         self.next_tab_id += 1;
     }
     
+    /// Cap the back/forward history of every tab, including ones opened later
+    fn set_max_history(&mut self, max_history: usize) {
+        self.max_history = Some(max_history);
+        for tab in &mut self.tabs {
+            tab.set_max_history(max_history);
+        }
+    }
+    
     fn close_tab(&mut self, tab_index: usize) {
         if self.tabs.len() <= 1 {
             return; // Don't close the last tab
         }
         
+        self.cancel_navigation(tab_index);
         self.tabs.remove(tab_index);
         
         // Adjust active tab index
@@ -116,6 +1210,369 @@ impl BrowserApp {
         }
     }
     
+    fn move_tab(&mut self, tab_id: usize, new_index: usize) {
+        let Some(from) = self.tabs.iter().position(|tab| tab.id == tab_id) else {
+            return;
+        };
+        
+        // Remember which tab is active so the selection follows it
+        let active_tab_id = self.get_active_tab().map(|tab| tab.id);
+        
+        // Clamp out-of-range indices to the end of the tab strip
+        let new_index = new_index.min(self.tabs.len() - 1);
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(new_index, tab);
+        
+        if let Some(active_tab_id) = active_tab_id {
+            if let Some(index) = self.tabs.iter().position(|tab| tab.id == active_tab_id) {
+                self.active_tab_index = index;
+            }
+        }
+    }
+    
+    /// Handle the find bar keyboard shortcuts, returning true if the key was consumed
+    fn handle_find_key(&mut self, key: egui::Key, modifiers: egui::Modifiers) -> bool {
+        match key {
+            egui::Key::F if modifiers.command => {
+                self.find_active = !self.find_active;
+                if self.find_active {
+                    self.update_find_matches();
+                } else {
+                    self.find_matches.clear();
+                }
+                true
+            }
+            egui::Key::Escape if self.find_active => {
+                self.find_active = false;
+                self.find_matches.clear();
+                true
+            }
+            egui::Key::Enter if self.find_active => {
+                self.find_next();
+                true
+            }
+            _ => false,
+        }
+    }
+    
+    /// Handle the page zoom shortcuts, returning true if the key was consumed
+    fn handle_zoom_key(&mut self, key: egui::Key, modifiers: egui::Modifiers) -> bool {
+        if !modifiers.command {
+            return false;
+        }
+        
+        let Some(tab) = self.get_active_tab_mut() else {
+            return false;
+        };
+        
+        match key {
+            egui::Key::Plus | egui::Key::Equals => tab.set_zoom(tab.zoom + ZOOM_STEP),
+            egui::Key::Minus => tab.set_zoom(tab.zoom - ZOOM_STEP),
+            egui::Key::Num0 => tab.set_zoom(1.0),
+            _ => return false,
+        }
+        true
+    }
+    
+    /// Handle the window management shortcuts, returning true if the key was consumed
+    fn handle_window_key(&mut self, ctx: &egui::Context, key: egui::Key, modifiers: egui::Modifiers) -> bool {
+        match key {
+            egui::Key::M if modifiers.command => {
+                self.minimize(ctx);
+                true
+            }
+            egui::Key::F11 => {
+                // The window system puts a fullscreen window on the monitor it is on
+                let fullscreen = ctx.input(|i| i.viewport().fullscreen.unwrap_or(false));
+                ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(!fullscreen));
+                true
+            }
+            _ => false,
+        }
+    }
+    
+    /// Ask the window system to minimize the window
+    ///
+    /// `is_minimized` changes once the window system reports the new state.
+    fn minimize(&self, ctx: &egui::Context) {
+        ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+    }
+    
+    /// Whether the window is minimized
+    fn is_minimized(&self) -> bool {
+        self.minimized
+    }
+    
+    /// Get the monitor the window is on, if the platform has reported it
+    fn current_monitor(&self) -> Option<MonitorInfo> {
+        self.current_monitor
+    }
+    
+    /// Record the window's monitor, returning true if it changed
+    ///
+    /// Frames that do not report a monitor keep the last one seen.
+    fn track_monitor(&mut self, info: &egui::ViewportInfo) -> bool {
+        let Some(monitor) = MonitorInfo::from_viewport(info) else {
+            return false;
+        };
+        if self.current_monitor() == Some(monitor) {
+            return false;
+        }
+        
+        let [width, height] = monitor.physical_size();
+        info!("Window is on a {}x{} monitor at scale {}", width, height, monitor.scale_factor);
+        self.current_monitor = Some(monitor);
+        true
+    }
+    
+    /// Whether frames should be drawn and repaints requested
+    fn is_rendering_active(&self) -> bool {
+        !self.is_minimized()
+    }
+    
+    /// Track the window's minimized state, returning the event for any change
+    ///
+    /// Script timers are held back while the window is minimized.
+    fn process_event(&mut self, info: &egui::ViewportInfo) -> Option<WindowEvent> {
+        let event = convert_viewport_event(self.is_minimized(), info)?;
+        self.minimized = event == WindowEvent::Minimized;
+        match event {
+            WindowEvent::Minimized => self.script_runtime.pause_timers(),
+            WindowEvent::Restored => self.script_runtime.resume_timers(),
+        }
+        info!("Window {:?}", event);
+        Some(event)
+    }
+    
+    /// Scroll the page with Space, Page Up/Down, Home/End and the arrow keys, returning true if the key was consumed
+    ///
+    /// The page only gets these keys while no toolbar widget, such as the URL field, has keyboard focus.
+    fn handle_scroll_key(&mut self, ctx: &egui::Context, key: egui::Key, modifiers: egui::Modifiers) -> bool {
+        if ctx.memory(|memory| memory.focused().is_some()) {
+            return false;
+        }
+        
+        let Some(extent) = self.page_extent.get() else {
+            return false;
+        };
+        let Some(tab) = self.get_active_tab().filter(|tab| tab.dom.is_some()) else {
+            return false;
+        };
+        let Some(offset) = extent.scroll_for_key(tab.scroll_offset, key, modifiers) else {
+            return false;
+        };
+        
+        self.scroll_request = Some(offset);
+        self.handle_scroll(offset);
+        true
+    }
+    
+    /// Handle Enter for the page's focus, returning true if the key was consumed
+    ///
+    /// The page only gets the key while no toolbar widget has keyboard focus. Tab is
+    /// handled before the frame by `take_page_focus_keys`.
+    fn handle_focus_key(&mut self, ctx: &egui::Context, key: egui::Key) -> bool {
+        if ctx.memory(|memory| memory.focused().is_some()) {
+            return false;
+        }
+        
+        match key {
+            egui::Key::Enter => self.activate_focused_node(),
+            _ => false,
+        }
+    }
+    
+    /// Move the page's focus for Tab and Shift+Tab, removing the presses from the frame's input
+    ///
+    /// egui gives the first toolbar widget focus on Tab when nothing has focus, which would
+    /// end page focus cycling after one press, so egui must never see these keys.
+    fn take_page_focus_keys(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        if ctx.memory(|memory| memory.focused().is_some()) {
+            return;
+        }
+        
+        raw_input.events.retain(|event| match event {
+            egui::Event::Key { key: egui::Key::Tab, pressed, modifiers, .. } => {
+                if *pressed {
+                    self.move_page_focus(modifiers.shift);
+                }
+                false
+            }
+            _ => true,
+        });
+    }
+    
+    /// Move the active page's focus to the next focusable node, or the previous one
+    fn move_page_focus(&mut self, backward: bool) -> Vec<NodeId> {
+        let Some(tab) = self.tabs.get_mut(self.active_tab_index) else {
+            return Vec::new();
+        };
+        let Some(document) = &tab.dom else {
+            return Vec::new();
+        };
+        tab.interaction.focus_next(document.get_dom_tree(), backward)
+    }
+    
+    /// Click the focused node, following it if it is a link, returning false if nothing has focus
+    fn activate_focused_node(&mut self) -> bool {
+        let Some(tab) = self.get_active_tab() else {
+            return false;
+        };
+        let (Some(document), Some(target)) = (&tab.dom, tab.interaction.focused()) else {
+            return false;
+        };
+        
+        let link = document.link_url(target);
+        match self.dispatch_click(target) {
+            Ok(true) => {
+                if let Some(url) = link {
+                    self.handle_action(Action::Navigate(url));
+                }
+            }
+            Ok(false) => {}
+            Err(e) => error!("Failed to dispatch click: {}", e),
+        }
+        true
+    }
+    
+    /// Handle the bookmark shortcut, returning true if the key was consumed
+    fn handle_bookmark_key(&mut self, key: egui::Key, modifiers: egui::Modifiers) -> bool {
+        if key != egui::Key::D || !modifiers.command {
+            return false;
+        }
+        
+        if self.bookmark_active_tab() {
+            self.save_bookmarks();
+        }
+        true
+    }
+    
+    /// Write the bookmarks to disk
+    fn save_bookmarks(&self) {
+        let bookmarks_path = bookmarks_file_path();
+        if let Err(e) = self.bookmarks.save(&bookmarks_path) {
+            error!("Failed to save bookmarks to {}: {}", bookmarks_path.display(), e);
+        }
+    }
+    
+    /// Bookmark the active tab's page, returning false if there is no page to bookmark
+    fn bookmark_active_tab(&mut self) -> bool {
+        let Some(tab) = self.tabs.get(self.active_tab_index) else {
+            return false;
+        };
+        if tab.url.is_empty() {
+            return false;
+        }
+        
+        self.bookmarks.add(&tab.url, &tab.title);
+        info!("Bookmarked {}", tab.url);
+        true
+    }
+    
+    /// Handle the address bar shortcuts, returning true if the key was consumed
+    fn handle_url_key(&self, ctx: &egui::Context, key: egui::Key, modifiers: egui::Modifiers) -> bool {
+        match key {
+            egui::Key::L if modifiers.command => {
+                if let Some(tab) = self.get_active_tab() {
+                    select_url_text(ctx, &tab.url);
+                    ctx.memory_mut(|memory| memory.request_focus(url_input_id()));
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+    
+    /// Run clipboard shortcuts aimed at the focused URL field through the browser's clipboard
+    ///
+    /// The events are taken out of the input so the text field does not handle them again.
+    fn handle_url_clipboard(&mut self, ctx: &egui::Context) {
+        let id = url_input_id();
+        if !ctx.memory(|memory| memory.has_focus(id)) {
+            return;
+        }
+        
+        let commands: Vec<ClipboardCommand> = ctx.input_mut(|i| {
+            let mut commands = Vec::new();
+            i.events.retain(|event| match ClipboardCommand::from_event(event) {
+                Some(command) => {
+                    commands.push(command);
+                    false
+                }
+                None => true,
+            });
+            commands
+        });
+        let Some(tab) = self.tabs.get_mut(self.active_tab_index) else {
+            return;
+        };
+        if commands.is_empty() {
+            return;
+        }
+        
+        let mut state = egui::TextEdit::load_state(ctx, id).unwrap_or_default();
+        let end = tab.url.chars().count();
+        let mut selection = state.cursor.char_range().map_or(end..end, |range| range.as_sorted_char_range());
+        for command in commands {
+            selection = self.clipboard.apply(command, &mut tab.url, selection);
+        }
+        
+        state.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+            egui::text::CCursor::new(selection.start),
+            egui::text::CCursor::new(selection.end),
+        )));
+        state.store(ctx, id);
+    }
+    
+    /// Apply the UI theme, following the system preference unless overridden
+    fn apply_theme(&mut self, ctx: &egui::Context) {
+        // High contrast always uses the dark base theme
+        let theme = if self.high_contrast {
+            egui::Theme::Dark
+        } else {
+            resolve_theme(self.theme_override, ctx.system_theme())
+        };
+        
+        if self.applied_theme != Some((theme, self.high_contrast)) {
+            info!("Switching UI theme to {:?} (high contrast: {})", theme, self.high_contrast);
+            ctx.set_theme(theme);
+            let mut visuals = if self.high_contrast {
+                high_contrast_visuals()
+            } else {
+                theme.default_visuals()
+            };
+            if self.window_config.transparent {
+                visuals.panel_fill = visuals.panel_fill.gamma_multiply(TRANSPARENT_PANEL_OPACITY);
+            }
+            ctx.set_visuals_of(theme, visuals);
+            self.applied_theme = Some((theme, self.high_contrast));
+        }
+    }
+    
+    /// Re-run the find query against the active tab's document
+    fn update_find_matches(&mut self) {
+        self.find_matches = self
+            .get_active_tab()
+            .and_then(|tab| tab.dom.as_ref())
+            .map(|document| document.find_text(&self.find_query, false))
+            .unwrap_or_default();
+        self.find_match_index = 0;
+    }
+    
+    /// Advance to the next find match, wrapping around at the end
+    fn find_next(&mut self) {
+        if self.find_matches.is_empty() {
+            self.update_find_matches();
+        } else {
+            self.find_match_index = (self.find_match_index + 1) % self.find_matches.len();
+        }
+    }
+    
+    /// Get the currently highlighted find match
+    fn current_find_match(&self) -> Option<NodeId> {
+        self.find_matches.get(self.find_match_index).copied()
+    }
+    
     fn get_active_tab_mut(&mut self) -> Option<&mut Tab> {
         self.tabs.get_mut(self.active_tab_index)
     }
@@ -124,6 +1581,95 @@ impl BrowserApp {
         self.tabs.get(self.active_tab_index)
     }
     
+    /// Background of the content area in the current color scheme, used when a page sets none
+    fn content_background(&self) -> Color {
+        let visuals = match self.applied_theme {
+            Some((_, true)) => high_contrast_visuals(),
+            Some((theme, false)) => theme.default_visuals(),
+            None => egui::Visuals::light(),
+        };
+        from_color32(visuals.extreme_bg_color)
+    }
+    
+    /// Color the active page is cleared to before anything is drawn
+    fn page_clear_color(&self) -> Color {
+        let fallback = self.content_background();
+        match self.get_active_tab() {
+            Some(Tab { dom: Some(_), page_background, .. }) => page_background.unwrap_or(fallback),
+            _ => fallback,
+        }
+    }
+    
+    /// Paint the active page into an offscreen framebuffer, without a window
+    ///
+    /// The page is laid out at `size` and its boxes are painted with their background
+    /// colors over the page background; text and images are not painted yet.
+    fn render_to_image(&self, size: Size) -> VeloraResult<PixelBuffer> {
+        let layout = match self.get_active_tab() {
+            Some(tab @ Tab { dom: Some(document), .. }) => {
+                Some((document, layout_page(document, &tab.style_rules, size, tab.is_mobile(), &self.images)?))
+            }
+            _ => None,
+        };
+        let background = layout.as_ref()
+            .and_then(|(document, layout)| page_background(document, layout))
+            .unwrap_or_else(|| self.content_background());
+        
+        let mut renderer = Renderer::new()?;
+        renderer.initialize(size)?;
+        renderer.set_background(background);
+        renderer.render()?;
+        
+        if let (Some((_, layout)), Some(back)) = (&layout, renderer.back_buffer_mut()) {
+            page_display_list(layout).rasterize(back);
+        }
+        renderer.swap();
+        
+        renderer.target()
+            .cloned()
+            .ok_or_else(|| VeloraError::InvalidState("Renderer has no framebuffer".to_string()))
+    }
+    
+    /// Describe the active page's DOM as an indented tree, one node per line
+    ///
+    /// Elements show their tag, `#id` and `.classes`; text is quoted and cut off after
+    /// `DUMP_TEXT_LIMIT` characters. Empty if no page is loaded.
+    fn dump_dom_tree(&self) -> String {
+        let mut dump = String::new();
+        if let Some(document) = self.get_active_tab().and_then(|tab| tab.dom.as_ref()) {
+            if let Some(root) = document.get_dom_tree().get_root() {
+                dump_node(document, root, 0, &mut dump);
+            }
+        }
+        dump
+    }
+    
+    /// Computed style of a node on the active page, like `getComputedStyle`
+    ///
+    /// Cascades the built-in and page stylesheets with the node's inline style, then
+    /// fills in values inherited from its ancestors. Empty if no page is loaded.
+    fn computed_style(&self, node_id: NodeId) -> HashMap<String, CssValue> {
+        let Some(tab) = self.get_active_tab() else {
+            return HashMap::new();
+        };
+        let Some(document) = &tab.dom else {
+            return HashMap::new();
+        };
+        
+        let mut rules = cascade::user_agent_rules();
+        rules.extend(tab.style_rules.iter().cloned());
+        let [width, height] = DEFAULT_WINDOW_SIZE;
+        StyleEngine::new(Size::new(width, height)).computed_properties(document, &rules, node_id)
+    }
+    
+    /// Render the active page offscreen and write it to a PNG file
+    fn save_screenshot(&self, path: &str, size: Size) -> VeloraResult<()> {
+        let png = self.render_to_image(size)?.encode_png()?;
+        std::fs::write(path, png)?;
+        info!("Saved screenshot to {}", path);
+        Ok(())
+    }
+    
     
     
     fn process_navigation_queue(&mut self) {
@@ -131,148 +1677,164 @@ impl BrowserApp {
         while let Some(request) = self.navigation_queue.pop() {
             if let Some(rt) = &self.runtime {
                 let url = request.url.clone();
-                let tab_index = request.tab_index;
+                let tab_id = request.tab_id;
+                let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == tab_id) else {
+                    // The tab was closed before its load started
+                    continue;
+                };
+                let user_agent = tab.user_agent;
+                let token = CancellationToken::default();
                 
-                // Update tab loading state
-                if let Some(tab) = self.tabs.get_mut(tab_index) {
-                    tab.loading = true;
-                    tab.title = url.clone();
+                // Update tab loading state, aborting any load already in flight
+                if let Some(previous) = tab.cancel_token.replace(token.clone()) {
+                    previous.cancel();
                 }
+                tab.loading = true;
+                tab.title = url.clone();
                 
-                // Process navigation asynchronously
+                // Load in the background, reusing the app's connection pool
                 let sender = self.result_sender.clone();
-                rt.block_on(async {
-                    // Create a new HTTP client for this request
-                    if let Ok(client) = HttpClient::new() {
-                        match client.get(&url).await {
-                            Ok(response) => {
-                                if response.status.is_success() {
-                                    if let Ok(html_content) = response.text() {
-                                        info!("Received HTML content: {} bytes", html_content.len());
-                                        
-                                        // Parse HTML into DOM
-                                        let html_parser = HtmlParser::new();
-                                        match html_parser.parse_html(&html_content) {
-                                            Ok(document) => {
-                                                info!("Successfully parsed HTML into DOM");
-                                                
-                                                // Send success result through channel
-                                                if let Some(sender) = sender {
-                                                    let title = extract_title_from_html(&html_content);
-                                                    let _ = sender.send(NavigationResult {
-                                                        tab_index,
-                                                        success: true,
-                                                        content: Some(html_content),
-                                                        dom: Some(document),
-                                                        title: Some(title),
-                                                        error: None,
-                                                    });
-                                                }
-                                            }
-                                            Err(e) => {
-                                                error!("Failed to parse HTML: {:?}", e);
-                                                
-                                                // Send error result through channel
-                                                if let Some(sender) = sender {
-                                                    let _ = sender.send(NavigationResult {
-                                                        tab_index,
-                                                        success: false,
-                                                        content: Some(format!("Error parsing HTML: {:?}", e)),
-                                                        dom: None,
-                                                        title: None,
-                                                        error: Some(format!("HTML parsing failed: {:?}", e)),
-                                                    });
-                                                }
-                                            }
-                                        }
-                                    } else {
-                                        error!("Failed to decode response as text");
-                                        
-                                        // Send error result through channel
-                                        if let Some(sender) = sender {
-                                            let _ = sender.send(NavigationResult {
-                                                tab_index,
-                                                success: false,
-                                                content: Some("Error: Failed to decode response as text".to_string()),
-                                                dom: None,
-                                                title: None,
-                                                error: Some("Failed to decode response as text".to_string()),
-                                            });
-                                        }
-                                    }
-                                } else {
-                                    error!("HTTP request failed: {} {}", response.status.code, response.status.reason);
-                                    
-                                    // Send error result through channel
-                                    if let Some(sender) = sender {
-                                        let _ = sender.send(NavigationResult {
-                                            tab_index,
-                                            success: false,
-                                            content: Some(format!("HTTP Error: {} {}", response.status.code, response.status.reason)),
-                                            dom: None,
-                                            title: None,
-                                            error: Some(format!("HTTP request failed: {} {}", response.status.code, response.status.reason)),
-                                        });
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                error!("Request failed: {:?}", e);
-                                
-                                // Send error result through channel
-                                if let Some(sender) = sender {
-                                    let _ = sender.send(NavigationResult {
-                                        tab_index,
-                                        success: false,
-                                        content: Some(format!("Request Error: {:?}", e)),
-                                        dom: None,
-                                        title: None,
-                                        error: Some(format!("Request failed: {:?}", e)),
-                                    });
-                                }
-                            }
-                        }
-                    } else {
-                        error!("Failed to create HTTP client");
-                        
-                        // Send error result through channel
-                        if let Some(sender) = sender {
-                            let _ = sender.send(NavigationResult {
-                                tab_index,
-                                success: false,
-                                content: Some("Error: Failed to create HTTP client".to_string()),
-                                dom: None,
-                                title: None,
-                                error: Some("Failed to create HTTP client".to_string()),
-                            });
+                let client = self.http_client.clone();
+                rt.spawn(async move {
+                    let load = tokio::time::timeout(NAVIGATION_TIMEOUT, load_page(client, url, tab_id, user_agent));
+                    let result = match run_cancellable(&token, load).await {
+                        Ok(Ok(result)) => result,
+                        Ok(Err(_)) => {
+                            error!("Navigation timed out after {:?}", NAVIGATION_TIMEOUT);
+                            NavigationResult::failure(
+                                tab_id,
+                                "Error: The page took too long to respond".to_string(),
+                                "Navigation timed out".to_string(),
+                            )
                         }
+                        Err(Cancelled) => NavigationResult::cancelled(tab_id),
+                    };
+                    
+                    if let Some(sender) = sender {
+                        let _ = sender.send(result);
                     }
                 });
+            }
+        }
+    }
+    
+    /// Apply an action collected from the UI
+    fn handle_action(&mut self, action: Action) {
+        match action {
+            Action::Switch(index) => {
+                if index != self.active_tab_index {
+                    self.cancel_navigation(self.active_tab_index);
+                }
+                self.active_tab_index = index;
+            }
+            Action::Close(index) => self.close_tab(index),
+            Action::Move(tab_id, new_index) => self.move_tab(tab_id, new_index),
+            Action::New => self.add_new_tab(),
+            Action::Stop => self.stop_loading(),
+            Action::Download(offer) => {
+                self.start_download(&offer);
+            }
+            Action::Navigate(url) => {
+                if let Some(tab) = self.get_active_tab_mut() {
+                    tab.navigate_to(url.clone());
+                }
+                self.url_history.record_visit(&url);
+                self.navigation_history.record(&url);
+                self.url_suggestions.clear();
                 
-                // Update tab loading state
-                if let Some(tab) = self.tabs.get_mut(tab_index) {
-                    tab.loading = false;
+                // Queue the navigation request to avoid borrowing issues
+                if let Some(tab_id) = self.get_active_tab().map(|tab| tab.id) {
+                    self.navigation_queue.push(NavigationRequest { url: url.clone(), tab_id });
+                }
+                info!("Navigation queued to: {}", url);
+            }
+            Action::Back | Action::Forward => {
+                let target = self.get_active_tab_mut().and_then(|tab| match action {
+                    Action::Back => tab.go_back(),
+                    _ => tab.go_forward(),
+                }.map(|url| (url, tab.id)));
+                
+                if let Some((url, tab_id)) = target {
+                    self.navigation_history.record(&url);
+                    self.navigation_queue.push(NavigationRequest { url, tab_id });
                 }
             }
         }
     }
     
+    /// Save a response into the downloads directory, returning the download's id
+    fn start_download(&mut self, offer: &DownloadOffer) -> Option<usize> {
+        let (Some(runtime), Some(client)) = (&self.runtime, &self.http_client) else {
+            error!("Cannot download {} without an HTTP client", offer.url);
+            return None;
+        };
+        let id = self.downloads.start(runtime, client, offer);
+        info!("Download {} started: {}", id, offer.url);
+        Some(id)
+    }
+    
+    /// Abort the active tab's load and go back to showing the current page
+    fn stop_loading(&mut self) {
+        self.cancel_navigation(self.active_tab_index);
+        if let Some(tab) = self.get_active_tab_mut() {
+            tab.loading = false;
+            tab.cancel_token = None;
+            if let Some(url) = &tab.committed_url {
+                tab.url = url.clone();
+            }
+        }
+    }
+    
+    /// Abort the pending load of a tab, if it has one
+    fn cancel_navigation(&mut self, tab_index: usize) {
+        if let Some(token) = self.tabs.get(tab_index).and_then(|tab| tab.cancel_token.as_ref()) {
+            token.cancel();
+        }
+    }
+    
     fn process_navigation_results(&mut self) {
+        let mut committed = Vec::new();
+        
         // Process all available navigation results
         if let Some(receiver) = &self.result_receiver {
             while let Ok(result) = receiver.try_recv() {
-                if let Some(tab) = self.tabs.get_mut(result.tab_index) {
+                // Look the tab up by id, since closing or moving tabs shifts indices
+                if let Some(tab_index) = self.tabs.iter().position(|tab| tab.id == result.tab_id) {
+                    let tab = &mut self.tabs[tab_index];
+                    if result.cancelled {
+                        // A newer load may already be running in this tab
+                        if tab.cancel_token.as_ref().is_none_or(CancellationToken::is_cancelled) {
+                            tab.loading = false;
+                            tab.cancel_token = None;
+                        }
+                        info!("Navigation cancelled in tab {}", result.tab_id);
+                        continue;
+                    }
+                    
                     tab.loading = false;
+                    tab.cancel_token = None;
+                    tab.download_offer = result.download_offer;
                     
                     if result.success {
                         if let Some(content) = result.content {
                             tab.content = Some(content);
                         }
                         if let Some(dom) = result.dom {
+                            tab.committed_url = dom.url().map(str::to_string);
                             tab.dom = Some(dom);
+                            tab.interaction.clear();
+                            tab.scroll_offset = 0.0;
+                            tab.lazy_images = LazyImages::default();
+                            tab.image_textures.clear();
+                            committed.push(tab_index);
                         }
+                        tab.favicon = result.favicon;
+                        tab.favicon_texture = None;
+                        tab.style_rules = result.style_rules;
+                        // Untitled pages keep showing their URL
                         if let Some(title) = result.title {
                             if !title.is_empty() {
+                                self.navigation_history.set_title(&tab.url, &title);
                                 tab.title = title;
                             }
                         }
@@ -287,7 +1849,236 @@ impl BrowserApp {
                 }
             }
         }
-    }
+        
+        // Lay out each new page to find where its images are
+        for tab_index in committed {
+            self.relayout_page(tab_index);
+            self.load_visible_images(tab_index);
+        }
+    }
+    
+    /// Size of the page viewport: the content area, or the default window before the first frame
+    fn page_viewport_size(&self) -> Size {
+        match self.content_rect.get() {
+            Some(rect) => Size::new(rect.width, rect.height),
+            None => {
+                let [width, height] = DEFAULT_WINDOW_SIZE;
+                Size::new(width, height)
+            }
+        }
+    }
+    
+    /// Lay out a tab's page for the current viewport and zoom, placing its images
+    ///
+    /// Images are painted at these positions, in the same page coordinates the scroll
+    /// offset is measured in.
+    fn relayout_page(&mut self, tab_index: usize) {
+        let viewport = self.page_viewport_size();
+        let Some(tab) = self.tabs.get_mut(tab_index) else {
+            return;
+        };
+        let Some(document) = &tab.dom else {
+            return;
+        };
+        let size = tab.layout_viewport(viewport);
+        tab.layout_size = Some(size);
+        match layout_page(document, &tab.style_rules, size, tab.is_mobile(), &self.images) {
+            Ok(layout) => {
+                tab.page_height = layout.rects.values().map(|rect| rect.y + rect.height).fold(0.0, f32::max);
+                tab.page_background = page_background(document, &layout);
+                tab.lazy_images.set_layout(document, &layout.rects);
+            }
+            Err(e) => error!("Failed to lay out page: {}", e),
+        }
+    }
+    
+    /// Lay out a tab's page again if the viewport or zoom changed since its last layout
+    fn relayout_if_resized(&mut self, tab_index: usize) {
+        let viewport = self.page_viewport_size();
+        let Some(tab) = self.tabs.get(tab_index) else {
+            return;
+        };
+        if tab.dom.is_some() && tab.layout_size != Some(tab.layout_viewport(viewport)) {
+            self.relayout_page(tab_index);
+            self.load_visible_images(tab_index);
+        }
+    }
+    
+    /// Start fetching a tab's images that are eager or inside its scrolled viewport
+    fn load_visible_images(&mut self, tab_index: usize) {
+        let viewport = self.page_viewport_size();
+        let Some(tab) = self.tabs.get_mut(tab_index) else {
+            return;
+        };
+        let visible = tab.visible_rect(viewport);
+        let sources = tab.lazy_images.take_visible(visible, &self.images);
+        let tab_id = tab.id;
+        let user_agent = tab.user_agent.map(|preset| preset.user_agent());
+        
+        let (Some(rt), Some(client)) = (&self.runtime, &self.http_client) else {
+            return;
+        };
+        for src in sources {
+            let sender = self.image_sender.clone();
+            let mut loader = ResourceLoader::with_client(client.clone());
+            loader.set_user_agent(user_agent);
+            rt.spawn(async move {
+                let loaded = loader.load_resource(&src).await
+                    .and_then(|resource| Ok((DecodedImage::decode(&resource.data)?, resource.data)));
+                match loaded {
+                    Ok((image, data)) => {
+                        if let Some(sender) = sender {
+                            let _ = sender.send(LoadedImage { tab_id, src, data, image });
+                        }
+                    }
+                    Err(e) => info!("Image {} failed to load: {}", src, e),
+                }
+            });
+        }
+    }
+    
+    /// Store fetched images, laying out again every page that shows them
+    fn process_loaded_images(&mut self) {
+        let Some(receiver) = &self.image_receiver else {
+            return;
+        };
+        let mut updated = Vec::new();
+        while let Ok(loaded) = receiver.try_recv() {
+            if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == loaded.tab_id) {
+                tab.lazy_images.requested.remove(&loaded.src);
+            }
+            for (tab_index, tab) in self.tabs.iter().enumerate() {
+                let shown = tab.lazy_images.images().iter().any(|image| image.src == loaded.src);
+                if shown && !updated.contains(&tab_index) {
+                    updated.push(tab_index);
+                }
+            }
+            self.images.insert_image(&loaded.src, loaded.data, loaded.image);
+        }
+        
+        // An image's natural size replaces its placeholder and moves what comes after it
+        for tab_index in updated {
+            self.relayout_page(tab_index);
+            self.load_visible_images(tab_index);
+        }
+    }
+    
+    /// Upload the active page's on-screen images as textures, dropping those scrolled away
+    ///
+    /// Pixels are read through the shared image cache, which decodes evicted images again.
+    fn upload_page_images(&mut self, ctx: &egui::Context) {
+        let viewport = self.page_viewport_size();
+        let Some(tab) = self.tabs.get_mut(self.active_tab_index) else {
+            return;
+        };
+        let visible: HashSet<String> = tab.lazy_images.visible(tab.visible_rect(viewport))
+            .map(|image| image.src.clone())
+            .collect();
+        tab.image_textures.retain(|src, _| visible.contains(src));
+        
+        for src in visible {
+            if tab.image_textures.contains_key(&src) {
+                continue;
+            }
+            match self.images.get_image(&src) {
+                Ok(Some(image)) => {
+                    let pixels = egui::ColorImage::from_rgba_unmultiplied(
+                        [image.width as usize, image.height as usize],
+                        &image.pixels,
+                    );
+                    let texture = ctx.load_texture(format!("image-{}", src), pixels, egui::TextureOptions::LINEAR);
+                    tab.image_textures.insert(src, texture);
+                }
+                Ok(None) => {}
+                Err(e) => error!("Failed to decode image {}: {}", src, e),
+            }
+        }
+    }
+    
+    /// Paint a page's uploaded images at their laid out rects, with the page's top left at `origin`
+    fn paint_page_images(&self, ui: &egui::Ui, tab: &Tab, origin: egui::Pos2) {
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+        for image in tab.lazy_images.images() {
+            let Some(texture) = tab.image_textures.get(&image.src) else {
+                continue;
+            };
+            let rect = egui::Rect::from_min_size(
+                origin + egui::vec2(image.rect.x, image.rect.y) * tab.zoom,
+                egui::vec2(image.rect.width, image.rect.height) * tab.zoom,
+            );
+            ui.painter().image(texture.id(), rect, uv, egui::Color32::WHITE);
+        }
+    }
+    
+    /// Record a new scroll offset for the active page, loading images scrolled into view
+    fn handle_scroll(&mut self, offset: f32) {
+        let tab_index = self.active_tab_index;
+        let Some(tab) = self.tabs.get_mut(tab_index) else {
+            return;
+        };
+        tab.scroll_offset = offset;
+        self.load_visible_images(tab_index);
+    }
+    
+    /// Move the hover in the active tab to a node, returning the nodes that need restyling
+    fn update_hover_target(&mut self, target: Option<NodeId>) -> Vec<NodeId> {
+        let Some(tab) = self.tabs.get_mut(self.active_tab_index) else {
+            return Vec::new();
+        };
+        let Some(document) = &tab.dom else {
+            return Vec::new();
+        };
+        tab.interaction.set_hover_target(document.get_dom_tree(), target)
+    }
+    
+    /// Dispatch a click event at a node of the active page
+    ///
+    /// Returns false if a listener cancelled the default action.
+    fn dispatch_click(&self, target: NodeId) -> VeloraResult<bool> {
+        let Some(document) = self.get_active_tab().and_then(|tab| tab.dom.as_ref()) else {
+            return Ok(true);
+        };
+        document.get_dom_tree().dispatch_event(target, Event::new(EventType::Click, target))
+    }
+    
+    /// Open the link under a node of the active page in a new tab, staying on the current one
+    ///
+    /// Returns the new tab's index, or None if the node is not inside a link.
+    fn open_link_in_background_tab(&mut self, target: NodeId) -> Option<usize> {
+        let url = self.get_active_tab()
+            .and_then(|tab| tab.dom.as_ref())
+            .and_then(|document| document.link_url(target))?;
+        
+        let active_tab_index = self.active_tab_index;
+        self.add_new_tab();
+        self.active_tab_index = active_tab_index;
+        
+        let tab_index = self.tabs.len() - 1;
+        let tab_id = self.tabs[tab_index].id;
+        self.tabs[tab_index].navigate_to(url.clone());
+        self.url_history.record_visit(&url);
+        self.navigation_history.record(&url);
+        info!("Opening {} in a background tab", url);
+        self.navigation_queue.push(NavigationRequest { url, tab_id });
+        Some(tab_index)
+    }
+    
+    /// Upload decoded favicons to the GPU for tabs that don't have a texture yet
+    fn upload_favicons(&mut self, ctx: &egui::Context) {
+        for tab in &mut self.tabs {
+            if let (Some(icon), None) = (&tab.favicon, &tab.favicon_texture) {
+                let image = egui::ColorImage::from_rgba_unmultiplied(
+                    [icon.width as usize, icon.height as usize],
+                    &icon.pixels,
+                );
+                tab.favicon_texture = Some(ctx.load_texture(
+                    format!("favicon-{}", tab.id),
+                    image,
+                    egui::TextureOptions::LINEAR,
+                ));
+            }
+        }
+    }
     
     fn render_dom_content(&self, ui: &mut egui::Ui, document: &Document) {
         if let Some(root_node) = document.get_dom_tree().get_root() {
@@ -327,10 +2118,14 @@ impl BrowserApp {
                     }
                     "a" => {
                         if let Some(text) = &node.node_value {
-                            if ui.link(text).clicked() {
+                            let response = ui.link(text);
+                            if response.clicked() {
                                 // TODO: Handle link clicks
                                 info!("Link clicked: {}", text);
                             }
+                            if response.middle_clicked() {
+                                self.middle_clicked_node.set(Some(node.id));
+                            }
                         }
                     }
                     _ => {
@@ -350,7 +2145,29 @@ impl BrowserApp {
             }
             NodeType::Text => {
                 if let Some(text) = &node.node_value {
-                    ui.label(text);
+                    let response = if self.current_find_match() == Some(node.id) {
+                        // Highlight the active find-in-page match
+                        ui.label(egui::RichText::new(text).background_color(egui::Color32::YELLOW))
+                    } else {
+                        ui.label(text)
+                    };
+                    let response = response.interact(egui::Sense::click());
+                    if response.hovered() {
+                        self.hovered_node.set(Some(node.id));
+                    }
+                    if response.clicked() {
+                        self.clicked_node.set(Some(node.id));
+                    }
+                    if response.middle_clicked() {
+                        self.middle_clicked_node.set(Some(node.id));
+                    }
+                    response.context_menu(|ui| {
+                        if ui.button("Inspect").clicked() {
+                            // Text takes its style from the enclosing element
+                            self.inspected_node.set(Some(node.parent_id.unwrap_or(node.id)));
+                            ui.close();
+                        }
+                    });
                 }
             }
             _ => {
@@ -363,47 +2180,391 @@ impl BrowserApp {
     }
 }
 
-// Helper function to extract title from HTML content
-fn extract_title_from_html(html: &str) -> String {
-    if let Some(title_start) = html.find("<title>") {
-        if let Some(title_end) = html.find("</title>") {
-            if title_end > title_start {
-                let title = &html[title_start + 7..title_end];
-                return title.trim().to_string();
-            }
+// Pick the UI theme: a manual override wins, then the detected system theme, then dark
+fn resolve_theme(theme_override: Option<egui::Theme>, system_theme: Option<egui::Theme>) -> egui::Theme {
+    theme_override.or(system_theme).unwrap_or(egui::Theme::Dark)
+}
+
+// High-contrast visuals: pure black/white surfaces, yellow selection and thick focus strokes
+fn high_contrast_visuals() -> egui::Visuals {
+    let mut visuals = egui::Visuals::dark();
+    let fg = egui::Color32::WHITE;
+    let bg = egui::Color32::BLACK;
+    let accent = egui::Color32::YELLOW;
+    
+    visuals.override_text_color = Some(fg);
+    visuals.panel_fill = bg;
+    visuals.window_fill = bg;
+    visuals.extreme_bg_color = bg;
+    visuals.faint_bg_color = bg;
+    visuals.hyperlink_color = accent;
+    visuals.selection.bg_fill = accent;
+    visuals.selection.stroke = egui::Stroke::new(2.0, bg);
+    
+    for widget in [
+        &mut visuals.widgets.noninteractive,
+        &mut visuals.widgets.inactive,
+        &mut visuals.widgets.hovered,
+        &mut visuals.widgets.active,
+        &mut visuals.widgets.open,
+    ] {
+        widget.bg_fill = bg;
+        widget.weak_bg_fill = bg;
+        widget.bg_stroke = egui::Stroke::new(2.0, fg);
+        widget.fg_stroke = egui::Stroke::new(2.0, fg);
+    }
+    
+    // Hovered and focused widgets get a thicker accent outline
+    visuals.widgets.hovered.bg_stroke = egui::Stroke::new(3.0, accent);
+    visuals.widgets.active.bg_stroke = egui::Stroke::new(3.0, accent);
+    
+    visuals
+}
+
+// Fetch and decode the page icon, returning None so the tab shows the default glyph
+async fn fetch_favicon(client: &HttpClient, document: &Document, user_agent: Option<&str>) -> Option<DecodedImage> {
+    let favicon_url = document.favicon_url()?;
+    
+    let mut loader = ResourceLoader::with_client(client.clone());
+    loader.set_user_agent(user_agent);
+    let resource = loader.load_resource(&favicon_url).await;
+    let icon = resource
+        .and_then(|resource| ImageRenderer::new()?.decode_image(&resource.data))
+        .and_then(|decoded| decoded.thumbnail(FAVICON_SIZE));
+    
+    match icon {
+        Ok(icon) => Some(icon),
+        Err(e) => {
+            info!("No favicon from {}: {}", favicon_url, e);
+            None
         }
     }
+}
+
+// Fetch and parse the page's stylesheets, skipping any that fail or take longer than `timeout`
+//
+// Linked sheets download concurrently, but their rules keep the document order.
+async fn fetch_stylesheets(client: &HttpClient, document: &Document, user_agent: Option<&'static str>, timeout: Duration) -> Vec<CssRule> {
+    // Dropping the set aborts the fetches, so a cancelled navigation stops them too
+    let mut fetches = tokio::task::JoinSet::new();
+    for (index, stylesheet) in document.collect_stylesheets().into_iter().enumerate() {
+        let client = client.clone();
+        fetches.spawn(async move {
+            let css = match stylesheet {
+                StylesheetRef::Inline(css) => Some(css),
+                StylesheetRef::Linked(url) => match tokio::time::timeout(timeout, fetch_stylesheet(&client, &url, user_agent)).await {
+                    Ok(css) => css,
+                    Err(_) => {
+                        info!("Stylesheet {} timed out after {:?}", url, timeout);
+                        None
+                    }
+                },
+            };
+            (index, css)
+        });
+    }
+    
+    let mut sheets: Vec<(usize, String)> = fetches.join_all().await
+        .into_iter()
+        .filter_map(|(index, css)| Some((index, css?)))
+        .collect();
+    sheets.sort_by_key(|(index, _)| *index);
     
-    // Fallback: extract from first h1 or use URL
-    if let Some(h1_start) = html.find("<h1>") {
-        if let Some(h1_end) = html.find("</h1>") {
-            if h1_end > h1_start {
-                let h1 = &html[h1_start + 4..h1_end];
-                return h1.trim().to_string();
+    let parser = CssParser::new();
+    let mut rules = Vec::new();
+    for (_, css) in sheets {
+        match parser.parse_css(&css) {
+            Ok(parsed) => rules.extend(parsed),
+            Err(e) => info!("Skipping unparsable stylesheet: {}", e),
+        }
+    }
+    rules
+}
+
+// Fetch one linked stylesheet's text, or None if it failed
+async fn fetch_stylesheet(client: &HttpClient, url: &str, user_agent: Option<&str>) -> Option<String> {
+    match client.get_with_user_agent(url, user_agent).await {
+        Ok(response) if response.status.is_success() => match response.text() {
+            Ok(css) => Some(css),
+            Err(e) => {
+                info!("Stylesheet {} is not UTF-8: {}", url, e);
+                None
             }
+        },
+        Ok(response) => {
+            info!("Stylesheet {} failed: {} {}", url, response.status.code, response.status.reason);
+            None
         }
+        Err(e) => {
+            info!("Stylesheet {} failed: {}", url, e);
+            None
+        }
+    }
+}
+
+// Fetch and parse a page, describing the outcome as a navigation result
+async fn load_page(client: Option<HttpClient>, url: String, tab_id: usize, user_agent: Option<UserAgentPreset>) -> NavigationResult {
+    let Some(client) = client else {
+        error!("No HTTP client available");
+        return NavigationResult::failure(
+            tab_id,
+            "Error: Failed to create HTTP client".to_string(),
+            "Failed to create HTTP client".to_string(),
+        );
+    };
+    
+    let user_agent = user_agent.map(|preset| preset.user_agent());
+    let response = match client.open(&url, user_agent).await {
+        Ok(response) => response,
+        Err(e) => {
+            error!("Request failed: {:?}", e);
+            return NavigationResult::failure(
+                tab_id,
+                format!("Request Error: {:?}", e),
+                format!("Request failed: {:?}", e),
+            );
+        }
+    };
+    
+    if !response.status.is_success() {
+        error!("HTTP request failed: {} {}", response.status.code, response.status.reason);
+        return NavigationResult::failure(
+            tab_id,
+            format!("HTTP Error: {} {}", response.status.code, response.status.reason),
+            format!("HTTP request failed: {} {}", response.status.code, response.status.reason),
+        );
+    }
+    
+    // Don't feed images, PDFs and other binaries to the HTML parser, or even read them
+    if let Some(content_type) = response.content_type().filter(|content_type| !is_displayable(content_type)) {
+        let content_type = content_type.to_string();
+        return offer_download(tab_id, url, response, &content_type);
     }
+    let response = match response.read().await {
+        Ok(response) => response,
+        Err(e) => {
+            error!("Request failed: {:?}", e);
+            return NavigationResult::failure(
+                tab_id,
+                format!("Request Error: {:?}", e),
+                format!("Request failed: {:?}", e),
+            );
+        }
+    };
+    
+    let content_type = response.content_type();
+    let parsed = match parse_resource(&content_type, &response.body) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            error!("Failed to parse {}: {}", content_type, e);
+            return NavigationResult::failure(
+                tab_id,
+                format!("Error parsing {}: {}", content_type, e),
+                format!("Parsing failed: {}", e),
+            );
+        }
+    };
     
-    "Untitled".to_string()
+    let success = |content: String, dom: Option<Document>, title: Option<String>, favicon, style_rules| NavigationResult {
+        tab_id,
+        success: true,
+        cancelled: false,
+        content: Some(content),
+        dom,
+        title,
+        favicon,
+        style_rules,
+        error: None,
+        download_offer: None,
+    };
+    
+    match parsed {
+        ParsedResource::Html(mut document) => {
+            info!("Successfully parsed {} bytes of HTML into DOM", response.body.len());
+            document.set_url(url);
+            let favicon = fetch_favicon(&client, &document, user_agent).await;
+            let style_rules = fetch_stylesheets(&client, &document, user_agent, STYLESHEET_TIMEOUT).await;
+            let title = document.title().map(str::to_string);
+            let html_content = String::from_utf8_lossy(&response.body).into_owned();
+            success(html_content, Some(*document), title, favicon, style_rules)
+        }
+        ParsedResource::Stylesheet(rules) => success(describe_rules(&rules), None, None, None, Vec::new()),
+        ParsedResource::Text(text) => success(text, None, None, None, Vec::new()),
+        // Only a sniffed type gets here; the body has been read already, so save that
+        ParsedResource::Unsupported => offer_download(tab_id, url, response.into(), &content_type),
+    }
+}
+
+/// Offer a response the browser cannot display for saving, keeping its body unread
+fn offer_download(tab_id: usize, url: String, response: PendingResponse, content_type: &str) -> NavigationResult {
+    error!("Refusing to render {} as a page", content_type);
+    let suggested_name = suggested_file_name(&url, &response.headers);
+    NavigationResult {
+        download_offer: Some(DownloadOffer { url, suggested_name, response: HeldResponse::new(response) }),
+        ..NavigationResult::failure(
+            tab_id,
+            format!("Error: Cannot display content of type {}", content_type),
+            format!("Unexpected content type: {}", content_type),
+        )
+    }
+}
+
+/// A response body turned into something a tab can show
+#[derive(Debug)]
+enum ParsedResource {
+    /// An HTML page
+    Html(Box<Document>),
+    /// A stylesheet opened directly
+    Stylesheet(Vec<CssRule>),
+    /// Plain text, shown as written rather than as markup
+    Text(String),
+    /// Content the browser cannot display, such as images and binaries
+    Unsupported,
+}
+
+/// Parse a response body according to its Content-Type
+fn parse_resource(content_type: &str, bytes: &[u8]) -> VeloraResult<ParsedResource> {
+    let parsed = match ResourceKind::from_content_type(content_type) {
+        ResourceKind::Html => ParsedResource::Html(Box::new(HtmlParser::new().parse_bytes(bytes)?)),
+        ResourceKind::Stylesheet => {
+            let css = String::from_utf8_lossy(bytes);
+            ParsedResource::Stylesheet(CssParser::new().parse_css(&css)?)
+        }
+        ResourceKind::Text => ParsedResource::Text(String::from_utf8_lossy(bytes).into_owned()),
+        ResourceKind::Script | ResourceKind::Image | ResourceKind::Other => ParsedResource::Unsupported,
+    };
+    Ok(parsed)
+}
+
+/// Whether a tab can show a response of this type, rather than offering to save it
+fn is_displayable(content_type: &str) -> bool {
+    matches!(
+        ResourceKind::from_content_type(content_type),
+        ResourceKind::Html | ResourceKind::Stylesheet | ResourceKind::Text
+    )
+}
+
+/// Readable listing of parsed stylesheet rules, one block per rule
+fn describe_rules(rules: &[CssRule]) -> String {
+    let mut listing = format!("{} rules\n", rules.len());
+    for (index, rule) in rules.iter().enumerate() {
+        listing.push_str(&format!("\nRule {} ({} selectors)\n", index + 1, rule.selectors.len()));
+        for property in &rule.properties {
+            let important = if property.important { " !important" } else { "" };
+            listing.push_str(&format!("    {}: {}{};\n", property.name, property.value, important));
+        }
+    }
+    listing
+}
+
+// Location of the persisted browser session
+fn session_file_path() -> PathBuf {
+    std::env::temp_dir().join("velora_session.json")
+}
+
+fn bookmarks_file_path() -> PathBuf {
+    std::env::temp_dir().join("velora_bookmarks.json")
+}
+
+// Where downloads are saved: ~/Downloads if there is one, otherwise the temp directory
+fn downloads_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(|home| PathBuf::from(home).join("Downloads"))
+        .filter(|dir| dir.is_dir())
+        .unwrap_or_else(std::env::temp_dir)
 }
 
 impl eframe::App for BrowserApp {
+    fn raw_input_hook(&mut self, ctx: &egui::Context, raw_input: &mut egui::RawInput) {
+        self.take_page_focus_keys(ctx, raw_input);
+    }
+    
+    fn clear_color(&self, visuals: &egui::Visuals) -> [f32; 4] {
+        self.window_config.clear_color(visuals)
+    }
+    
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Track the system color scheme (re-evaluated every frame to catch theme changes)
+        self.apply_theme(ctx);
+        self.upload_favicons(ctx);
+        self.upload_page_images(ctx);
+        let viewport = ctx.input(|i| i.viewport().clone());
+        self.process_event(&viewport);
+        self.track_monitor(&viewport);
+        
+        // Nothing is drawn or polled while minimized; restoring the window repaints it
+        if !self.is_rendering_active() {
+            return;
+        }
+        
+        // Keep polling for results while pages and files load in the background
+        self.downloads.poll();
+        if self.tabs.iter().any(|tab| tab.loading) || self.downloads.is_active() {
+            ctx.request_repaint_after(LOADING_POLL_INTERVAL);
+        }
+        
+        // Global find shortcuts (Enter is handled by the find field itself)
+        let key_presses: Vec<(egui::Key, egui::Modifiers)> = ctx.input(|i| {
+            i.events.iter().filter_map(|event| match event {
+                egui::Event::Key { key, pressed: true, modifiers, .. } => Some((*key, *modifiers)),
+                _ => None,
+            }).collect()
+        });
+        self.handle_url_clipboard(ctx);
+        for (key, modifiers) in key_presses {
+            if !self.handle_focus_key(ctx, key)
+                && key != egui::Key::Enter
+                && !self.handle_find_key(key, modifiers)
+                && !self.handle_zoom_key(key, modifiers)
+                && !self.handle_url_key(ctx, key, modifiers)
+                && !self.handle_bookmark_key(key, modifiers)
+                && !self.handle_scroll_key(ctx, key, modifiers)
+            {
+                self.handle_window_key(ctx, key, modifiers);
+            }
+        }
+        
+        self.hovered_node.set(None);
+        self.clicked_node.set(None);
+        self.middle_clicked_node.set(None);
         egui::CentralPanel::default().show(ctx, |ui| {
             // Actions vector for collecting UI actions
             let mut actions = Vec::new();
             
             // Tab bar
             ui.horizontal(|ui| {
+                let mut tab_rects = Vec::with_capacity(self.tabs.len());
+                let mut dropped_tab = None;
+                
                 // Tab list
                 for (index, tab) in self.tabs.iter().enumerate() {
                     let is_active = index == self.active_tab_index;
                     
                     ui.horizontal(|ui| {
-                        // Tab button
-                        if ui.selectable_label(is_active, &tab.title).clicked() {
+                        // Favicon, or a placeholder glyph of the same size
+                        match &tab.favicon_texture {
+                            Some(texture) => {
+                                let size = egui::vec2(FAVICON_SIZE as f32, FAVICON_SIZE as f32);
+                                ui.add(egui::Image::new(texture).fit_to_exact_size(size));
+                            }
+                            None => {
+                                ui.label(DEFAULT_FAVICON_GLYPH);
+                            }
+                        }
+                        
+                        // Tab button (draggable to reorder)
+                        let tab_response = ui
+                            .selectable_label(is_active, &tab.title)
+                            .interact(egui::Sense::drag());
+                        if tab_response.clicked() {
                             actions.push(Action::Switch(index));
                         }
+                        if tab_response.drag_stopped() {
+                            if let Some(pointer) = tab_response.interact_pointer_pos() {
+                                dropped_tab = Some((index, tab.id, pointer.x));
+                            }
+                        }
+                        tab_rects.push(tab_response.rect);
                         
                         // Close button (only show if more than one tab)
                         if self.tabs.len() > 1 && ui.button("×").clicked() {
@@ -412,6 +2573,20 @@ impl eframe::App for BrowserApp {
                     });
                 }
                 
+                // Work out where a dragged tab was dropped
+                if let Some((from, tab_id, pointer_x)) = dropped_tab {
+                    let mut new_index = tab_rects
+                        .iter()
+                        .filter(|rect| rect.center().x < pointer_x)
+                        .count();
+                    if new_index > from {
+                        new_index -= 1;
+                    }
+                    if new_index != from {
+                        actions.push(Action::Move(tab_id, new_index));
+                    }
+                }
+                
                 // New tab button
                 if ui.button("+").clicked() {
                     actions.push(Action::New);
@@ -421,16 +2596,21 @@ impl eframe::App for BrowserApp {
             ui.separator();
             
             // Browser header with URL bar
-            if let Some(active_tab) = self.get_active_tab_mut() {
-                ui.horizontal(|ui| {
-                    // Back button (placeholder)
-                    if ui.button("←").clicked() {
-                        // TODO: Implement back navigation
+            if let Some(active_tab) = self.tabs.get_mut(self.active_tab_index) {
+                let url_changed = ui.horizontal(|ui| {
+                    // Back button
+                    if ui.add_enabled(active_tab.can_go_back(), egui::Button::new("←")).clicked() {
+                        actions.push(Action::Back);
+                    }
+                    
+                    // Forward button
+                    if ui.add_enabled(active_tab.can_go_forward(), egui::Button::new("→")).clicked() {
+                        actions.push(Action::Forward);
                     }
                     
-                    // Forward button (placeholder)
-                    if ui.button("→").clicked() {
-                        // TODO: Implement forward navigation
+                    // Stop button, only while the page is loading
+                    if ui.add_enabled(active_tab.loading, egui::Button::new("⛌")).clicked() {
+                        actions.push(Action::Stop);
                     }
                     
                     // Refresh button (placeholder)
@@ -439,7 +2619,11 @@ impl eframe::App for BrowserApp {
                     }
                     
                     // URL input box
-                    let url_response = ui.text_edit_singleline(&mut active_tab.url);
+                    let url_response = ui.add(egui::TextEdit::singleline(&mut active_tab.url).id(url_input_id()));
+                    if url_response.gained_focus() {
+                        // Like other address bars, focusing selects the whole URL
+                        select_url_text(ui.ctx(), &active_tab.url);
+                    }
                     
                     // Go button
                     if (ui.button("Go").clicked() || url_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))) 
@@ -448,49 +2632,251 @@ impl eframe::App for BrowserApp {
                         // Add navigation action
                         actions.push(Action::Navigate(active_tab.url.clone()));
                     }
-                });
+                    url_response.changed()
+                }).inner;
+                
+                // History suggestions for what has been typed
+                if url_changed {
+                    self.url_suggestions = self.url_history.suggest(&active_tab.url);
+                }
+                for suggestion in &self.url_suggestions {
+                    if ui.selectable_label(false, suggestion).clicked() {
+                        actions.push(Action::Navigate(suggestion.clone()));
+                    }
+                }
             }
             
-            // Execute actions after the UI loop
-            for action in actions {
-                match action {
-                    Action::Switch(index) => self.active_tab_index = index,
-                    Action::Close(index) => self.close_tab(index),
-                    Action::New => self.add_new_tab(),
-                    Action::Navigate(url) => {
-                        // Queue the navigation request to avoid borrowing issues
-                        self.navigation_queue.push(NavigationRequest {
-                            url: url.clone(),
-                            tab_index: self.active_tab_index,
+            // Bookmarks bar
+            if !self.bookmarks.list().is_empty() {
+                let mut removed = None;
+                ui.horizontal_wrapped(|ui| {
+                    for bookmark in self.bookmarks.list() {
+                        let response = ui.button(&bookmark.title).on_hover_text(&bookmark.url);
+                        if response.clicked() {
+                            actions.push(Action::Navigate(bookmark.url.clone()));
+                        }
+                        response.context_menu(|ui| {
+                            if ui.button("Remove bookmark").clicked() {
+                                removed = Some(bookmark.url.clone());
+                                ui.close();
+                            }
                         });
-                        info!("Navigation queued to: {}", url);
                     }
+                });
+                
+                if let Some(url) = removed {
+                    self.bookmarks.remove(&url);
+                    self.save_bookmarks();
+                }
+            }
+            
+            // Color scheme selection (System follows the OS preference)
+            ui.horizontal(|ui| {
+                ui.label("Theme:");
+                ui.radio_value(&mut self.theme_override, None, "System");
+                ui.radio_value(&mut self.theme_override, Some(egui::Theme::Light), "Light");
+                ui.radio_value(&mut self.theme_override, Some(egui::Theme::Dark), "Dark");
+                ui.checkbox(&mut self.high_contrast, "High contrast");
+            });
+            
+            // User-Agent for the active tab, for sites that serve different pages by browser
+            if let Some(tab) = self.tabs.get_mut(self.active_tab_index) {
+                let was_mobile = tab.is_mobile();
+                ui.horizontal(|ui| {
+                    ui.label("User agent:");
+                    ui.radio_value(&mut tab.user_agent, None, "Default");
+                    for preset in UserAgentPreset::ALL {
+                        ui.radio_value(&mut tab.user_agent, Some(preset), preset.name());
+                    }
+                });
+                // The viewport follows at once; the page itself is requested as the new agent on reload
+                if tab.is_mobile() != was_mobile {
+                    self.relayout_page(self.active_tab_index);
+                }
+            }
+            
+            // Find bar
+            if self.find_active {
+                ui.horizontal(|ui| {
+                    ui.label("Find:");
+                    let find_response = ui.text_edit_singleline(&mut self.find_query);
+                    if find_response.changed() {
+                        self.update_find_matches();
+                    }
+                    if find_response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        self.handle_find_key(egui::Key::Enter, egui::Modifiers::NONE);
+                        find_response.request_focus();
+                    }
+                    
+                    if self.find_matches.is_empty() {
+                        ui.label("No matches");
+                    } else {
+                        ui.label(format!("{} of {}", self.find_match_index + 1, self.find_matches.len()));
+                    }
+                    
+                    if ui.button("×").clicked() {
+                        self.handle_find_key(egui::Key::Escape, egui::Modifiers::NONE);
+                    }
+                });
+            }
+            
+            // History panel
+            if !self.navigation_history.entries().is_empty() {
+                let mut cleared = false;
+                ui.collapsing("History", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Search:");
+                        ui.text_edit_singleline(&mut self.history_query);
+                        cleared = ui.button("Clear history").clicked();
+                    });
+                    for entry in self.navigation_history.search(&self.history_query).into_iter().rev() {
+                        let hover = format!("{}\nVisited {}", entry.url, time_ago(entry.timestamp));
+                        if ui.selectable_label(false, &entry.title).on_hover_text(hover).clicked() {
+                            actions.push(Action::Navigate(entry.url.clone()));
+                        }
+                    }
+                });
+                
+                if cleared {
+                    self.navigation_history.clear();
                 }
             }
             
+            // Downloads panel
+            if !self.downloads.list().is_empty() {
+                ui.collapsing("Downloads", |ui| {
+                    for download in self.downloads.list() {
+                        ui.horizontal(|ui| {
+                            let name = download.path.file_name().unwrap_or_default().to_string_lossy();
+                            ui.label(name).on_hover_text(&download.url);
+                            match &download.state {
+                                DownloadState::InProgress => match download.fraction() {
+                                    Some(fraction) => {
+                                        ui.add(egui::ProgressBar::new(fraction).show_percentage());
+                                    }
+                                    None => {
+                                        ui.spinner();
+                                        ui.label(format!("{} bytes", download.received));
+                                    }
+                                },
+                                DownloadState::Completed => {
+                                    ui.label(format!("Done, {} bytes", download.received));
+                                }
+                                DownloadState::Failed(e) => {
+                                    ui.label(format!("Failed: {}", e));
+                                }
+                            }
+                        });
+                    }
+                });
+            }
+            
+            // Network panel: recent requests with their status, size and timing, newest first;
+            // only the rows scrolled into view are read from the log
+            let network_log = self.http_client.as_ref()
+                .and_then(HttpClient::network_log)
+                .filter(|log| !log.is_empty());
+            if let Some(log) = network_log {
+                ui.collapsing("Network", |ui| {
+                    let row_height = ui.spacing().interact_size.y;
+                    egui::ScrollArea::vertical()
+                        .id_salt("network")
+                        .max_height(NETWORK_PANEL_HEIGHT)
+                        .show_rows(ui, row_height, log.len(), |ui, rows| log.with_entries(|requests| {
+                            for request in requests.iter().rev().skip(rows.start).take(rows.len()) {
+                                ui.horizontal(|ui| {
+                                    let status = request.status.map_or("failed".to_string(), |code| code.to_string());
+                                    ui.label(format!("{} {}", request.method, status));
+                                    ui.label(&request.url).on_hover_text(time_ago(request.started));
+                                    ui.label(format!("{} bytes in {} ms", request.bytes, request.duration.as_millis()));
+                                });
+                            }
+                        }));
+                });
+            }
+            
+            // Inspector panel: computed style of the node picked with "Inspect"
+            if let Some(node_id) = self.inspected_node.get() {
+                let mut properties: Vec<_> = self.computed_style(node_id).into_iter().collect();
+                properties.sort_by(|a, b| a.0.cmp(&b.0));
+                ui.collapsing("Inspector", |ui| {
+                    for (name, value) in properties {
+                        ui.label(format!("{}: {:?}", name, value));
+                    }
+                });
+            }
+            
+            // Persist the session whenever the tab state changes
+            let session_changed = !actions.is_empty();
+            
+            // Execute actions after the UI loop
+            for action in actions {
+                self.handle_action(action);
+            }
+            
+            if session_changed {
+                self.save_session();
+            }
+            
             // Process navigation requests after the UI loop
             self.process_navigation_queue();
             
             // Process navigation results
             self.process_navigation_results();
+            self.process_loaded_images();
             
             ui.separator();
             
             // Content area
+            let mut save_offer = None;
+            let scroll_request = self.scroll_request.take();
             if let Some(active_tab) = self.get_active_tab() {
                 ui.allocate_ui(ui.available_size(), |ui| {
+                    self.content_rect.set(Some(to_rect(ui.max_rect())));
+                    
+                    // Scale page text and spacing by the tab's zoom level
+                    let zoom = active_tab.zoom;
+                    let style = ui.style_mut();
+                    for font in style.text_styles.values_mut() {
+                        font.size *= zoom;
+                    }
+                    style.spacing.item_spacing *= zoom;
+                    
                     if active_tab.loading {
                         ui.centered_and_justified(|ui| {
                             ui.label("Loading...");
                             ui.label(format!("Fetching: {}", active_tab.url));
                         });
+                    } else if let Some(offer) = &active_tab.download_offer {
+                        ui.label(active_tab.content.as_deref().unwrap_or_default());
+                        if ui.button(format!("Save {}", offer.suggested_name)).clicked() {
+                            save_offer = Some(offer.clone());
+                        }
                     } else if let Some(content) = &active_tab.content {
                         if let Some(document) = &active_tab.dom {
-                            // Render DOM content
-                            self.render_dom_content(ui, document);
+                            // Clear to the page background, then render DOM content
+                            ui.painter().rect_filled(ui.max_rect(), 0.0, to_color32(self.page_clear_color()));
+                            let mut area = egui::ScrollArea::vertical().id_salt(("page", active_tab.id));
+                            if let Some(offset) = scroll_request {
+                                area = area.vertical_scroll_offset(offset);
+                            }
+                            let scroll = area.show(ui, |ui| {
+                                // Images are placed by the engine layout, from the top of the scrolled content
+                                let origin = ui.cursor().min;
+                                ui.set_min_height(active_tab.page_height * zoom);
+                                self.paint_page_images(ui, active_tab, origin);
+                                self.render_dom_content(ui, document);
+                            });
+                            self.page_extent.set(Some(ScrollExtent {
+                                content_height: scroll.content_size.y,
+                                viewport_height: scroll.inner_rect.height(),
+                            }));
+                            if scroll.state.offset.y != active_tab.scroll_offset {
+                                self.scrolled_to.set(Some(scroll.state.offset.y));
+                            }
                         } else {
                             // Fallback to raw content display
-                            ui.label("Raw HTML Content:");
+                            ui.label("Page source:");
                             ui.separator();
                             ui.text_edit_multiline(&mut content.clone());
                         }
@@ -504,24 +2890,1865 @@ impl eframe::App for BrowserApp {
                     }
                 });
             }
+            if let Some(offer) = save_offer {
+                self.handle_action(Action::Download(offer));
+            }
         });
+        
+        if let Some(target) = self.clicked_node.take() {
+            if let Err(e) = self.dispatch_click(target) {
+                error!("Failed to dispatch click: {}", e);
+            }
+        }
+        
+        if let Some(target) = self.middle_clicked_node.take() {
+            self.open_link_in_background_tab(target);
+        }
+        
+        if let Some(offset) = self.scrolled_to.take() {
+            self.handle_scroll(offset);
+        }
+        self.relayout_if_resized(self.active_tab_index);
+        
+        // Hover changes restyle the affected nodes on the next frame
+        let hovered = self.hovered_node.take();
+        if !self.update_hover_target(hovered).is_empty() {
+            ctx.request_repaint();
+        }
     }
 }
 
-fn main() -> Result<(), eframe::Error> {
-    // Initialize logging
-    env_logger::init();
-    
-    let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1200.0, 800.0])
-            .with_min_inner_size([800.0, 600.0]),
-        ..Default::default()
-    };
-    
+/// Convert an engine color to an egui color
+fn to_color32(color: Color) -> egui::Color32 {
+    egui::Color32::from_rgba_unmultiplied(color.r, color.g, color.b, color.a)
+}
+
+/// Convert an egui color to an engine color
+fn from_color32(color: egui::Color32) -> Color {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    Color::rgba(r, g, b, a)
+}
+
+/// Convert an egui screen rect to an engine rect
+fn to_rect(rect: egui::Rect) -> Rect {
+    Rect::new(rect.min.x, rect.min.y, rect.width(), rect.height())
+}
+
+/// An `<img>` with a source, placed by a layout of its page
+#[derive(Debug, Clone, PartialEq)]
+struct PageImage {
+    /// Source URL, resolved against the page
+    src: String,
+    /// Layout rect in page coordinates
+    rect: Rect,
+    /// Whether `loading="eager"` asks for it right away
+    eager: bool,
+}
+
+/// Images of a page, each held back until it comes into view
+#[derive(Debug, Clone, Default)]
+struct LazyImages {
+    /// Every `<img>` with a source, at its rect from the latest layout
+    images: Vec<PageImage>,
+    /// Sources being fetched, not requested again until they arrive
+    requested: HashSet<String>,
+}
+
+impl LazyImages {
+    /// Place every `<img>` with a source at its rect from a new layout of the page
+    fn set_layout(&mut self, document: &Document, rects: &HashMap<NodeId, Rect>) {
+        let tree = document.get_dom_tree();
+        self.images = document.get_elements_by_tag_name("img").into_iter()
+            .filter_map(|node| {
+                let element = tree.get_element(node.element_id?).ok()?;
+                let src = element.get_attribute("src").filter(|src| !src.trim().is_empty())?;
+                Some(PageImage {
+                    src: document.resolve_url(src)?,
+                    rect: *rects.get(&node.id)?,
+                    eager: element.get_attribute("loading").is_some_and(|loading| loading.eq_ignore_ascii_case("eager")),
+                })
+            })
+            .collect();
+    }
+    
+    /// The page's images, in document order
+    fn images(&self) -> &[PageImage] {
+        &self.images
+    }
+    
+    /// Images meeting a viewport in page coordinates
+    fn visible(&self, viewport: Rect) -> impl Iterator<Item = &PageImage> {
+        self.images.iter().filter(move |image| image.rect.intersects(&viewport))
+    }
+    
+    /// Take the sources of images due for a viewport, in page coordinates, that are neither
+    /// stored in `loaded` nor already being fetched
+    ///
+    /// An image the cache has since dropped is fetched again once it is due.
+    fn take_visible(&mut self, viewport: Rect, loaded: &ImageRenderer) -> Vec<String> {
+        let mut sources = Vec::new();
+        for image in &self.images {
+            // Another copy of a fetched image needs no request of its own
+            if should_load_image(image.rect, viewport, image.eager)
+                && !loaded.contains_image(&image.src)
+                && self.requested.insert(image.src.clone())
+            {
+                sources.push(image.src.clone());
+            }
+        }
+        sources
+    }
+}
+
+/// An image fetched and decoded for a tab
+struct LoadedImage {
+    tab_id: usize,
+    src: String,
+    /// Encoded data, kept to decode the image again after it is evicted
+    data: Vec<u8>,
+    image: DecodedImage,
+}
+
+/// Heights that bound how far a page can scroll
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ScrollExtent {
+    content_height: f32,
+    viewport_height: f32,
+}
+
+impl ScrollExtent {
+    /// Keep a scroll offset between the top of the page and its last screenful
+    fn clamp(&self, offset: f32) -> f32 {
+        offset.clamp(0.0, (self.content_height - self.viewport_height).max(0.0))
+    }
+    
+    /// Scroll offset after a scrolling key, or None if the key does not scroll
+    ///
+    /// Space and Page Up/Down move a screenful less `PAGE_SCROLL_OVERLAP`, the arrow keys
+    /// move `SCROLL_STEP`, and Home and End jump to the top and bottom.
+    fn scroll_for_key(&self, offset: f32, key: egui::Key, modifiers: egui::Modifiers) -> Option<f32> {
+        if modifiers.command || modifiers.alt {
+            return None;
+        }
+        
+        let page = (self.viewport_height - PAGE_SCROLL_OVERLAP).max(SCROLL_STEP);
+        let target = match key {
+            egui::Key::Space if modifiers.shift => offset - page,
+            egui::Key::Space | egui::Key::PageDown => offset + page,
+            egui::Key::PageUp => offset - page,
+            egui::Key::ArrowDown => offset + SCROLL_STEP,
+            egui::Key::ArrowUp => offset - SCROLL_STEP,
+            egui::Key::Home => 0.0,
+            egui::Key::End => f32::INFINITY,
+            _ => return None,
+        };
+        Some(self.clamp(target))
+    }
+}
+
+/// Whether to fetch an image now: eager images always, lazy ones once they meet the viewport
+fn should_load_image(rect: Rect, viewport: Rect, eager: bool) -> bool {
+    eager || rect.intersects(&viewport)
+}
+
+/// Lay out a local HTML file without opening a window and print the rects
+fn run_layout(path: &str) -> VeloraResult<()> {
+    let html = std::fs::read_to_string(path)?;
+    let [width, height] = DEFAULT_WINDOW_SIZE;
+    let rects = render_pipeline(&html, Size::new(width, height), &ImageRenderer::new()?)?;
+    
+    let mut rects: Vec<_> = rects.into_iter().collect();
+    rects.sort_by_key(|(node_id, _)| node_id.0);
+    for (node_id, rect) in rects {
+        println!("{}: {} {} {}x{}", node_id.0, rect.x, rect.y, rect.width, rect.height);
+    }
+    Ok(())
+}
+
+/// Append one line per node of a subtree to a DOM dump, indented two spaces per level
+fn dump_node(document: &Document, node: &Node, depth: usize, dump: &mut String) {
+    let tree = document.get_dom_tree();
+    let label = match node.node_type {
+        NodeType::Element => {
+            let mut label = node.node_name.clone();
+            if let Some(element) = node.element_id.and_then(|id| tree.get_element(id).ok()) {
+                if let Some(id) = element.get_attribute("id") {
+                    label.push_str(&format!("#{}", id));
+                }
+                for class in element.get_attribute("class").unwrap_or("").split_ascii_whitespace() {
+                    label.push_str(&format!(".{}", class));
+                }
+            }
+            label
+        }
+        NodeType::Text => {
+            let text = node.node_value.as_deref().unwrap_or("");
+            let mut shown: String = text.chars().take(DUMP_TEXT_LIMIT).collect();
+            if text.chars().count() > DUMP_TEXT_LIMIT {
+                shown.push('…');
+            }
+            format!("{:?}", shown)
+        }
+        NodeType::Comment => format!("<!--{}-->", node.node_value.as_deref().unwrap_or("")),
+        _ => node.node_name.clone(),
+    };
+    dump.push_str(&format!("{}{}\n", "  ".repeat(depth), label));
+    
+    for child in node.child_ids.iter().filter_map(|&child_id| tree.get_node(child_id).ok()) {
+        dump_node(document, child, depth + 1, dump);
+    }
+}
+
+/// Load a page without opening a window
+fn load_headless(url: &str) -> BrowserApp {
+    let mut app = BrowserApp::with_runtime();
+    app.add_new_tab();
+    if let Some(tab) = app.get_active_tab_mut() {
+        tab.navigate_to(url.to_string());
+    }
+    let tab_id = app.tabs[0].id;
+    app.navigation_queue.push(NavigationRequest { url: url.to_string(), tab_id });
+    app.process_navigation_queue();
+    
+    // Loads run in the background, so wait for the page to arrive
+    while app.get_active_tab().is_some_and(|tab| tab.loading) {
+        std::thread::sleep(LOADING_POLL_INTERVAL);
+        app.process_navigation_results();
+    }
+    app
+}
+
+/// Load a page without opening a window and save it as a PNG
+fn run_headless(url: &str, output: &str) -> VeloraResult<()> {
+    let app = load_headless(url);
+    let [width, height] = DEFAULT_WINDOW_SIZE;
+    app.save_screenshot(output, Size::new(width, height))
+}
+
+/// Set up egui options the browser relies on, once at startup
+fn configure_context(ctx: &egui::Context) {
+    // Ctrl +/-/0 zoom the page; egui would otherwise also scale the whole UI
+    ctx.options_mut(|options| options.zoom_with_keyboard = false);
+}
+
+fn main() -> Result<(), eframe::Error> {
+    // Initialize logging
+    env_logger::init();
+    
+    // Headless mode: velora_browser --screenshot <url> <output.png>
+    let args: Vec<String> = std::env::args().collect();
+    if let [_, flag, url, output] = args.as_slice() {
+        if flag == "--screenshot" {
+            if let Err(e) = run_headless(url, output) {
+                error!("Screenshot of {} failed: {}", url, e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+    }
+    
+    // Layout dump: velora_browser --layout <file.html>
+    if let [_, flag, path] = args.as_slice() {
+        if flag == "--layout" {
+            if let Err(e) = run_layout(path) {
+                error!("Layout of {} failed: {}", path, e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+    }
+    
+    // DOM dump: velora_browser --dump-dom <url>
+    if let [_, flag, url] = args.as_slice() {
+        if flag == "--dump-dom" {
+            print!("{}", load_headless(url).dump_dom_tree());
+            return Ok(());
+        }
+    }
+    
+    let window_config = WindowConfig::default()
+        .with_transparent(args.iter().any(|arg| arg == "--transparent"))
+        .with_blur(args.iter().any(|arg| arg == "--blur"));
+    let max_history = args.iter()
+        .position(|arg| arg == "--max-history")
+        .and_then(|index| args.get(index + 1)?.parse::<usize>().ok());
+    if window_config.blur && !window_config.blur_enabled() {
+        info!("Background blur needs --transparent on macOS or Windows; ignoring --blur");
+    }
+    
+    let options = eframe::NativeOptions {
+        viewport: window_config.viewport(),
+        ..Default::default()
+    };
+    
     eframe::run_native(
         "Velora Browser",
         options,
-        Box::new(|_cc| Ok(Box::new(BrowserApp::new()))),
+        Box::new(move |cc| {
+            configure_context(&cc.egui_ctx);
+            let mut app = BrowserApp::new();
+            app.window_config = window_config;
+            if let Some(max_history) = max_history {
+                app.set_max_history(max_history);
+            }
+            Ok(Box::new(app))
+        }),
     )
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use velora_parser::{CssParser, InlineStyle};
+    use velora_dom::viewport::LEGACY_MOBILE_WIDTH;
+    
+    fn app_with_tabs(count: usize) -> BrowserApp {
+        let mut app = BrowserApp::default();
+        for _ in 0..count {
+            app.add_new_tab();
+        }
+        app
+    }
+    
+    fn tab_order(app: &BrowserApp) -> Vec<usize> {
+        app.tabs.iter().map(|tab| tab.id).collect()
+    }
+    
+    #[test]
+    fn test_session_round_trip() {
+        let mut app = app_with_tabs(2);
+        app.tabs[0].navigate_to("https://example.com".to_string());
+        app.tabs[0].navigate_to("https://example.com/about".to_string());
+        app.tabs[0].title = "About".to_string();
+        app.tabs[1].navigate_to("https://rust-lang.org".to_string());
+        app.tabs[1].title = "Rust".to_string();
+        app.active_tab_index = 0;
+        
+        let json = app.serialize_session();
+        
+        let mut restored = app_with_tabs(1);
+        restored.restore_session(&json).unwrap();
+        
+        assert_eq!(restored.tabs.len(), 2);
+        assert_eq!(restored.active_tab_index, 0);
+        
+        let first = &restored.tabs[0];
+        assert_eq!(first.url, "https://example.com/about");
+        assert_eq!(first.title, "About");
+        assert_eq!(first.history, vec!["https://example.com", "https://example.com/about"]);
+        assert_eq!(first.history_index, 1);
+        
+        let second = &restored.tabs[1];
+        assert_eq!(second.url, "https://rust-lang.org");
+        assert_eq!(second.title, "Rust");
+        
+        // Restored history is usable for back navigation
+        let first = &mut restored.tabs[0];
+        assert_eq!(first.go_back(), Some("https://example.com".to_string()));
+    }
+    
+    #[test]
+    fn test_session_restores_active_tab() {
+        let mut app = app_with_tabs(3);
+        app.active_tab_index = 2;
+        app.tabs[2].title = "Third".to_string();
+        
+        let mut restored = BrowserApp::default();
+        restored.restore_session(&app.serialize_session()).unwrap();
+        
+        assert_eq!(restored.active_tab_index, 2);
+        assert_eq!(restored.get_active_tab().unwrap().title, "Third");
+    }
+    
+    #[test]
+    fn test_restore_malformed_session() {
+        let mut app = app_with_tabs(1);
+        
+        let result = app.restore_session("{ not json");
+        assert!(matches!(result, Err(VeloraError::Serialization(_))));
+        
+        let result = app.restore_session(r#"{"tabs":[],"active_tab_index":0}"#);
+        assert!(result.is_err());
+        
+        // The existing tabs are left untouched
+        assert_eq!(app.tabs.len(), 1);
+    }
+    
+    fn app_with_find_document() -> (BrowserApp, Vec<NodeId>) {
+        let mut app = app_with_tabs(1);
+        let mut document = Document::default();
+        let body = document.create_element("body").unwrap();
+        let mut text_ids = Vec::new();
+        for text in ["velora engine", "rendering", "Velora browser"] {
+            let text_id = document.create_text_node(text).unwrap();
+            document.append_child(body, text_id).unwrap();
+            text_ids.push(text_id);
+        }
+        app.tabs[0].dom = Some(document);
+        (app, text_ids)
+    }
+    
+    #[test]
+    fn test_find_shortcut_toggles_find_bar() {
+        let (mut app, _) = app_with_find_document();
+        assert!(!app.find_active);
+        
+        assert!(app.handle_find_key(egui::Key::F, egui::Modifiers::COMMAND));
+        assert!(app.find_active);
+        
+        assert!(app.handle_find_key(egui::Key::F, egui::Modifiers::COMMAND));
+        assert!(!app.find_active);
+        
+        // Plain F without the modifier is not a shortcut
+        assert!(!app.handle_find_key(egui::Key::F, egui::Modifiers::NONE));
+        assert!(!app.find_active);
+        
+        // Escape closes an open find bar
+        app.handle_find_key(egui::Key::F, egui::Modifiers::COMMAND);
+        assert!(app.handle_find_key(egui::Key::Escape, egui::Modifiers::NONE));
+        assert!(!app.find_active);
+        assert!(!app.handle_find_key(egui::Key::Escape, egui::Modifiers::NONE));
+    }
+    
+    #[test]
+    fn test_find_enter_cycles_matches() {
+        let (mut app, text_ids) = app_with_find_document();
+        app.find_query = "velora".to_string();
+        app.handle_find_key(egui::Key::F, egui::Modifiers::COMMAND);
+        
+        assert_eq!(app.find_matches, vec![text_ids[0], text_ids[2]]);
+        assert_eq!(app.current_find_match(), Some(text_ids[0]));
+        
+        app.handle_find_key(egui::Key::Enter, egui::Modifiers::NONE);
+        assert_eq!(app.current_find_match(), Some(text_ids[2]));
+        
+        // Wraps back to the first match
+        app.handle_find_key(egui::Key::Enter, egui::Modifiers::NONE);
+        assert_eq!(app.current_find_match(), Some(text_ids[0]));
+    }
+    
+    #[test]
+    fn test_zoom_is_clamped() {
+        let mut app = app_with_tabs(1);
+        assert_eq!(app.tabs[0].zoom, 1.0);
+        
+        for _ in 0..100 {
+            app.handle_zoom_key(egui::Key::Plus, egui::Modifiers::COMMAND);
+        }
+        assert_eq!(app.tabs[0].zoom, MAX_ZOOM);
+        
+        for _ in 0..100 {
+            app.handle_zoom_key(egui::Key::Minus, egui::Modifiers::COMMAND);
+        }
+        assert_eq!(app.tabs[0].zoom, MIN_ZOOM);
+    }
+    
+    #[test]
+    fn test_zoom_reset_and_per_tab() {
+        let mut app = app_with_tabs(2);
+        app.active_tab_index = 0;
+        
+        assert!(app.handle_zoom_key(egui::Key::Plus, egui::Modifiers::COMMAND));
+        assert!(app.handle_zoom_key(egui::Key::Plus, egui::Modifiers::COMMAND));
+        assert!((app.tabs[0].zoom - 1.2).abs() < f32::EPSILON);
+        
+        // Other tabs keep their own zoom level
+        assert_eq!(app.tabs[1].zoom, 1.0);
+        
+        assert!(app.handle_zoom_key(egui::Key::Num0, egui::Modifiers::COMMAND));
+        assert_eq!(app.tabs[0].zoom, 1.0);
+        
+        // Shortcuts require the command modifier
+        assert!(!app.handle_zoom_key(egui::Key::Plus, egui::Modifiers::NONE));
+        assert_eq!(app.tabs[0].zoom, 1.0);
+    }
+    
+    #[test]
+    fn test_resolve_theme_follows_system() {
+        assert_eq!(resolve_theme(None, Some(egui::Theme::Light)), egui::Theme::Light);
+        assert_eq!(resolve_theme(None, Some(egui::Theme::Dark)), egui::Theme::Dark);
+        
+        // Falls back to dark when the platform can't report a preference
+        assert_eq!(resolve_theme(None, None), egui::Theme::Dark);
+    }
+    
+    #[test]
+    fn test_theme_override_disables_detection() {
+        assert_eq!(resolve_theme(Some(egui::Theme::Dark), Some(egui::Theme::Light)), egui::Theme::Dark);
+        assert_eq!(resolve_theme(Some(egui::Theme::Light), None), egui::Theme::Light);
+        
+        let ctx = egui::Context::default();
+        let mut app = app_with_tabs(1);
+        app.theme_override = Some(egui::Theme::Light);
+        app.apply_theme(&ctx);
+        assert_eq!(app.applied_theme, Some((egui::Theme::Light, false)));
+        assert_eq!(ctx.theme(), egui::Theme::Light);
+    }
+    
+    // WCAG relative luminance of an sRGB color
+    fn relative_luminance(color: egui::Color32) -> f32 {
+        let channel = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.03928 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+        };
+        0.2126 * channel(color.r()) + 0.7152 * channel(color.g()) + 0.0722 * channel(color.b())
+    }
+    
+    fn contrast_ratio(a: egui::Color32, b: egui::Color32) -> f32 {
+        let (la, lb) = (relative_luminance(a), relative_luminance(b));
+        (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+    }
+    
+    #[test]
+    fn test_high_contrast_tab_colors() {
+        let visuals = high_contrast_visuals();
+        
+        // Active tab: selection text over the selection fill
+        let active_ratio = contrast_ratio(visuals.selection.stroke.color, visuals.selection.bg_fill);
+        // Inactive tab: regular widget text over the panel background
+        let inactive_ratio = contrast_ratio(visuals.widgets.inactive.fg_stroke.color, visuals.panel_fill);
+        
+        // WCAG AAA requires at least 7:1
+        assert!(active_ratio >= 7.0, "active tab contrast {}", active_ratio);
+        assert!(inactive_ratio >= 7.0, "inactive tab contrast {}", inactive_ratio);
+        
+        // Active and inactive tab text must be distinguishable from each other
+        assert!(contrast_ratio(visuals.selection.stroke.color, visuals.widgets.inactive.fg_stroke.color) >= 7.0);
+        
+        // Focus indicators are thicker than the default theme's
+        assert!(visuals.widgets.hovered.bg_stroke.width > egui::Visuals::dark().widgets.hovered.bg_stroke.width);
+    }
+    
+    #[test]
+    fn test_high_contrast_is_applied() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_tabs(1);
+        app.theme_override = Some(egui::Theme::Light);
+        app.high_contrast = true;
+        app.apply_theme(&ctx);
+        
+        assert_eq!(app.applied_theme, Some((egui::Theme::Dark, true)));
+        assert_eq!(ctx.style().visuals.panel_fill, egui::Color32::BLACK);
+    }
+    
+    #[test]
+    fn test_zoom_keys_leave_ui_scale_alone() {
+        let ctx = egui::Context::default();
+        configure_context(&ctx);
+        let mut app = app_with_tabs(1);
+        
+        let zoom_in = egui::Event::Key {
+            key: egui::Key::Plus,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: egui::Modifiers::COMMAND,
+        };
+        for events in [vec![zoom_in], Vec::new()] {
+            let input = egui::RawInput { events, ..Default::default() };
+            let _ = ctx.run(input, |ctx| {
+                let pressed = ctx.input(|i| i.events.iter().find_map(|event| match event {
+                    egui::Event::Key { key, pressed: true, modifiers, .. } => Some((*key, *modifiers)),
+                    _ => None,
+                }));
+                if let Some((key, modifiers)) = pressed {
+                    app.handle_zoom_key(key, modifiers);
+                }
+            });
+        }
+        
+        assert_eq!(app.tabs[0].zoom, 1.0 + ZOOM_STEP);
+        assert_eq!(ctx.zoom_factor(), 1.0);
+    }
+    
+    #[test]
+    fn test_favicon_texture_upload() {
+        let ctx = egui::Context::default();
+        let mut app = app_with_tabs(2);
+        app.tabs[0].favicon = Some(DecodedImage {
+            width: 2,
+            height: 2,
+            pixels: vec![255; 2 * 2 * 4],
+        });
+        
+        app.upload_favicons(&ctx);
+        
+        let texture = app.tabs[0].favicon_texture.as_ref().unwrap();
+        assert_eq!(texture.size(), [2, 2]);
+        // Tabs without an icon keep the default glyph
+        assert!(app.tabs[1].favicon_texture.is_none());
+    }
+    
+    #[test]
+    fn test_move_tab_forward() {
+        let mut app = app_with_tabs(4);
+        app.active_tab_index = 1;
+        
+        app.move_tab(0, 2);
+        assert_eq!(tab_order(&app), vec![1, 2, 0, 3]);
+        
+        // The active tab (id 1) is still selected
+        assert_eq!(app.get_active_tab().unwrap().id, 1);
+    }
+    
+    #[test]
+    fn test_move_tab_backward() {
+        let mut app = app_with_tabs(4);
+        app.active_tab_index = 3;
+        
+        app.move_tab(3, 0);
+        assert_eq!(tab_order(&app), vec![3, 0, 1, 2]);
+        assert_eq!(app.active_tab_index, 0);
+        assert_eq!(app.get_active_tab().unwrap().id, 3);
+    }
+    
+    #[test]
+    fn test_move_tab_clamps_index() {
+        let mut app = app_with_tabs(3);
+        app.active_tab_index = 0;
+        
+        app.move_tab(0, 99);
+        assert_eq!(tab_order(&app), vec![1, 2, 0]);
+        assert_eq!(app.get_active_tab().unwrap().id, 0);
+        
+        // Unknown tab IDs are ignored
+        app.move_tab(42, 0);
+        assert_eq!(tab_order(&app), vec![1, 2, 0]);
+    }
+    
+    #[test]
+    fn test_render_to_image() {
+        let mut app = app_with_tabs(1);
+        let mut document = HtmlParser::new().parse_html("<p>Hello</p>").unwrap();
+        let body = document.body().unwrap().element_id.unwrap();
+        document.get_dom_tree_mut().get_element_mut(body).unwrap().set_style_property("background-color", "#336699");
+        app.tabs[0].dom = Some(document);
+        
+        let image = app.render_to_image(Size::new(64.0, 48.0)).unwrap();
+        let pixels = image.to_argb();
+        assert_eq!((image.width(), image.height()), (64, 48));
+        assert_eq!(pixels.len(), 64 * 48);
+        assert!(pixels.iter().all(|&pixel| pixel == 0xFF336699));
+    }
+    
+    #[test]
+    fn test_render_to_image_uses_stylesheet_rules() {
+        let mut app = app_with_tabs(1);
+        let html = "<!DOCTYPE html><html><head><style>body { background-color: #112233; }</style></head><body><p>Hi</p></body></html>";
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        
+        let stylesheets = document.collect_stylesheets();
+        let [StylesheetRef::Inline(css)] = stylesheets.as_slice() else {
+            panic!("expected one inline stylesheet");
+        };
+        app.tabs[0].style_rules = CssParser::new().parse_css(css).unwrap();
+        app.tabs[0].dom = Some(document);
+        
+        let pixels = app.render_to_image(Size::new(4.0, 4.0)).unwrap().to_argb();
+        assert!(pixels.iter().all(|&pixel| pixel == 0xFF112233));
+    }
+    
+    #[test]
+    fn test_render_to_image_paints_boxes() {
+        let mut app = app_with_tabs(1);
+        let html = r#"<body style="background-color: #112233"><div class="box" style="width: 6px; height: 3px"></div></body>"#;
+        app.tabs[0].style_rules = CssParser::new().parse_css(".box { background-color: #ff0000 }").unwrap();
+        app.tabs[0].dom = Some(HtmlParser::new().parse_html(html).unwrap());
+        
+        let image = app.render_to_image(Size::new(10.0, 5.0)).unwrap();
+        let pixels = image.to_argb();
+        let at = |x: usize, y: usize| pixels[y * 10 + x];
+        assert_eq!(at(0, 0), 0xFFFF0000);
+        assert_eq!(at(5, 2), 0xFFFF0000);
+        assert_eq!(at(6, 0), 0xFF112233);
+        assert_eq!(at(0, 3), 0xFF112233);
+    }
+    
+    #[test]
+    fn test_page_clear_color_from_body_background() {
+        let mut app = app_with_tabs(1);
+        assert_eq!(app.page_clear_color(), Color::white());
+        
+        let document = HtmlParser::new().parse_html("<p>Hi</p>").unwrap();
+        app.tabs[0].style_rules = CssParser::new().parse_css("body { background: #ff0000 }").unwrap();
+        app.tabs[0].dom = Some(document);
+        app.relayout_page(0);
+        let color = app.page_clear_color();
+        assert_eq!((color.r, color.g, color.b, color.a), (255, 0, 0, 255));
+        
+        // A transparent body shows the color scheme's content background
+        app.tabs[0].style_rules = CssParser::new().parse_css("body { background: transparent }").unwrap();
+        app.relayout_page(0);
+        app.applied_theme = Some((egui::Theme::Dark, false));
+        assert_eq!(app.page_clear_color(), from_color32(egui::Visuals::dark().extreme_bg_color));
+    }
+    
+    #[test]
+    fn test_parse_resource_routes_by_content_type() {
+        let html = parse_resource("text/html; charset=utf-8", b"<p>Hi</p>").unwrap();
+        assert!(matches!(html, ParsedResource::Html(document) if document.get_elements_by_tag_name("p").len() == 1));
+        
+        let css = parse_resource("text/css", b"p { color: red; } a { margin: 0 !important; }").unwrap();
+        let ParsedResource::Stylesheet(rules) = css else {
+            panic!("expected a stylesheet");
+        };
+        assert_eq!(rules.len(), 2);
+        assert_eq!(describe_rules(&rules), "2 rules\n\nRule 1 (1 selectors)\n    color: red;\n\nRule 2 (1 selectors)\n    margin: 0 !important;\n");
+        
+        // Plain text keeps its markup characters instead of being parsed
+        let text = parse_resource("TEXT/PLAIN", b"<b>not bold</b>").unwrap();
+        assert!(matches!(text, ParsedResource::Text(text) if text == "<b>not bold</b>"));
+        
+        for content_type in ["image/png", "application/javascript", "application/octet-stream"] {
+            assert!(matches!(parse_resource(content_type, b"\x89PNG").unwrap(), ParsedResource::Unsupported));
+        }
+    }
+    
+    #[test]
+    fn test_dump_dom_tree() {
+        let mut app = app_with_tabs(1);
+        assert_eq!(app.dump_dom_tree(), "");
+        
+        let html = "<div id=\"main\" class=\"card wide\"><p>Hello <em>there</em></p><!-- note --><p>This sentence is long enough to be cut off in the dump</p></div>";
+        app.tabs[0].dom = Some(HtmlParser::new().parse_html(html).unwrap());
+        
+        let expected = [
+            "html",
+            "  head",
+            "  body",
+            "    div#main.card.wide",
+            "      p",
+            "        \"Hello \"",
+            "        em",
+            "          \"there\"",
+            "      <!-- note -->",
+            "      p",
+            "        \"This sentence is long enough to be cut o…\"",
+        ];
+        assert_eq!(app.dump_dom_tree(), expected.join("\n") + "\n");
+    }
+    
+    #[test]
+    fn test_computed_style_inherits_color_not_margin() {
+        let mut app = app_with_tabs(1);
+        let html = r#"<div style="color: #336699; margin: 4px"><span>Hi</span></div>"#;
+        app.tabs[0].dom = Some(HtmlParser::new().parse_html(html).unwrap());
+        let document = app.tabs[0].dom.as_ref().unwrap();
+        let parent = document.get_elements_by_tag_name("div")[0].id;
+        let child = document.get_elements_by_tag_name("span")[0].id;
+        
+        let parent_style = app.computed_style(parent);
+        assert_eq!(parent_style["color"], CssValue::Color(Color::rgb(0x33, 0x66, 0x99)));
+        assert_eq!(parent_style["margin"], CssValue::Length(4.0, velora_core::CssUnit::Px));
+        
+        let child_style = app.computed_style(child);
+        assert_eq!(child_style["color"], CssValue::Color(Color::rgb(0x33, 0x66, 0x99)));
+        assert!(!child_style.contains_key("margin"));
+    }
+    
+    #[test]
+    fn test_render_to_image_without_page() {
+        let app = app_with_tabs(1);
+        let pixels = app.render_to_image(Size::new(8.0, 8.0)).unwrap().to_argb();
+        
+        assert_eq!(pixels.len(), 64);
+        assert_eq!(pixels[0], Color::white().to_u32());
+    }
+    
+    #[test]
+    fn test_save_screenshot() {
+        let app = app_with_tabs(1);
+        let path = std::env::temp_dir().join(format!("velora_screenshot_{}.png", std::process::id()));
+        let path = path.to_str().unwrap();
+        
+        app.save_screenshot(path, Size::new(12.0, 7.0)).unwrap();
+        let data = std::fs::read(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        
+        let image = ImageRenderer::new().unwrap().decode_image(&data).unwrap();
+        assert_eq!((image.width, image.height), (12, 7));
+    }
+    
+    #[test]
+    fn test_save_screenshot_reports_io_errors() {
+        let app = app_with_tabs(1);
+        let path = std::env::temp_dir().join("velora_missing_dir").join("shot.png");
+        
+        let result = app.save_screenshot(path.to_str().unwrap(), Size::new(4.0, 4.0));
+        assert!(matches!(result, Err(VeloraError::Io(_))));
+    }
+    
+    #[tokio::test]
+    async fn test_run_cancellable_completes() {
+        let token = CancellationToken::default();
+        assert_eq!(run_cancellable(&token, async { 7 }).await, Ok(7));
+    }
+    
+    #[tokio::test]
+    async fn test_run_cancellable_resolves_when_signaled() {
+        let token = CancellationToken::default();
+        let signal = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            signal.cancel();
+        });
+        
+        let result = run_cancellable(&token, std::future::pending::<()>()).await;
+        assert_eq!(result, Err(Cancelled));
+        assert!(token.is_cancelled());
+    }
+    
+    #[tokio::test]
+    async fn test_run_cancellable_already_cancelled() {
+        let token = CancellationToken::default();
+        token.cancel();
+        assert_eq!(run_cancellable(&token, async { 7 }).await, Err(Cancelled));
+    }
+    
+    #[test]
+    fn test_cancelled_result_clears_loading() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = app_with_tabs(1);
+        app.result_receiver = Some(receiver);
+        
+        let token = CancellationToken::default();
+        app.tabs[0].loading = true;
+        app.tabs[0].cancel_token = Some(token.clone());
+        app.cancel_navigation(0);
+        
+        sender.send(NavigationResult::cancelled(0)).unwrap();
+        app.process_navigation_results();
+        assert!(!app.tabs[0].loading);
+        assert!(app.tabs[0].cancel_token.is_none());
+    }
+    
+    #[test]
+    fn test_stale_cancelled_result_keeps_newer_load() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = app_with_tabs(1);
+        app.result_receiver = Some(receiver);
+        
+        // A newer navigation replaced the cancelled one
+        app.tabs[0].loading = true;
+        app.tabs[0].cancel_token = Some(CancellationToken::default());
+        
+        sender.send(NavigationResult::cancelled(0)).unwrap();
+        app.process_navigation_results();
+        assert!(app.tabs[0].loading);
+    }
+    
+    #[test]
+    fn test_result_follows_tab_after_close_and_move() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = app_with_tabs(3);
+        app.result_receiver = Some(receiver);
+        let loading_id = app.tabs[2].id;
+        app.tabs[2].loading = true;
+        
+        // Closing an earlier tab and reordering shift the loading tab's index
+        app.close_tab(0);
+        app.move_tab(loading_id, 0);
+        
+        let result = NavigationResult {
+            success: true,
+            title: Some("Loaded".to_string()),
+            ..NavigationResult::failure(loading_id, "page".to_string(), String::new())
+        };
+        sender.send(result).unwrap();
+        app.process_navigation_results();
+        
+        assert_eq!(app.tabs[0].id, loading_id);
+        assert!(!app.tabs[0].loading);
+        assert_eq!(app.tabs[0].title, "Loaded");
+        assert_eq!(app.tabs[1].title, "New Tab");
+    }
+    
+    #[test]
+    fn test_hover_target_matches_hover_selector() {
+        let mut app = app_with_tabs(1);
+        let document = HtmlParser::new().parse_html("<div class=\"feature-card\"><p>Fast</p></div>").unwrap();
+        let card = document.get_elements_by_tag_name("div")[0].id;
+        let paragraph = document.get_elements_by_tag_name("p")[0].id;
+        app.tabs[0].dom = Some(document);
+        
+        let selector = CssParser::new().parse_selector(".feature-card:hover").unwrap();
+        let matches = |app: &BrowserApp| {
+            let tab = &app.tabs[0];
+            selector.matches_with_state(tab.dom.as_ref().unwrap().get_dom_tree(), card, &tab.interaction)
+        };
+        assert!(!matches(&app));
+        
+        let dirty = app.update_hover_target(Some(paragraph));
+        assert!(dirty.contains(&card) && dirty.contains(&paragraph));
+        assert!(matches(&app));
+        
+        // Staying on the same node dirties nothing
+        assert!(app.update_hover_target(Some(paragraph)).is_empty());
+        
+        app.update_hover_target(None);
+        assert!(!matches(&app));
+    }
+    
+    #[test]
+    fn test_click_dispatches_to_listeners() {
+        let mut app = app_with_tabs(1);
+        let mut document = HtmlParser::new().parse_html("<div><p>Press me</p></div>").unwrap();
+        let div = document.get_elements_by_tag_name("div")[0].id;
+        let text = document.find_text("Press me", true)[0];
+        
+        let clicks = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&clicks);
+        document.get_dom_tree_mut().add_event_listener(div, EventType::Click, false, Arc::new(move |event: &mut Event| {
+            recorded.lock().unwrap().push(event.target);
+            event.prevent_default();
+        })).unwrap();
+        app.tabs[0].dom = Some(document);
+        
+        // The click on the text bubbles up to the div's listener
+        assert!(!app.dispatch_click(text).unwrap());
+        assert_eq!(*clicks.lock().unwrap(), vec![text]);
+    }
+    
+    #[test]
+    fn test_stop_action_clears_loading() {
+        let mut app = app_with_tabs(2);
+        let token = CancellationToken::default();
+        app.tabs[1].loading = true;
+        app.tabs[1].cancel_token = Some(token.clone());
+        app.tabs[1].committed_url = Some("https://example.com/".to_string());
+        app.tabs[1].url = "https://example.com/slow".to_string();
+        
+        app.handle_action(Action::Stop);
+        assert!(token.is_cancelled());
+        assert!(!app.tabs[1].loading);
+        assert!(app.tabs[1].cancel_token.is_none());
+        assert_eq!(app.tabs[1].url, "https://example.com/");
+    }
+    
+    #[test]
+    fn test_stop_action_only_affects_active_tab() {
+        let mut app = app_with_tabs(2);
+        app.tabs[0].loading = true;
+        app.tabs[1].url = "https://example.com/typed".to_string();
+        
+        app.handle_action(Action::Stop);
+        assert!(app.tabs[0].loading);
+        assert_eq!(app.tabs[1].url, "https://example.com/typed");
+    }
+    
+    #[test]
+    fn test_window_config_stores_flags() {
+        let config = WindowConfig::default();
+        assert!(!config.transparent);
+        assert!(!config.blur);
+        assert!(!config.blur_enabled());
+        
+        let config = config.with_transparent(true).with_blur(true);
+        assert!(config.transparent);
+        assert!(config.blur);
+        assert_eq!(config.blur_enabled(), BLUR_SUPPORTED);
+        
+        // Blur only applies behind a transparent background
+        assert!(!WindowConfig::default().with_blur(true).blur_enabled());
+    }
+    
+    #[test]
+    fn test_transparent_window_uses_alpha_surface() {
+        let visuals = egui::Visuals::dark();
+        
+        let opaque = WindowConfig::default();
+        assert_eq!(opaque.viewport().transparent, Some(false));
+        assert_eq!(opaque.clear_color(&visuals)[3], 1.0);
+        
+        let transparent = WindowConfig::default().with_transparent(true);
+        assert_eq!(transparent.viewport().transparent, Some(true));
+        assert_eq!(transparent.clear_color(&visuals), [0.0; 4]);
+    }
+    
+    #[test]
+    fn test_transparent_window_uses_translucent_panels() {
+        let mut app = BrowserApp {
+            window_config: WindowConfig::default().with_transparent(true),
+            theme_override: Some(egui::Theme::Dark),
+            ..Default::default()
+        };
+        
+        let ctx = egui::Context::default();
+        app.apply_theme(&ctx);
+        assert!(ctx.style().visuals.panel_fill.a() < 255);
+    }
+    
+    fn viewport_info(minimized: Option<bool>) -> egui::ViewportInfo {
+        egui::ViewportInfo {
+            minimized,
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn test_convert_viewport_event() {
+        assert_eq!(convert_viewport_event(false, &viewport_info(Some(true))), Some(WindowEvent::Minimized));
+        assert_eq!(convert_viewport_event(true, &viewport_info(Some(false))), Some(WindowEvent::Restored));
+        assert_eq!(convert_viewport_event(true, &viewport_info(Some(true))), None);
+        assert_eq!(convert_viewport_event(false, &viewport_info(Some(false))), None);
+        
+        // Unknown state is not a change
+        assert_eq!(convert_viewport_event(true, &viewport_info(None)), None);
+    }
+    
+    #[test]
+    fn test_process_event_tracks_minimized() {
+        let mut app = app_with_tabs(1);
+        assert!(!app.is_minimized());
+        
+        assert_eq!(app.process_event(&viewport_info(Some(true))), Some(WindowEvent::Minimized));
+        assert!(app.is_minimized());
+        assert_eq!(app.process_event(&viewport_info(None)), None);
+        assert!(app.is_minimized());
+        
+        assert_eq!(app.process_event(&viewport_info(Some(false))), Some(WindowEvent::Restored));
+        assert!(!app.is_minimized());
+    }
+    
+    #[test]
+    fn test_minimize_shortcut_sends_viewport_command() {
+        let mut app = app_with_tabs(1);
+        let ctx = egui::Context::default();
+        
+        assert!(!app.handle_window_key(&ctx, egui::Key::M, egui::Modifiers::NONE));
+        assert!(app.handle_window_key(&ctx, egui::Key::M, egui::Modifiers::COMMAND));
+    }
+    
+    #[test]
+    fn test_rendering_pauses_while_minimized() {
+        let mut app = app_with_tabs(1);
+        assert!(app.is_rendering_active());
+        
+        app.process_event(&viewport_info(Some(true)));
+        assert!(!app.is_rendering_active());
+        
+        // Frames that report no state change keep rendering paused
+        app.process_event(&viewport_info(None));
+        assert!(!app.is_rendering_active());
+        
+        app.process_event(&viewport_info(Some(false)));
+        assert!(app.is_rendering_active());
+    }
+    
+    #[test]
+    fn test_script_timers_pause_while_minimized() {
+        let runtime = Runtime::new().unwrap();
+        let mut app = app_with_tabs(1);
+        let context_id = app.script_runtime.create_context().unwrap();
+        app.script_runtime.set_timeout(context_id, "tick();", Duration::from_millis(10)).unwrap();
+        
+        app.process_event(&viewport_info(Some(true)));
+        assert!(app.script_runtime.timers_paused());
+        let fired = runtime.block_on(app.script_runtime.pump_timers(Duration::from_millis(20)));
+        assert_eq!(fired.unwrap(), 0);
+        
+        app.process_event(&viewport_info(Some(false)));
+        assert!(!app.script_runtime.timers_paused());
+        let fired = runtime.block_on(app.script_runtime.pump_timers(Duration::from_millis(30)));
+        assert_eq!(fired.unwrap(), 1);
+    }
+    
+    #[test]
+    fn test_monitor_info_from_viewport() {
+        let info = egui::ViewportInfo {
+            monitor_size: Some(egui::vec2(1280.0, 720.0)),
+            native_pixels_per_point: Some(1.5),
+            ..Default::default()
+        };
+        let monitor = MonitorInfo::from_viewport(&info).unwrap();
+        assert_eq!(monitor.size, egui::vec2(1280.0, 720.0));
+        assert_eq!(monitor.scale_factor, 1.5);
+        assert_eq!(monitor.physical_size(), [1920, 1080]);
+        
+        // Both the size and the scale are needed
+        assert_eq!(MonitorInfo::from_viewport(&egui::ViewportInfo::default()), None);
+        let info = egui::ViewportInfo {
+            monitor_size: Some(egui::vec2(1280.0, 720.0)),
+            ..Default::default()
+        };
+        assert_eq!(MonitorInfo::from_viewport(&info), None);
+    }
+    
+    #[test]
+    fn test_track_monitor_keeps_last_known() {
+        let mut app = app_with_tabs(1);
+        assert_eq!(app.current_monitor(), None);
+        
+        let info = egui::ViewportInfo {
+            monitor_size: Some(egui::vec2(1920.0, 1080.0)),
+            native_pixels_per_point: Some(1.0),
+            ..Default::default()
+        };
+        assert!(app.track_monitor(&info));
+        assert!(!app.track_monitor(&info));
+        assert!(!app.track_monitor(&egui::ViewportInfo::default()));
+        assert_eq!(app.current_monitor().map(|monitor| monitor.physical_size()), Some([1920, 1080]));
+        
+        // Moving to a HiDPI monitor is a change
+        let info = egui::ViewportInfo {
+            monitor_size: Some(egui::vec2(1440.0, 900.0)),
+            native_pixels_per_point: Some(2.0),
+            ..Default::default()
+        };
+        assert!(app.track_monitor(&info));
+        assert_eq!(app.current_monitor().map(|monitor| monitor.physical_size()), Some([2880, 1800]));
+    }
+    
+    /// Clipboard storage shared with the test, standing in for the system clipboard
+    struct MockClipboard(std::rc::Rc<std::cell::RefCell<Option<String>>>);
+    
+    impl ClipboardBackend for MockClipboard {
+        fn get_text(&mut self) -> Option<String> {
+            self.0.borrow().clone()
+        }
+        
+        fn set_text(&mut self, text: &str) -> bool {
+            *self.0.borrow_mut() = Some(text.to_string());
+            true
+        }
+    }
+    
+    #[test]
+    fn test_clipboard_copy_cut_paste() {
+        let system = std::rc::Rc::new(std::cell::RefCell::new(None));
+        let mut clipboard = Clipboard::with_backend(Box::new(MockClipboard(system.clone())));
+        let mut text = "https://example.com/path".to_string();
+        
+        // Copy keeps the text and selection
+        assert_eq!(clipboard.apply(ClipboardCommand::Copy, &mut text, 8..19), 8..19);
+        assert_eq!(system.borrow().as_deref(), Some("example.com"));
+        assert_eq!(text, "https://example.com/path");
+        
+        // Cut removes the selection and collapses the cursor
+        assert_eq!(clipboard.apply(ClipboardCommand::Cut, &mut text, 19..24), 19..19);
+        assert_eq!(system.borrow().as_deref(), Some("/path"));
+        assert_eq!(text, "https://example.com");
+        
+        // Paste replaces the selection, dropping line breaks
+        *system.borrow_mut() = Some("rust-lang.org\n".to_string());
+        assert_eq!(clipboard.apply(ClipboardCommand::Paste, &mut text, 8..19), 21..21);
+        assert_eq!(text, "https://rust-lang.org");
+        
+        // Nothing selected: copy and cut leave the clipboard alone
+        assert_eq!(clipboard.apply(ClipboardCommand::Cut, &mut text, 3..3), 3..3);
+        assert_eq!(system.borrow().as_deref(), Some("rust-lang.org\n"));
+    }
+    
+    #[test]
+    fn test_clipboard_falls_back_to_internal_buffer() {
+        let mut clipboard = Clipboard::default();
+        let mut text = "héllo wörld".to_string();
+        
+        // Char ranges stay correct around multi-byte characters
+        clipboard.apply(ClipboardCommand::Copy, &mut text, 6..11);
+        assert_eq!(clipboard.apply(ClipboardCommand::Paste, &mut text, 0..5), 5..5);
+        assert_eq!(text, "wörld wörld");
+    }
+    
+    /// Run one frame showing just the URL field
+    fn run_url_frame(ctx: &egui::Context, url: &mut String, events: Vec<egui::Event>) {
+        let input = egui::RawInput {
+            events,
+            ..Default::default()
+        };
+        let _ = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.add(egui::TextEdit::singleline(url).id(url_input_id()));
+            });
+        });
+    }
+    
+    fn url_selection(ctx: &egui::Context) -> Option<Range<usize>> {
+        egui::TextEdit::load_state(ctx, url_input_id())
+            .and_then(|state| state.cursor.char_range())
+            .map(|range| range.as_sorted_char_range())
+    }
+    
+    fn key_press(key: egui::Key, modifiers: egui::Modifiers) -> egui::Event {
+        egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers,
+        }
+    }
+    
+    #[test]
+    fn test_focus_shortcut_selects_url() {
+        let app = app_with_tabs(1);
+        let ctx = egui::Context::default();
+        let mut url = app.tabs[0].url.clone();
+        run_url_frame(&ctx, &mut url, Vec::new());
+        
+        assert!(!app.handle_url_key(&ctx, egui::Key::L, egui::Modifiers::NONE));
+        assert!(app.handle_url_key(&ctx, egui::Key::L, egui::Modifiers::COMMAND));
+        assert!(ctx.memory(|memory| memory.has_focus(url_input_id())));
+        assert_eq!(url_selection(&ctx), Some(0..url.chars().count()));
+    }
+    
+    #[test]
+    fn test_shift_arrow_extends_url_selection() {
+        let ctx = egui::Context::default();
+        let mut url = "https://example.com".to_string();
+        run_url_frame(&ctx, &mut url, Vec::new());
+        ctx.memory_mut(|memory| memory.request_focus(url_input_id()));
+        run_url_frame(&ctx, &mut url, vec![key_press(egui::Key::End, egui::Modifiers::NONE)]);
+        assert_eq!(url_selection(&ctx), Some(19..19));
+        
+        let shift_left = key_press(egui::Key::ArrowLeft, egui::Modifiers::SHIFT);
+        run_url_frame(&ctx, &mut url, vec![shift_left.clone(), shift_left.clone(), shift_left]);
+        assert_eq!(url_selection(&ctx), Some(16..19));
+        
+        run_url_frame(&ctx, &mut url, vec![key_press(egui::Key::Home, egui::Modifiers::SHIFT)]);
+        assert_eq!(url_selection(&ctx), Some(0..19));
+    }
+    
+    #[test]
+    fn test_typing_replaces_selected_url() {
+        let ctx = egui::Context::default();
+        let mut url = "https://example.com".to_string();
+        run_url_frame(&ctx, &mut url, Vec::new());
+        ctx.memory_mut(|memory| memory.request_focus(url_input_id()));
+        
+        // Replace just the host
+        let id = url_input_id();
+        let mut state = egui::TextEdit::load_state(&ctx, id).unwrap_or_default();
+        state.cursor.set_char_range(Some(egui::text::CCursorRange::two(
+            egui::text::CCursor::new(8),
+            egui::text::CCursor::new(19),
+        )));
+        state.store(&ctx, id);
+        run_url_frame(&ctx, &mut url, vec![egui::Event::Text("rust-lang.org".to_string())]);
+        assert_eq!(url, "https://rust-lang.org");
+        
+        // With everything selected, typing replaces the whole URL
+        select_url_text(&ctx, &url);
+        run_url_frame(&ctx, &mut url, vec![egui::Event::Text("docs.rs".to_string())]);
+        assert_eq!(url, "docs.rs");
+    }
+    
+    #[test]
+    fn test_url_suggestions_match_prefix() {
+        let mut history = UrlHistory::default();
+        history.record_visit("https://www.example.com/");
+        history.record_visit("https://example.org/docs");
+        history.record_visit("https://rust-lang.org/");
+        
+        let mut suggestions = history.suggest("exam");
+        suggestions.sort();
+        assert_eq!(suggestions, vec!["https://example.org/docs", "https://www.example.com/"]);
+        assert_eq!(history.suggest("HTTPS://RUST"), vec!["https://rust-lang.org/"]);
+        assert!(history.suggest("docs").is_empty());
+        assert!(history.suggest("  ").is_empty());
+    }
+    
+    #[test]
+    fn test_url_suggestions_rank_by_visits_then_recency() {
+        let mut history = UrlHistory::default();
+        history.record_visit("https://example.com/a");
+        history.record_visit("https://example.com/b");
+        history.record_visit("https://example.com/b");
+        history.record_visit("https://example.com/c");
+        history.record_visit("https://example.com/a");
+        history.record_visit("https://example.com/d");
+        
+        assert_eq!(history.suggest("example.com"), vec![
+            "https://example.com/a",
+            "https://example.com/b",
+            "https://example.com/d",
+            "https://example.com/c",
+        ]);
+    }
+    
+    #[test]
+    fn test_navigation_records_url_history() {
+        let mut app = app_with_tabs(2);
+        app.handle_action(Action::Navigate("https://example.com/".to_string()));
+        app.handle_action(Action::Switch(1));
+        app.handle_action(Action::Navigate("https://example.org/".to_string()));
+        
+        assert_eq!(app.url_history.suggest("example").len(), 2);
+    }
+    
+    #[test]
+    fn test_bookmarks_add_remove_dedup() {
+        let mut bookmarks = Bookmarks::default();
+        assert!(bookmarks.add("https://example.com/", "Example"));
+        assert!(bookmarks.add("https://rust-lang.org/", "Rust"));
+        
+        // Bookmarking again renames instead of duplicating
+        assert!(!bookmarks.add("https://example.com/", "Example Domain"));
+        let titles: Vec<&str> = bookmarks.list().iter().map(|bookmark| bookmark.title.as_str()).collect();
+        assert_eq!(titles, vec!["Example Domain", "Rust"]);
+        
+        assert!(bookmarks.remove("https://example.com/"));
+        assert!(!bookmarks.remove("https://example.com/"));
+        assert_eq!(bookmarks.list().len(), 1);
+    }
+    
+    #[test]
+    fn test_bookmarks_round_trip() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("https://example.com/", "Example");
+        bookmarks.add("https://rust-lang.org/", "Rust");
+        
+        let path = std::env::temp_dir().join(format!("velora_bookmarks_test_{}.json", std::process::id()));
+        bookmarks.save(&path).unwrap();
+        let loaded = Bookmarks::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), bookmarks);
+        
+        assert!(Bookmarks::load(&path).is_err());
+    }
+    
+    #[test]
+    fn test_bookmark_active_tab() {
+        let mut app = app_with_tabs(2);
+        app.tabs[1].url = "https://example.com/".to_string();
+        app.tabs[1].title = "Example".to_string();
+        app.active_tab_index = 1;
+        
+        assert!(app.bookmark_active_tab());
+        assert!(app.bookmark_active_tab());
+        assert_eq!(app.bookmarks.list(), &[Bookmark {
+            url: "https://example.com/".to_string(),
+            title: "Example".to_string(),
+        }]);
+        
+        app.tabs[1].url.clear();
+        assert!(!app.bookmark_active_tab());
+    }
+    
+    #[test]
+    fn test_middle_click_opens_link_in_background_tab() {
+        let mut app = app_with_tabs(1);
+        let mut document = Document::new(NodeId(1));
+        document.set_url("https://example.com/index.html".to_string());
+        let anchor = document.create_element("a").unwrap();
+        let element_id = document.get_dom_tree().get_node(anchor).unwrap().element_id.unwrap();
+        document.get_dom_tree_mut().get_element_mut(element_id).unwrap()
+            .set_attribute("href".to_string(), "/news".to_string());
+        let text = document.create_text_node("News").unwrap();
+        document.append_child(anchor, text).unwrap();
+        let plain = document.create_text_node("Plain text").unwrap();
+        app.tabs[0].dom = Some(document);
+        
+        assert_eq!(app.open_link_in_background_tab(plain), None);
+        assert_eq!(app.tabs.len(), 1);
+        
+        assert_eq!(app.open_link_in_background_tab(text), Some(1));
+        assert_eq!(app.tabs.len(), 2);
+        assert_eq!(app.active_tab_index, 0);
+        assert_eq!(app.tabs[1].url, "https://example.com/news");
+        assert_eq!(app.navigation_queue.len(), 1);
+        assert_eq!(app.navigation_queue[0].tab_id, app.tabs[1].id);
+    }
+    
+    #[test]
+    fn test_tab_cycles_page_focus_and_enter_follows_link() {
+        let mut app = app_with_tabs(1);
+        let mut document = Document::new(NodeId(1));
+        document.set_url("https://example.com/".to_string());
+        let body = document.create_element("body").unwrap();
+        let mut add = |tag: &str, href: Option<&str>| {
+            let node_id = document.create_element(tag).unwrap();
+            if let Some(href) = href {
+                let element_id = document.get_dom_tree().get_node(node_id).unwrap().element_id.unwrap();
+                document.get_dom_tree_mut().get_element_mut(element_id).unwrap()
+                    .set_attribute("href".to_string(), href.to_string());
+            }
+            document.append_child(body, node_id).unwrap();
+            node_id
+        };
+        let link = add("a", Some("/next"));
+        let input = add("input", None);
+        app.tabs[0].dom = Some(document);
+        
+        // Each frame has a focusable toolbar field, which egui would hand Tab focus to
+        let ctx = egui::Context::default();
+        let mut url = String::new();
+        let mut press_tab = |app: &mut BrowserApp, modifiers: egui::Modifiers| {
+            let tab = egui::Event::Key { key: egui::Key::Tab, physical_key: None, pressed: true, repeat: false, modifiers };
+            let mut input = egui::RawInput { events: vec![tab], ..Default::default() };
+            app.take_page_focus_keys(&ctx, &mut input);
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| ui.text_edit_singleline(&mut url));
+            });
+        };
+        
+        assert!(!app.activate_focused_node());
+        press_tab(&mut app, egui::Modifiers::NONE);
+        assert_eq!(app.tabs[0].interaction.focused(), Some(link));
+        press_tab(&mut app, egui::Modifiers::NONE);
+        assert_eq!(app.tabs[0].interaction.focused(), Some(input));
+        press_tab(&mut app, egui::Modifiers::SHIFT);
+        assert_eq!(app.tabs[0].interaction.focused(), Some(link));
+        assert!(ctx.memory(|memory| memory.focused().is_none()));
+        
+        assert!(app.handle_focus_key(&ctx, egui::Key::Enter));
+        assert_eq!(app.tabs[0].url, "https://example.com/next");
+        assert_eq!(app.navigation_queue.len(), 1);
+    }
+    
+    #[test]
+    fn test_download_progress_from_chunks() {
+        let mut downloads = Downloads::default();
+        let id = downloads.add("https://example.com/archive.zip", PathBuf::from("archive.zip"));
+        assert_eq!(downloads.list()[0].state, DownloadState::InProgress);
+        assert_eq!(downloads.list()[0].fraction(), None);
+        
+        downloads.apply(id, DownloadUpdate::Progress(DownloadEvent::Started { total: Some(100) }));
+        for chunk in [30, 30, 15] {
+            downloads.apply(id, DownloadUpdate::Progress(DownloadEvent::Received(chunk)));
+        }
+        assert_eq!(downloads.list()[0].received, 75);
+        assert_eq!(downloads.list()[0].fraction(), Some(0.75));
+        assert!(downloads.is_active());
+        
+        downloads.apply(id, DownloadUpdate::Progress(DownloadEvent::Received(25)));
+        downloads.apply(id, DownloadUpdate::Finished(Ok(())));
+        assert_eq!(downloads.list()[0].state, DownloadState::Completed);
+        assert_eq!(downloads.list()[0].fraction(), Some(1.0));
+        assert!(!downloads.is_active());
+        
+        // Stray chunks after the download finished are ignored
+        downloads.apply(id, DownloadUpdate::Progress(DownloadEvent::Received(10)));
+        assert_eq!(downloads.list()[0].received, 100);
+    }
+    
+    #[test]
+    fn test_download_failure_and_unknown_size() {
+        let mut downloads = Downloads::default();
+        let first = downloads.add("https://example.com/a.bin", PathBuf::from("a.bin"));
+        let second = downloads.add("https://example.com/b.bin", PathBuf::from("b.bin"));
+        
+        downloads.apply(second, DownloadUpdate::Progress(DownloadEvent::Started { total: None }));
+        downloads.apply(second, DownloadUpdate::Progress(DownloadEvent::Received(512)));
+        downloads.apply(first, DownloadUpdate::Finished(Err("connection reset".to_string())));
+        
+        assert_eq!(downloads.list()[0].state, DownloadState::Failed("connection reset".to_string()));
+        assert_eq!(downloads.list()[1].received, 512);
+        assert_eq!(downloads.list()[1].fraction(), None);
+        assert!(downloads.is_active());
+        
+        // Progress sent over the channel is applied on poll
+        downloads.sender.send((second, DownloadUpdate::Finished(Ok(())))).unwrap();
+        downloads.poll();
+        assert_eq!(downloads.list()[1].state, DownloadState::Completed);
+    }
+    
+    #[test]
+    fn test_suggested_file_name() {
+        let mut headers = HashMap::new();
+        assert_eq!(suggested_file_name("https://example.com/files/report.pdf?v=2", &headers), "report.pdf");
+        assert_eq!(suggested_file_name("https://example.com/", &headers), DEFAULT_DOWNLOAD_NAME);
+        
+        headers.insert("Content-Disposition".to_string(), r#"attachment; filename="../../evil.sh""#.to_string());
+        assert_eq!(suggested_file_name("https://example.com/get", &headers), "evil.sh");
+    }
+    
+    #[test]
+    fn test_create_download_file_never_reuses_a_name() {
+        let dir = std::env::temp_dir().join(format!("velora_downloads_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        
+        let (first, _) = create_download_file(&dir, "data.tar.gz").unwrap();
+        let (second, _) = create_download_file(&dir, "data.tar.gz").unwrap();
+        let (third, _) = create_download_file(&dir, "data.tar.gz").unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        
+        assert_eq!(first, dir.join("data.tar.gz"));
+        assert_eq!(second, dir.join("data.tar (1).gz"));
+        assert_eq!(third, dir.join("data.tar (2).gz"));
+    }
+    
+    #[test]
+    fn test_offered_download_is_fetched_once() {
+        let runtime = Runtime::new().unwrap();
+        // A server that answers every connection with the same small body
+        let listener = runtime.block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+        let url = format!("http://{}/velora-offer-{}.bin", listener.local_addr().unwrap(), std::process::id());
+        let connections = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = connections.clone();
+        runtime.spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            while let Ok((mut socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut request = [0; 1024];
+                let _ = socket.read(&mut request).await;
+                let _ = socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: 4\r\n\r\nDATA").await;
+            }
+        });
+        
+        let client = HttpClient::new().unwrap();
+        let result = runtime.block_on(load_page(Some(client.clone()), url, 0, None));
+        let offer = result.download_offer.unwrap();
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+        
+        let mut downloads = Downloads::default();
+        let id = downloads.start(&runtime, &client, &offer);
+        while downloads.is_active() {
+            downloads.poll();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let download = downloads.list().iter().find(|download| download.id == id).unwrap().clone();
+        let contents = std::fs::read(&download.path);
+        std::fs::remove_file(&download.path).unwrap();
+        
+        assert_eq!(download.state, DownloadState::Completed);
+        assert_eq!(contents.unwrap(), b"DATA");
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+    
+    #[test]
+    fn test_navigation_history_across_tabs() {
+        let mut app = app_with_tabs(2);
+        app.handle_action(Action::Switch(0));
+        app.handle_action(Action::Navigate("https://example.com/".to_string()));
+        app.handle_action(Action::Switch(1));
+        app.handle_action(Action::Navigate("https://example.org/news".to_string()));
+        app.handle_action(Action::Switch(0));
+        app.handle_action(Action::Navigate("https://example.com/about".to_string()));
+        app.handle_action(Action::Back);
+        
+        let urls: Vec<&str> = app.navigation_history.entries().iter().map(|entry| entry.url.as_str()).collect();
+        assert_eq!(urls, vec![
+            "https://example.com/",
+            "https://example.org/news",
+            "https://example.com/about",
+            "https://example.com/",
+        ]);
+        let timestamps: Vec<SystemTime> = app.navigation_history.entries().iter().map(|entry| entry.timestamp).collect();
+        assert!(timestamps.windows(2).all(|pair| pair[0] <= pair[1]));
+        
+        // Going back truncates nothing in the global log
+        assert_eq!(app.tabs[0].history.len(), 2);
+        
+        app.navigation_history.clear();
+        assert!(app.navigation_history.entries().is_empty());
+    }
+    
+    #[test]
+    fn test_navigation_history_search() {
+        let mut history = NavigationHistory::default();
+        history.record("https://example.com/");
+        history.record("https://rust-lang.org/learn");
+        history.record("https://example.com/blog");
+        history.set_title("https://rust-lang.org/learn", "Learn Rust");
+        history.set_title("https://example.com/blog", "Example Blog");
+        
+        let urls = |query: &str| -> Vec<String> {
+            history.search(query).into_iter().map(|entry| entry.url.clone()).collect()
+        };
+        assert_eq!(urls("example"), vec!["https://example.com/", "https://example.com/blog"]);
+        assert_eq!(urls("rust"), vec!["https://rust-lang.org/learn"]);
+        assert_eq!(urls("BLOG"), vec!["https://example.com/blog"]);
+        assert!(urls("missing").is_empty());
+        assert_eq!(urls("").len(), 3);
+    }
+    
+    #[test]
+    fn test_history_cap_drops_oldest_entries() {
+        let mut app = app_with_tabs(1);
+        assert_eq!(app.tabs[0].max_history, DEFAULT_MAX_HISTORY);
+        app.set_max_history(3);
+        app.add_new_tab();
+        assert_eq!(app.tabs[1].max_history, 3);
+        
+        let tab = &mut app.tabs[0];
+        
+        for page in 0..5 {
+            tab.navigate_to(format!("https://example.com/{}", page));
+        }
+        assert_eq!(tab.history.len(), 3);
+        assert_eq!(tab.history_index, 2);
+        assert_eq!(tab.history[0], "https://example.com/2");
+        
+        assert_eq!(tab.go_back().as_deref(), Some("https://example.com/3"));
+        assert_eq!(tab.go_back().as_deref(), Some("https://example.com/2"));
+        assert_eq!(tab.go_back(), None);
+        assert_eq!(tab.go_forward().as_deref(), Some("https://example.com/3"));
+        
+        // Navigating from the middle discards forward entries before trimming
+        tab.navigate_to("https://example.com/new".to_string());
+        assert_eq!(tab.history, vec!["https://example.com/2", "https://example.com/3", "https://example.com/new"]);
+        
+        // Lowering the cap keeps the current page
+        tab.set_max_history(1);
+        assert_eq!(tab.history, vec!["https://example.com/new"]);
+        assert_eq!(tab.history_index, 0);
+        assert!(!tab.can_go_back());
+    }
+    
+    #[test]
+    fn test_history_cap_keeps_current_entry() {
+        let mut tab = app_with_tabs(1).tabs.remove(0);
+        for page in 0..5 {
+            tab.navigate_to(format!("https://example.com/{}", page));
+        }
+        tab.go_back();
+        tab.go_back();
+        tab.go_back();
+        assert_eq!(tab.url, "https://example.com/1");
+        
+        // Too few entries sit behind the current one, so forward entries go as well
+        tab.set_max_history(2);
+        assert_eq!(tab.history, vec!["https://example.com/1", "https://example.com/2"]);
+        assert_eq!(tab.history_index, 0);
+        assert_eq!(tab.history[tab.history_index], tab.url);
+        
+        // Restored sessions are trimmed the same way
+        tab.history = (0..5).map(|page| format!("https://example.com/{}", page)).collect();
+        tab.history_index = 0;
+        tab.trim_history();
+        assert_eq!(tab.history, vec!["https://example.com/0", "https://example.com/1"]);
+        assert_eq!(tab.history_index, 0);
+    }
+    
+    #[test]
+    fn test_tab_user_agent_sets_subresource_header_and_viewport() {
+        // A server that records each request's User-Agent and answers with an empty stylesheet
+        let runtime = Runtime::new().unwrap();
+        let listener = runtime.block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let (agents, received) = mpsc::channel();
+        runtime.spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = vec![0; 4096];
+                let read = socket.read(&mut request).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..read]).to_string();
+                let agent = request.lines()
+                    .find_map(|line| line.strip_prefix("user-agent: ").or_else(|| line.strip_prefix("User-Agent: ")))
+                    .unwrap_or_default()
+                    .to_string();
+                let _ = agents.send(agent);
+                let _ = socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/css\r\nContent-Length: 0\r\n\r\n").await;
+            }
+        });
+        
+        let html = format!(r#"<link rel="stylesheet" href="{}/site.css"><p>Hi</p>"#, base);
+        let document = HtmlParser::new().parse_html(&html).unwrap();
+        let mobile = UserAgentPreset::MobileSafari;
+        runtime.block_on(fetch_stylesheets(&HttpClient::new().unwrap(), &document, Some(mobile.user_agent()), STYLESHEET_TIMEOUT));
+        assert_eq!(received.recv_timeout(Duration::from_secs(5)).unwrap(), mobile.user_agent());
+        
+        // Without a viewport meta tag, the phone lays the page out at the legacy mobile width
+        let mut app = app_with_tabs(1);
+        app.content_rect.set(Some(Rect::new(0.0, 0.0, 400.0, 600.0)));
+        app.tabs[0].dom = Some(document);
+        app.relayout_page(0);
+        assert_eq!(app.tabs[0].page_height, 600.0);
+        app.tabs[0].user_agent = Some(mobile);
+        app.relayout_page(0);
+        assert_eq!(app.tabs[0].page_height, 600.0 * LEGACY_MOBILE_WIDTH / 400.0);
+    }
+    
+    #[tokio::test]
+    async fn test_stylesheets_fetch_concurrently_in_document_order() {
+        // A server that accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stalled = format!("http://{}/slow.css", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                connections.push(socket);
+            }
+        });
+        
+        let path = std::env::temp_dir().join(format!("velora_sheet_{}.css", std::process::id()));
+        std::fs::write(&path, "p { color: blue }").unwrap();
+        let linked = format!("file://{}", path.display());
+        let html = format!(
+            r#"<link rel="stylesheet" href="{}"><style>p {{ color: red }}</style><link rel="stylesheet" href="{}">"#,
+            stalled, linked,
+        );
+        let document = HtmlParser::new().parse_html(&html).unwrap();
+        
+        let started = std::time::Instant::now();
+        let rules = fetch_stylesheets(&HttpClient::new().unwrap(), &document, None, Duration::from_millis(200)).await;
+        std::fs::remove_file(&path).ok();
+        
+        assert!(started.elapsed() < Duration::from_secs(2));
+        let colors: Vec<&str> = rules.iter().map(|rule| rule.properties[0].value.as_str()).collect();
+        assert_eq!(colors, vec!["red", "blue"]);
+    }
+    
+    #[test]
+    fn test_scroll_for_key() {
+        let extent = ScrollExtent { content_height: 2000.0, viewport_height: 600.0 };
+        let none = egui::Modifiers::NONE;
+        let shift = egui::Modifiers::SHIFT;
+        let cases = [
+            (egui::Key::Space, none, 500.0, 1060.0),
+            (egui::Key::Space, shift, 500.0, 0.0),
+            (egui::Key::Space, shift, 1000.0, 440.0),
+            (egui::Key::PageDown, none, 500.0, 1060.0),
+            (egui::Key::PageDown, none, 1200.0, 1400.0),
+            (egui::Key::PageUp, none, 1200.0, 640.0),
+            (egui::Key::ArrowDown, none, 500.0, 540.0),
+            (egui::Key::ArrowUp, none, 500.0, 460.0),
+            (egui::Key::ArrowUp, none, 10.0, 0.0),
+            (egui::Key::Home, none, 500.0, 0.0),
+            (egui::Key::End, none, 500.0, 1400.0),
+        ];
+        for (key, modifiers, offset, expected) in cases {
+            assert_eq!(extent.scroll_for_key(offset, key, modifiers), Some(expected), "{:?} from {}", key, offset);
+        }
+        
+        assert_eq!(extent.scroll_for_key(500.0, egui::Key::A, none), None);
+        assert_eq!(extent.scroll_for_key(500.0, egui::Key::End, egui::Modifiers::COMMAND), None);
+        
+        // A page shorter than the viewport does not scroll
+        let short = ScrollExtent { content_height: 300.0, viewport_height: 600.0 };
+        assert_eq!(short.scroll_for_key(0.0, egui::Key::End, none), Some(0.0));
+    }
+    
+    #[test]
+    fn test_scroll_keys_move_active_page() {
+        let mut app = app_with_tabs(1);
+        let ctx = egui::Context::default();
+        assert!(!app.handle_scroll_key(&ctx, egui::Key::PageDown, egui::Modifiers::NONE));
+        
+        app.tabs[0].dom = Some(HtmlParser::new().parse_html("<p>Hi</p>").unwrap());
+        app.page_extent.set(Some(ScrollExtent { content_height: 2000.0, viewport_height: 600.0 }));
+        assert!(app.handle_scroll_key(&ctx, egui::Key::End, egui::Modifiers::NONE));
+        assert_eq!(app.tabs[0].scroll_offset, 1400.0);
+        assert_eq!(app.scroll_request, Some(1400.0));
+        
+        assert!(app.handle_scroll_key(&ctx, egui::Key::ArrowUp, egui::Modifiers::NONE));
+        assert_eq!(app.tabs[0].scroll_offset, 1360.0);
+        
+        // Keys typed into a focused toolbar field do not scroll the page
+        ctx.memory_mut(|memory| memory.request_focus(egui::Id::new("url")));
+        assert!(!app.handle_scroll_key(&ctx, egui::Key::Home, egui::Modifiers::NONE));
+        assert_eq!(app.tabs[0].scroll_offset, 1360.0);
+    }
+    
+    #[test]
+    fn test_should_load_image() {
+        let viewport = Rect::new(0.0, 0.0, 800.0, 600.0);
+        let below = Rect::new(0.0, 900.0, 100.0, 50.0);
+        assert!(!should_load_image(below, viewport, false));
+        assert!(should_load_image(below, viewport, true));
+        
+        // Scrolling down brings it into view
+        let scrolled = Rect::new(0.0, 400.0, 800.0, 600.0);
+        assert!(should_load_image(below, scrolled, false));
+        assert!(should_load_image(Rect::new(0.0, 590.0, 10.0, 20.0), viewport, false));
+    }
+    
+    /// Image sources of a page not requested yet, in document order
+    fn pending_images(lazy: &LazyImages) -> Vec<String> {
+        let mut pending: Vec<String> = Vec::new();
+        for image in lazy.images() {
+            if !lazy.requested.contains(&image.src) && !pending.contains(&image.src) {
+                pending.push(image.src.clone());
+            }
+        }
+        pending
+    }
+    
+    #[test]
+    fn test_lazy_images_load_on_scroll() {
+        let mut app = app_with_tabs(1);
+        let html = r#"<img src="top.png"><div style="height: 2000px"></div><img src="below.png"><img src="far.png" loading="eager"><img src="top.png">"#;
+        let mut document = HtmlParser::new().parse_html(html).unwrap();
+        document.set_url("https://example.com/page".to_string());
+        app.tabs[0].dom = Some(document);
+        app.content_rect.set(Some(Rect::new(0.0, 0.0, 800.0, 600.0)));
+        
+        app.relayout_page(0);
+        let sources = |app: &BrowserApp| pending_images(&app.tabs[0].lazy_images);
+        assert_eq!(sources(&app).len(), 3);
+        
+        // The first image is on screen and the eager one loads anyway
+        let mut lazy = app.tabs[0].lazy_images.clone();
+        assert_eq!(lazy.take_visible(Rect::new(0.0, 0.0, 800.0, 600.0), &app.images), vec!["https://example.com/top.png", "https://example.com/far.png"]);
+        
+        app.load_visible_images(0);
+        assert_eq!(sources(&app), vec!["https://example.com/below.png"]);
+        
+        app.handle_scroll(100.0);
+        assert_eq!(sources(&app).len(), 1);
+        app.handle_scroll(1800.0);
+        assert!(sources(&app).is_empty());
+        assert_eq!(app.tabs[0].scroll_offset, 1800.0);
+    }
+    
+    #[test]
+    fn test_loaded_image_moves_later_content_and_is_uploaded() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = app_with_tabs(1);
+        app.image_receiver = Some(receiver);
+        let mut document = HtmlParser::new().parse_html(r#"<img src="a.png"><img src="b.png">"#).unwrap();
+        document.set_url("https://example.com/".to_string());
+        app.tabs[0].dom = Some(document);
+        app.content_rect.set(Some(Rect::new(0.0, 0.0, 800.0, 600.0)));
+        app.relayout_page(0);
+        
+        let rect_of = |app: &BrowserApp, src: &str| app.tabs[0].lazy_images.images().iter()
+            .find(|image| image.src == format!("https://example.com/{}", src))
+            .unwrap()
+            .rect;
+        let placeholder = rect_of(&app, "a.png");
+        assert_eq!(rect_of(&app, "b.png").y, placeholder.y + placeholder.height);
+        
+        let data = PixelBuffer::new(200, 100).encode_png().unwrap();
+        let image = DecodedImage::decode(&data).unwrap();
+        let src = "https://example.com/a.png".to_string();
+        sender.send(LoadedImage { tab_id: app.tabs[0].id, src: src.clone(), data, image }).unwrap();
+        app.process_loaded_images();
+        
+        let loaded = rect_of(&app, "a.png");
+        assert_eq!((loaded.width, loaded.height), (200.0, 100.0));
+        assert_eq!(rect_of(&app, "b.png").y, loaded.y + 100.0);
+        
+        // Only fetched images on screen get a texture
+        let ctx = egui::Context::default();
+        app.upload_page_images(&ctx);
+        assert_eq!(app.tabs[0].image_textures[&src].size(), [200, 100]);
+        assert_eq!(app.tabs[0].image_textures.len(), 1);
+        
+        app.tabs[0].scroll_offset = 5000.0;
+        app.upload_page_images(&ctx);
+        assert!(app.tabs[0].image_textures.is_empty());
+    }
+    
+    #[test]
+    fn test_image_cache_is_shared_between_tabs() {
+        let (sender, receiver) = mpsc::channel();
+        let mut app = app_with_tabs(2);
+        app.image_receiver = Some(receiver);
+        app.content_rect.set(Some(Rect::new(0.0, 0.0, 800.0, 600.0)));
+        for tab_index in 0..2 {
+            let mut document = HtmlParser::new().parse_html(r#"<img src="a.png">"#).unwrap();
+            document.set_url("https://example.com/".to_string());
+            app.tabs[tab_index].dom = Some(document);
+            app.relayout_page(tab_index);
+            app.load_visible_images(tab_index);
+        }
+        
+        // One tab's fetch sizes the image in both, and stops the other asking for it again
+        let data = PixelBuffer::new(200, 100).encode_png().unwrap();
+        let image = DecodedImage::decode(&data).unwrap();
+        let src = "https://example.com/a.png".to_string();
+        sender.send(LoadedImage { tab_id: app.tabs[0].id, src: src.clone(), data, image }).unwrap();
+        app.process_loaded_images();
+        for tab in &app.tabs {
+            assert_eq!(tab.lazy_images.images()[0].rect.width, 200.0);
+        }
+        let viewport = Rect::new(0.0, 0.0, 800.0, 600.0);
+        assert!(app.tabs[0].lazy_images.clone().take_visible(viewport, &app.images).is_empty());
+        
+        // An image the cache had to drop is fetched again when it is due
+        app.images.set_source_budget(0);
+        assert_eq!(app.tabs[0].lazy_images.clone().take_visible(viewport, &app.images), vec![src]);
+    }
+}
@@ -78,6 +78,57 @@ pub enum ParserError {
     
     #[error("Unexpected end of input")]
     UnexpectedEof,
+    
+    #[error("Unexpected {found} at {position}, expected {expected}")]
+    UnexpectedToken {
+        expected: String,
+        found: String,
+        position: CssSourcePosition,
+    },
+    
+    #[error("Syntax error at {position}: {message}")]
+    Syntax {
+        position: CssSourcePosition,
+        message: String,
+    },
+}
+
+/// Source position information
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CssSourcePosition {
+    /// Line number (1-based)
+    pub line: u32,
+    
+    /// Column number (1-based, in characters)
+    pub column: u32,
+    
+    /// Source file name
+    pub file: Option<String>,
+}
+
+impl CssSourcePosition {
+    /// Create a source position in unnamed source text
+    pub fn new(line: u32, column: u32) -> Self {
+        Self { line, column, file: None }
+    }
+    
+    /// Compute the position of a byte offset within some source text
+    pub fn from_offset(source: &str, offset: usize) -> Self {
+        let before = &source[..offset.min(source.len())];
+        let line = before.matches('\n').count() as u32 + 1;
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        let column = before[line_start..].chars().count() as u32 + 1;
+        Self::new(line, column)
+    }
+}
+
+impl std::fmt::Display for CssSourcePosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}: ", file)?;
+        }
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
 }
 
 /// Layout-related errors
@@ -129,6 +180,9 @@ pub enum NetworkError {
     
     #[error("Redirect limit exceeded")]
     RedirectLimitExceeded,
+    
+    #[error("Unexpected content type: {0}")]
+    UnexpectedContentType(String),
 }
 
 /// JavaScript runtime errors
@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::{Add, Div, Mul, Sub};
 
 /// Unique identifier for DOM nodes
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -65,6 +66,91 @@ impl Size {
     }
 }
 
+impl Add for Point {
+    type Output = Point;
+    
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+    
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+/// Offset a point by a size
+impl Add<Size> for Point {
+    type Output = Point;
+    
+    fn add(self, size: Size) -> Point {
+        Point::new(self.x + size.width, self.y + size.height)
+    }
+}
+
+impl Mul<f32> for Point {
+    type Output = Point;
+    
+    fn mul(self, scale: f32) -> Point {
+        Point::new(self.x * scale, self.y * scale)
+    }
+}
+
+impl Div<f32> for Point {
+    type Output = Point;
+    
+    fn div(self, scale: f32) -> Point {
+        Point::new(self.x / scale, self.y / scale)
+    }
+}
+
+impl From<Size> for Point {
+    fn from(size: Size) -> Self {
+        Point::new(size.width, size.height)
+    }
+}
+
+impl Add for Size {
+    type Output = Size;
+    
+    fn add(self, other: Size) -> Size {
+        Size::new(self.width + other.width, self.height + other.height)
+    }
+}
+
+impl Sub for Size {
+    type Output = Size;
+    
+    fn sub(self, other: Size) -> Size {
+        Size::new(self.width - other.width, self.height - other.height)
+    }
+}
+
+impl Mul<f32> for Size {
+    type Output = Size;
+    
+    fn mul(self, scale: f32) -> Size {
+        Size::new(self.width * scale, self.height * scale)
+    }
+}
+
+impl Div<f32> for Size {
+    type Output = Size;
+    
+    fn div(self, scale: f32) -> Size {
+        Size::new(self.width / scale, self.height / scale)
+    }
+}
+
+impl From<Point> for Size {
+    fn from(point: Point) -> Self {
+        Size::new(point.x, point.y)
+    }
+}
+
 /// 2D rectangle with position and size
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Rect {
@@ -101,6 +187,38 @@ impl Rect {
         !(self.x + self.width <= other.x || other.x + other.width <= self.x ||
           self.y + self.height <= other.y || other.y + other.height <= self.y)
     }
+    
+    /// Smallest rectangle containing both rectangles
+    pub fn union(&self, other: &Rect) -> Rect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.width).max(other.x + other.width);
+        let bottom = (self.y + self.height).max(other.y + other.height);
+        Rect::new(x, y, right - x, bottom - y)
+    }
+    
+    /// Overlapping area of both rectangles, or None when they do not overlap
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.intersects(other) {
+            return None;
+        }
+        
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        Some(Rect::new(x, y, right - x, bottom - y))
+    }
+    
+    /// Shrink the rectangle by dx horizontally and dy vertically on each side
+    pub fn inset(&self, dx: f32, dy: f32) -> Rect {
+        Rect::new(
+            self.x + dx,
+            self.y + dy,
+            (self.width - 2.0 * dx).max(0.0),
+            (self.height - 2.0 * dy).max(0.0),
+        )
+    }
 }
 
 /// Color representation with RGBA components
@@ -136,6 +254,55 @@ impl Color {
     pub fn transparent() -> Self {
         Self::rgba(0, 0, 0, 0)
     }
+    
+    /// Same color with a different alpha
+    pub fn with_alpha(&self, a: u8) -> Self {
+        Self { a, ..*self }
+    }
+    
+    /// Composite this color over a background using source-over
+    pub fn blend_over(&self, background: Color) -> Color {
+        let src_a = self.a as f32 / 255.0;
+        let dst_a = background.a as f32 / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+        if out_a <= 0.0 {
+            return Color::transparent();
+        }
+        
+        let channel = |s: u8, d: u8| {
+            ((s as f32 * src_a + d as f32 * dst_a * (1.0 - src_a)) / out_a).round() as u8
+        };
+        Color::rgba(
+            channel(self.r, background.r),
+            channel(self.g, background.g),
+            channel(self.b, background.b),
+            (out_a * 255.0).round() as u8,
+        )
+    }
+    
+    /// Relative luminance as defined by WCAG, ignoring alpha
+    pub fn luminance(&self) -> f32 {
+        let linear = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+    
+    /// Pack into 0xAARRGGBB
+    pub fn to_u32(&self) -> u32 {
+        u32::from_be_bytes([self.a, self.r, self.g, self.b])
+    }
+    
+    /// Unpack from 0xAARRGGBB
+    pub fn from_u32(value: u32) -> Self {
+        let [a, r, g, b] = value.to_be_bytes();
+        Self { r, g, b, a }
+    }
 }
 
 /// CSS property value that can be different types
@@ -152,6 +319,22 @@ pub enum CssValue {
     List(Vec<CssValue>),
 }
 
+/// A color stop in a gradient
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ColorStop {
+    pub color: Color,
+    /// Position along the gradient line as a fraction (0.0 to 1.0), if given
+    pub position: Option<f32>,
+}
+
+/// A CSS linear gradient
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Gradient {
+    /// Direction of the gradient line in degrees (0 points up, 90 points right)
+    pub angle: f32,
+    pub stops: Vec<ColorStop>,
+}
+
 /// CSS length units
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum CssUnit {
@@ -165,6 +348,35 @@ pub enum CssUnit {
     None,
 }
 
+/// Context needed to convert relative CSS lengths to pixels
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LengthContext {
+    /// Viewport size for vw/vh
+    pub viewport: Size,
+    /// Computed font size of the element, for em
+    pub font_size: f32,
+    /// Font size of the root element, for rem
+    pub root_font_size: f32,
+    /// Size that percentages refer to, if the property has one
+    pub percentage_base: Option<f32>,
+}
+
+impl LengthContext {
+    pub fn new(viewport: Size) -> Self {
+        Self {
+            viewport,
+            font_size: 16.0,
+            root_font_size: 16.0,
+            percentage_base: None,
+        }
+    }
+    
+    pub fn with_percentage_base(mut self, base: f32) -> Self {
+        self.percentage_base = Some(base);
+        self
+    }
+}
+
 /// Display property values
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Display {
@@ -176,6 +388,17 @@ pub enum Display {
     None,
 }
 
+/// White-space property values
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WhiteSpace {
+    /// Collapse whitespace and allow wrapping
+    Normal,
+    /// Keep whitespace and line breaks as written
+    Pre,
+    /// Collapse whitespace but never wrap
+    Nowrap,
+}
+
 /// Position property values
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Position {
@@ -186,6 +409,26 @@ pub enum Position {
     Sticky,
 }
 
+/// Overflow property values
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Overflow {
+    /// Content may paint outside the box
+    Visible,
+    /// Content is clipped to the padding box
+    Hidden,
+    /// Content is clipped and can be scrolled
+    Scroll,
+    /// Content is clipped and scrolls only when it overflows
+    Auto,
+}
+
+impl Overflow {
+    /// Check if content is clipped to the padding box
+    pub fn clips(&self) -> bool {
+        !matches!(self, Overflow::Visible)
+    }
+}
+
 /// Flexbox direction
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum FlexDirection {
@@ -232,6 +475,56 @@ pub enum EventType {
     Custom(String),
 }
 
+impl EventType {
+    /// Look up an event type by its DOM name, such as "click"; unknown names are custom events
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "click" => EventType::Click,
+            "mousedown" => EventType::MouseDown,
+            "mouseup" => EventType::MouseUp,
+            "mousemove" => EventType::MouseMove,
+            "keydown" => EventType::KeyDown,
+            "keyup" => EventType::KeyUp,
+            "load" => EventType::Load,
+            "unload" => EventType::Unload,
+            "scroll" => EventType::Scroll,
+            "resize" => EventType::Resize,
+            _ => EventType::Custom(name.to_string()),
+        }
+    }
+    
+    /// Get the DOM name of the event type
+    pub fn name(&self) -> &str {
+        match self {
+            EventType::Click => "click",
+            EventType::MouseDown => "mousedown",
+            EventType::MouseUp => "mouseup",
+            EventType::MouseMove => "mousemove",
+            EventType::KeyDown => "keydown",
+            EventType::KeyUp => "keyup",
+            EventType::Load => "load",
+            EventType::Unload => "unload",
+            EventType::Scroll => "scroll",
+            EventType::Resize => "resize",
+            EventType::Custom(name) => name,
+        }
+    }
+}
+
+/// Stage of dispatch an event is in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventPhase {
+    /// Not being dispatched
+    #[default]
+    None,
+    /// Travelling from the root down to the target's parent
+    Capturing,
+    /// At the target node
+    AtTarget,
+    /// Travelling from the target's parent back up to the root
+    Bubbling,
+}
+
 /// Event with associated data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
@@ -240,6 +533,12 @@ pub struct Event {
     pub current_target: NodeId,
     pub timestamp: f64,
     pub data: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub phase: EventPhase,
+    #[serde(default)]
+    pub propagation_stopped: bool,
+    #[serde(default)]
+    pub default_prevented: bool,
 }
 
 impl Event {
@@ -250,8 +549,21 @@ impl Event {
             current_target: target,
             timestamp: 0.0, // Will be set by event system
             data: HashMap::new(),
+            phase: EventPhase::None,
+            propagation_stopped: false,
+            default_prevented: false,
         }
     }
+    
+    /// Stop the event from reaching any further nodes once the current node's listeners have run
+    pub fn stop_propagation(&mut self) {
+        self.propagation_stopped = true;
+    }
+    
+    /// Cancel the default action the browser would take for this event
+    pub fn prevent_default(&mut self) {
+        self.default_prevented = true;
+    }
 }
 
 /// HTTP method for network requests
@@ -302,3 +614,108 @@ impl HttpStatus {
         self.code >= 500 && self.code < 600
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_point_operators() {
+        let a = Point::new(1.0, 2.0);
+        let b = Point::new(3.0, 5.0);
+        
+        assert_eq!(a + b, Point::new(4.0, 7.0));
+        assert_eq!(b - a, Point::new(2.0, 3.0));
+        assert_eq!(a * 2.0, Point::new(2.0, 4.0));
+        assert_eq!(b / 2.0, Point::new(1.5, 2.5));
+        assert_eq!(a + Size::new(10.0, 20.0), Point::new(11.0, 22.0));
+    }
+    
+    #[test]
+    fn test_size_operators() {
+        let a = Size::new(10.0, 20.0);
+        let b = Size::new(4.0, 5.0);
+        
+        assert_eq!(a + b, Size::new(14.0, 25.0));
+        assert_eq!(a - b, Size::new(6.0, 15.0));
+        assert_eq!(b * 0.5, Size::new(2.0, 2.5));
+        assert_eq!(a / 4.0, Size::new(2.5, 5.0));
+    }
+    
+    #[test]
+    fn test_point_size_conversions() {
+        assert_eq!(Point::from(Size::new(3.0, 4.0)), Point::new(3.0, 4.0));
+        assert_eq!(Size::from(Point::new(3.0, 4.0)), Size::new(3.0, 4.0));
+    }
+    
+    #[test]
+    fn test_color_blend_half_alpha_over_white() {
+        let blended = Color::rgba(0, 0, 0, 128).blend_over(Color::white());
+        
+        assert_eq!(blended.a, 255);
+        assert_eq!((blended.r, blended.g, blended.b), (127, 127, 127));
+        assert_eq!(Color::rgb(10, 20, 30).blend_over(Color::white()), Color::rgb(10, 20, 30));
+        assert_eq!(Color::transparent().blend_over(Color::transparent()), Color::transparent());
+    }
+    
+    #[test]
+    fn test_color_luminance() {
+        assert!((Color::rgb(128, 128, 128).luminance() - 0.2159).abs() < 0.001);
+        assert_eq!(Color::black().luminance(), 0.0);
+        assert!((Color::white().luminance() - 1.0).abs() < 0.001);
+    }
+    
+    #[test]
+    fn test_color_u32_round_trip() {
+        let color = Color::rgba(0x12, 0x34, 0x56, 0x78);
+        
+        assert_eq!(color.to_u32(), 0x78123456);
+        assert_eq!(Color::from_u32(0x78123456), color);
+        assert_eq!(color.with_alpha(255).a, 255);
+    }
+    
+    #[test]
+    fn test_rect_overlapping() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(5.0, 5.0, 10.0, 10.0);
+        
+        assert_eq!(a.union(&b), Rect::new(0.0, 0.0, 15.0, 15.0));
+        assert_eq!(a.intersection(&b), Some(Rect::new(5.0, 5.0, 5.0, 5.0)));
+    }
+    
+    #[test]
+    fn test_rect_touching() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(10.0, 0.0, 10.0, 10.0);
+        
+        assert_eq!(a.union(&b), Rect::new(0.0, 0.0, 20.0, 10.0));
+        assert_eq!(a.intersection(&b), None);
+    }
+    
+    #[test]
+    fn test_rect_disjoint() {
+        let a = Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = Rect::new(20.0, 30.0, 5.0, 5.0);
+        
+        assert_eq!(a.union(&b), Rect::new(0.0, 0.0, 25.0, 35.0));
+        assert_eq!(a.intersection(&b), None);
+    }
+    
+    #[test]
+    fn test_rect_inset() {
+        let rect = Rect::new(0.0, 0.0, 10.0, 20.0);
+        
+        assert_eq!(rect.inset(2.0, 3.0), Rect::new(2.0, 3.0, 6.0, 14.0));
+        assert_eq!(rect.inset(-1.0, 0.0), Rect::new(-1.0, 0.0, 12.0, 20.0));
+        assert_eq!(rect.inset(6.0, 0.0).width, 0.0);
+    }
+    
+    #[test]
+    fn test_event_type_names_round_trip() {
+        for name in ["click", "keydown", "resize", "my-event"] {
+            assert_eq!(EventType::from_name(name).name(), name);
+        }
+        assert_eq!(EventType::from_name("click"), EventType::Click);
+        assert_eq!(EventType::from_name("tap"), EventType::Custom("tap".to_string()));
+    }
+}
@@ -40,6 +40,12 @@ impl IdGenerator {
     }
 }
 
+impl Clone for IdGenerator {
+    fn clone(&self) -> Self {
+        Self::with_start(self.current())
+    }
+}
+
 impl Default for IdGenerator {
     fn default() -> Self {
         Self::new()
@@ -97,12 +103,18 @@ impl Default for Timer {
 /// Utility for building strings efficiently
 pub struct StringBuilder {
     parts: Vec<String>,
+    capacity: usize,
 }
 
 impl StringBuilder {
     /// Create a new string builder
     pub fn new() -> Self {
-        Self { parts: Vec::new() }
+        Self { parts: Vec::new(), capacity: 0 }
+    }
+    
+    /// Create a string builder expecting roughly `capacity` bytes of output
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { parts: Vec::new(), capacity }
     }
     
     /// Add a string part
@@ -110,6 +122,14 @@ impl StringBuilder {
         self.parts.push(s.into());
     }
     
+    /// Add a single character, extending the last part rather than adding a new one
+    pub fn push_char(&mut self, c: char) {
+        match self.parts.last_mut() {
+            Some(last) => last.push(c),
+            None => self.parts.push(c.to_string()),
+        }
+    }
+    
     /// Add a string part with a separator
     pub fn push_with_separator(&mut self, separator: &str, s: impl Into<String>) {
         if !self.parts.is_empty() {
@@ -118,9 +138,23 @@ impl StringBuilder {
         self.parts.push(s.into());
     }
     
+    /// Length in bytes of the string built so far, without separators
+    pub fn len(&self) -> usize {
+        self.parts.iter().map(String::len).sum()
+    }
+    
+    /// Whether nothing has been pushed yet
+    pub fn is_empty(&self) -> bool {
+        self.parts.iter().all(String::is_empty)
+    }
+    
     /// Build the final string
     pub fn build(self) -> String {
-        self.parts.join("")
+        let mut result = String::with_capacity(self.capacity.max(self.len()));
+        for part in &self.parts {
+            result.push_str(part);
+        }
+        result
     }
     
     /// Build the final string with a separator between parts
@@ -137,7 +171,33 @@ impl Default for StringBuilder {
 
 /// Utility for working with CSS values
 pub mod css {
-    use crate::types::{CssValue, CssUnit, Color};
+    use crate::types::{CssValue, CssUnit, Color, ColorStop, Gradient, LengthContext, WhiteSpace};
+    
+    /// Apply a white-space mode to text: collapse and trim it, or keep it as written
+    pub fn normalize_whitespace(text: &str, mode: WhiteSpace) -> String {
+        match mode {
+            WhiteSpace::Pre => text.to_string(),
+            WhiteSpace::Normal | WhiteSpace::Nowrap => collapse_whitespace(text).trim().to_string(),
+        }
+    }
+    
+    /// Replace every run of whitespace with a single space, keeping the ends
+    pub fn collapse_whitespace(text: &str) -> String {
+        let mut collapsed = String::with_capacity(text.len());
+        let mut in_whitespace = false;
+        for c in text.chars() {
+            if c.is_ascii_whitespace() {
+                if !in_whitespace {
+                    collapsed.push(' ');
+                }
+                in_whitespace = true;
+            } else {
+                collapsed.push(c);
+                in_whitespace = false;
+            }
+        }
+        collapsed
+    }
     
     /// Parse a CSS length value from a string
     pub fn parse_length(s: &str) -> Option<CssValue> {
@@ -169,6 +229,18 @@ pub mod css {
             }
         }
         
+        if let Some(stripped) = s.strip_suffix("vw") {
+            if let Ok(value) = stripped.parse::<f32>() {
+                return Some(CssValue::Length(value, CssUnit::Vw));
+            }
+        }
+        
+        if let Some(stripped) = s.strip_suffix("vh") {
+            if let Ok(value) = stripped.parse::<f32>() {
+                return Some(CssValue::Length(value, CssUnit::Vh));
+            }
+        }
+        
         if let Some(stripped) = s.strip_suffix("%") {
             if let Ok(value) = stripped.parse::<f32>() {
                 return Some(CssValue::Percentage(value));
@@ -182,6 +254,29 @@ pub mod css {
         None
     }
     
+    /// Resolve a length value to pixels
+    ///
+    /// Percentages resolve against `context.percentage_base` and are 0 without one.
+    /// Keywords such as `auto` also resolve to 0; callers handle them before resolving.
+    pub fn resolve_length(value: &CssValue, context: &LengthContext) -> f32 {
+        let percentage = |percent: f32| context.percentage_base.map_or(0.0, |base| base * percent / 100.0);
+        
+        match value {
+            CssValue::Length(length, unit) => match unit {
+                CssUnit::Px => *length,
+                CssUnit::Em => length * context.font_size,
+                CssUnit::Rem => length * context.root_font_size,
+                CssUnit::Vw => length * context.viewport.width / 100.0,
+                CssUnit::Vh => length * context.viewport.height / 100.0,
+                CssUnit::Percent => percentage(*length),
+                CssUnit::Auto | CssUnit::None => 0.0,
+            },
+            CssValue::Percentage(percent) => percentage(*percent),
+            CssValue::Number(number) => *number,
+            _ => 0.0,
+        }
+    }
+    
     /// Parse a CSS color value from a string
     pub fn parse_color(s: &str) -> Option<CssValue> {
         let s = s.trim();
@@ -209,11 +304,129 @@ pub mod css {
                     }
                 }
                 
+                // Handle hsl/hsla functions
+                if s.starts_with("hsl(") || s.starts_with("hsla(") {
+                    if let Some(color) = parse_hsl_color(s) {
+                        return Some(CssValue::Color(color));
+                    }
+                }
+                
                 None
             }
         }
     }
     
+    /// Parse a `linear-gradient(...)` value
+    ///
+    /// The direction may be an angle (`135deg`, `0.5turn`, `1rad`) or a `to <side>` keyword and
+    /// defaults to `to bottom`. Stop positions are only supported as percentages.
+    pub fn parse_linear_gradient(s: &str) -> Option<Gradient> {
+        let s = s.trim();
+        let open = s.find('(')?;
+        if !s[..open].trim().eq_ignore_ascii_case("linear-gradient") {
+            return None;
+        }
+        let args = split_top_level_commas(s[open + 1..].strip_suffix(')')?);
+        
+        let (angle, stop_args) = match parse_gradient_direction(args.first()?) {
+            Some(angle) => (angle, &args[1..]),
+            None => (180.0, &args[..]),
+        };
+        
+        let stops = stop_args
+            .iter()
+            .map(|stop| parse_color_stop(stop))
+            .collect::<Option<Vec<_>>>()?;
+        if stops.is_empty() {
+            return None;
+        }
+        
+        Some(Gradient { angle, stops })
+    }
+    
+    /// Parse a gradient direction into degrees
+    fn parse_gradient_direction(s: &str) -> Option<f32> {
+        let s = s.trim().to_ascii_lowercase();
+        
+        if let Some(sides) = s.strip_prefix("to ") {
+            let mut vertical = None;
+            let mut horizontal = None;
+            for side in sides.split_whitespace() {
+                match side {
+                    "top" => vertical = Some(0.0),
+                    "bottom" => vertical = Some(180.0),
+                    "left" => horizontal = Some(270.0),
+                    "right" => horizontal = Some(90.0),
+                    _ => return None,
+                }
+            }
+            
+            return match (vertical, horizontal) {
+                (Some(v), None) => Some(v),
+                (None, Some(h)) => Some(h),
+                // Corners point halfway between the two sides
+                (Some(0.0), Some(270.0)) => Some(315.0),
+                (Some(v), Some(h)) => Some((v + h) / 2.0),
+                (None, None) => None,
+            };
+        }
+        
+        let (number, degrees_per_unit) = if let Some(n) = s.strip_suffix("deg") {
+            (n, 1.0)
+        } else if let Some(n) = s.strip_suffix("grad") {
+            (n, 0.9)
+        } else if let Some(n) = s.strip_suffix("rad") {
+            (n, 180.0 / std::f32::consts::PI)
+        } else if let Some(n) = s.strip_suffix("turn") {
+            (n, 360.0)
+        } else {
+            return None;
+        };
+        
+        Some((number.trim().parse::<f32>().ok()? * degrees_per_unit).rem_euclid(360.0))
+    }
+    
+    /// Parse a color stop such as `#667eea` or `rgb(0 0 0) 50%`
+    fn parse_color_stop(s: &str) -> Option<ColorStop> {
+        let s = s.trim();
+        
+        // A trailing percentage outside any parentheses is the stop position
+        if let Some((color, position)) = s.rsplit_once(char::is_whitespace) {
+            if let Some(percent) = position.strip_suffix('%') {
+                if let (Some(CssValue::Color(color)), Ok(percent)) = (parse_color(color), percent.parse::<f32>()) {
+                    return Some(ColorStop { color, position: Some(percent / 100.0) });
+                }
+            }
+        }
+        
+        match parse_color(s)? {
+            CssValue::Color(color) => Some(ColorStop { color, position: None }),
+            _ => None,
+        }
+    }
+    
+    /// Split function arguments on commas that are not nested in parentheses
+    fn split_top_level_commas(s: &str) -> Vec<&str> {
+        let mut parts = Vec::new();
+        let mut depth = 0usize;
+        let mut start = 0;
+        
+        for (index, ch) in s.char_indices() {
+            match ch {
+                '(' => depth += 1,
+                ')' => depth = depth.saturating_sub(1),
+                ',' if depth == 0 => {
+                    parts.push(s[start..index].trim());
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        parts.push(s[start..].trim());
+        
+        parts
+    }
+    
     /// Parse a hex color value
     fn parse_hex_color(s: &str) -> Option<Color> {
         let s = &s[1..]; // Remove #
@@ -245,31 +458,84 @@ pub mod css {
         }
     }
     
-    /// Parse an rgb/rgba color value
+    /// Parse an rgb/rgba color value in legacy (comma) or modern (space and slash) syntax
     fn parse_rgb_color(s: &str) -> Option<Color> {
-        // Simple parsing for rgb(r,g,b) and rgba(r,g,b,a)
-        let start = if s.starts_with("rgba(") { 5 } else { 4 };
-        let end = s.len() - 1; // Remove closing )
+        let (channels, alpha) = parse_color_function_args(s)?;
+        if channels.len() != 3 {
+            return None;
+        }
         
-        let values: Vec<f32> = s[start..end]
-            .split(',')
-            .filter_map(|v| v.trim().parse::<f32>().ok())
-            .collect();
+        let mut rgb = [0u8; 3];
+        for (value, channel) in rgb.iter_mut().zip(&channels) {
+            *value = match channel.strip_suffix('%') {
+                Some(percent) => (percent.trim().parse::<f32>().ok()?.clamp(0.0, 100.0) * 2.55).round() as u8,
+                None => channel.parse::<f32>().ok()?.clamp(0.0, 255.0).round() as u8,
+            };
+        }
         
-        match values.len() {
-            3 => Some(Color::rgb(
-                values[0].clamp(0.0, 255.0) as u8,
-                values[1].clamp(0.0, 255.0) as u8,
-                values[2].clamp(0.0, 255.0) as u8,
-            )),
-            4 => Some(Color::rgba(
-                values[0].clamp(0.0, 255.0) as u8,
-                values[1].clamp(0.0, 255.0) as u8,
-                values[2].clamp(0.0, 255.0) as u8,
-                (values[3].clamp(0.0, 1.0) * 255.0) as u8,
-            )),
-            _ => None,
+        Some(Color::rgba(rgb[0], rgb[1], rgb[2], alpha))
+    }
+    
+    /// Parse an hsl/hsla color value, converting it to RGBA
+    fn parse_hsl_color(s: &str) -> Option<Color> {
+        let (channels, alpha) = parse_color_function_args(s)?;
+        if channels.len() != 3 {
+            return None;
         }
+        
+        let hue = channels[0].strip_suffix("deg").unwrap_or(channels[0]).trim().parse::<f32>().ok()?;
+        let saturation = channels[1].strip_suffix('%')?.trim().parse::<f32>().ok()?.clamp(0.0, 100.0) / 100.0;
+        let lightness = channels[2].strip_suffix('%')?.trim().parse::<f32>().ok()?.clamp(0.0, 100.0) / 100.0;
+        
+        let hue = hue.rem_euclid(360.0);
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+        let m = lightness - chroma / 2.0;
+        
+        let (r, g, b) = match hue {
+            h if h < 60.0 => (chroma, x, 0.0),
+            h if h < 120.0 => (x, chroma, 0.0),
+            h if h < 180.0 => (0.0, chroma, x),
+            h if h < 240.0 => (0.0, x, chroma),
+            h if h < 300.0 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        
+        let to_byte = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+        Some(Color::rgba(to_byte(r), to_byte(g), to_byte(b), alpha))
+    }
+    
+    /// Split the arguments of a color function into channels and an alpha byte
+    ///
+    /// Accepts both `f(a, b, c, alpha)` and `f(a b c / alpha)`; alpha defaults to opaque.
+    fn parse_color_function_args(s: &str) -> Option<(Vec<&str>, u8)> {
+        let open = s.find('(')?;
+        let args = s[open + 1..].strip_suffix(')')?.trim();
+        
+        let (channels, alpha): (Vec<&str>, Option<&str>) = if args.contains(',') {
+            let mut parts: Vec<&str> = args.split(',').map(str::trim).collect();
+            let alpha = if parts.len() == 4 { parts.pop() } else { None };
+            (parts, alpha)
+        } else {
+            let (channels, alpha) = match args.split_once('/') {
+                Some((channels, alpha)) => (channels, Some(alpha.trim())),
+                None => (args, None),
+            };
+            (channels.split_whitespace().collect(), alpha)
+        };
+        
+        let alpha = match alpha {
+            Some(alpha) => {
+                let value = match alpha.strip_suffix('%') {
+                    Some(percent) => percent.trim().parse::<f32>().ok()? / 100.0,
+                    None => alpha.parse::<f32>().ok()?,
+                };
+                (value.clamp(0.0, 1.0) * 255.0).round() as u8
+            }
+            None => 255,
+        };
+        
+        Some((channels, alpha))
     }
 }
 
@@ -311,7 +577,7 @@ pub mod url {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{CssValue, CssUnit, Color};
+    use crate::types::{CssValue, CssUnit, Color, ColorStop, LengthContext, WhiteSpace};
     
     #[test]
     fn test_id_generator() {
@@ -322,6 +588,16 @@ mod tests {
         assert_eq!(id2, 2);
     }
     
+    #[test]
+    fn test_id_generator_clone_is_independent() {
+        let gen = IdGenerator::new();
+        gen.next();
+        
+        let copy = gen.clone();
+        assert_eq!(copy.next(), 2);
+        assert_eq!(gen.next(), 2);
+    }
+    
     #[test]
     fn test_timer() {
         let timer = Timer::new();
@@ -337,6 +613,41 @@ mod tests {
         assert_eq!(builder.build(), "Hello World");
     }
     
+    #[test]
+    fn test_string_builder_push_char() {
+        let mut builder = StringBuilder::new();
+        builder.push_char('<');
+        builder.push("div");
+        builder.push_char('>');
+        
+        assert_eq!(builder.len(), 5);
+        assert_eq!(builder.build(), "<div>");
+    }
+    
+    #[test]
+    fn test_string_builder_capacity_keeps_output() {
+        let build = |mut builder: StringBuilder| {
+            builder.push("a");
+            builder.push_with_separator(", ", "b");
+            builder.push_char('c');
+            builder.build()
+        };
+        
+        assert!(StringBuilder::with_capacity(64).is_empty());
+        assert_eq!(build(StringBuilder::with_capacity(64)), build(StringBuilder::new()));
+        assert_eq!(build(StringBuilder::with_capacity(0)), "a, bc");
+    }
+    
+    #[test]
+    fn test_normalize_whitespace() {
+        let text = "  Hello \n\n   world\t! ";
+        
+        assert_eq!(css::normalize_whitespace(text, WhiteSpace::Normal), "Hello world !");
+        assert_eq!(css::normalize_whitespace(text, WhiteSpace::Nowrap), "Hello world !");
+        assert_eq!(css::normalize_whitespace(text, WhiteSpace::Pre), text);
+        assert_eq!(css::collapse_whitespace(" a  b "), " a b ");
+    }
+    
     #[test]
     fn test_css_parsing() {
         assert_eq!(
@@ -349,4 +660,82 @@ mod tests {
             Some(CssValue::Color(Color::rgb(255, 0, 0)))
         );
     }
+    
+    #[test]
+    fn test_resolve_length() {
+        let context = LengthContext {
+            viewport: crate::types::Size::new(800.0, 600.0),
+            font_size: 20.0,
+            root_font_size: 16.0,
+            percentage_base: Some(300.0),
+        };
+        let resolve = |s: &str| css::resolve_length(&css::parse_length(s).unwrap(), &context);
+        
+        assert_eq!(resolve("50vw"), 400.0);
+        assert_eq!(resolve("10vh"), 60.0);
+        assert_eq!(resolve("12px"), 12.0);
+        assert_eq!(resolve("1.5em"), 30.0);
+        assert_eq!(resolve("2rem"), 32.0);
+        assert_eq!(resolve("50%"), 150.0);
+        assert_eq!(css::resolve_length(&CssValue::Length(10.0, CssUnit::Percent), &context), 30.0);
+        
+        // Percentages need a reference size
+        let no_base = LengthContext::new(crate::types::Size::new(800.0, 600.0));
+        assert_eq!(css::resolve_length(&CssValue::Percentage(50.0), &no_base), 0.0);
+        assert_eq!(css::resolve_length(&CssValue::Percentage(50.0), &no_base.with_percentage_base(90.0)), 45.0);
+        assert_eq!(css::resolve_length(&CssValue::Keyword("auto".to_string()), &context), 0.0);
+    }
+    
+    #[test]
+    fn test_linear_gradient_parsing() {
+        let gradient = css::parse_linear_gradient("linear-gradient(135deg, #667eea 0%, #764ba2 100%)").unwrap();
+        assert_eq!(gradient.angle, 135.0);
+        assert_eq!(gradient.stops, vec![
+            ColorStop { color: Color::rgb(0x66, 0x7e, 0xea), position: Some(0.0) },
+            ColorStop { color: Color::rgb(0x76, 0x4b, 0xa2), position: Some(1.0) },
+        ]);
+        
+        let gradient = css::parse_linear_gradient("linear-gradient(135deg, #667eea, #764ba2)").unwrap();
+        assert_eq!(gradient.stops.len(), 2);
+        assert!(gradient.stops.iter().all(|stop| stop.position.is_none()));
+    }
+    
+    #[test]
+    fn test_linear_gradient_keyword_directions() {
+        let gradient = css::parse_linear_gradient("linear-gradient(to bottom, red, rgba(0, 0, 255, 0.5) 40%, white)").unwrap();
+        assert_eq!(gradient.angle, 180.0);
+        assert_eq!(gradient.stops.len(), 3);
+        assert_eq!(gradient.stops[1], ColorStop { color: Color::rgba(0, 0, 255, 128), position: Some(0.4) });
+        
+        assert_eq!(css::parse_linear_gradient("linear-gradient(to right, red, blue)").unwrap().angle, 90.0);
+        assert_eq!(css::parse_linear_gradient("linear-gradient(to top left, red, blue)").unwrap().angle, 315.0);
+        assert_eq!(css::parse_linear_gradient("linear-gradient(0.25turn, red, blue)").unwrap().angle, 90.0);
+        
+        // Direction defaults to top-to-bottom
+        assert_eq!(css::parse_linear_gradient("linear-gradient(red, blue)").unwrap().angle, 180.0);
+        
+        assert!(css::parse_linear_gradient("radial-gradient(red, blue)").is_none());
+        assert!(css::parse_linear_gradient("linear-gradient(to nowhere, red)").is_none());
+    }
+    
+    #[test]
+    fn test_hsl_color_parsing() {
+        assert_eq!(css::parse_color("hsl(120,100%,50%)"), Some(CssValue::Color(Color::rgb(0, 255, 0))));
+        assert_eq!(css::parse_color("hsl(0 0% 100%)"), Some(CssValue::Color(Color::white())));
+        
+        // Hue wraps around and out-of-range percentages are clamped
+        assert_eq!(css::parse_color("hsl(480deg, 150%, 50%)"), Some(CssValue::Color(Color::rgb(0, 255, 0))));
+        assert_eq!(css::parse_color("hsla(240, 100%, 50%, 0.5)"), Some(CssValue::Color(Color::rgba(0, 0, 255, 128))));
+        
+        assert_eq!(css::parse_color("hsl(120, 100, 50%)"), None);
+    }
+    
+    #[test]
+    fn test_modern_rgb_color_parsing() {
+        assert_eq!(css::parse_color("rgb(0 0 0 / 50%)"), Some(CssValue::Color(Color::rgba(0, 0, 0, 128))));
+        assert_eq!(css::parse_color("rgb(255 128 0)"), Some(CssValue::Color(Color::rgb(255, 128, 0))));
+        assert_eq!(css::parse_color("rgba(10, 20, 30, 25%)"), Some(CssValue::Color(Color::rgba(10, 20, 30, 64))));
+        assert_eq!(css::parse_color("rgb(100%, 0%, 0%)"), Some(CssValue::Color(Color::rgb(255, 0, 0))));
+        assert_eq!(css::parse_color("rgb(1 2)"), None);
+    }
 }
@@ -1,10 +1,85 @@
 //! Document structure for the Velora web engine
 
 use velora_core::{NodeId, VeloraResult};
-use super::{Node, DomTree};
+use velora_core::utils::css::collapse_whitespace;
+use super::{Node, DomTree, ViewportConfig};
+use serde::{Deserialize, Serialize};
+
+/// A stylesheet referenced by a document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StylesheetRef {
+    /// CSS text of a `<style>` element
+    Inline(String),
+    /// Resolved URL of a `<link rel="stylesheet">`
+    Linked(String),
+}
+
+/// Rendering mode chosen from a document's DOCTYPE
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuirksMode {
+    /// Standards mode
+    #[default]
+    NoQuirks,
+    /// Standards mode except for a few legacy table-cell sizing quirks
+    LimitedQuirks,
+    /// Emulate legacy browsers, e.g. in box sizing and line heights
+    Quirks,
+}
+
+/// Public identifier prefixes of legacy DOCTYPEs that trigger quirks mode
+const QUIRKS_PUBLIC_PREFIXES: &[&str] = &[
+    "-//w3c//dtd html 3",
+    "-//w3c//dtd html 4.0 transitional//",
+    "-//w3c//dtd html 4.0 frameset//",
+    "-//ietf//dtd html",
+    "-//netscape comm. corp.//dtd html//",
+];
+
+/// Public identifier prefixes that trigger quirks mode only without a system identifier
+const TRANSITIONAL_PUBLIC_PREFIXES: &[&str] = &[
+    "-//w3c//dtd html 4.01 transitional//",
+    "-//w3c//dtd html 4.01 frameset//",
+];
+
+/// Public identifier prefixes of XHTML DOCTYPEs that trigger limited quirks mode
+const LIMITED_QUIRKS_PUBLIC_PREFIXES: &[&str] = &[
+    "-//w3c//dtd xhtml 1.0 transitional//",
+    "-//w3c//dtd xhtml 1.0 frameset//",
+];
+
+/// A `<!DOCTYPE>` declaration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Doctype {
+    /// Root element name, normally `html`
+    pub name: String,
+    /// Public identifier, as in `PUBLIC "-//W3C//DTD HTML 4.01//EN"`
+    pub public_id: Option<String>,
+    /// System identifier, usually a DTD URL
+    pub system_id: Option<String>,
+}
+
+impl Doctype {
+    /// Pick the rendering mode this DOCTYPE asks for, following the HTML standard's rules
+    pub fn quirks_mode(&self) -> QuirksMode {
+        if !self.name.eq_ignore_ascii_case("html") {
+            return QuirksMode::Quirks;
+        }
+        
+        let public_id = self.public_id.as_deref().unwrap_or("").to_ascii_lowercase();
+        let has_prefix = |prefixes: &[&str]| prefixes.iter().any(|prefix| public_id.starts_with(prefix));
+        
+        if has_prefix(QUIRKS_PUBLIC_PREFIXES) || (self.system_id.is_none() && has_prefix(TRANSITIONAL_PUBLIC_PREFIXES)) {
+            QuirksMode::Quirks
+        } else if has_prefix(LIMITED_QUIRKS_PUBLIC_PREFIXES) || has_prefix(TRANSITIONAL_PUBLIC_PREFIXES) {
+            QuirksMode::LimitedQuirks
+        } else {
+            QuirksMode::NoQuirks
+        }
+    }
+}
 
 /// A complete HTML document
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Document {
     /// Unique identifier for this document
     id: NodeId,
@@ -20,6 +95,14 @@ pub struct Document {
     
     /// The DOM tree containing all nodes
     dom_tree: DomTree,
+    
+    /// The document's DOCTYPE, if it had one
+    #[serde(default)]
+    doctype: Option<Doctype>,
+    
+    /// Rendering mode derived from the DOCTYPE
+    #[serde(default)]
+    quirks_mode: QuirksMode,
 }
 
 impl Document {
@@ -31,6 +114,8 @@ impl Document {
             url: None,
             encoding: "UTF-8".to_string(),
             dom_tree: DomTree::new(),
+            doctype: None,
+            quirks_mode: QuirksMode::NoQuirks,
         }
     }
     
@@ -69,6 +154,24 @@ impl Document {
         self.encoding = encoding;
     }
     
+    /// Get the document's DOCTYPE
+    pub fn doctype(&self) -> Option<&Doctype> {
+        self.doctype.as_ref()
+    }
+    
+    /// Get the rendering mode
+    pub fn quirks_mode(&self) -> QuirksMode {
+        self.quirks_mode
+    }
+    
+    /// Record the parsed DOCTYPE and switch to the mode it asks for
+    ///
+    /// A parsed document without a DOCTYPE renders in quirks mode.
+    pub fn set_doctype(&mut self, doctype: Option<Doctype>) {
+        self.quirks_mode = doctype.as_ref().map_or(QuirksMode::Quirks, Doctype::quirks_mode);
+        self.doctype = doctype;
+    }
+    
     /// Get the DOM tree
     pub fn get_dom_tree(&self) -> &DomTree {
         &self.dom_tree
@@ -104,9 +207,13 @@ impl Document {
         self.dom_tree.find_element_by_id(id)
     }
     
-    /// Get elements by tag name
+    /// Get elements by tag name, in document order
+    ///
+    /// Matching is ASCII case-insensitive and `*` matches every element.
     pub fn get_elements_by_tag_name(&self, tag_name: &str) -> Vec<&Node> {
-        self.dom_tree.find_nodes_by_name(tag_name)
+        self.nodes()
+            .filter(|node| node.is_element() && (tag_name == "*" || node.node_name.eq_ignore_ascii_case(tag_name)))
+            .collect()
     }
     
     /// Get elements by class name
@@ -114,6 +221,223 @@ impl Document {
         self.dom_tree.find_elements_by_class(class_name)
     }
     
+    /// Find all text nodes containing the query, in document order
+    ///
+    /// The number of returned nodes is the match count shown by the find bar.
+    pub fn find_text(&self, query: &str, case_sensitive: bool) -> Vec<NodeId> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+        
+        let needle = if case_sensitive { query.to_string() } else { query.to_lowercase() };
+        
+        self.nodes()
+            .filter(|node| node.is_text())
+            .filter(|node| {
+                node.node_value.as_ref().is_some_and(|text| {
+                    if case_sensitive {
+                        text.contains(&needle)
+                    } else {
+                        text.to_lowercase().contains(&needle)
+                    }
+                })
+            })
+            .map(|node| node.id)
+            .collect()
+    }
+    
+    /// Get the visible text of the document, with line breaks between blocks
+    ///
+    /// Unlike joining text nodes, block boundaries become newlines, whitespace collapses
+    /// outside `<pre>`, and script and style contents are left out.
+    pub fn inner_text(&self) -> String {
+        let mut text = String::new();
+        if let Some(root) = self.dom_tree.get_root() {
+            self.collect_inner_text(root, false, &mut text);
+        }
+        text.trim_matches(|c| c == '\n' || c == ' ').to_string()
+    }
+    
+    /// Append the visible text of a subtree
+    fn collect_inner_text(&self, node: &Node, preformatted: bool, text: &mut String) {
+        if node.is_text() {
+            let value = node.node_value.as_deref().unwrap_or("");
+            if preformatted {
+                text.push_str(value);
+            } else {
+                // Collapse across node boundaries too, so "a " + " b" reads "a b"
+                let collapsed = collapse_whitespace(value);
+                let at_break = text.is_empty() || text.ends_with(' ') || text.ends_with('\n');
+                text.push_str(if at_break { collapsed.trim_start() } else { &collapsed });
+            }
+            return;
+        }
+        if !node.is_element() {
+            return;
+        }
+        
+        let tag_name = node.node_name.to_ascii_lowercase();
+        match tag_name.as_str() {
+            "script" | "style" | "head" | "template" => return,
+            "br" => {
+                end_line(text);
+                text.push('\n');
+                return;
+            }
+            _ => {}
+        }
+        
+        let preformatted = preformatted || matches!(tag_name.as_str(), "pre" | "textarea");
+        let is_block = node.element_id
+            .and_then(|element_id| self.dom_tree.get_element(element_id).ok())
+            .is_some_and(|element| element.is_block_element());
+        let break_line = |text: &mut String| {
+            end_line(text);
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push('\n');
+            }
+        };
+        
+        if is_block {
+            break_line(text);
+        }
+        for child in node.child_ids.iter().filter_map(|&child_id| self.dom_tree.get_node(child_id).ok()) {
+            self.collect_inner_text(child, preformatted, text);
+        }
+        if is_block {
+            break_line(text);
+        }
+    }
+    
+    /// Iterate over all nodes reachable from the root, in document order
+    fn nodes(&self) -> impl Iterator<Item = &Node> + '_ {
+        self.dom_tree.get_root()
+            .map(|root| root.id)
+            .into_iter()
+            .flat_map(|root_id| self.dom_tree.descendants(root_id))
+    }
+    
+    /// Get the URL that relative references resolve against
+    ///
+    /// This is the first `<base href>` resolved against the document URL, or the document URL itself.
+    pub fn base_url(&self) -> Option<String> {
+        let document_url = self.url.as_deref().and_then(|url| url::Url::parse(url).ok());
+        
+        let base_href = self.get_elements_by_tag_name("base")
+            .into_iter()
+            .filter_map(|node| node.element_id.and_then(|id| self.dom_tree.get_element(id).ok()))
+            .find_map(|element| element.get_attribute("href"));
+        let base = match (base_href, document_url) {
+            (Some(href), Some(document_url)) => document_url.join(href.trim()).ok(),
+            (Some(href), None) => url::Url::parse(href.trim()).ok(),
+            (None, document_url) => document_url,
+        };
+        
+        base.map(String::from)
+    }
+    
+    /// Resolve a possibly relative reference against the document's base URL (RFC 3986)
+    pub fn resolve_url(&self, href: &str) -> Option<String> {
+        let base = self.base_url().and_then(|base| url::Url::parse(&base).ok());
+        let resolved = match base {
+            Some(base) => base.join(href.trim()),
+            None => url::Url::parse(href.trim()),
+        };
+        resolved.ok().map(String::from)
+    }
+    
+    /// Get the URL of the page icon, resolved against the base URL
+    ///
+    /// Uses the first `<link rel="icon">` href, falling back to `/favicon.ico` on the page origin.
+    pub fn favicon_url(&self) -> Option<String> {
+        let mut href = None;
+        let _ = self.dom_tree.traverse_dfs(|node| {
+            if href.is_none() && node.is_element() && node.node_name.eq_ignore_ascii_case("link") {
+                let element = node.element_id.and_then(|id| self.dom_tree.get_element(id).ok());
+                if let Some(element) = element {
+                    let is_icon = element.get_attribute("rel").is_some_and(|rel| {
+                        rel.split_ascii_whitespace().any(|token| token.eq_ignore_ascii_case("icon"))
+                    });
+                    if let (true, Some(value)) = (is_icon, element.get_attribute("href")) {
+                        href = Some(value.trim().to_string());
+                    }
+                }
+            }
+            Ok(href.is_none())
+        });
+        
+        if let Some(href) = href {
+            return self.resolve_url(&href);
+        }
+        
+        let page = self.url.as_deref().and_then(|url| url::Url::parse(url).ok())?;
+        if !page.scheme().starts_with("http") {
+            return None;
+        }
+        page.join("/favicon.ico").ok().map(String::from)
+    }
+    
+    /// Get the layout viewport settings from the first `<meta name="viewport">`
+    pub fn viewport_config(&self) -> Option<ViewportConfig> {
+        self.get_elements_by_tag_name("meta")
+            .into_iter()
+            .filter_map(|node| node.element_id.and_then(|id| self.dom_tree.get_element(id).ok()))
+            .find(|element| element.get_attribute("name").is_some_and(|name| name.eq_ignore_ascii_case("viewport")))
+            .and_then(|element| element.get_attribute("content"))
+            .map(ViewportConfig::parse)
+    }
+    
+    /// Collect the document's stylesheets in document order
+    ///
+    /// Alternate stylesheets and links whose href does not resolve are skipped.
+    pub fn collect_stylesheets(&self) -> Vec<StylesheetRef> {
+        self.nodes()
+            .filter(|node| node.is_element())
+            .filter_map(|node| match node.node_name.to_ascii_lowercase().as_str() {
+                "style" => {
+                    let css: String = node.child_ids.iter()
+                        .filter_map(|&child_id| self.dom_tree.get_node(child_id).ok())
+                        .filter(|child| child.is_text())
+                        .filter_map(|child| child.node_value.as_deref())
+                        .collect();
+                    Some(StylesheetRef::Inline(css))
+                }
+                "link" => {
+                    let element = node.element_id.and_then(|id| self.dom_tree.get_element(id).ok())?;
+                    let rel = element.get_attribute("rel")?.to_ascii_lowercase();
+                    let mut tokens = rel.split_ascii_whitespace();
+                    let is_stylesheet = tokens.clone().any(|token| token == "stylesheet")
+                        && !tokens.any(|token| token == "alternate");
+                    if !is_stylesheet {
+                        return None;
+                    }
+                    self.resolve_url(element.get_attribute("href")?).map(StylesheetRef::Linked)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+    
+    /// Get the URL a node links to, resolved against the base URL
+    ///
+    /// The link is the node itself or its nearest `<a href>` ancestor. Hrefs that do not
+    /// resolve to a URL are ignored.
+    pub fn link_url(&self, node_id: NodeId) -> Option<String> {
+        let mut current = self.dom_tree.get_node(node_id).ok();
+        while let Some(node) = current {
+            if node.is_element() && node.node_name.eq_ignore_ascii_case("a") {
+                let href = node.element_id
+                    .and_then(|id| self.dom_tree.get_element(id).ok())
+                    .and_then(|element| element.get_attribute("href"));
+                if let Some(href) = href {
+                    return self.resolve_url(href);
+                }
+            }
+            current = node.parent_id.and_then(|parent_id| self.dom_tree.get_node(parent_id).ok());
+        }
+        None
+    }
+    
     /// Create a new element
     pub fn create_element(&mut self, tag_name: &str) -> VeloraResult<NodeId> {
         self.dom_tree.create_element(tag_name)
@@ -124,6 +448,11 @@ impl Document {
         self.dom_tree.create_text_node(text)
     }
     
+    /// Create a new comment node
+    pub fn create_comment(&mut self, text: &str) -> VeloraResult<NodeId> {
+        self.dom_tree.create_comment(text)
+    }
+    
     /// Append a child to the document
     pub fn append_child(&mut self, parent_id: NodeId, child_id: NodeId) -> VeloraResult<()> {
         self.dom_tree.append_child(parent_id, child_id)
@@ -134,21 +463,50 @@ impl Document {
         self.dom_tree.remove_child(parent_id, child_id)
     }
     
+    /// Insert a child before an existing child in the document
+    pub fn insert_before(&mut self, parent_id: NodeId, new_child_id: NodeId, reference_child_id: NodeId) -> VeloraResult<()> {
+        self.dom_tree.insert_before(parent_id, new_child_id, reference_child_id)
+    }
+    
+    /// Replace a child in the document
+    pub fn replace_child(&mut self, parent_id: NodeId, new_child_id: NodeId, old_child_id: NodeId) -> VeloraResult<()> {
+        self.dom_tree.replace_child(parent_id, new_child_id, old_child_id)
+    }
+    
     /// Duplicate the document
     pub fn duplicate(&self) -> Self {
         Self {
-            id: NodeId(velora_core::next_id()),
+            id: self.id,
             title: self.title.clone(),
             url: self.url.clone(),
             encoding: self.encoding.clone(),
             dom_tree: self.dom_tree.clone(),
+            doctype: self.doctype.clone(),
+            quirks_mode: self.quirks_mode,
         }
     }
+    
+    /// Serialize the whole document, including its tree, to JSON
+    pub fn to_json(&self) -> VeloraResult<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+    
+    /// Restore a document from JSON produced by `to_json`
+    pub fn from_json(json: &str) -> VeloraResult<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+/// Drop collapsible spaces left at the end of a line
+fn end_line(text: &mut String) {
+    let trimmed = text.trim_end_matches(' ').len();
+    text.truncate(trimmed);
 }
 
 impl Default for Document {
     fn default() -> Self {
-        Self::new(NodeId(velora_core::next_id()))
+        // Node IDs are allocated per document starting at 1, so 0 is free for the document itself
+        Self::new(NodeId(0))
     }
 }
 
@@ -165,6 +523,101 @@ mod tests {
         assert!(doc.url().is_none());
     }
     
+    #[test]
+    fn test_documents_allocate_ids_independently() {
+        let mut first = Document::default();
+        let mut second = Document::default();
+        
+        let a = first.create_element("div").unwrap();
+        let b = first.create_text_node("text").unwrap();
+        let c = second.create_element("div").unwrap();
+        
+        assert_eq!((a, b), (NodeId(1), NodeId(2)));
+        assert_eq!(c, NodeId(1));
+        
+        let element = |doc: &Document, id| doc.get_dom_tree().get_node(id).unwrap().element_id.unwrap();
+        assert_eq!(element(&first, a), element(&second, c));
+    }
+    
+    #[test]
+    fn test_json_round_trip() {
+        let mut document = Document::default();
+        document.set_title("Snapshot".to_string());
+        document.set_url("https://example.com/".to_string());
+        
+        let root = document.create_element("div").unwrap();
+        let first = document.create_element("p").unwrap();
+        let text = document.create_text_node("Hello").unwrap();
+        document.append_child(root, first).unwrap();
+        document.append_child(first, text).unwrap();
+        
+        let mut restored = Document::from_json(&document.to_json().unwrap()).unwrap();
+        assert_eq!(restored.title(), Some("Snapshot"));
+        assert_eq!(restored.url(), Some("https://example.com/"));
+        assert_eq!(restored.get_dom_tree().get_root().map(|node| node.id), Some(root));
+        assert_eq!(restored.get_dom_tree().get_node(root).unwrap().child_ids, vec![first]);
+        assert_eq!(restored.find_text("Hello", true), vec![text]);
+        
+        // New nodes must not collide with restored ones
+        let next = restored.create_element("span").unwrap();
+        assert_eq!(next, NodeId(4));
+    }
+    
+    #[test]
+    fn test_json_rejects_dangling_root() {
+        let mut document = Document::default();
+        document.create_element("div").unwrap();
+        
+        let json = document.to_json().unwrap().replace("\"root_id\":1", "\"root_id\":42");
+        assert!(Document::from_json(&json).is_err());
+    }
+    
+    /// Build `<tag>text</tag>` under a parent
+    fn append_text_element(document: &mut Document, parent: NodeId, tag: &str, text: &str) -> NodeId {
+        let element = document.create_element(tag).unwrap();
+        let text = document.create_text_node(text).unwrap();
+        document.append_child(element, text).unwrap();
+        document.append_child(parent, element).unwrap();
+        element
+    }
+    
+    #[test]
+    fn test_inner_text_block_boundaries() {
+        let mut document = Document::default();
+        let body = document.create_element("body").unwrap();
+        append_text_element(&mut document, body, "p", "a");
+        append_text_element(&mut document, body, "p", "b");
+        
+        assert_eq!(document.inner_text(), "a\nb");
+    }
+    
+    #[test]
+    fn test_inner_text_skips_scripts() {
+        let mut document = Document::default();
+        let body = document.create_element("body").unwrap();
+        append_text_element(&mut document, body, "script", "alert(1)");
+        let paragraph = append_text_element(&mut document, body, "p", "Hello ");
+        append_text_element(&mut document, paragraph, "em", "world");
+        append_text_element(&mut document, body, "style", "p { color: red }");
+        let tail = append_text_element(&mut document, body, "span", "one");
+        let line_break = document.create_element("br").unwrap();
+        document.append_child(tail, line_break).unwrap();
+        let text = document.create_text_node("two").unwrap();
+        document.append_child(tail, text).unwrap();
+        
+        assert_eq!(document.inner_text(), "Hello world\none\ntwo");
+    }
+    
+    #[test]
+    fn test_inner_text_whitespace() {
+        let mut document = Document::default();
+        let body = document.create_element("body").unwrap();
+        append_text_element(&mut document, body, "p", "  lots   of\n\n  space  ");
+        append_text_element(&mut document, body, "pre", "keep   this\n  layout");
+        
+        assert_eq!(document.inner_text(), "lots of space\nkeep   this\n  layout");
+    }
+    
     #[test]
     fn test_document_title() {
         let mut doc = Document::new(NodeId(1));
@@ -179,6 +632,143 @@ mod tests {
         assert_eq!(doc.url(), Some("https://example.com"));
     }
     
+    #[test]
+    fn test_find_text() {
+        let mut doc = Document::new(NodeId(1));
+        let body = doc.create_element("body").unwrap();
+        let first = doc.create_text_node("The quick brown fox").unwrap();
+        let paragraph = doc.create_element("p").unwrap();
+        let second = doc.create_text_node("jumps over the lazy dog").unwrap();
+        let third = doc.create_text_node("THE END").unwrap();
+        
+        doc.append_child(body, first).unwrap();
+        doc.append_child(body, paragraph).unwrap();
+        doc.append_child(paragraph, second).unwrap();
+        doc.append_child(body, third).unwrap();
+        
+        // Case-insensitive matches come back in document order
+        let matches = doc.find_text("the", false);
+        assert_eq!(matches, vec![first, second, third]);
+        assert_eq!(matches.len(), 3);
+        
+        // Case-sensitive search only matches the exact casing
+        assert_eq!(doc.find_text("the", true), vec![second]);
+        assert_eq!(doc.find_text("The", true), vec![first]);
+        
+        assert!(doc.find_text("cat", false).is_empty());
+        assert!(doc.find_text("", false).is_empty());
+    }
+    
+    fn add_link(doc: &mut Document, parent: NodeId, rel: &str, href: &str) {
+        let link = doc.create_element("link").unwrap();
+        let element_id = doc.get_dom_tree().get_node(link).unwrap().element_id.unwrap();
+        let element = doc.get_dom_tree_mut().get_element_mut(element_id).unwrap();
+        element.set_attribute("rel".to_string(), rel.to_string());
+        element.set_attribute("href".to_string(), href.to_string());
+        doc.append_child(parent, link).unwrap();
+    }
+    
+    #[test]
+    fn test_favicon_url_from_link() {
+        let mut doc = Document::new(NodeId(1));
+        doc.set_url("https://example.com/docs/page.html".to_string());
+        let head = doc.create_element("head").unwrap();
+        add_link(&mut doc, head, "stylesheet", "/style.css");
+        add_link(&mut doc, head, "shortcut icon", "images/icon.png");
+        add_link(&mut doc, head, "icon", "/other.ico");
+        
+        // The first icon link wins and relative hrefs resolve against the page URL
+        assert_eq!(doc.favicon_url().as_deref(), Some("https://example.com/docs/images/icon.png"));
+    }
+    
+    #[test]
+    fn test_favicon_url_fallback() {
+        let mut doc = Document::new(NodeId(1));
+        assert_eq!(doc.favicon_url(), None);
+        
+        doc.set_url("https://example.com/docs/page.html".to_string());
+        let head = doc.create_element("head").unwrap();
+        add_link(&mut doc, head, "stylesheet", "/style.css");
+        assert_eq!(doc.favicon_url().as_deref(), Some("https://example.com/favicon.ico"));
+    }
+    
+    #[test]
+    fn test_viewport_config_from_meta() {
+        let mut doc = Document::new(NodeId(1));
+        let head = doc.create_element("head").unwrap();
+        assert_eq!(doc.viewport_config(), None);
+        
+        for (name, content) in [("description", "width=100"), ("viewport", "width=device-width,initial-scale=1")] {
+            let meta = doc.create_element("meta").unwrap();
+            let element_id = doc.get_dom_tree().get_node(meta).unwrap().element_id.unwrap();
+            let element = doc.get_dom_tree_mut().get_element_mut(element_id).unwrap();
+            element.set_attribute("name".to_string(), name.to_string());
+            element.set_attribute("content".to_string(), content.to_string());
+            doc.append_child(head, meta).unwrap();
+        }
+        
+        assert_eq!(doc.viewport_config(), Some(ViewportConfig::default()));
+    }
+    
+    #[test]
+    fn test_base_href_changes_resolution() {
+        let mut doc = Document::new(NodeId(1));
+        doc.set_url("https://example.com/docs/page.html".to_string());
+        assert_eq!(doc.resolve_url("img.png").as_deref(), Some("https://example.com/docs/img.png"));
+        
+        let head = doc.create_element("head").unwrap();
+        let base = doc.create_element("base").unwrap();
+        let element_id = doc.get_dom_tree().get_node(base).unwrap().element_id.unwrap();
+        doc.get_dom_tree_mut().get_element_mut(element_id).unwrap()
+            .set_attribute("href".to_string(), "https://cdn.example/".to_string());
+        doc.append_child(head, base).unwrap();
+        
+        assert_eq!(doc.base_url().as_deref(), Some("https://cdn.example/"));
+        assert_eq!(doc.resolve_url("img.png").as_deref(), Some("https://cdn.example/img.png"));
+        assert_eq!(doc.resolve_url("/a/../b?q#f").as_deref(), Some("https://cdn.example/b?q#f"));
+        
+        // Icons and links follow the base too
+        add_link(&mut doc, head, "icon", "icon.png");
+        assert_eq!(doc.favicon_url().as_deref(), Some("https://cdn.example/icon.png"));
+    }
+    
+    #[test]
+    fn test_collect_stylesheets() {
+        let mut doc = Document::new(NodeId(1));
+        doc.set_url("https://example.com/docs/page.html".to_string());
+        let head = doc.create_element("head").unwrap();
+        add_link(&mut doc, head, "icon", "/icon.png");
+        add_link(&mut doc, head, "stylesheet", "css/site.css");
+        add_link(&mut doc, head, "alternate stylesheet", "/print.css");
+        
+        let style = doc.create_element("style").unwrap();
+        let css = doc.create_text_node("p { color: red; }").unwrap();
+        doc.append_child(style, css).unwrap();
+        doc.append_child(head, style).unwrap();
+        
+        assert_eq!(doc.collect_stylesheets(), vec![
+            StylesheetRef::Linked("https://example.com/docs/css/site.css".to_string()),
+            StylesheetRef::Inline("p { color: red; }".to_string()),
+        ]);
+    }
+    
+    #[test]
+    fn test_link_url_from_ancestor_anchor() {
+        let mut doc = Document::new(NodeId(1));
+        doc.set_url("https://example.com/docs/page.html".to_string());
+        let anchor = doc.create_element("a").unwrap();
+        let element_id = doc.get_dom_tree().get_node(anchor).unwrap().element_id.unwrap();
+        doc.get_dom_tree_mut().get_element_mut(element_id).unwrap()
+            .set_attribute("href".to_string(), "../about.html".to_string());
+        let text = doc.create_text_node("About").unwrap();
+        doc.append_child(anchor, text).unwrap();
+        let outside = doc.create_text_node("Not a link").unwrap();
+        
+        assert_eq!(doc.link_url(text).as_deref(), Some("https://example.com/about.html"));
+        assert_eq!(doc.link_url(anchor).as_deref(), Some("https://example.com/about.html"));
+        assert_eq!(doc.link_url(outside), None);
+    }
+    
     #[test]
     fn test_document_encoding() {
         let mut doc = Document::new(NodeId(1));
@@ -2,8 +2,15 @@
 
 use velora_core::ElementId;
 use serde::{Deserialize, Serialize};
+use indexmap::IndexMap;
 use std::collections::HashMap;
 
+/// Namespace of HTML elements
+pub const HTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+
+/// Namespace of SVG elements
+pub const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
 /// A DOM element with attributes and properties
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Element {
@@ -13,8 +20,8 @@ pub struct Element {
     /// Tag name of this element
     pub tag_name: String,
     
-    /// Element attributes
-    pub attributes: HashMap<String, String>,
+    /// Element attributes, in the order they were first set
+    pub attributes: IndexMap<String, String>,
     
     /// Element properties
     pub properties: HashMap<String, serde_json::Value>,
@@ -30,6 +37,10 @@ pub struct Element {
     
     /// Element dataset (data-* attributes)
     pub dataset: HashMap<String, String>,
+    
+    /// Namespace URI; None means the HTML namespace
+    #[serde(default)]
+    pub namespace: Option<String>,
 }
 
 impl Element {
@@ -38,15 +49,29 @@ impl Element {
         Self {
             id,
             tag_name,
-            attributes: HashMap::new(),
+            attributes: IndexMap::new(),
             properties: HashMap::new(),
             classes: Vec::new(),
             element_id: None,
             style: None,
             dataset: HashMap::new(),
+            namespace: None,
         }
     }
     
+    /// Create a new element in the given namespace
+    pub fn new_ns(id: ElementId, tag_name: String, namespace: Option<String>) -> Self {
+        Self {
+            namespace,
+            ..Self::new(id, tag_name)
+        }
+    }
+    
+    /// Check if the element is in the HTML namespace
+    pub fn is_html(&self) -> bool {
+        self.namespace.as_deref().is_none_or(|namespace| namespace == HTML_NAMESPACE)
+    }
+    
     /// Get the tag name
     pub fn tag_name(&self) -> &str {
         &self.tag_name
@@ -82,7 +107,7 @@ impl Element {
     
     /// Remove an attribute
     pub fn remove_attribute(&mut self, name: &str) -> Option<String> {
-        let value = self.attributes.remove(name);
+        let value = self.attributes.shift_remove(name);
         
         // Handle special attributes
         match name {
@@ -111,7 +136,7 @@ impl Element {
     }
     
     /// Get all attributes as a reference
-    pub fn get_attributes(&self) -> &HashMap<String, String> {
+    pub fn get_attributes(&self) -> &IndexMap<String, String> {
         &self.attributes
     }
     
@@ -157,7 +182,7 @@ impl Element {
         if let Some(id_value) = id {
             self.attributes.insert("id".to_string(), id_value);
         } else {
-            self.attributes.remove("id");
+            self.attributes.shift_remove("id");
         }
     }
     
@@ -201,6 +226,36 @@ impl Element {
         }
     }
     
+    /// Add or remove a class, like `classList.toggle(class, force)`
+    ///
+    /// Returns whether the class list changed.
+    pub fn set_class(&mut self, class: &str, present: bool) -> bool {
+        match (present, self.has_class(class)) {
+            (true, false) => {
+                self.add_class(class.to_string());
+                true
+            }
+            (false, true) => self.remove_class(class),
+            _ => false,
+        }
+    }
+    
+    /// Replace a class in place, like `classList.replace(old, new)`
+    ///
+    /// Returns false and changes nothing if the element does not have `old`.
+    pub fn replace_class(&mut self, old: &str, new: String) -> bool {
+        if !self.has_class(old) {
+            return false;
+        }
+        
+        // The first of either class takes the new name and any other copies go
+        let pos = self.classes.iter().position(|c| c == old || *c == new).unwrap_or_default();
+        self.classes.retain(|c| c != old && *c != new);
+        self.classes.insert(pos, new);
+        self.update_class_attribute();
+        true
+    }
+    
     /// Get the style attribute
     pub fn get_style(&self) -> Option<&str> {
         self.style.as_deref()
@@ -213,7 +268,7 @@ impl Element {
         if let Some(style_value) = style {
             self.attributes.insert("style".to_string(), style_value);
         } else {
-            self.attributes.remove("style");
+            self.attributes.shift_remove("style");
         }
     }
     
@@ -233,13 +288,13 @@ impl Element {
     pub fn remove_dataset(&mut self, key: &str) -> Option<String> {
         let value = self.dataset.remove(key);
         let attr_name = format!("data-{}", key);
-        self.attributes.remove(&attr_name);
+        self.attributes.shift_remove(&attr_name);
         value
     }
     
     /// Check if the element is a void element (self-closing)
     pub fn is_void_element(&self) -> bool {
-        matches!(
+        self.is_html() && matches!(
             self.tag_name.as_str(),
             "area" | "base" | "br" | "col" | "embed" | "hr" | "img" | "input" |
             "link" | "meta" | "param" | "source" | "track" | "wbr"
@@ -248,7 +303,7 @@ impl Element {
     
     /// Check if the element is a block-level element
     pub fn is_block_element(&self) -> bool {
-        matches!(
+        self.is_html() && matches!(
             self.tag_name.as_str(),
             "address" | "article" | "aside" | "blockquote" | "canvas" | "dd" | "div" |
             "dl" | "dt" | "fieldset" | "figcaption" | "figure" | "footer" | "form" |
@@ -266,7 +321,7 @@ impl Element {
     /// Update the class attribute based on the classes vector
     fn update_class_attribute(&mut self) {
         if self.classes.is_empty() {
-            self.attributes.remove("class");
+            self.attributes.shift_remove("class");
         } else {
             let class_value = self.classes.join(" ");
             self.attributes.insert("class".to_string(), class_value);
@@ -284,6 +339,7 @@ impl Element {
             element_id: self.element_id.clone(),
             style: self.style.clone(),
             dataset: self.dataset.clone(),
+            namespace: self.namespace.clone(),
         }
     }
 }
@@ -346,6 +402,37 @@ mod tests {
         assert_eq!(element.get_classes().len(), 1);
     }
     
+    #[test]
+    fn test_set_class_forces_presence() {
+        let mut element = Element::new(ElementId(1), "div".to_string());
+        
+        assert!(element.set_class("hover", true));
+        assert!(!element.set_class("hover", true));
+        assert!(element.has_class("hover"));
+        assert_eq!(element.get_attribute("class"), Some("hover"));
+        
+        assert!(element.set_class("hover", false));
+        assert!(!element.set_class("hover", false));
+        assert!(!element.has_class("hover"));
+        assert_eq!(element.get_attribute("class"), None);
+    }
+    
+    #[test]
+    fn test_replace_class() {
+        let mut element = Element::new(ElementId(1), "div".to_string());
+        element.set_attribute("class".to_string(), "card active wide".to_string());
+        
+        assert!(!element.replace_class("missing", "other".to_string()));
+        assert_eq!(element.get_attribute("class"), Some("card active wide"));
+        
+        assert!(element.replace_class("active", "inactive".to_string()));
+        assert_eq!(element.get_attribute("class"), Some("card inactive wide"));
+        
+        // Replacing with a class already present keeps a single copy in the earlier spot
+        assert!(element.replace_class("card", "wide".to_string()));
+        assert_eq!(element.get_classes(), ["wide", "inactive"]);
+    }
+    
     #[test]
     fn test_id_management() {
         let mut element = Element::new(ElementId(1), "div".to_string());
@@ -396,6 +483,19 @@ mod tests {
         assert!(img.is_void_element());
     }
     
+    #[test]
+    fn test_svg_elements_skip_html_classification() {
+        let html_link = Element::new(ElementId(1), "link".to_string());
+        let svg_link = Element::new_ns(ElementId(2), "link".to_string(), Some(SVG_NAMESPACE.to_string()));
+        let svg_video = Element::new_ns(ElementId(3), "video".to_string(), Some(SVG_NAMESPACE.to_string()));
+        let explicit_html = Element::new_ns(ElementId(4), "hr".to_string(), Some(HTML_NAMESPACE.to_string()));
+        
+        assert!(html_link.is_void_element());
+        assert!(!svg_link.is_void_element());
+        assert!(!svg_video.is_block_element());
+        assert!(explicit_html.is_void_element() && explicit_html.is_block_element());
+    }
+    
     #[test]
     fn test_property_management() {
         let mut element = Element::new(ElementId(1), "div".to_string());
@@ -407,4 +507,27 @@ mod tests {
         element.remove_property("checked");
         assert!(!element.has_property("checked"));
     }
+    
+    #[test]
+    fn test_attribute_order_is_preserved() {
+        let mut element = Element::new(ElementId(1), "a".to_string());
+        for name in ["href", "class", "id", "data-tracking", "title"] {
+            element.set_attribute(name.to_string(), format!("{}-value", name));
+        }
+        
+        // Updating an attribute keeps its place; removing one closes the gap
+        element.set_attribute("class".to_string(), "active".to_string());
+        element.remove_attribute("id");
+        assert_eq!(element.get_attribute_names(), vec!["href", "class", "data-tracking", "title"]);
+        
+        let json = serde_json::to_string(&element).unwrap();
+        let positions: Vec<usize> = ["\"href\"", "\"class\"", "\"data-tracking\"", "\"title\""]
+            .iter()
+            .map(|name| json.find(name).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        
+        let restored: Element = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_attribute_names(), element.get_attribute_names());
+    }
 }
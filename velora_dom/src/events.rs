@@ -0,0 +1,128 @@
+//! Event listeners for the Velora web engine
+
+use velora_core::{Event, EventType, NodeId, IdGenerator};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Callback invoked when an event reaches a listener
+pub type EventCallback = Arc<dyn Fn(&mut Event) + Send + Sync>;
+
+/// Identifier of a registered listener, used to remove it again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(pub u64);
+
+/// A callback registered on a node for one event type
+#[derive(Clone)]
+pub struct EventListener {
+    /// Identifier returned on registration
+    pub id: ListenerId,
+    
+    /// Event type the listener handles
+    pub event_type: EventType,
+    
+    /// Whether the listener runs during the capture phase instead of the bubble phase
+    pub capture: bool,
+    
+    /// The callback itself
+    pub callback: EventCallback,
+}
+
+impl fmt::Debug for EventListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventListener")
+            .field("id", &self.id)
+            .field("event_type", &self.event_type)
+            .field("capture", &self.capture)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Listener tables for every node of a tree
+#[derive(Debug, Clone, Default)]
+pub struct EventListeners {
+    /// Listeners per node, in registration order
+    by_node: HashMap<NodeId, Vec<EventListener>>,
+    
+    /// Allocator for listener IDs
+    ids: IdGenerator,
+}
+
+impl EventListeners {
+    /// Create empty listener tables
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Register a listener on a node and return its ID
+    pub fn add(&mut self, node_id: NodeId, event_type: EventType, capture: bool, callback: EventCallback) -> ListenerId {
+        let id = ListenerId(self.ids.next());
+        self.by_node.entry(node_id).or_default().push(EventListener {
+            id,
+            event_type,
+            capture,
+            callback,
+        });
+        id
+    }
+    
+    /// Remove a listener from a node; returns false if it was not registered there
+    pub fn remove(&mut self, node_id: NodeId, listener_id: ListenerId) -> bool {
+        let Some(listeners) = self.by_node.get_mut(&node_id) else {
+            return false;
+        };
+        
+        let before = listeners.len();
+        listeners.retain(|listener| listener.id != listener_id);
+        let removed = listeners.len() != before;
+        
+        if listeners.is_empty() {
+            self.by_node.remove(&node_id);
+        }
+        removed
+    }
+    
+    /// Remove every listener registered on a node
+    pub fn remove_all(&mut self, node_id: NodeId) {
+        self.by_node.remove(&node_id);
+    }
+    
+    /// Listeners on a node for an event type, in registration order
+    pub fn listeners_for(&self, node_id: NodeId, event_type: &EventType) -> Vec<EventListener> {
+        self.by_node
+            .get(&node_id)
+            .map(|listeners| {
+                listeners.iter()
+                    .filter(|listener| &listener.event_type == event_type)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+    
+    /// Get the number of listeners registered on a node
+    pub fn count(&self, node_id: NodeId) -> usize {
+        self.by_node.get(&node_id).map_or(0, Vec::len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_add_and_remove_listener() {
+        let mut listeners = EventListeners::new();
+        let node = NodeId(1);
+        let click = listeners.add(node, EventType::Click, false, Arc::new(|_| {}));
+        listeners.add(node, EventType::KeyDown, true, Arc::new(|_| {}));
+        
+        assert_eq!(listeners.count(node), 2);
+        assert_eq!(listeners.listeners_for(node, &EventType::Click).len(), 1);
+        
+        assert!(listeners.remove(node, click));
+        assert!(!listeners.remove(node, click));
+        assert!(listeners.listeners_for(node, &EventType::Click).is_empty());
+        assert_eq!(listeners.count(node), 1);
+    }
+}
@@ -0,0 +1,60 @@
+//! Document fragments for the Velora web engine
+
+use velora_core::NodeId;
+use serde::{Deserialize, Serialize};
+
+/// A lightweight container for detached nodes that are inserted into a tree together
+///
+/// The nodes themselves live in a `DomTree`; the fragment only records which detached
+/// nodes form its top level and in what order. `DomTree::append_fragment` moves them
+/// under a parent in one step and leaves the fragment empty.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DocumentFragment {
+    /// Top-level node IDs, in insertion order
+    pub child_ids: Vec<NodeId>,
+}
+
+impl DocumentFragment {
+    /// Create an empty fragment
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Add a node to the end of the fragment
+    pub fn append(&mut self, node_id: NodeId) {
+        self.child_ids.push(node_id);
+    }
+    
+    /// Get the number of top-level nodes
+    pub fn len(&self) -> usize {
+        self.child_ids.len()
+    }
+    
+    /// Check if the fragment has no nodes
+    pub fn is_empty(&self) -> bool {
+        self.child_ids.is_empty()
+    }
+    
+    /// Remove and return the top-level nodes, leaving the fragment empty
+    pub fn take_children(&mut self) -> Vec<NodeId> {
+        std::mem::take(&mut self.child_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_fragment_append_and_take() {
+        let mut fragment = DocumentFragment::new();
+        assert!(fragment.is_empty());
+        
+        fragment.append(NodeId(3));
+        fragment.append(NodeId(1));
+        assert_eq!(fragment.len(), 2);
+        
+        assert_eq!(fragment.take_children(), vec![NodeId(3), NodeId(1)]);
+        assert!(fragment.is_empty());
+    }
+}
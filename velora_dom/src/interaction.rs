@@ -0,0 +1,256 @@
+//! User interaction state for the Velora web engine
+//!
+//! Tracks which nodes are hovered, focused and active so that the `:hover`, `:focus` and
+//! `:active` pseudo-classes can be matched. Hover and active apply to the target and all
+//! of its ancestors, as in browsers. Every update returns the nodes whose state changed,
+//! which are the ones that need restyling and repainting.
+
+use velora_core::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use crate::{DomTree, Node};
+
+/// Elements that take keyboard focus without a `tabindex`; links need an `href` too
+const FOCUSABLE_TAGS: &[&str] = &["button", "input", "select", "textarea"];
+
+/// Per-node interaction state kept alongside a DOM tree
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InteractionState {
+    /// The hover target and its ancestors
+    hovered: HashSet<NodeId>,
+    
+    /// The focused node, if any
+    focused: Option<NodeId>,
+    
+    /// The node being pressed and its ancestors
+    active: HashSet<NodeId>,
+}
+
+impl InteractionState {
+    /// Create an empty interaction state
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Check if a node is hovered
+    pub fn is_hovered(&self, node_id: NodeId) -> bool {
+        self.hovered.contains(&node_id)
+    }
+    
+    /// Check if a node has focus
+    pub fn is_focused(&self, node_id: NodeId) -> bool {
+        self.focused == Some(node_id)
+    }
+    
+    /// Get the focused node, if any
+    pub fn focused(&self) -> Option<NodeId> {
+        self.focused
+    }
+    
+    /// Check if a node is active
+    pub fn is_active(&self, node_id: NodeId) -> bool {
+        self.active.contains(&node_id)
+    }
+    
+    /// Move the hover to a node, or clear it with None, returning the nodes that changed
+    pub fn set_hover_target(&mut self, tree: &DomTree, target: Option<NodeId>) -> Vec<NodeId> {
+        update_chain(&mut self.hovered, tree, target)
+    }
+    
+    /// Move the focus to a node, or clear it with None, returning the nodes that changed
+    pub fn set_focus(&mut self, target: Option<NodeId>) -> Vec<NodeId> {
+        if self.focused == target {
+            return Vec::new();
+        }
+        
+        let previous = std::mem::replace(&mut self.focused, target);
+        previous.into_iter().chain(target).collect()
+    }
+    
+    /// Move the focus along the tab order, as Tab (or Shift+Tab with `backward`) does
+    ///
+    /// Focus wraps around at either end. Without a focused node in the order, focus goes
+    /// to the first node, or the last when going backward. Returns the nodes that changed.
+    pub fn focus_next(&mut self, tree: &DomTree, backward: bool) -> Vec<NodeId> {
+        let order = tab_order(tree);
+        if order.is_empty() {
+            return Vec::new();
+        }
+        
+        let current = self.focused.and_then(|focused| order.iter().position(|&id| id == focused));
+        let next = match (current, backward) {
+            (Some(index), false) => (index + 1) % order.len(),
+            (Some(index), true) => (index + order.len() - 1) % order.len(),
+            (None, false) => 0,
+            (None, true) => order.len() - 1,
+        };
+        self.set_focus(Some(order[next]))
+    }
+    
+    /// Mark a node as active while it is pressed, or clear it with None, returning the nodes that changed
+    pub fn set_active_target(&mut self, tree: &DomTree, target: Option<NodeId>) -> Vec<NodeId> {
+        update_chain(&mut self.active, tree, target)
+    }
+    
+    /// Forget all interaction state, e.g. when a new document is loaded
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Get the nodes that Tab moves focus through, in order
+///
+/// Elements with a positive `tabindex` come first, lowest first, followed by the other
+/// focusable elements in document order. A negative `tabindex`, `disabled`, or
+/// `<input type="hidden">` takes an element out of the order.
+pub fn tab_order(tree: &DomTree) -> Vec<NodeId> {
+    let mut focusable = Vec::new();
+    let _ = tree.traverse_dfs(|node| {
+        if let Some(index) = tab_index(tree, node) {
+            focusable.push((index, node.id));
+        }
+        Ok(true)
+    });
+    
+    // The sort is stable, so equal indices keep document order; 0 sorts after positives
+    focusable.sort_by_key(|&(index, _)| if index > 0 { index } else { i32::MAX });
+    focusable.into_iter().map(|(_, id)| id).collect()
+}
+
+/// The tab index of a node that can be tabbed to, or None if it is not in the tab order
+fn tab_index(tree: &DomTree, node: &Node) -> Option<i32> {
+    let element = node.element_id.and_then(|id| tree.get_element(id).ok())?;
+    if element.get_attribute("disabled").is_some() {
+        return None;
+    }
+    if let Some(index) = element.get_attribute("tabindex").and_then(|value| value.trim().parse::<i32>().ok()) {
+        return (index >= 0).then_some(index);
+    }
+    
+    let tag = node.node_name.to_ascii_lowercase();
+    let focusable = match tag.as_str() {
+        "a" => element.get_attribute("href").is_some(),
+        "input" => !element.get_attribute("type").is_some_and(|kind| kind.eq_ignore_ascii_case("hidden")),
+        _ => FOCUSABLE_TAGS.contains(&tag.as_str()),
+    };
+    focusable.then_some(0)
+}
+
+/// Replace a set with the target and its ancestors, returning the symmetric difference
+fn update_chain(set: &mut HashSet<NodeId>, tree: &DomTree, target: Option<NodeId>) -> Vec<NodeId> {
+    let mut chain = HashSet::new();
+    let mut current = target;
+    while let Some(node_id) = current {
+        let Ok(node) = tree.get_node(node_id) else {
+            break;
+        };
+        chain.insert(node_id);
+        current = node.parent_id;
+    }
+    
+    let mut changed: Vec<NodeId> = set.symmetric_difference(&chain).copied().collect();
+    changed.sort_by_key(|id| id.0);
+    *set = chain;
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn nested_tree() -> (DomTree, NodeId, NodeId, NodeId) {
+        let mut tree = DomTree::new();
+        let outer = tree.create_element("div").unwrap();
+        let inner = tree.create_element("span").unwrap();
+        let other = tree.create_element("p").unwrap();
+        tree.append_child(outer, inner).unwrap();
+        tree.append_child(outer, other).unwrap();
+        (tree, outer, inner, other)
+    }
+    
+    #[test]
+    fn test_hover_includes_ancestors() {
+        let (tree, outer, inner, other) = nested_tree();
+        let mut state = InteractionState::new();
+        
+        assert_eq!(state.set_hover_target(&tree, Some(inner)), vec![outer, inner]);
+        assert!(state.is_hovered(inner) && state.is_hovered(outer));
+        assert!(!state.is_hovered(other));
+        
+        // Moving to a sibling only dirties the nodes that changed
+        assert_eq!(state.set_hover_target(&tree, Some(other)), vec![inner, other]);
+        assert_eq!(state.set_hover_target(&tree, Some(other)), Vec::<NodeId>::new());
+        
+        assert_eq!(state.set_hover_target(&tree, None), vec![outer, other]);
+    }
+    
+    #[test]
+    fn test_focus_is_single_node() {
+        let (tree, outer, inner, _) = nested_tree();
+        let mut state = InteractionState::new();
+        
+        assert_eq!(state.set_focus(Some(inner)), vec![inner]);
+        assert!(state.is_focused(inner) && !state.is_focused(outer));
+        assert_eq!(state.set_focus(Some(outer)), vec![inner, outer]);
+        
+        state.set_active_target(&tree, Some(inner));
+        state.clear();
+        assert!(!state.is_focused(outer) && !state.is_active(inner));
+    }
+    
+    /// Append an element with the given attributes to `parent`
+    fn add_element(tree: &mut DomTree, parent: NodeId, tag: &str, attributes: &[(&str, &str)]) -> NodeId {
+        let node_id = tree.create_element(tag).unwrap();
+        let element_id = tree.get_node(node_id).unwrap().element_id.unwrap();
+        let element = tree.get_element_mut(element_id).unwrap();
+        for (name, value) in attributes {
+            element.set_attribute(name.to_string(), value.to_string());
+        }
+        tree.append_child(parent, node_id).unwrap();
+        node_id
+    }
+    
+    #[test]
+    fn test_tab_order() {
+        let mut tree = DomTree::new();
+        let body = tree.create_element("body").unwrap();
+        let link = add_element(&mut tree, body, "a", &[("href", "/about")]);
+        add_element(&mut tree, body, "a", &[]);
+        let input = add_element(&mut tree, body, "input", &[]);
+        add_element(&mut tree, body, "input", &[("type", "hidden")]);
+        add_element(&mut tree, body, "button", &[("disabled", "")]);
+        add_element(&mut tree, body, "button", &[("tabindex", "-1")]);
+        let div = add_element(&mut tree, body, "div", &[("tabindex", "0")]);
+        let first = add_element(&mut tree, body, "textarea", &[("tabindex", "2")]);
+        let very_first = add_element(&mut tree, body, "select", &[("tabindex", "1")]);
+        
+        assert_eq!(tab_order(&tree), vec![very_first, first, link, input, div]);
+    }
+    
+    #[test]
+    fn test_focus_cycles_with_wraparound() {
+        let mut tree = DomTree::new();
+        let body = tree.create_element("body").unwrap();
+        let link = add_element(&mut tree, body, "a", &[("href", "/")]);
+        let input = add_element(&mut tree, body, "input", &[]);
+        let button = add_element(&mut tree, body, "button", &[]);
+        let mut state = InteractionState::new();
+        
+        // Forward from nothing starts at the first node and wraps past the last
+        assert_eq!(state.focus_next(&tree, false), vec![link]);
+        state.focus_next(&tree, false);
+        assert_eq!(state.focused(), Some(input));
+        state.focus_next(&tree, false);
+        assert_eq!(state.focus_next(&tree, false), vec![button, link]);
+        
+        // Backward wraps past the first
+        assert_eq!(state.focus_next(&tree, true), vec![link, button]);
+        state.focus_next(&tree, true);
+        assert_eq!(state.focused(), Some(input));
+        
+        // Backward from nothing starts at the last node
+        state.set_focus(None);
+        state.focus_next(&tree, true);
+        assert_eq!(state.focused(), Some(button));
+    }
+}
@@ -5,21 +5,33 @@
 
 pub mod document;
 pub mod element;
+pub mod events;
+pub mod fragment;
+pub mod interaction;
 pub mod node;
 pub mod parser;
 pub mod tree;
+pub mod viewport;
 
-pub use document::Document;
-pub use element::Element;
+pub use document::{Doctype, Document, QuirksMode, StylesheetRef};
+pub use element::{Element, HTML_NAMESPACE, SVG_NAMESPACE};
+pub use events::{EventCallback, EventListener, ListenerId};
+pub use fragment::DocumentFragment;
+pub use interaction::{InteractionState, tab_order};
 pub use node::{Node, NodeType};
 pub use parser::HtmlParser;
-pub use tree::DomTree;
+pub use tree::{DomTree, TreeError};
+pub use viewport::{ViewportConfig, ViewportWidth};
 
 /// Re-export commonly used items for convenience
 pub mod prelude {
-    pub use super::document::Document;
-    pub use super::element::Element;
+    pub use super::document::{Doctype, Document, QuirksMode, StylesheetRef};
+    pub use super::element::{Element, HTML_NAMESPACE, SVG_NAMESPACE};
+    pub use super::events::{EventCallback, EventListener, ListenerId};
+    pub use super::fragment::DocumentFragment;
+    pub use super::interaction::{InteractionState, tab_order};
     pub use super::node::{Node, NodeType};
     pub use super::parser::HtmlParser;
-    pub use super::tree::DomTree;
+    pub use super::tree::{DomTree, TreeError};
+    pub use super::viewport::{ViewportConfig, ViewportWidth};
 }
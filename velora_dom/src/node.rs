@@ -48,6 +48,10 @@ pub struct Node {
     
     /// Additional node data
     pub data: HashMap<String, serde_json::Value>,
+    
+    /// Namespace URI of an element node; None means the HTML namespace
+    #[serde(default)]
+    pub namespace: Option<String>,
 }
 
 impl Node {
@@ -69,6 +73,7 @@ impl Node {
             next_sibling_id: None,
             element_id: None,
             data: HashMap::new(),
+            namespace: None,
         }
     }
     
@@ -87,6 +92,11 @@ impl Node {
         Self::new(id, NodeType::Comment, "#comment".to_string(), Some(comment))
     }
     
+    /// Create a new processing instruction node, named after its target
+    pub fn new_processing_instruction(id: NodeId, target: String, data: String) -> Self {
+        Self::new(id, NodeType::ProcessingInstruction, target, Some(data))
+    }
+    
     /// Create a new document node
     pub fn new_document(id: NodeId) -> Self {
         Self::new(id, NodeType::Document, "#document".to_string(), None)
@@ -194,6 +204,16 @@ impl Node {
         self.next_sibling_id = Some(sibling_id);
     }
     
+    /// Clear the previous sibling
+    pub fn clear_previous_sibling(&mut self) {
+        self.previous_sibling_id = None;
+    }
+    
+    /// Clear the next sibling
+    pub fn clear_next_sibling(&mut self) {
+        self.next_sibling_id = None;
+    }
+    
     /// Clear sibling relationships
     pub fn clear_siblings(&mut self) {
         self.previous_sibling_id = None;
@@ -258,6 +278,7 @@ impl Node {
             next_sibling_id: None,
             element_id: None, // Reset element ID
             data: self.data.clone(),
+            namespace: self.namespace.clone(),
         }
     }
 }
@@ -1,12 +1,15 @@
 //! DOM tree management for the Velora web engine
 
-use velora_core::{NodeId, ElementId, VeloraResult, VeloraError};
+use velora_core::{NodeId, ElementId, VeloraResult, VeloraError, IdGenerator, Event, EventPhase, EventType};
 use velora_core::error::DomError;
-use super::{Node, Element};
-use std::collections::HashMap;
+use super::{Node, Element, DocumentFragment};
+use crate::events::{EventCallback, EventListeners, ListenerId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 /// A DOM tree that manages the hierarchical relationship between nodes
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "DomTreeData")]
 pub struct DomTree {
     /// All nodes in the tree, indexed by their ID
     nodes: HashMap<NodeId, Node>,
@@ -17,11 +20,98 @@ pub struct DomTree {
     /// Root node ID
     root_id: Option<NodeId>,
     
-    /// Next available node ID
-    next_node_id: u64,
+    /// Allocator for node IDs, local to this tree
+    #[serde(skip)]
+    node_ids: IdGenerator,
     
-    /// Next available element ID
-    next_element_id: u64,
+    /// Allocator for element IDs, local to this tree
+    #[serde(skip)]
+    element_ids: IdGenerator,
+    
+    /// Event listeners registered on the tree's nodes
+    #[serde(skip)]
+    listeners: EventListeners,
+}
+
+/// A broken invariant found by `DomTree::validate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeError {
+    /// The root node has a parent
+    RootHasParent(NodeId),
+    
+    /// A node links to a node that is not in the tree
+    MissingNode { node: NodeId, missing: NodeId },
+    
+    /// A child and its parent disagree about their relationship
+    ParentMismatch { child: NodeId, parent: NodeId },
+    
+    /// A node's sibling does not link back to it
+    SiblingMismatch { node: NodeId, sibling: NodeId },
+    
+    /// Following next-sibling links from a node comes back around to it
+    SiblingCycle(NodeId),
+}
+
+/// Serialized form of a DomTree, checked before it becomes a tree
+#[derive(Deserialize)]
+struct DomTreeData {
+    nodes: HashMap<NodeId, Node>,
+    elements: HashMap<ElementId, Element>,
+    root_id: Option<NodeId>,
+}
+
+impl TryFrom<DomTreeData> for DomTree {
+    type Error = VeloraError;
+    
+    fn try_from(data: DomTreeData) -> VeloraResult<Self> {
+        let missing_node = |id: NodeId| {
+            VeloraError::Dom(DomError::TreeCorruption(format!("Node {} is referenced but missing", id.0)))
+        };
+        
+        if let Some(root_id) = data.root_id {
+            if !data.nodes.contains_key(&root_id) {
+                return Err(missing_node(root_id));
+            }
+        }
+        
+        for (id, node) in &data.nodes {
+            if node.id != *id {
+                return Err(VeloraError::Dom(DomError::TreeCorruption(
+                    format!("Node {} is stored under ID {}", node.id.0, id.0)
+                )));
+            }
+            
+            let links = node.parent_id.iter()
+                .chain(&node.child_ids)
+                .chain(&node.previous_sibling_id)
+                .chain(&node.next_sibling_id);
+            for linked in links {
+                if !data.nodes.contains_key(linked) {
+                    return Err(missing_node(*linked));
+                }
+            }
+            
+            if let Some(element_id) = node.element_id {
+                if !data.elements.contains_key(&element_id) {
+                    return Err(VeloraError::Dom(DomError::TreeCorruption(
+                        format!("Element {} is referenced but missing", element_id.0)
+                    )));
+                }
+            }
+        }
+        
+        let next_node_id = data.nodes.keys().map(|id| id.0 + 1).max().unwrap_or(1);
+        let next_element_id = data.elements.keys().map(|id| id.0 + 1).max().unwrap_or(1);
+        
+        Ok(Self {
+            nodes: data.nodes,
+            elements: data.elements,
+            root_id: data.root_id,
+            node_ids: IdGenerator::with_start(next_node_id),
+            element_ids: IdGenerator::with_start(next_element_id),
+            listeners: EventListeners::new(),
+        })
+    }
 }
 
 impl DomTree {
@@ -31,8 +121,9 @@ impl DomTree {
             nodes: HashMap::new(),
             elements: HashMap::new(),
             root_id: None,
-            next_node_id: 1,
-            next_element_id: 1,
+            node_ids: IdGenerator::new(),
+            element_ids: IdGenerator::new(),
+            listeners: EventListeners::new(),
         }
     }
     
@@ -41,10 +132,9 @@ impl DomTree {
         let node_id = node.id;
         self.nodes.insert(node_id, node);
         
-        // Update next ID if necessary
-        let id_value = node_id.0;
-        if id_value >= self.next_node_id {
-            self.next_node_id = id_value + 1;
+        // Never hand out an ID that is already taken
+        if node_id.0 >= self.node_ids.current() {
+            self.node_ids.reset(node_id.0 + 1);
         }
         
         // Set as root if it's the first node
@@ -58,10 +148,9 @@ impl DomTree {
         let element_id = element.id;
         self.elements.insert(element_id, element);
         
-        // Update next ID if necessary
-        let id_value = element_id.0;
-        if id_value >= self.next_element_id {
-            self.next_element_id = id_value + 1;
+        // Never hand out an ID that is already taken
+        if element_id.0 >= self.element_ids.current() {
+            self.element_ids.reset(element_id.0 + 1);
         }
     }
     
@@ -134,33 +223,49 @@ impl DomTree {
     
     /// Create a new element node
     pub fn create_element(&mut self, tag_name: &str) -> VeloraResult<NodeId> {
-        let node_id = NodeId(self.next_node_id);
-        let element_id = ElementId(self.next_element_id);
+        self.create_element_ns(tag_name, None)
+    }
+    
+    /// Create a new element node in a namespace; None means the HTML namespace
+    pub fn create_element_ns(&mut self, tag_name: &str, namespace: Option<&str>) -> VeloraResult<NodeId> {
+        let node_id = NodeId(self.node_ids.next());
+        let element_id = ElementId(self.element_ids.next());
+        let namespace = namespace.map(str::to_string);
         
         // Create the element
-        let element = Element::new(element_id, tag_name.to_string());
+        let element = Element::new_ns(element_id, tag_name.to_string(), namespace.clone());
         self.add_element(element);
         
         // Create the node
         let mut node = Node::new_element(node_id, tag_name.to_string());
+        node.namespace = namespace;
         node.set_element_id(element_id)?;
         self.add_node(node);
         
-        // Update IDs
-        self.next_node_id += 1;
-        self.next_element_id += 1;
-        
         Ok(node_id)
     }
     
     /// Create a new text node
     pub fn create_text_node(&mut self, text: &str) -> VeloraResult<NodeId> {
-        let node_id = NodeId(self.next_node_id);
+        let node_id = NodeId(self.node_ids.next());
         
         let node = Node::new_text(node_id, text.to_string());
         self.add_node(node);
         
-        self.next_node_id += 1;
+        Ok(node_id)
+    }
+    
+    /// Create a new comment node
+    pub fn create_comment(&mut self, text: &str) -> VeloraResult<NodeId> {
+        let node_id = NodeId(self.node_ids.next());
+        self.add_node(Node::new_comment(node_id, text.to_string()));
+        Ok(node_id)
+    }
+    
+    /// Create a new processing instruction node
+    pub fn create_processing_instruction(&mut self, target: &str, data: &str) -> VeloraResult<NodeId> {
+        let node_id = NodeId(self.node_ids.next());
+        self.add_node(Node::new_processing_instruction(node_id, target.to_string(), data.to_string()));
         Ok(node_id)
     }
     
@@ -169,7 +274,7 @@ impl DomTree {
         // First, get all the data we need to avoid multiple mutable borrows
         let last_child_id = {
             let parent = self.get_node(parent_id)?;
-            parent.child_ids.iter().rev().nth(1).copied()
+            parent.child_ids.last().copied().filter(|&last_child_id| last_child_id != child_id)
         };
         
         // Add child to parent
@@ -225,20 +330,18 @@ impl DomTree {
         // Update sibling relationships
         if let Some(prev_sibling_id) = prev_sibling_id {
             if let Ok(prev_sibling) = self.get_node_mut(prev_sibling_id) {
-                if let Some(next_sibling_id) = next_sibling_id {
-                    prev_sibling.set_next_sibling(next_sibling_id);
-                } else {
-                    prev_sibling.set_next_sibling(prev_sibling_id); // No next sibling, clear it
+                match next_sibling_id {
+                    Some(next_sibling_id) => prev_sibling.set_next_sibling(next_sibling_id),
+                    None => prev_sibling.clear_next_sibling(),
                 }
             }
         }
         
         if let Some(next_sibling_id) = next_sibling_id {
             if let Ok(next_sibling) = self.get_node_mut(next_sibling_id) {
-                if let Some(prev_sibling_id) = prev_sibling_id {
-                    next_sibling.set_previous_sibling(prev_sibling_id);
-                } else {
-                    next_sibling.set_previous_sibling(next_sibling_id); // No prev sibling, clear it
+                match prev_sibling_id {
+                    Some(prev_sibling_id) => next_sibling.set_previous_sibling(prev_sibling_id),
+                    None => next_sibling.clear_previous_sibling(),
                 }
             }
         }
@@ -246,6 +349,296 @@ impl DomTree {
         Ok(())
     }
     
+    /// Insert a child before an existing child of the parent
+    pub fn insert_before(&mut self, parent_id: NodeId, new_child_id: NodeId, reference_child_id: NodeId) -> VeloraResult<()> {
+        if new_child_id == reference_child_id {
+            return Ok(());
+        }
+        
+        self.check_insertable(parent_id, new_child_id)?;
+        self.child_position(parent_id, reference_child_id)?;
+        self.detach(new_child_id)?;
+        
+        // Look the reference up again: detaching may have shifted it
+        let index = self.child_position(parent_id, reference_child_id)?;
+        self.get_node_mut(parent_id)?.child_ids.insert(index, new_child_id);
+        self.get_node_mut(new_child_id)?.set_parent(parent_id);
+        self.relink_siblings(parent_id)
+    }
+    
+    /// Replace an existing child of the parent with another node
+    pub fn replace_child(&mut self, parent_id: NodeId, new_child_id: NodeId, old_child_id: NodeId) -> VeloraResult<()> {
+        if new_child_id == old_child_id {
+            return Ok(());
+        }
+        
+        self.check_insertable(parent_id, new_child_id)?;
+        self.child_position(parent_id, old_child_id)?;
+        self.detach(new_child_id)?;
+        
+        let index = self.child_position(parent_id, old_child_id)?;
+        self.get_node_mut(parent_id)?.child_ids[index] = new_child_id;
+        self.get_node_mut(new_child_id)?.set_parent(parent_id);
+        
+        {
+            let old_child = self.get_node_mut(old_child_id)?;
+            old_child.clear_parent();
+            old_child.clear_siblings();
+        }
+        
+        self.relink_siblings(parent_id)
+    }
+    
+    /// Move every top-level node of a fragment to the end of the parent's children, in order
+    ///
+    /// The nodes must be detached and listed once each. All nodes are validated before any
+    /// is moved, so a failure leaves both the tree and the fragment unchanged. On success the
+    /// fragment is left empty.
+    pub fn append_fragment(&mut self, parent_id: NodeId, fragment: &mut DocumentFragment) -> VeloraResult<()> {
+        self.get_node(parent_id)?;
+        let mut seen = HashSet::new();
+        for &child_id in &fragment.child_ids {
+            self.check_insertable(parent_id, child_id)?;
+            if !seen.insert(child_id) {
+                return Err(VeloraError::Dom(DomError::TreeCorruption(
+                    format!("Node {} appears more than once in the fragment", child_id.0)
+                )));
+            }
+            if let Some(current_parent) = self.get_node(child_id)?.parent_id {
+                return Err(VeloraError::Dom(DomError::TreeCorruption(
+                    format!("Fragment node {} is already a child of node {}", child_id.0, current_parent.0)
+                )));
+            }
+        }
+        
+        let child_ids = fragment.take_children();
+        for &child_id in &child_ids {
+            self.get_node_mut(child_id)?.set_parent(parent_id);
+        }
+        self.get_node_mut(parent_id)?.child_ids.extend(child_ids);
+        
+        // Siblings are linked once for the whole batch
+        self.relink_siblings(parent_id)
+    }
+    
+    /// Deep-copy a node and all its descendants, returning the ID of the detached copy
+    pub fn clone_subtree(&mut self, node_id: NodeId) -> VeloraResult<NodeId> {
+        let source = self.get_node(node_id)?.clone();
+        
+        let clone_id = NodeId(self.node_ids.next());
+        let mut clone = source.clone_with_id(clone_id);
+        
+        if let Some(element_id) = source.element_id {
+            let new_element_id = ElementId(self.element_ids.next());
+            let element = self.get_element(element_id)?.clone_with_id(new_element_id);
+            self.add_element(element);
+            clone.set_element_id(new_element_id)?;
+        }
+        self.add_node(clone);
+        
+        for child_id in source.child_ids {
+            let child_clone_id = self.clone_subtree(child_id)?;
+            self.get_node_mut(clone_id)?.add_child(child_clone_id);
+            self.get_node_mut(child_clone_id)?.set_parent(clone_id);
+        }
+        
+        self.relink_siblings(clone_id)?;
+        Ok(clone_id)
+    }
+    
+    /// Position of a child within its parent's child list
+    fn child_position(&self, parent_id: NodeId, child_id: NodeId) -> VeloraResult<usize> {
+        self.get_node(parent_id)?
+            .child_ids
+            .iter()
+            .position(|&id| id == child_id)
+            .ok_or_else(|| VeloraError::Dom(DomError::NodeNotFound(
+                format!("Child {} not found in parent {}", child_id.0, parent_id.0)
+            )))
+    }
+    
+    /// Ensure a node can be inserted under a parent without creating a cycle
+    fn check_insertable(&self, parent_id: NodeId, child_id: NodeId) -> VeloraResult<()> {
+        self.get_node(child_id)?;
+        
+        let mut current = Some(parent_id);
+        while let Some(id) = current {
+            if id == child_id {
+                return Err(VeloraError::Dom(DomError::TreeCorruption(
+                    format!("Cannot insert node {} into its own descendant {}", child_id.0, parent_id.0)
+                )));
+            }
+            current = self.get_node(id)?.parent_id;
+        }
+        
+        Ok(())
+    }
+    
+    /// Remove a node from its current parent, if it has one
+    fn detach(&mut self, node_id: NodeId) -> VeloraResult<()> {
+        let Some(parent_id) = self.get_node(node_id)?.parent_id else {
+            return Ok(());
+        };
+        
+        self.get_node_mut(parent_id)?.remove_child(node_id);
+        {
+            let node = self.get_node_mut(node_id)?;
+            node.clear_parent();
+            node.clear_siblings();
+        }
+        
+        self.relink_siblings(parent_id)
+    }
+    
+    /// Rebuild the sibling pointers of a parent's children from their order
+    fn relink_siblings(&mut self, parent_id: NodeId) -> VeloraResult<()> {
+        let child_ids = self.get_node(parent_id)?.child_ids.clone();
+        
+        for (index, &child_id) in child_ids.iter().enumerate() {
+            let child = self.get_node_mut(child_id)?;
+            child.previous_sibling_id = index.checked_sub(1).map(|prev| child_ids[prev]);
+            child.next_sibling_id = child_ids.get(index + 1).copied();
+        }
+        
+        Ok(())
+    }
+    
+    /// Check the parent, child and sibling links of every node
+    ///
+    /// Returns every broken invariant found, so tests can pinpoint a faulty mutation.
+    pub fn validate(&self) -> Result<(), Vec<TreeError>> {
+        let mut errors = Vec::new();
+        let mut node_ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        node_ids.sort_by_key(|id| id.0);
+        
+        if let Some(root) = self.root_id.and_then(|root_id| self.nodes.get(&root_id)) {
+            if root.parent_id.is_some() {
+                errors.push(TreeError::RootHasParent(root.id));
+            }
+        }
+        
+        for &node_id in &node_ids {
+            let node = &self.nodes[&node_id];
+            
+            if let Some(parent_id) = node.parent_id {
+                match self.nodes.get(&parent_id) {
+                    None => errors.push(TreeError::MissingNode { node: node_id, missing: parent_id }),
+                    Some(parent) if !parent.child_ids.contains(&node_id) => {
+                        errors.push(TreeError::ParentMismatch { child: node_id, parent: parent_id });
+                    }
+                    Some(_) => {}
+                }
+            }
+            
+            for &child_id in &node.child_ids {
+                match self.nodes.get(&child_id) {
+                    None => errors.push(TreeError::MissingNode { node: node_id, missing: child_id }),
+                    Some(child) if child.parent_id != Some(node_id) => {
+                        errors.push(TreeError::ParentMismatch { child: child_id, parent: node_id });
+                    }
+                    Some(_) => {}
+                }
+            }
+            
+            // The next sibling should point back through its previous link, and vice versa
+            let links = [(node.next_sibling_id, true), (node.previous_sibling_id, false)];
+            for (sibling_id, is_next) in links {
+                let Some(sibling_id) = sibling_id else {
+                    continue;
+                };
+                let Some(sibling) = self.nodes.get(&sibling_id) else {
+                    errors.push(TreeError::MissingNode { node: node_id, missing: sibling_id });
+                    continue;
+                };
+                let back_link = if is_next { sibling.previous_sibling_id } else { sibling.next_sibling_id };
+                if back_link != Some(node_id) {
+                    errors.push(TreeError::SiblingMismatch { node: node_id, sibling: sibling_id });
+                }
+            }
+        }
+        
+        // Walk each next-sibling chain once; revisiting a node within one walk is a cycle
+        let mut visited = HashSet::new();
+        for &start in &node_ids {
+            let mut walk = HashSet::new();
+            let mut current = Some(start);
+            while let Some(node_id) = current {
+                if walk.contains(&node_id) {
+                    errors.push(TreeError::SiblingCycle(node_id));
+                    break;
+                }
+                if !visited.insert(node_id) {
+                    break;
+                }
+                walk.insert(node_id);
+                current = self.nodes.get(&node_id).and_then(|node| node.next_sibling_id);
+            }
+        }
+        
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+    
+    /// Register a listener for an event type on a node
+    ///
+    /// Capture listeners run while the event travels down to the target; the others run
+    /// at the target and while it bubbles back up.
+    pub fn add_event_listener(&mut self, node_id: NodeId, event_type: EventType, capture: bool, callback: EventCallback) -> VeloraResult<ListenerId> {
+        self.get_node(node_id)?;
+        Ok(self.listeners.add(node_id, event_type, capture, callback))
+    }
+    
+    /// Remove a listener from a node; returns false if it was not registered there
+    pub fn remove_event_listener(&mut self, node_id: NodeId, listener_id: ListenerId) -> bool {
+        self.listeners.remove(node_id, listener_id)
+    }
+    
+    /// Dispatch an event at a node through the capture, target and bubble phases
+    ///
+    /// Returns false if a listener called `prevent_default`, like `dispatchEvent` in browsers.
+    pub fn dispatch_event(&self, target: NodeId, mut event: Event) -> VeloraResult<bool> {
+        // Ancestors from the target's parent up to the root
+        let mut ancestors = Vec::new();
+        let mut current = self.get_node(target)?.parent_id;
+        while let Some(node_id) = current {
+            ancestors.push(node_id);
+            current = self.get_node(node_id)?.parent_id;
+        }
+        
+        event.target = target;
+        event.propagation_stopped = false;
+        
+        let path = ancestors.iter().rev().map(|&id| (id, EventPhase::Capturing))
+            .chain(std::iter::once((target, EventPhase::AtTarget)))
+            .chain(ancestors.iter().map(|&id| (id, EventPhase::Bubbling)));
+        
+        for (node_id, phase) in path {
+            event.current_target = node_id;
+            event.phase = phase;
+            
+            for listener in self.listeners.listeners_for(node_id, &event.event_type) {
+                let runs = match phase {
+                    EventPhase::Capturing => listener.capture,
+                    EventPhase::Bubbling => !listener.capture,
+                    _ => true,
+                };
+                if runs {
+                    (listener.callback)(&mut event);
+                }
+            }
+            
+            // Listeners on the node that stopped propagation still all run
+            if event.propagation_stopped {
+                break;
+            }
+        }
+        
+        Ok(!event.default_prevented)
+    }
+    
     /// Get the number of nodes in the tree
     pub fn node_count(&self) -> usize {
         self.nodes.len()
@@ -282,6 +675,63 @@ impl DomTree {
         Ok(())
     }
     
+    /// Traverse the tree in breadth-first (level) order
+    ///
+    /// Every node at depth `n` is visited before any node at depth `n + 1`, and siblings
+    /// are visited in child order. As with `traverse_dfs`, returning false from the
+    /// visitor skips that node's children.
+    pub fn traverse_bfs<F>(&self, mut visitor: F) -> VeloraResult<()>
+    where
+        F: FnMut(&Node) -> VeloraResult<bool>,
+    {
+        let Some(root_id) = self.root_id else {
+            return Ok(());
+        };
+        
+        let mut queue = VecDeque::from([root_id]);
+        while let Some(node_id) = queue.pop_front() {
+            let node = self.get_node(node_id)?;
+            if visitor(node)? {
+                queue.extend(node.child_ids.iter().copied());
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Iterate over a node and its descendants in breadth-first (level) order
+    pub fn descendants_bfs(&self, start: NodeId) -> impl Iterator<Item = &Node> + '_ {
+        let mut queue = VecDeque::from([start]);
+        
+        std::iter::from_fn(move || {
+            while let Some(node_id) = queue.pop_front() {
+                if let Some(node) = self.nodes.get(&node_id) {
+                    queue.extend(node.child_ids.iter().copied());
+                    return Some(node);
+                }
+            }
+            None
+        })
+    }
+    
+    /// Iterate over a node and its descendants in depth-first pre-order
+    ///
+    /// Yields the same sequence as `traverse_dfs` started at `start`; an unknown start yields nothing.
+    pub fn descendants(&self, start: NodeId) -> impl Iterator<Item = &Node> + '_ {
+        let mut stack = vec![start];
+        
+        std::iter::from_fn(move || {
+            while let Some(node_id) = stack.pop() {
+                if let Some(node) = self.nodes.get(&node_id) {
+                    // Push children in reverse so the first child is visited next
+                    stack.extend(node.child_ids.iter().rev());
+                    return Some(node);
+                }
+            }
+            None
+        })
+    }
+    
     /// Traverse a specific node and its descendants
     fn traverse_node_dfs<F>(&self, node_id: NodeId, visitor: &mut F) -> VeloraResult<()>
     where
@@ -313,6 +763,7 @@ impl Default for DomTree {
 mod tests {
     use super::*;
     use velora_core::NodeId;
+    use std::sync::{Arc, Mutex};
     
     #[test]
     fn test_dom_tree_creation() {
@@ -402,4 +853,440 @@ mod tests {
         tree.remove_child(parent_id, child_id).unwrap();
         assert!(!tree.get_node(parent_id).unwrap().has_child(child_id));
     }
+    
+    fn assert_sibling_links(tree: &DomTree, parent_id: NodeId, expected: &[NodeId]) {
+        assert_eq!(tree.get_node(parent_id).unwrap().child_ids, expected);
+        
+        for (index, &child_id) in expected.iter().enumerate() {
+            let child = tree.get_node(child_id).unwrap();
+            assert_eq!(child.parent_id, Some(parent_id));
+            assert_eq!(child.previous_sibling_id, index.checked_sub(1).map(|prev| expected[prev]));
+            assert_eq!(child.next_sibling_id, expected.get(index + 1).copied());
+        }
+    }
+    
+    #[test]
+    fn test_insert_before() {
+        let mut tree = DomTree::new();
+        let parent_id = tree.create_element("ul").unwrap();
+        let first = tree.create_element("li").unwrap();
+        let last = tree.create_element("li").unwrap();
+        let front = tree.create_element("li").unwrap();
+        let middle = tree.create_element("li").unwrap();
+        
+        tree.append_child(parent_id, first).unwrap();
+        tree.append_child(parent_id, last).unwrap();
+        
+        // Insert at the front
+        tree.insert_before(parent_id, front, first).unwrap();
+        assert_sibling_links(&tree, parent_id, &[front, first, last]);
+        
+        // Insert in the middle
+        tree.insert_before(parent_id, middle, last).unwrap();
+        assert_sibling_links(&tree, parent_id, &[front, first, middle, last]);
+        
+        // Moving an existing child relinks both positions
+        tree.insert_before(parent_id, last, front).unwrap();
+        assert_sibling_links(&tree, parent_id, &[last, front, first, middle]);
+    }
+    
+    #[test]
+    fn test_validate_built_tree() {
+        let mut tree = DomTree::new();
+        let root = tree.create_element("html").unwrap();
+        tree.set_root(root).unwrap();
+        let body = tree.create_element("body").unwrap();
+        tree.append_child(root, body).unwrap();
+        let items: Vec<NodeId> = (0..4).map(|_| tree.create_element("p").unwrap()).collect();
+        for &item in &items {
+            tree.append_child(body, item).unwrap();
+        }
+        tree.remove_child(body, items[3]).unwrap();
+        tree.insert_before(body, items[3], items[0]).unwrap();
+        tree.remove_child(body, items[1]).unwrap();
+        
+        assert_eq!(tree.validate(), Ok(()));
+    }
+    
+    #[test]
+    fn test_validate_detects_corruption() {
+        let mut tree = DomTree::new();
+        let root = tree.create_element("div").unwrap();
+        tree.set_root(root).unwrap();
+        let items: Vec<NodeId> = (0..3).map(|_| tree.create_element("span").unwrap()).collect();
+        for &item in &items {
+            tree.append_child(root, item).unwrap();
+        }
+        
+        // Point the last sibling back at the first, closing a loop
+        tree.get_node_mut(items[2]).unwrap().next_sibling_id = Some(items[0]);
+        let errors = tree.validate().unwrap_err();
+        assert!(errors.contains(&TreeError::SiblingCycle(items[0])));
+        assert!(errors.contains(&TreeError::SiblingMismatch { node: items[2], sibling: items[0] }));
+        
+        tree.get_node_mut(items[2]).unwrap().next_sibling_id = None;
+        tree.get_node_mut(root).unwrap().parent_id = Some(items[1]);
+        let errors = tree.validate().unwrap_err();
+        assert!(errors.contains(&TreeError::RootHasParent(root)));
+        assert!(errors.contains(&TreeError::ParentMismatch { child: root, parent: items[1] }));
+    }
+    
+    #[test]
+    fn test_append_child_links_siblings() {
+        let mut tree = DomTree::new();
+        let parent_id = tree.create_element("ul").unwrap();
+        let items: Vec<NodeId> = (0..3).map(|_| tree.create_element("li").unwrap()).collect();
+        
+        for (count, &item) in items.iter().enumerate() {
+            tree.append_child(parent_id, item).unwrap();
+            assert_sibling_links(&tree, parent_id, &items[..=count]);
+        }
+        
+        assert_eq!(tree.get_node(items[0]).unwrap().previous_sibling_id, None);
+        assert_eq!(tree.get_node(items[0]).unwrap().next_sibling_id, Some(items[1]));
+        assert_eq!(tree.get_node(items[1]).unwrap().previous_sibling_id, Some(items[0]));
+        assert_eq!(tree.get_node(items[1]).unwrap().next_sibling_id, Some(items[2]));
+        assert_eq!(tree.get_node(items[2]).unwrap().previous_sibling_id, Some(items[1]));
+        assert_eq!(tree.get_node(items[2]).unwrap().next_sibling_id, None);
+    }
+    
+    #[test]
+    fn test_remove_edge_child_clears_sibling_links() {
+        let mut tree = DomTree::new();
+        let parent_id = tree.create_element("ul").unwrap();
+        let first = tree.create_element("li").unwrap();
+        let middle = tree.create_element("li").unwrap();
+        let last = tree.create_element("li").unwrap();
+        
+        tree.append_child(parent_id, last).unwrap();
+        tree.insert_before(parent_id, middle, last).unwrap();
+        tree.insert_before(parent_id, first, middle).unwrap();
+        
+        // The new last child must not point at itself
+        tree.remove_child(parent_id, last).unwrap();
+        assert_eq!(tree.get_node(middle).unwrap().next_sibling_id, None);
+        assert_sibling_links(&tree, parent_id, &[first, middle]);
+        
+        tree.remove_child(parent_id, first).unwrap();
+        assert_eq!(tree.get_node(middle).unwrap().previous_sibling_id, None);
+        assert_sibling_links(&tree, parent_id, &[middle]);
+    }
+    
+    #[test]
+    fn test_insert_before_non_child_reference() {
+        let mut tree = DomTree::new();
+        let parent_id = tree.create_element("div").unwrap();
+        let child_id = tree.create_element("span").unwrap();
+        let stranger = tree.create_element("p").unwrap();
+        
+        let result = tree.insert_before(parent_id, child_id, stranger);
+        assert!(matches!(result, Err(VeloraError::Dom(DomError::NodeNotFound(_)))));
+        
+        // Nothing was moved
+        assert!(!tree.get_node(parent_id).unwrap().has_children());
+        assert!(!tree.get_node(child_id).unwrap().has_parent());
+    }
+    
+    #[test]
+    fn test_replace_child() {
+        let mut tree = DomTree::new();
+        let parent_id = tree.create_element("div").unwrap();
+        let first = tree.create_element("span").unwrap();
+        let old_child = tree.create_element("span").unwrap();
+        let last = tree.create_element("span").unwrap();
+        let new_child = tree.create_element("em").unwrap();
+        
+        tree.append_child(parent_id, first).unwrap();
+        tree.append_child(parent_id, old_child).unwrap();
+        tree.append_child(parent_id, last).unwrap();
+        
+        tree.replace_child(parent_id, new_child, old_child).unwrap();
+        assert_sibling_links(&tree, parent_id, &[first, new_child, last]);
+        
+        let old = tree.get_node(old_child).unwrap();
+        assert!(!old.has_parent());
+        assert!(!old.has_siblings());
+        
+        let result = tree.replace_child(parent_id, old_child, NodeId(999));
+        assert!(result.is_err());
+    }
+    
+    #[test]
+    fn test_append_fragment() {
+        let mut tree = DomTree::new();
+        let parent_id = tree.create_element("ul").unwrap();
+        let existing = tree.create_element("li").unwrap();
+        tree.append_child(parent_id, existing).unwrap();
+        
+        let mut fragment = DocumentFragment::new();
+        let items: Vec<NodeId> = (0..3).map(|_| tree.create_element("li").unwrap()).collect();
+        for &item in &items {
+            fragment.append(item);
+        }
+        
+        tree.append_fragment(parent_id, &mut fragment).unwrap();
+        
+        assert!(fragment.is_empty());
+        assert_sibling_links(&tree, parent_id, &[existing, items[0], items[1], items[2]]);
+    }
+    
+    #[test]
+    fn test_append_fragment_rejects_cycles() {
+        let mut tree = DomTree::new();
+        let parent_id = tree.create_element("div").unwrap();
+        let child_id = tree.create_element("span").unwrap();
+        let grandchild_id = tree.create_element("em").unwrap();
+        tree.append_child(parent_id, child_id).unwrap();
+        tree.append_child(child_id, grandchild_id).unwrap();
+        
+        let mut fragment = DocumentFragment::new();
+        fragment.append(tree.create_text_node("ok").unwrap());
+        fragment.append(parent_id);
+        
+        let result = tree.append_fragment(grandchild_id, &mut fragment);
+        assert!(matches!(result, Err(VeloraError::Dom(DomError::TreeCorruption(_)))));
+        
+        // Nothing was moved and the fragment keeps its nodes
+        assert_eq!(fragment.len(), 2);
+        assert!(!tree.get_node(grandchild_id).unwrap().has_children());
+    }
+    
+    #[test]
+    fn test_append_fragment_rejects_duplicate_and_attached_nodes() {
+        let mut tree = DomTree::new();
+        let parent_id = tree.create_element("ul").unwrap();
+        let other_id = tree.create_element("ol").unwrap();
+        let attached = tree.create_element("li").unwrap();
+        tree.append_child(other_id, attached).unwrap();
+        let item = tree.create_element("li").unwrap();
+        
+        let mut fragment = DocumentFragment::new();
+        fragment.append(item);
+        fragment.append(item);
+        let result = tree.append_fragment(parent_id, &mut fragment);
+        assert!(matches!(result, Err(VeloraError::Dom(DomError::TreeCorruption(_)))));
+        assert_eq!(fragment.len(), 2);
+        
+        let mut fragment = DocumentFragment::new();
+        fragment.append(item);
+        fragment.append(attached);
+        let result = tree.append_fragment(parent_id, &mut fragment);
+        assert!(matches!(result, Err(VeloraError::Dom(DomError::TreeCorruption(_)))));
+        
+        // The attached node stays where it was and nothing joined the parent
+        assert_eq!(tree.get_node(attached).unwrap().parent_id, Some(other_id));
+        assert!(!tree.get_node(parent_id).unwrap().has_children());
+        assert!(tree.get_node(item).unwrap().parent_id.is_none());
+    }
+    
+    /// A chain of nested elements, outermost first
+    fn nested_chain(tree: &mut DomTree, depth: usize) -> Vec<NodeId> {
+        let chain: Vec<NodeId> = (0..depth).map(|_| tree.create_element("div").unwrap()).collect();
+        for pair in chain.windows(2) {
+            tree.append_child(pair[0], pair[1]).unwrap();
+        }
+        chain
+    }
+    
+    /// Record (node, phase) each time a listener runs
+    fn recorder(log: &Arc<Mutex<Vec<(u64, EventPhase)>>>) -> EventCallback {
+        let log = Arc::clone(log);
+        Arc::new(move |event: &mut Event| log.lock().unwrap().push((event.current_target.0, event.phase)))
+    }
+    
+    #[test]
+    fn test_dispatch_bubbles_to_root() {
+        let mut tree = DomTree::new();
+        let chain = nested_chain(&mut tree, 4);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        for &node_id in &chain {
+            tree.add_event_listener(node_id, EventType::Click, false, recorder(&log)).unwrap();
+        }
+        // Listeners for other event types are not invoked
+        tree.add_event_listener(chain[0], EventType::KeyDown, false, recorder(&log)).unwrap();
+        
+        let target = chain[3];
+        assert!(tree.dispatch_event(target, Event::new(EventType::Click, target)).unwrap());
+        
+        let order: Vec<u64> = log.lock().unwrap().iter().map(|&(id, _)| id).collect();
+        assert_eq!(order, chain.iter().rev().map(|id| id.0).collect::<Vec<_>>());
+        assert_eq!(log.lock().unwrap()[0].1, EventPhase::AtTarget);
+        assert!(log.lock().unwrap()[1..].iter().all(|&(_, phase)| phase == EventPhase::Bubbling));
+    }
+    
+    #[test]
+    fn test_dispatch_capture_before_bubble() {
+        let mut tree = DomTree::new();
+        let chain = nested_chain(&mut tree, 3);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        tree.add_event_listener(chain[0], EventType::Click, true, recorder(&log)).unwrap();
+        tree.add_event_listener(chain[0], EventType::Click, false, recorder(&log)).unwrap();
+        tree.add_event_listener(chain[2], EventType::Click, true, recorder(&log)).unwrap();
+        
+        tree.dispatch_event(chain[2], Event::new(EventType::Click, chain[2])).unwrap();
+        
+        assert_eq!(*log.lock().unwrap(), vec![
+            (chain[0].0, EventPhase::Capturing),
+            (chain[2].0, EventPhase::AtTarget),
+            (chain[0].0, EventPhase::Bubbling),
+        ]);
+    }
+    
+    #[test]
+    fn test_stop_propagation_halts_bubbling() {
+        let mut tree = DomTree::new();
+        let chain = nested_chain(&mut tree, 4);
+        let log = Arc::new(Mutex::new(Vec::new()));
+        for &node_id in &chain {
+            tree.add_event_listener(node_id, EventType::Click, false, recorder(&log)).unwrap();
+        }
+        tree.add_event_listener(chain[2], EventType::Click, false, Arc::new(|event: &mut Event| {
+            event.stop_propagation();
+            event.prevent_default();
+        })).unwrap();
+        
+        let not_cancelled = tree.dispatch_event(chain[3], Event::new(EventType::Click, chain[3])).unwrap();
+        
+        assert!(!not_cancelled);
+        let order: Vec<u64> = log.lock().unwrap().iter().map(|&(id, _)| id).collect();
+        assert_eq!(order, vec![chain[3].0, chain[2].0]);
+    }
+    
+    #[test]
+    fn test_remove_event_listener() {
+        let mut tree = DomTree::new();
+        let node_id = tree.create_element("button").unwrap();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let listener = tree.add_event_listener(node_id, EventType::Click, false, recorder(&log)).unwrap();
+        
+        assert!(tree.remove_event_listener(node_id, listener));
+        tree.dispatch_event(node_id, Event::new(EventType::Click, node_id)).unwrap();
+        assert!(log.lock().unwrap().is_empty());
+        
+        assert!(tree.add_event_listener(NodeId(999), EventType::Click, false, recorder(&log)).is_err());
+    }
+    
+    #[test]
+    fn test_clone_subtree() {
+        let mut tree = DomTree::new();
+        let root = tree.create_element("div").unwrap();
+        let heading = tree.create_element("h1").unwrap();
+        let text = tree.create_text_node("Title").unwrap();
+        let paragraph = tree.create_element("p").unwrap();
+        tree.append_child(root, heading).unwrap();
+        tree.append_child(heading, text).unwrap();
+        tree.insert_before(root, paragraph, heading).unwrap();
+        
+        let heading_element = tree.get_node(heading).unwrap().element_id.unwrap();
+        tree.get_element_mut(heading_element).unwrap().set_attribute("class".to_string(), "big".to_string());
+        
+        let clone = tree.clone_subtree(root).unwrap();
+        assert_ne!(clone, root);
+        assert!(!tree.get_node(clone).unwrap().has_parent());
+        assert_eq!(tree.node_count(), 8);
+        
+        // Same shape, fresh IDs
+        let clone_children = tree.get_node(clone).unwrap().child_ids.clone();
+        assert_eq!(clone_children.len(), 2);
+        assert!(!clone_children.contains(&paragraph) && !clone_children.contains(&heading));
+        assert_sibling_links(&tree, clone, &clone_children);
+        
+        let names: Vec<_> = clone_children.iter().map(|&id| tree.get_node(id).unwrap().node_name.clone()).collect();
+        assert_eq!(names, vec!["p", "h1"]);
+        
+        let heading_clone = tree.get_node(clone_children[1]).unwrap().clone();
+        assert_eq!(heading_clone.child_ids.len(), 1);
+        let text_clone = tree.get_node(heading_clone.child_ids[0]).unwrap();
+        assert_ne!(text_clone.id, text);
+        assert_eq!(text_clone.node_value.as_deref(), Some("Title"));
+        
+        // Elements are copied, not shared
+        let element_clone_id = heading_clone.element_id.unwrap();
+        assert_ne!(element_clone_id, heading_element);
+        tree.get_element_mut(element_clone_id).unwrap().set_attribute("class".to_string(), "small".to_string());
+        assert_eq!(tree.get_element(heading_element).unwrap().get_attribute("class"), Some("big"));
+        
+        // Mutating the clone leaves the original untouched
+        tree.remove_child(clone, clone_children[0]).unwrap();
+        assert_eq!(tree.get_node(root).unwrap().child_ids, vec![paragraph, heading]);
+    }
+    
+    #[test]
+    fn test_descendants_matches_traverse_dfs() {
+        let mut tree = DomTree::new();
+        let root = tree.create_element("div").unwrap();
+        let list = tree.create_element("ul").unwrap();
+        let first = tree.create_element("li").unwrap();
+        let second = tree.create_element("li").unwrap();
+        let text = tree.create_text_node("item").unwrap();
+        let footer = tree.create_element("p").unwrap();
+        tree.append_child(root, list).unwrap();
+        tree.append_child(list, first).unwrap();
+        tree.append_child(first, text).unwrap();
+        tree.append_child(list, second).unwrap();
+        tree.append_child(root, footer).unwrap();
+        
+        let mut visited = Vec::new();
+        tree.traverse_dfs(|node| {
+            visited.push(node.id);
+            Ok(true)
+        }).unwrap();
+        
+        let iterated: Vec<_> = tree.descendants(root).map(|node| node.id).collect();
+        assert_eq!(iterated, visited);
+        assert_eq!(iterated, vec![root, list, first, text, second, footer]);
+        
+        // Composes with iterator adapters and can start anywhere
+        let items: Vec<_> = tree.descendants(root).filter(|node| node.node_name == "li").map(|node| node.id).take(1).collect();
+        assert_eq!(items, vec![first]);
+        assert_eq!(tree.descendants(list).count(), 4);
+        assert_eq!(tree.descendants(NodeId(999)).count(), 0);
+    }
+    
+    #[test]
+    fn test_bfs_order() {
+        // root
+        // ├── a
+        // │   └── a1
+        // │       └── a1x
+        // └── b
+        //     ├── b1
+        //     └── b2
+        let mut tree = DomTree::new();
+        let root = tree.create_element("div").unwrap();
+        let a = tree.create_element("section").unwrap();
+        let a1 = tree.create_element("p").unwrap();
+        let a1x = tree.create_text_node("deep").unwrap();
+        let b = tree.create_element("ul").unwrap();
+        let b1 = tree.create_element("li").unwrap();
+        let b2 = tree.create_element("li").unwrap();
+        tree.append_child(root, a).unwrap();
+        tree.append_child(a, a1).unwrap();
+        tree.append_child(a1, a1x).unwrap();
+        tree.append_child(root, b).unwrap();
+        tree.append_child(b, b1).unwrap();
+        tree.append_child(b, b2).unwrap();
+        
+        let mut visited = Vec::new();
+        tree.traverse_bfs(|node| {
+            visited.push(node.id);
+            Ok(true)
+        }).unwrap();
+        
+        let expected = vec![root, a, b, a1, b1, b2, a1x];
+        assert_eq!(visited, expected);
+        assert_eq!(tree.descendants_bfs(root).map(|node| node.id).collect::<Vec<_>>(), expected);
+        
+        let dfs: Vec<_> = tree.descendants(root).map(|node| node.id).collect();
+        assert_eq!(dfs, vec![root, a, a1, a1x, b, b1, b2]);
+        assert_ne!(visited, dfs);
+        
+        // Returning false skips a node's children
+        let mut pruned = Vec::new();
+        tree.traverse_bfs(|node| {
+            pruned.push(node.id);
+            Ok(node.id != a)
+        }).unwrap();
+        assert_eq!(pruned, vec![root, a, b, b1, b2]);
+    }
 }
@@ -0,0 +1,131 @@
+//! Viewport meta tag handling for the Velora web engine
+//!
+//! Pages declare their layout viewport with `<meta name="viewport" content="...">`.
+//! Only `width` and `initial-scale` are understood; other keys are ignored.
+
+use velora_core::Size;
+use serde::{Deserialize, Serialize};
+
+/// Smallest and largest `initial-scale` accepted, as in browsers
+const SCALE_RANGE: (f32, f32) = (0.1, 10.0);
+
+/// Smallest and largest explicit `width` accepted, in CSS pixels
+const WIDTH_RANGE: (f32, f32) = (1.0, 10000.0);
+
+/// Layout width mobile browsers give pages without a viewport meta tag, which were written
+/// for desktop screens
+pub const LEGACY_MOBILE_WIDTH: f32 = 980.0;
+
+/// Requested width of the layout viewport
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ViewportWidth {
+    /// As wide as the window, divided by the initial scale
+    DeviceWidth,
+    /// A fixed width in CSS pixels
+    Pixels(f32),
+}
+
+/// Layout viewport settings from a viewport meta tag
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ViewportConfig {
+    /// Layout viewport width
+    pub width: ViewportWidth,
+    /// Zoom applied when the page is first shown
+    pub initial_scale: f32,
+}
+
+impl Default for ViewportConfig {
+    fn default() -> Self {
+        Self { width: ViewportWidth::DeviceWidth, initial_scale: 1.0 }
+    }
+}
+
+impl ViewportConfig {
+    /// Viewport for a page without a viewport meta tag
+    ///
+    /// Desktop browsers lay such pages out at the window size; mobile ones assume a desktop
+    /// page and lay it out at `LEGACY_MOBILE_WIDTH`, scaled to fit.
+    pub fn fallback(mobile: bool) -> Self {
+        if mobile {
+            Self { width: ViewportWidth::Pixels(LEGACY_MOBILE_WIDTH), initial_scale: 1.0 }
+        } else {
+            Self::default()
+        }
+    }
+    
+    /// Parse the `content` attribute of a viewport meta tag
+    ///
+    /// Entries are separated by commas or semicolons; unknown keys and invalid values are skipped.
+    pub fn parse(content: &str) -> Self {
+        let mut config = Self::default();
+        
+        for entry in content.split([',', ';']) {
+            let Some((key, value)) = entry.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().to_ascii_lowercase();
+            
+            match key.trim().to_ascii_lowercase().as_str() {
+                "width" if value == "device-width" => config.width = ViewportWidth::DeviceWidth,
+                "width" => {
+                    if let Ok(width) = value.parse::<f32>() {
+                        config.width = ViewportWidth::Pixels(width.clamp(WIDTH_RANGE.0, WIDTH_RANGE.1));
+                    }
+                }
+                "initial-scale" => {
+                    if let Some(scale) = value.parse::<f32>().ok().filter(|scale| *scale > 0.0) {
+                        config.initial_scale = scale.clamp(SCALE_RANGE.0, SCALE_RANGE.1);
+                    }
+                }
+                _ => {}
+            }
+        }
+        
+        config
+    }
+    
+    /// Size of the layout viewport for a window, keeping the window's aspect ratio
+    pub fn layout_size(&self, window: Size) -> Size {
+        let width = match self.width {
+            ViewportWidth::DeviceWidth => window.width / self.initial_scale,
+            ViewportWidth::Pixels(width) => width,
+        };
+        
+        if window.width <= 0.0 {
+            return window;
+        }
+        Size::new(width, window.height * width / window.width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_parse_device_width() {
+        let config = ViewportConfig::parse("width=device-width, initial-scale=1");
+        assert_eq!(config, ViewportConfig { width: ViewportWidth::DeviceWidth, initial_scale: 1.0 });
+        
+        let config = ViewportConfig::parse("width=980; initial-scale=0.01; user-scalable=no");
+        assert_eq!(config, ViewportConfig { width: ViewportWidth::Pixels(980.0), initial_scale: 0.1 });
+        
+        // Garbage leaves the defaults in place
+        assert_eq!(ViewportConfig::parse("width=wide, initial-scale"), ViewportConfig::default());
+    }
+    
+    #[test]
+    fn test_layout_size() {
+        let window = Size::new(800.0, 600.0);
+        assert_eq!(ViewportConfig::default().layout_size(window), window);
+        assert_eq!(ViewportConfig::parse("width=device-width,initial-scale=2").layout_size(window), Size::new(400.0, 300.0));
+        assert_eq!(ViewportConfig::parse("width=1600").layout_size(window), Size::new(1600.0, 1200.0));
+    }
+    
+    #[test]
+    fn test_fallback_for_mobile() {
+        let window = Size::new(400.0, 800.0);
+        assert_eq!(ViewportConfig::fallback(false).layout_size(window), window);
+        assert_eq!(ViewportConfig::fallback(true).layout_size(window), Size::new(980.0, 1960.0));
+    }
+}
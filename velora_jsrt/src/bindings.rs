@@ -1,13 +1,47 @@
 //! DOM bindings for JavaScript in the Velora web engine
 
-use velora_core::{VeloraResult, VeloraError};
+use velora_core::{VeloraResult, VeloraError, Event, EventType, NodeId};
 use velora_core::error::JsRuntimeError;
+use velora_dom::{DomTree, ListenerId};
+use velora_net::HttpClient;
+use super::context::JsContext;
+use super::fetch::{check_fetch_url, FetchClient, FetchResponse};
+use super::promise::JsPromise;
+use std::sync::{Arc, Mutex};
+
+/// A script listener registered through `addEventListener`
+#[derive(Debug, Clone)]
+struct ScriptListener {
+    id: ListenerId,
+    node_id: NodeId,
+    event_type: EventType,
+    source: String,
+}
+
+/// A script callback whose event has fired and that is waiting to run
+#[derive(Debug, Clone)]
+pub struct PendingCallback {
+    /// Script source of the listener function
+    pub source: String,
+    
+    /// The event as the listener saw it
+    pub event: Event,
+}
 
 /// DOM bindings for JavaScript
 #[derive(Debug)]
 pub struct DomBindings {
     /// Binding registry
     bindings: std::collections::HashMap<String, Box<dyn std::any::Any>>,
+    
+    /// Listeners added from scripts
+    script_listeners: Vec<ScriptListener>,
+    
+    /// Callbacks queued by dispatched events, run by `JsRuntime::run_event_callbacks`
+    pending_callbacks: Arc<Mutex<Vec<PendingCallback>>>,
+    
+    /// Client that `fetch` requests go through
+    fetch_client: Arc<dyn FetchClient>,
 }
 
 impl DomBindings {
@@ -15,6 +49,98 @@ impl DomBindings {
     pub fn new() -> VeloraResult<Self> {
         Ok(Self {
             bindings: std::collections::HashMap::new(),
+            script_listeners: Vec::new(),
+            pending_callbacks: Arc::new(Mutex::new(Vec::new())),
+            fetch_client: Arc::new(HttpClient::new()?),
+        })
+    }
+    
+    /// Send `fetch` requests through another client, such as the browser's shared one
+    pub fn set_fetch_client(&mut self, client: Arc<dyn FetchClient>) {
+        self.fetch_client = client;
+    }
+    
+    /// `fetch(url)`: start a GET request and return a promise for its response
+    ///
+    /// The request runs on the current tokio runtime. Its promise settles when the
+    /// request finishes, and the reactions run the next time `context` drains its
+    /// microtasks. URLs other than http, https and data are rejected.
+    pub fn fetch(&self, context: &JsContext, url: &str) -> JsPromise<FetchResponse> {
+        let promise = context.create_promise();
+        if let Err(e) = check_fetch_url(url) {
+            promise.reject(e.to_string());
+            return promise;
+        }
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            promise.reject("fetch requires a tokio runtime");
+            return promise;
+        };
+        
+        let request = self.fetch_client.get(url);
+        let settle = promise.clone();
+        let url = url.to_string();
+        runtime.spawn(async move {
+            match request.await {
+                Ok(response) => settle.resolve(FetchResponse::from_response(&url, response)),
+                Err(e) => settle.reject(e.to_string()),
+            }
+        });
+        promise
+    }
+    
+    /// `element.addEventListener(type, fn)`: register a script function on a node
+    ///
+    /// Dispatching a matching event queues the function to run in a script context.
+    /// As in browsers, adding the same function twice for one type has no effect.
+    pub fn add_event_listener(&mut self, tree: &mut DomTree, node_id: NodeId, event_type: &str, source: &str) -> VeloraResult<ListenerId> {
+        let event_type = EventType::from_name(event_type);
+        if let Some(existing) = self.find_script_listener(node_id, &event_type, source) {
+            return Ok(existing.id);
+        }
+        
+        let queue = Arc::clone(&self.pending_callbacks);
+        let callback_source = source.to_string();
+        let id = tree.add_event_listener(node_id, event_type.clone(), false, Arc::new(move |event: &mut Event| {
+            if let Ok(mut queue) = queue.lock() {
+                queue.push(PendingCallback {
+                    source: callback_source.clone(),
+                    event: event.clone(),
+                });
+            }
+        }))?;
+        
+        self.script_listeners.push(ScriptListener {
+            id,
+            node_id,
+            event_type,
+            source: source.to_string(),
+        });
+        Ok(id)
+    }
+    
+    /// `element.removeEventListener(type, fn)`: returns false if the function was not registered
+    pub fn remove_event_listener(&mut self, tree: &mut DomTree, node_id: NodeId, event_type: &str, source: &str) -> bool {
+        let event_type = EventType::from_name(event_type);
+        let Some(id) = self.find_script_listener(node_id, &event_type, source).map(|listener| listener.id) else {
+            return false;
+        };
+        
+        self.script_listeners.retain(|listener| listener.id != id);
+        tree.remove_event_listener(node_id, id)
+    }
+    
+    /// Take the callbacks queued by dispatched events, oldest first
+    pub fn take_pending_callbacks(&self) -> Vec<PendingCallback> {
+        self.pending_callbacks
+            .lock()
+            .map(|mut queue| std::mem::take(&mut *queue))
+            .unwrap_or_default()
+    }
+    
+    /// Find a script listener by node, type and function
+    fn find_script_listener(&self, node_id: NodeId, event_type: &EventType, source: &str) -> Option<&ScriptListener> {
+        self.script_listeners.iter().find(|listener| {
+            listener.node_id == node_id && &listener.event_type == event_type && listener.source == source
         })
     }
     
@@ -54,6 +180,32 @@ impl DomBindings {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::fetch::FetchFuture;
+    use super::super::promise::PromiseState;
+    use velora_core::{HttpStatus, JsContextId};
+    use velora_net::client::HttpResponse;
+    
+    /// Answers every request with the same body, without touching the network
+    #[derive(Debug)]
+    struct MockFetchClient {
+        body: &'static str,
+    }
+    
+    impl FetchClient for MockFetchClient {
+        fn get(&self, _url: &str) -> FetchFuture {
+            let body = self.body.as_bytes().to_vec();
+            Box::pin(async move {
+                Ok(HttpResponse::new(HttpStatus::ok(), std::collections::HashMap::new(), body))
+            })
+        }
+    }
+    
+    /// Let spawned requests run until the promise settles
+    async fn settle<T: Clone + Send + 'static>(promise: &JsPromise<T>) {
+        while matches!(promise.state(), PromiseState::Pending) {
+            tokio::task::yield_now().await;
+        }
+    }
     
     #[test]
     fn test_dom_bindings_creation() {
@@ -117,4 +269,65 @@ mod tests {
         assert_eq!(bindings.binding_count(), 0);
         assert!(!bindings.has_binding("test"));
     }
+    
+    #[test]
+    fn test_add_event_listener_queues_callback() {
+        let mut bindings = DomBindings::new().unwrap();
+        let mut tree = DomTree::new();
+        let button = tree.create_element("button").unwrap();
+        
+        let first = bindings.add_event_listener(&mut tree, button, "click", "clicked()").unwrap();
+        let again = bindings.add_event_listener(&mut tree, button, "click", "clicked()").unwrap();
+        assert_eq!(first, again);
+        
+        tree.dispatch_event(button, Event::new(EventType::Click, button)).unwrap();
+        tree.dispatch_event(button, Event::new(EventType::KeyDown, button)).unwrap();
+        
+        let pending = bindings.take_pending_callbacks();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].source, "clicked()");
+        assert_eq!(pending[0].event.target, button);
+        assert!(bindings.take_pending_callbacks().is_empty());
+    }
+    
+    #[test]
+    fn test_remove_event_listener() {
+        let mut bindings = DomBindings::new().unwrap();
+        let mut tree = DomTree::new();
+        let button = tree.create_element("button").unwrap();
+        
+        bindings.add_event_listener(&mut tree, button, "click", "clicked()").unwrap();
+        assert!(!bindings.remove_event_listener(&mut tree, button, "click", "other()"));
+        assert!(bindings.remove_event_listener(&mut tree, button, "click", "clicked()"));
+        
+        tree.dispatch_event(button, Event::new(EventType::Click, button)).unwrap();
+        assert!(bindings.take_pending_callbacks().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_fetch_resolves_with_response_text() {
+        let mut bindings = DomBindings::new().unwrap();
+        bindings.set_fetch_client(Arc::new(MockFetchClient { body: "hello from the server" }));
+        let context = JsContext::new(JsContextId(1)).unwrap();
+        
+        // fetch(url).then(response => stored = response.text())
+        let stored = Arc::new(Mutex::new(None));
+        let captured = Arc::clone(&stored);
+        let promise = bindings.fetch(&context, "https://example.com/greeting.txt");
+        promise.then(move |response| *captured.lock().unwrap() = response.text().ok());
+        
+        settle(&promise).await;
+        assert_eq!(*stored.lock().unwrap(), None);
+        assert_eq!(context.run_microtasks(), 1);
+        assert_eq!(stored.lock().unwrap().as_deref(), Some("hello from the server"));
+    }
+    
+    #[tokio::test]
+    async fn test_fetch_rejects_disallowed_schemes() {
+        let bindings = DomBindings::new().unwrap();
+        let context = JsContext::new(JsContextId(1)).unwrap();
+        
+        let promise = bindings.fetch(&context, "file:///etc/passwd");
+        assert!(matches!(promise.state(), PromiseState::Rejected(_)));
+    }
 }
@@ -2,6 +2,7 @@
 
 use velora_core::{VeloraResult, VeloraError, JsContextId};
 use velora_core::error::JsRuntimeError;
+use super::promise::{JsPromise, MicrotaskQueue};
 
 /// JavaScript execution context
 #[derive(Debug)]
@@ -11,6 +12,9 @@ pub struct JsContext {
     
     /// Context state
     state: ContextState,
+    
+    /// Promise reactions waiting to run
+    microtasks: MicrotaskQueue,
 }
 
 /// Context execution state
@@ -32,6 +36,7 @@ impl JsContext {
         Ok(Self {
             id,
             state: ContextState::Ready,
+            microtasks: MicrotaskQueue::new(),
         })
     }
     
@@ -72,9 +77,25 @@ impl JsContext {
         }
         
         self.state = ContextState::Ready;
+        self.run_microtasks();
         Ok(())
     }
     
+    /// Run queued promise reactions until none are left, returning how many ran
+    pub fn run_microtasks(&self) -> usize {
+        self.microtasks.run_all()
+    }
+    
+    /// Get the context's microtask queue
+    pub fn microtask_queue(&self) -> &MicrotaskQueue {
+        &self.microtasks
+    }
+    
+    /// Create a pending promise whose reactions run in this context
+    pub fn create_promise<T: Clone + Send + 'static>(&self) -> JsPromise<T> {
+        JsPromise::new(&self.microtasks)
+    }
+    
     /// Check if the context is ready
     pub fn is_ready(&self) -> bool {
         matches!(self.state, ContextState::Ready)
@@ -160,6 +181,29 @@ mod tests {
         assert_eq!(context.get_error(), Some("JavaScript syntax error"));
     }
     
+    #[tokio::test]
+    async fn test_resolved_promise_then_runs_after_microtasks() {
+        let mut context = JsContext::new(JsContextId(1)).unwrap();
+        let observed = std::sync::Arc::new(std::sync::Mutex::new(None));
+        
+        let captured = std::sync::Arc::clone(&observed);
+        let promise = context.create_promise::<String>();
+        promise.resolve("done".to_string());
+        promise.then(move |value| *captured.lock().unwrap() = Some(value));
+        assert_eq!(*observed.lock().unwrap(), None);
+        
+        assert_eq!(context.run_microtasks(), 1);
+        assert_eq!(observed.lock().unwrap().as_deref(), Some("done"));
+        
+        // Executing a script drains reactions queued before it finished
+        let settled = context.create_promise::<i32>();
+        let captured = std::sync::Arc::clone(&observed);
+        settled.then(move |value| *captured.lock().unwrap() = Some(value.to_string()));
+        settled.resolve(7);
+        context.execute("tick();").await.unwrap();
+        assert_eq!(observed.lock().unwrap().as_deref(), Some("7"));
+    }
+    
     #[test]
     fn test_context_state_management() {
         let mut context = JsContext::new(JsContextId(1)).unwrap();
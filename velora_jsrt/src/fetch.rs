@@ -0,0 +1,118 @@
+//! `fetch()` support for the Velora web engine
+//!
+//! Requests run as tokio tasks. When one finishes it settles the promise returned to the
+//! script, which queues the promise's reactions on the calling context's microtask queue.
+
+use velora_core::{VeloraResult, VeloraError, HttpStatus};
+use velora_net::HttpClient;
+use velora_net::client::HttpResponse;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+
+/// URL schemes scripts may fetch; local files and script URLs are off limits
+const FETCH_SCHEMES: &[&str] = &["http", "https", "data"];
+
+/// Future returned by a [`FetchClient`]
+pub type FetchFuture = Pin<Box<dyn Future<Output = VeloraResult<HttpResponse>> + Send>>;
+
+/// Performs the network requests behind `fetch()`
+pub trait FetchClient: fmt::Debug + Send + Sync {
+    /// Make a GET request
+    fn get(&self, url: &str) -> FetchFuture;
+}
+
+impl FetchClient for HttpClient {
+    fn get(&self, url: &str) -> FetchFuture {
+        let client = self.clone();
+        let url = url.to_string();
+        Box::pin(async move { client.get(&url).await })
+    }
+}
+
+/// Check that a script is allowed to fetch `url`
+pub fn check_fetch_url(url: &str) -> VeloraResult<()> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| VeloraError::InvalidUrl(e.to_string()))?;
+    
+    if FETCH_SCHEMES.contains(&parsed.scheme()) {
+        Ok(())
+    } else {
+        Err(VeloraError::InvalidUrl(format!("fetch does not support '{}:' URLs", parsed.scheme())))
+    }
+}
+
+/// The `Response` object a fetch promise resolves to
+///
+/// The body has already been read in full, so `text` and `json` answer immediately.
+#[derive(Debug, Clone)]
+pub struct FetchResponse {
+    /// URL that was requested
+    pub url: String,
+    
+    /// Response status
+    pub status: HttpStatus,
+    
+    /// Response headers
+    pub headers: HashMap<String, String>,
+    
+    /// Response body
+    pub body: Vec<u8>,
+}
+
+impl FetchResponse {
+    /// Wrap a network response for script use
+    pub fn from_response(url: &str, response: HttpResponse) -> Self {
+        Self {
+            url: url.to_string(),
+            status: response.status,
+            headers: response.headers,
+            body: response.body,
+        }
+    }
+    
+    /// `response.ok`: whether the status is in the 2xx range
+    pub fn ok(&self) -> bool {
+        (200..300).contains(&self.status.code)
+    }
+    
+    /// `response.text()`
+    pub fn text(&self) -> VeloraResult<String> {
+        String::from_utf8(self.body.clone())
+            .map_err(|e| VeloraError::InvalidState(format!("Response body is not UTF-8: {}", e)))
+    }
+    
+    /// `response.json()`
+    pub fn json(&self) -> VeloraResult<serde_json::Value> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| VeloraError::InvalidState(format!("Response body is not JSON: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_check_fetch_url_schemes() {
+        assert!(check_fetch_url("https://example.com/").is_ok());
+        assert!(check_fetch_url("http://example.com/").is_ok());
+        assert!(check_fetch_url("data:text/plain,hi").is_ok());
+        assert!(check_fetch_url("file:///etc/passwd").is_err());
+        assert!(check_fetch_url("javascript:alert(1)").is_err());
+        assert!(check_fetch_url("not a url").is_err());
+    }
+    
+    #[test]
+    fn test_response_text_and_json() {
+        let response = FetchResponse::from_response(
+            "https://example.com/data.json",
+            HttpResponse::new(HttpStatus::ok(), HashMap::new(), br#"{"count": 3}"#.to_vec()),
+        );
+        
+        assert!(response.ok());
+        assert_eq!(response.text().unwrap(), r#"{"count": 3}"#);
+        assert_eq!(response.json().unwrap()["count"], 3);
+    }
+}
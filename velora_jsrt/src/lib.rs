@@ -6,14 +6,23 @@
 pub mod runtime;
 pub mod context;
 pub mod bindings;
+pub mod timers;
+pub mod promise;
+pub mod fetch;
 
 pub use runtime::JsRuntime;
 pub use context::JsContext;
-pub use bindings::DomBindings;
+pub use bindings::{DomBindings, PendingCallback};
+pub use timers::TimerId;
+pub use promise::{JsPromise, MicrotaskQueue, PromiseState};
+pub use fetch::{FetchClient, FetchResponse};
 
 /// Re-export commonly used items for convenience
 pub mod prelude {
     pub use super::runtime::JsRuntime;
     pub use super::context::JsContext;
-    pub use super::bindings::DomBindings;
+    pub use super::bindings::{DomBindings, PendingCallback};
+    pub use super::timers::TimerId;
+    pub use super::promise::{JsPromise, MicrotaskQueue, PromiseState};
+    pub use super::fetch::{FetchClient, FetchResponse};
 }
@@ -0,0 +1,253 @@
+//! Promises and the microtask queue for the Velora web engine
+//!
+//! Promise reactions never run synchronously: settling a promise, or calling `then` on
+//! one that has already settled, queues its reactions as microtasks. They run when the
+//! owning context drains its queue, after each script and each timer callback.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// A unit of work queued to run once the current script finishes
+pub type Microtask = Box<dyn FnOnce() + Send>;
+
+/// A FIFO queue of microtasks shared by a context and its promises
+#[derive(Clone, Default)]
+pub struct MicrotaskQueue {
+    tasks: Arc<Mutex<VecDeque<Microtask>>>,
+}
+
+impl MicrotaskQueue {
+    /// Create an empty queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Add a task to the end of the queue
+    pub fn enqueue(&self, task: Microtask) {
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.push_back(task);
+        }
+    }
+    
+    /// Run tasks until the queue is empty, including tasks queued while draining
+    ///
+    /// Returns the number of tasks that ran.
+    pub fn run_all(&self) -> usize {
+        let mut ran = 0;
+        // The lock is released before each task runs so it can queue more work
+        while let Some(task) = self.tasks.lock().ok().and_then(|mut tasks| tasks.pop_front()) {
+            task();
+            ran += 1;
+        }
+        ran
+    }
+    
+    /// Get the number of queued tasks
+    pub fn len(&self) -> usize {
+        self.tasks.lock().map_or(0, |tasks| tasks.len())
+    }
+    
+    /// Check if no tasks are queued
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl fmt::Debug for MicrotaskQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MicrotaskQueue").field("len", &self.len()).finish()
+    }
+}
+
+/// The settled or pending state of a promise
+#[derive(Debug, Clone, PartialEq)]
+pub enum PromiseState<T> {
+    /// Not settled yet
+    Pending,
+    /// Resolved with a value
+    Fulfilled(T),
+    /// Rejected with a reason
+    Rejected(String),
+}
+
+/// Callback run with the outcome of a settled promise
+type Reaction<T> = Box<dyn FnOnce(Result<T, String>) + Send>;
+
+/// Shared state of a promise
+struct PromiseInner<T> {
+    state: PromiseState<T>,
+    reactions: Vec<Reaction<T>>,
+}
+
+/// A promise whose reactions run on a microtask queue
+pub struct JsPromise<T> {
+    inner: Arc<Mutex<PromiseInner<T>>>,
+    queue: MicrotaskQueue,
+}
+
+impl<T> Clone for JsPromise<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for JsPromise<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.inner.lock() {
+            Ok(inner) => f.debug_struct("JsPromise").field("state", &inner.state).finish(),
+            Err(_) => f.debug_struct("JsPromise").finish_non_exhaustive(),
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> JsPromise<T> {
+    /// Create a pending promise whose reactions run on the given queue
+    pub fn new(queue: &MicrotaskQueue) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(PromiseInner {
+                state: PromiseState::Pending,
+                reactions: Vec::new(),
+            })),
+            queue: queue.clone(),
+        }
+    }
+    
+    /// `Promise.resolve(value)`
+    pub fn resolved(queue: &MicrotaskQueue, value: T) -> Self {
+        let promise = Self::new(queue);
+        promise.resolve(value);
+        promise
+    }
+    
+    /// `Promise.reject(reason)`
+    pub fn rejected(queue: &MicrotaskQueue, reason: impl Into<String>) -> Self {
+        let promise = Self::new(queue);
+        promise.reject(reason);
+        promise
+    }
+    
+    /// Fulfill the promise; ignored if it has already settled
+    pub fn resolve(&self, value: T) {
+        self.settle(Ok(value));
+    }
+    
+    /// Reject the promise; ignored if it has already settled
+    pub fn reject(&self, reason: impl Into<String>) {
+        self.settle(Err(reason.into()));
+    }
+    
+    /// Get the current state
+    pub fn state(&self) -> PromiseState<T> {
+        self.inner
+            .lock()
+            .map(|inner| inner.state.clone())
+            .unwrap_or_else(|_| PromiseState::Rejected("Promise state is poisoned".to_string()))
+    }
+    
+    /// `promise.then(callback)`: map the fulfilled value; rejections pass through unchanged
+    pub fn then<U, F>(&self, on_fulfilled: F) -> JsPromise<U>
+    where
+        U: Clone + Send + 'static,
+        F: FnOnce(T) -> U + Send + 'static,
+    {
+        let next = JsPromise::new(&self.queue);
+        let settle_next = next.clone();
+        self.react(Box::new(move |outcome| match outcome {
+            Ok(value) => settle_next.resolve(on_fulfilled(value)),
+            Err(reason) => settle_next.reject(reason),
+        }));
+        next
+    }
+    
+    /// `promise.catch(callback)`: recover from a rejection with a value
+    pub fn catch<F>(&self, on_rejected: F) -> JsPromise<T>
+    where
+        F: FnOnce(String) -> T + Send + 'static,
+    {
+        let next = JsPromise::new(&self.queue);
+        let settle_next = next.clone();
+        self.react(Box::new(move |outcome| match outcome {
+            Ok(value) => settle_next.resolve(value),
+            Err(reason) => settle_next.resolve(on_rejected(reason)),
+        }));
+        next
+    }
+    
+    /// Register a reaction, queueing it right away if the promise has settled
+    fn react(&self, reaction: Reaction<T>) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        let outcome = match &inner.state {
+            PromiseState::Pending => {
+                inner.reactions.push(reaction);
+                return;
+            }
+            PromiseState::Fulfilled(value) => Ok(value.clone()),
+            PromiseState::Rejected(reason) => Err(reason.clone()),
+        };
+        drop(inner);
+        self.queue.enqueue(Box::new(move || reaction(outcome)));
+    }
+    
+    /// Settle the promise and queue its reactions
+    fn settle(&self, outcome: Result<T, String>) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        if !matches!(inner.state, PromiseState::Pending) {
+            return;
+        }
+        
+        inner.state = match &outcome {
+            Ok(value) => PromiseState::Fulfilled(value.clone()),
+            Err(reason) => PromiseState::Rejected(reason.clone()),
+        };
+        let reactions = std::mem::take(&mut inner.reactions);
+        drop(inner);
+        
+        for reaction in reactions {
+            let outcome = outcome.clone();
+            self.queue.enqueue(Box::new(move || reaction(outcome)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_then_runs_only_when_drained() {
+        let queue = MicrotaskQueue::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        
+        let log = Arc::clone(&seen);
+        let promise = JsPromise::resolved(&queue, 1);
+        promise.then(move |value| log.lock().unwrap().push(value));
+        assert!(seen.lock().unwrap().is_empty());
+        
+        assert_eq!(queue.run_all(), 1);
+        assert_eq!(*seen.lock().unwrap(), vec![1]);
+    }
+    
+    #[test]
+    fn test_chained_then_and_catch() {
+        let queue = MicrotaskQueue::new();
+        let pending: JsPromise<i32> = JsPromise::new(&queue);
+        let doubled = pending.then(|value| value * 2);
+        let recovered = JsPromise::<i32>::rejected(&queue, "boom").then(|value| value + 1).catch(|_| -1);
+        
+        pending.resolve(21);
+        pending.resolve(0);
+        queue.run_all();
+        
+        assert_eq!(doubled.state(), PromiseState::Fulfilled(42));
+        assert_eq!(recovered.state(), PromiseState::Fulfilled(-1));
+        assert!(queue.is_empty());
+    }
+}
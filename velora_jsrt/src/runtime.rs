@@ -3,6 +3,9 @@
 use velora_core::{VeloraResult, VeloraError, JsContextId};
 use velora_core::error::JsRuntimeError;
 use super::context::JsContext;
+use super::bindings::DomBindings;
+use super::timers::{Timer, TimerId, TimerQueue};
+use std::time::Duration;
 
 /// JavaScript runtime using Deno
 #[derive(Debug)]
@@ -12,15 +15,21 @@ pub struct JsRuntime {
     
     /// Next available context ID
     next_context_id: u64,
+    
+    /// Pending `setTimeout` and `setInterval` callbacks
+    timers: TimerQueue,
+    
+    /// Clock time of the last `pump_timers` call; new timers are scheduled from here
+    now: Duration,
+    
+    /// Whether timers are held back, e.g. while the window is minimized
+    timers_paused: bool,
 }
 
 impl JsRuntime {
     /// Create a new JavaScript runtime
     pub fn new() -> VeloraResult<Self> {
-        Ok(Self {
-            contexts: std::collections::HashMap::new(),
-            next_context_id: 1,
-        })
+        Ok(Self::default())
     }
     
     /// Create a new JavaScript context
@@ -56,6 +65,88 @@ impl JsRuntime {
         Ok(())
     }
     
+    /// Run the script callbacks queued by dispatched events, returning how many ran
+    pub async fn run_event_callbacks(&self, context_id: JsContextId, bindings: &DomBindings) -> VeloraResult<usize> {
+        let callbacks = bindings.take_pending_callbacks();
+        for callback in &callbacks {
+            self.execute_script(context_id, &callback.source).await?;
+        }
+        Ok(callbacks.len())
+    }
+    
+    /// `setTimeout(fn, delay)`: run a callback once, `delay` after the current clock time
+    pub fn set_timeout(&mut self, context_id: JsContextId, source: &str, delay: Duration) -> VeloraResult<TimerId> {
+        self.schedule_timer(context_id, source, delay, None)
+    }
+    
+    /// `setInterval(fn, delay)`: run a callback every `delay` until it is cleared
+    pub fn set_interval(&mut self, context_id: JsContextId, source: &str, delay: Duration) -> VeloraResult<TimerId> {
+        self.schedule_timer(context_id, source, delay, Some(delay))
+    }
+    
+    /// `clearTimeout(id)`; returns false if the timer already fired or does not exist
+    pub fn clear_timeout(&mut self, timer_id: TimerId) -> bool {
+        self.timers.cancel(timer_id)
+    }
+    
+    /// `clearInterval(id)`; returns false if the interval does not exist
+    pub fn clear_interval(&mut self, timer_id: TimerId) -> bool {
+        self.timers.cancel(timer_id)
+    }
+    
+    /// Stop `pump_timers` from running callbacks until `resume_timers` is called
+    pub fn pause_timers(&mut self) {
+        self.timers_paused = true;
+    }
+    
+    /// Let `pump_timers` run callbacks again; timers that came due meanwhile run on the next pump
+    pub fn resume_timers(&mut self) {
+        self.timers_paused = false;
+    }
+    
+    /// Check if timers are paused
+    pub fn timers_paused(&self) -> bool {
+        self.timers_paused
+    }
+    
+    /// Advance the clock to `now` and run every timer that has come due, returning how many ran
+    ///
+    /// The event loop calls this once per frame. An interval that fell several periods
+    /// behind runs once, then again a full period later. Nothing runs while timers are paused.
+    pub async fn pump_timers(&mut self, now: Duration) -> VeloraResult<usize> {
+        if self.timers_paused {
+            return Ok(0);
+        }
+        self.now = self.now.max(now);
+        
+        let mut fired = 0;
+        while let Some((_, timer)) = self.timers.pop_due(self.now) {
+            self.execute_script(timer.context_id, &timer.source).await?;
+            // Promise reactions queued by the callback run before the next timer
+            if let Some(context) = self.contexts.get(&timer.context_id) {
+                context.run_microtasks();
+            }
+            fired += 1;
+        }
+        Ok(fired)
+    }
+    
+    /// Queue a timer in an existing context
+    fn schedule_timer(&mut self, context_id: JsContextId, source: &str, delay: Duration, interval: Option<Duration>) -> VeloraResult<TimerId> {
+        if !self.contexts.contains_key(&context_id) {
+            return Err(VeloraError::JsRuntime(JsRuntimeError::ExecutionFailed(
+                format!("Context {} not found", context_id.0)
+            )));
+        }
+        
+        let timer = Timer {
+            context_id,
+            source: source.to_string(),
+            interval,
+        };
+        Ok(self.timers.schedule(self.now + delay, timer))
+    }
+    
     /// Get a context by ID
     pub fn get_context(&self, context_id: JsContextId) -> Option<&JsContext> {
         self.contexts.get(&context_id)
@@ -77,6 +168,18 @@ impl JsRuntime {
     }
 }
 
+impl Default for JsRuntime {
+    fn default() -> Self {
+        Self {
+            contexts: std::collections::HashMap::new(),
+            next_context_id: 1,
+            timers: TimerQueue::new(),
+            now: Duration::ZERO,
+            timers_paused: false,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -140,4 +243,80 @@ mod tests {
         assert_eq!(runtime.context_count(), 0);
         assert!(runtime.get_context(context_id).is_none());
     }
+    
+    #[tokio::test]
+    async fn test_click_listener_runs_script_callback() {
+        let mut runtime = JsRuntime::new().unwrap();
+        let context_id = runtime.create_context().unwrap();
+        let mut bindings = DomBindings::new().unwrap();
+        
+        let mut tree = velora_dom::DomTree::new();
+        let body = tree.create_element("body").unwrap();
+        let button = tree.create_element("button").unwrap();
+        tree.append_child(body, button).unwrap();
+        bindings.add_event_listener(&mut tree, body, "click", "document.title = 'clicked';").unwrap();
+        
+        // Nothing has fired yet
+        assert_eq!(runtime.run_event_callbacks(context_id, &bindings).await.unwrap(), 0);
+        
+        // A synthetic click on the button bubbles to the body's listener
+        let event = velora_core::Event::new(velora_core::EventType::Click, button);
+        tree.dispatch_event(button, event).unwrap();
+        assert_eq!(runtime.run_event_callbacks(context_id, &bindings).await.unwrap(), 1);
+        assert_eq!(runtime.run_event_callbacks(context_id, &bindings).await.unwrap(), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_timeout_fires_after_delay() {
+        let mut runtime = JsRuntime::new().unwrap();
+        let context_id = runtime.create_context().unwrap();
+        runtime.set_timeout(context_id, "tick();", Duration::from_millis(10)).unwrap();
+        
+        assert_eq!(runtime.pump_timers(Duration::from_millis(5)).await.unwrap(), 0);
+        assert_eq!(runtime.pump_timers(Duration::from_millis(11)).await.unwrap(), 1);
+        assert_eq!(runtime.pump_timers(Duration::from_millis(50)).await.unwrap(), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_cleared_timers_do_not_fire() {
+        let mut runtime = JsRuntime::new().unwrap();
+        let context_id = runtime.create_context().unwrap();
+        let timeout = runtime.set_timeout(context_id, "tick();", Duration::from_millis(10)).unwrap();
+        let interval = runtime.set_interval(context_id, "tock();", Duration::from_millis(10)).unwrap();
+        
+        assert!(runtime.clear_timeout(timeout));
+        assert_eq!(runtime.pump_timers(Duration::from_millis(25)).await.unwrap(), 1);
+        assert_eq!(runtime.pump_timers(Duration::from_millis(35)).await.unwrap(), 1);
+        
+        assert!(runtime.clear_interval(interval));
+        assert_eq!(runtime.pump_timers(Duration::from_millis(100)).await.unwrap(), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_timers_are_scheduled_from_current_clock() {
+        let mut runtime = JsRuntime::new().unwrap();
+        let context_id = runtime.create_context().unwrap();
+        runtime.pump_timers(Duration::from_millis(100)).await.unwrap();
+        
+        runtime.set_timeout(context_id, "tick();", Duration::from_millis(10)).unwrap();
+        assert_eq!(runtime.pump_timers(Duration::from_millis(105)).await.unwrap(), 0);
+        assert_eq!(runtime.pump_timers(Duration::from_millis(110)).await.unwrap(), 1);
+        
+        assert!(runtime.set_timeout(JsContextId(999), "tick();", Duration::ZERO).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_paused_timers_run_after_resume() {
+        let mut runtime = JsRuntime::new().unwrap();
+        let context_id = runtime.create_context().unwrap();
+        runtime.set_timeout(context_id, "tick();", Duration::from_millis(10)).unwrap();
+        
+        runtime.pause_timers();
+        assert!(runtime.timers_paused());
+        assert_eq!(runtime.pump_timers(Duration::from_millis(20)).await.unwrap(), 0);
+        
+        runtime.resume_timers();
+        assert!(!runtime.timers_paused());
+        assert_eq!(runtime.pump_timers(Duration::from_millis(30)).await.unwrap(), 1);
+    }
 }
@@ -0,0 +1,144 @@
+//! Timer queue for `setTimeout` and `setInterval` in the Velora web engine
+
+use velora_core::JsContextId;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::Duration;
+
+/// Identifier returned by `setTimeout`/`setInterval` and accepted by the clear functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimerId(pub u64);
+
+/// A scheduled script callback
+#[derive(Debug, Clone)]
+pub struct Timer {
+    /// Context the callback runs in
+    pub context_id: JsContextId,
+    
+    /// Script source of the callback
+    pub source: String,
+    
+    /// Repeat period for intervals; None for one-shot timeouts
+    pub interval: Option<Duration>,
+}
+
+/// Timers ordered by due time
+///
+/// Times are offsets on the caller's monotonic clock. Timers due at the same time fire
+/// in the order they were scheduled.
+#[derive(Debug, Default)]
+pub struct TimerQueue {
+    /// Due time, then timer ID as a tie breaker, soonest first
+    heap: BinaryHeap<Reverse<(Duration, TimerId)>>,
+    
+    /// Live timers; cleared timers are dropped here and skipped when popped from the heap
+    timers: HashMap<TimerId, Timer>,
+    
+    /// Next timer ID to hand out
+    next_id: u64,
+}
+
+impl TimerQueue {
+    /// Create an empty timer queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Schedule a timer to first fire at `due`
+    pub fn schedule(&mut self, due: Duration, timer: Timer) -> TimerId {
+        self.next_id += 1;
+        let id = TimerId(self.next_id);
+        self.timers.insert(id, timer);
+        self.heap.push(Reverse((due, id)));
+        id
+    }
+    
+    /// Cancel a timer; returns false if it already fired or was never scheduled
+    pub fn cancel(&mut self, id: TimerId) -> bool {
+        self.timers.remove(&id).is_some()
+    }
+    
+    /// Pop the next timer due at or before `now`, rescheduling intervals
+    ///
+    /// An interval fires once however many periods have passed since it was due, and next
+    /// fires a full period after `now`, so a stalled page does not replay missed ticks.
+    pub fn pop_due(&mut self, now: Duration) -> Option<(TimerId, Timer)> {
+        while let Some(&Reverse((due, id))) = self.heap.peek() {
+            if due > now {
+                return None;
+            }
+            self.heap.pop();
+            
+            let Some(timer) = self.timers.get(&id).cloned() else {
+                continue;
+            };
+            match timer.interval {
+                // A zero period would fire forever within one pump
+                Some(period) => self.heap.push(Reverse((now + period.max(Duration::from_millis(1)), id))),
+                None => {
+                    self.timers.remove(&id);
+                }
+            }
+            return Some((id, timer));
+        }
+        None
+    }
+    
+    /// Get the number of live timers
+    pub fn len(&self) -> usize {
+        self.timers.len()
+    }
+    
+    /// Check if no timers are scheduled
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn timer(source: &str, interval: Option<Duration>) -> Timer {
+        Timer { context_id: JsContextId(1), source: source.to_string(), interval }
+    }
+    
+    #[test]
+    fn test_timers_fire_in_due_order() {
+        let mut queue = TimerQueue::new();
+        queue.schedule(Duration::from_millis(20), timer("late", None));
+        queue.schedule(Duration::from_millis(5), timer("early", None));
+        queue.schedule(Duration::from_millis(20), timer("late again", None));
+        
+        let now = Duration::from_millis(30);
+        let fired: Vec<String> = std::iter::from_fn(|| queue.pop_due(now)).map(|(_, timer)| timer.source).collect();
+        assert_eq!(fired, vec!["early", "late", "late again"]);
+        assert!(queue.is_empty());
+    }
+    
+    #[test]
+    fn test_late_interval_fires_once() {
+        let mut queue = TimerQueue::new();
+        queue.schedule(Duration::from_millis(10), timer("tick", Some(Duration::from_millis(10))));
+        
+        // Ten periods went by unserved; they collapse into one firing
+        let now = Duration::from_millis(105);
+        assert!(queue.pop_due(now).is_some());
+        assert!(queue.pop_due(now).is_none());
+        
+        // The next tick is a full period after the late one
+        assert!(queue.pop_due(Duration::from_millis(114)).is_none());
+        assert!(queue.pop_due(Duration::from_millis(115)).is_some());
+        assert_eq!(queue.len(), 1);
+    }
+    
+    #[test]
+    fn test_cancelled_timer_is_skipped() {
+        let mut queue = TimerQueue::new();
+        let id = queue.schedule(Duration::from_millis(1), timer("never", Some(Duration::from_millis(1))));
+        
+        assert!(queue.cancel(id));
+        assert!(!queue.cancel(id));
+        assert!(queue.pop_due(Duration::from_secs(1)).is_none());
+    }
+}
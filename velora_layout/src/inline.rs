@@ -0,0 +1,120 @@
+//! Inline formatting and line breaking for the Velora web engine
+
+use velora_core::Rect;
+use std::ops::Range;
+
+/// One line of inline content
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineBox {
+    /// Position and size of the line, relative to the containing block
+    pub rect: Rect,
+    
+    /// Indices of the words placed on this line
+    pub words: Range<usize>,
+}
+
+/// Greedy line breaker for a run of measured words
+#[derive(Debug, Clone)]
+pub struct InlineLayout {
+    /// Width available to each line
+    pub available_width: f32,
+    
+    /// Height of each line box
+    pub line_height: f32,
+    
+    /// Width of the space placed between words
+    pub space_width: f32,
+}
+
+impl InlineLayout {
+    /// Create a new inline layout
+    pub fn new(available_width: f32, line_height: f32, space_width: f32) -> Self {
+        Self {
+            available_width,
+            line_height,
+            space_width,
+        }
+    }
+    
+    /// Break words into lines, wrapping at the spaces between them
+    ///
+    /// A word wider than the available width gets a line of its own and overflows it.
+    pub fn break_lines(&self, word_widths: &[f32]) -> Vec<LineBox> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut width = 0.0;
+        
+        for (index, &word_width) in word_widths.iter().enumerate() {
+            if index > start && width + self.space_width + word_width > self.available_width {
+                lines.push(self.line_box(lines.len(), start..index, width));
+                start = index;
+                width = 0.0;
+            }
+            
+            if index > start {
+                width += self.space_width;
+            }
+            width += word_width;
+        }
+        
+        if start < word_widths.len() {
+            lines.push(self.line_box(lines.len(), start..word_widths.len(), width));
+        }
+        lines
+    }
+    
+    /// Build the box for the line at a given index
+    fn line_box(&self, line_index: usize, words: Range<usize>, width: f32) -> LineBox {
+        LineBox {
+            rect: Rect::new(0.0, line_index as f32 * self.line_height, width, self.line_height),
+            words,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_wrap_words() {
+        // "The quick brown fox jumps" with 10px per letter and 5px spaces
+        let words = [30.0, 50.0, 50.0, 30.0, 50.0];
+        let layout = InlineLayout::new(120.0, 20.0, 5.0);
+        
+        let lines = layout.break_lines(&words);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].words, 0..2);
+        assert_eq!(lines[0].rect, Rect::new(0.0, 0.0, 85.0, 20.0));
+        assert_eq!(lines[1].words, 2..4);
+        assert_eq!(lines[1].rect, Rect::new(0.0, 20.0, 85.0, 20.0));
+        assert_eq!(lines[2].words, 4..5);
+        assert_eq!(lines[2].rect, Rect::new(0.0, 40.0, 50.0, 20.0));
+    }
+    
+    #[test]
+    fn test_exact_fit_stays_on_one_line() {
+        let layout = InlineLayout::new(105.0, 16.0, 5.0);
+        let lines = layout.break_lines(&[50.0, 50.0]);
+        
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].rect.width, 105.0);
+    }
+    
+    #[test]
+    fn test_long_word_overflows() {
+        let layout = InlineLayout::new(40.0, 10.0, 5.0);
+        let lines = layout.break_lines(&[20.0, 100.0, 20.0]);
+        
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].words, 1..2);
+        assert_eq!(lines[1].rect.width, 100.0);
+        assert_eq!(lines[2].rect.y, 20.0);
+    }
+    
+    #[test]
+    fn test_no_words() {
+        let layout = InlineLayout::new(100.0, 10.0, 5.0);
+        assert!(layout.break_lines(&[]).is_empty());
+    }
+}
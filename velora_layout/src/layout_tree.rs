@@ -1,9 +1,13 @@
 //! Layout tree implementation for the Velora web engine
 
-use velora_core::{NodeId, VeloraResult, VeloraError, Size, Rect};
+use velora_core::{CssUnit, CssValue, NodeId, VeloraResult, VeloraError, Size, Rect, Point, Position, Overflow};
 use velora_core::error::LayoutError;
 use super::{BoxModel, FlexboxLayout, GridLayout};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 /// Layout node information
 #[derive(Debug, Clone)]
@@ -20,6 +24,165 @@ pub struct LayoutNode {
     pub parent_id: Option<NodeId>,
     /// Child node IDs
     pub child_ids: Vec<NodeId>,
+    /// Positioning scheme
+    pub position: Position,
+    /// Offsets used by relative and absolute positioning
+    pub offsets: BoxOffsets,
+    /// Stack level among overlapping positioned nodes; None means auto
+    pub z_index: Option<i32>,
+    /// How content that overflows the box is handled
+    pub overflow: Overflow,
+    /// Sizes that resolve against the containing block during layout
+    pub lengths: SpecifiedLengths,
+    /// Natural size of replaced content such as an image; None for ordinary boxes
+    pub intrinsic_size: Option<Size>,
+}
+
+/// Computed width, height and padding of a node, kept unresolved until layout
+///
+/// Values are pixel lengths or percentages of the containing block's content box.
+/// Percentage widths and all padding resolve against its width, percentage heights against
+/// its height. None leaves the size to the box model and the padding to `box_model.padding`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpecifiedLengths {
+    pub width: Option<CssValue>,
+    pub height: Option<CssValue>,
+    /// Padding in top, right, bottom, left order
+    pub padding: Option<[CssValue; 4]>,
+}
+
+impl SpecifiedLengths {
+    /// Read `width`, `height` and the padding properties from a node's computed values
+    ///
+    /// `padding-*` longhands win over a single-value `padding`; other values count as auto.
+    pub fn from_properties(properties: &HashMap<String, CssValue>) -> Self {
+        let length = |name: &str| properties.get(name).filter(|value| resolve_length(value, 0.0).is_some()).cloned();
+        let all = length("padding").unwrap_or(CssValue::Length(0.0, CssUnit::Px));
+        let sides = ["padding-top", "padding-right", "padding-bottom", "padding-left"]
+            .map(|name| length(name).unwrap_or_else(|| all.clone()));
+        let has_padding = ["padding", "padding-top", "padding-right", "padding-bottom", "padding-left"]
+            .iter()
+            .any(|name| length(name).is_some());
+        
+        Self {
+            width: length("width"),
+            height: length("height"),
+            padding: has_padding.then_some(sides),
+        }
+    }
+}
+
+/// Resolve a computed length, taking percentages of `base`; None for anything else
+fn resolve_length(value: &CssValue, base: f32) -> Option<f32> {
+    match value {
+        CssValue::Length(length, CssUnit::Px) => Some(*length),
+        CssValue::Percentage(percent) => Some(base * percent / 100.0),
+        CssValue::Number(number) if *number == 0.0 => Some(0.0),
+        _ => None,
+    }
+}
+
+/// The top/right/bottom/left offsets of a positioned node; None means auto
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BoxOffsets {
+    pub top: Option<f32>,
+    pub right: Option<f32>,
+    pub bottom: Option<f32>,
+    pub left: Option<f32>,
+}
+
+impl LayoutNode {
+    /// The z-index of a node that establishes a stacking context; z-index only applies to positioned nodes
+    pub fn stack_level(&self) -> Option<i32> {
+        match self.position {
+            Position::Static => None,
+            _ => self.z_index,
+        }
+    }
+    
+    /// Box model with the specified padding resolved against a containing block width
+    fn resolved_box_model(&self, containing_width: f32) -> BoxModel {
+        let mut model = self.box_model.clone();
+        if let Some(padding) = &self.lengths.padding {
+            let [top, right, bottom, left] = padding.each_ref()
+                .map(|side| resolve_length(side, containing_width).unwrap_or(0.0));
+            model.set_padding(Rect::new(left, top, left + right, top + bottom));
+        }
+        model
+    }
+}
+
+impl BoxOffsets {
+    /// Shift applied to a relatively positioned node; left and top win over right and bottom
+    pub fn relative_shift(&self) -> Point {
+        Point::new(
+            self.left.or(self.right.map(|right| -right)).unwrap_or(0.0),
+            self.top.or(self.bottom.map(|bottom| -bottom)).unwrap_or(0.0),
+        )
+    }
+}
+
+/// Rects from an earlier layout and the nodes that changed since
+struct LayoutCache<'a> {
+    previous: &'a HashMap<NodeId, Rect>,
+    /// Nodes whose own layout inputs changed
+    dirty: HashSet<NodeId>,
+    /// Dirty nodes and all their ancestors
+    affected: HashSet<NodeId>,
+}
+
+/// Content rects of nodes already sized under a given available size
+///
+/// Rects are relative to the node's margin box origin, so they hold wherever the node ends up.
+#[derive(Debug, Default)]
+struct SizeCache {
+    rects: Mutex<HashMap<(NodeId, u64), Rect>>,
+    /// Number of node sizes computed because they were not cached
+    computations: AtomicUsize,
+}
+
+impl SizeCache {
+    fn rects(&self) -> MutexGuard<'_, HashMap<(NodeId, u64), Rect>> {
+        self.rects.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+    
+    /// Forget the cached rects of one node
+    fn invalidate(&self, node_id: NodeId) {
+        self.rects().retain(|(cached_id, _), _| *cached_id != node_id);
+    }
+}
+
+impl Clone for SizeCache {
+    fn clone(&self) -> Self {
+        Self {
+            rects: Mutex::new(self.rects().clone()),
+            computations: AtomicUsize::new(self.computations.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// A node's box, worked out before its children are laid out
+struct PlacedNode {
+    /// Content box
+    rect: Rect,
+    /// Height the node takes up in the block flow
+    outer_height: f32,
+    /// Containing block for the node's absolutely positioned descendants
+    child_containing_block: Rect,
+}
+
+/// Cache key for an available size
+fn size_key(size: Size) -> u64 {
+    (u64::from(size.width.to_bits()) << 32) | u64::from(size.height.to_bits())
+}
+
+/// One entry in a stacking context's paint order, from `LayoutTree::paint_order`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaintLayer {
+    /// A node's own content, without its descendants
+    Node(NodeId),
+    /// A node painted as a unit together with its subtree: a nested stacking context or a clipping node
+    Context(NodeId),
 }
 
 /// A layout tree that manages the layout of DOM elements
@@ -29,6 +192,8 @@ pub struct LayoutTree {
     root_id: Option<NodeId>,
     /// Layout nodes indexed by NodeId
     nodes: HashMap<NodeId, LayoutNode>,
+    /// Node sizes from earlier layouts
+    cache: SizeCache,
 }
 
 impl LayoutTree {
@@ -37,6 +202,7 @@ impl LayoutTree {
         Self { 
             root_id: None,
             nodes: HashMap::new(),
+            cache: SizeCache::default(),
         }
     }
     
@@ -61,6 +227,7 @@ impl LayoutTree {
         }
         
         self.nodes.insert(node_id, node);
+        self.cache.invalidate(node_id);
         
         // Set as root if it's the first node
         if self.root_id.is_none() {
@@ -76,15 +243,29 @@ impl LayoutTree {
     }
     
     /// Get a mutable reference to a layout node by ID
+    ///
+    /// The node's cached sizes are dropped, since the caller may change its box model.
     pub fn get_node_mut(&mut self, node_id: NodeId) -> Option<&mut LayoutNode> {
+        self.cache.invalidate(node_id);
         self.nodes.get_mut(&node_id)
     }
     
     /// Remove a layout node
     pub fn remove_node(&mut self, node_id: NodeId) -> bool {
+        self.cache.invalidate(node_id);
         self.nodes.remove(&node_id).is_some()
     }
     
+    /// Forget all node sizes remembered from earlier layouts
+    pub fn clear_cache(&mut self) {
+        self.cache.rects().clear();
+    }
+    
+    /// Number of node sizes computed so far rather than taken from the cache
+    pub fn layout_computations(&self) -> usize {
+        self.cache.computations.load(Ordering::Relaxed)
+    }
+    
     /// Get the number of nodes in the tree
     pub fn node_count(&self) -> usize {
         self.nodes.len()
@@ -100,59 +281,370 @@ impl LayoutTree {
         let mut results = HashMap::new();
         
         if let Some(root_id) = self.root_id {
-            self.calculate_node_layout(root_id, container_size, &mut results)?;
+            // The viewport is the containing block of the root
+            let viewport = Rect::new(0.0, 0.0, container_size.width, container_size.height);
+            self.calculate_node_layout(root_id, Point::zero(), container_size, viewport, None, &mut results)?;
         }
         
         Ok(results)
     }
     
-    /// Calculate layout for a specific node and its descendants
+    /// Recalculate layout after some nodes changed, reusing `previous` rects where possible
+    ///
+    /// Dirty nodes are laid out again together with their descendants, their ancestors, and
+    /// every node that follows one of those in the flow. Everything else keeps its rect from
+    /// `previous`, which must come from a layout of this tree at the same container size.
+    pub fn calculate_layout_incremental(
+        &self,
+        container_size: Size,
+        dirty: &[NodeId],
+        previous: &HashMap<NodeId, Rect>,
+    ) -> VeloraResult<HashMap<NodeId, Rect>> {
+        let mut affected = HashSet::new();
+        for &node_id in dirty {
+            let mut current = Some(node_id);
+            while let Some(node_id) = current {
+                if !affected.insert(node_id) {
+                    break;
+                }
+                current = self.get_node(node_id).and_then(|node| node.parent_id);
+            }
+        }
+        
+        let cache = LayoutCache {
+            previous,
+            dirty: dirty.iter().copied().collect(),
+            affected,
+        };
+        let mut results = HashMap::new();
+        
+        if let Some(root_id) = self.root_id {
+            let viewport = Rect::new(0.0, 0.0, container_size.width, container_size.height);
+            self.calculate_node_layout(root_id, Point::zero(), container_size, viewport, Some(&cache), &mut results)?;
+        }
+        
+        Ok(results)
+    }
+    
+    /// Calculate layout for all nodes, laying out sibling subtrees on rayon's thread pool
+    ///
+    /// Produces the same rects as `calculate_layout`.
+    #[cfg(feature = "parallel")]
+    pub fn calculate_layout_parallel(&self, container_size: Size) -> VeloraResult<HashMap<NodeId, Rect>> {
+        let Some(root_id) = self.root_id else {
+            return Ok(HashMap::new());
+        };
+        
+        let viewport = Rect::new(0.0, 0.0, container_size.width, container_size.height);
+        self.calculate_subtree_parallel(root_id, Point::zero(), container_size, viewport)
+    }
+    
+    /// Lay out a node, then its child subtrees in parallel
+    #[cfg(feature = "parallel")]
+    fn calculate_subtree_parallel(
+        &self,
+        node_id: NodeId,
+        origin: Point,
+        available_size: Size,
+        containing_block: Rect,
+    ) -> VeloraResult<HashMap<NodeId, Rect>> {
+        let node = self.layout_node(node_id)?;
+        let placed = self.place_node(node, origin, available_size, containing_block)?;
+        let node_rect = placed.rect;
+        let child_containing_block = placed.child_containing_block;
+        
+        // A child's origin only depends on the heights of the in-flow siblings before it,
+        // which are known without laying out their subtrees
+        let mut flow_y = node_rect.y;
+        let mut children = Vec::with_capacity(node.child_ids.len());
+        for &child_id in &node.child_ids {
+            let child = self.layout_node(child_id)?;
+            let child_origin = Point::new(node_rect.x, flow_y);
+            
+            if child.position == Position::Absolute {
+                let size = Size::new(child_containing_block.width, child_containing_block.height);
+                children.push((child_id, child_origin, size));
+            } else {
+                let size = Size::new(node_rect.width, node_rect.height);
+                flow_y += self.place_node(child, child_origin, size, child_containing_block)?.outer_height;
+                children.push((child_id, child_origin, size));
+            }
+        }
+        
+        let mut results = children
+            .into_par_iter()
+            .map(|(child_id, child_origin, size)| {
+                self.calculate_subtree_parallel(child_id, child_origin, size, child_containing_block)
+            })
+            .try_reduce(HashMap::new, |mut merged, subtree| {
+                merged.extend(subtree);
+                Ok(merged)
+            })?;
+        results.insert(node_id, node_rect);
+        
+        Ok(results)
+    }
+    
+    /// Copy a subtree's rects from an earlier layout, returning the height it takes in the flow
+    ///
+    /// Percentage padding resolves against `containing_width`, as in `place_node`. Returns
+    /// None, copying nothing, if any node of the subtree is missing from `previous`.
+    fn reuse_layout(&self, node_id: NodeId, containing_width: f32, previous: &HashMap<NodeId, Rect>, results: &mut HashMap<NodeId, Rect>) -> Option<f32> {
+        let mut subtree = Vec::new();
+        let mut stack = vec![node_id];
+        while let Some(id) = stack.pop() {
+            subtree.push((id, *previous.get(&id)?));
+            stack.extend(self.get_node(id)?.child_ids.iter().copied());
+        }
+        
+        let model = self.get_node(node_id)?.resolved_box_model(containing_width);
+        let height = subtree[0].1.height + model.padding.height + model.border.height + model.margin.height;
+        results.extend(subtree);
+        Some(height)
+    }
+    
+    /// Calculate layout for a node whose margin box starts at `origin`, and its descendants
+    ///
+    /// Returns the height the node takes up in the block flow.
     fn calculate_node_layout(
         &self,
         node_id: NodeId,
+        origin: Point,
         available_size: Size,
+        containing_block: Rect,
+        cache: Option<&LayoutCache>,
         results: &mut HashMap<NodeId, Rect>,
-    ) -> VeloraResult<()> {
-        let node = self.get_node(node_id)
+    ) -> VeloraResult<f32> {
+        // Nothing in an unaffected subtree has moved, so its earlier rects still hold
+        if let Some(cache) = cache.filter(|cache| !cache.affected.contains(&node_id)) {
+            if let Some(height) = self.reuse_layout(node_id, available_size.width, cache.previous, results) {
+                return Ok(height);
+            }
+        }
+        
+        let node = self.layout_node(node_id)?;
+        let placed = self.place_node(node, origin, available_size, containing_block)?;
+        let node_rect = placed.rect;
+        let child_containing_block = placed.child_containing_block;
+        results.insert(node_id, node_rect);
+        
+        // Below a dirty node everything is laid out again
+        let mut child_cache = cache.filter(|cache| !cache.dirty.contains(&node_id));
+        
+        // Calculate children layouts, stacking in-flow children vertically
+        let mut flow_y = node_rect.y;
+        for &child_id in &node.child_ids {
+            let child_origin = Point::new(node_rect.x, flow_y);
+            let out_of_flow = self.get_node(child_id).is_some_and(|child| child.position == Position::Absolute);
+            
+            if out_of_flow {
+                let size = Size::new(child_containing_block.width, child_containing_block.height);
+                self.calculate_node_layout(child_id, child_origin, size, child_containing_block, child_cache, results)?;
+            } else {
+                let size = Size::new(node_rect.width, node_rect.height);
+                flow_y += self.calculate_node_layout(child_id, child_origin, size, child_containing_block, child_cache, results)?;
+            }
+            
+            // Siblings after an affected child may have moved
+            if child_cache.is_some_and(|cache| cache.affected.contains(&child_id)) {
+                child_cache = None;
+            }
+        }
+        
+        Ok(placed.outer_height)
+    }
+    
+    /// Get a layout node, failing if the tree does not have it
+    fn layout_node(&self, node_id: NodeId) -> VeloraResult<&LayoutNode> {
+        self.get_node(node_id)
             .ok_or_else(|| VeloraError::Layout(LayoutError::InvalidConstraints(
                 format!("Layout node {} not found", node_id.0)
-            )))?;
+            )))
+    }
+    
+    /// Size and position a node whose margin box starts at `origin`
+    ///
+    /// `available_size` is the containing block's content size, which percentages resolve against.
+    fn place_node(&self, node: &LayoutNode, origin: Point, available_size: Size, containing_block: Rect) -> VeloraResult<PlacedNode> {
+        // Content starts inside the margin, border and padding
+        let model = &node.resolved_box_model(available_size.width);
+        let inset = Point::new(
+            model.margin.x + model.border.x + model.padding.x,
+            model.margin.y + model.border.y + model.padding.y,
+        );
+        
+        // Reuse the size from an earlier layout under the same available size
+        let key = (node.node_id, size_key(available_size));
+        let cached = self.cache.rects().get(&key).copied();
+        let node_size = match cached {
+            Some(rect) => Size::new(rect.width, rect.height),
+            None => {
+                let size = self.calculate_node_size(node, model, available_size)?;
+                self.cache.rects().insert(key, Rect::from_point_size(inset, size));
+                self.cache.computations.fetch_add(1, Ordering::Relaxed);
+                size
+            }
+        };
+        let outer_size = Size::new(
+            node_size.width + model.padding.width + model.border.width + model.margin.width,
+            node_size.height + model.padding.height + model.border.height + model.margin.height,
+        );
+        
+        let flow_origin = origin + Size::from(inset);
+        let content_origin = match node.position {
+            Position::Relative => flow_origin + node.offsets.relative_shift(),
+            Position::Absolute => {
+                // Offsets are measured from the containing block's edges; auto keeps the flow position
+                let offsets = &node.offsets;
+                let x = match (offsets.left, offsets.right) {
+                    (Some(left), _) => containing_block.x + left + inset.x,
+                    (None, Some(right)) => containing_block.x + containing_block.width - right - outer_size.width + inset.x,
+                    (None, None) => flow_origin.x,
+                };
+                let y = match (offsets.top, offsets.bottom) {
+                    (Some(top), _) => containing_block.y + top + inset.y,
+                    (None, Some(bottom)) => containing_block.y + containing_block.height - bottom - outer_size.height + inset.y,
+                    (None, None) => flow_origin.y,
+                };
+                Point::new(x, y)
+            }
+            _ => flow_origin,
+        };
+        let node_rect = Rect::from_point_size(content_origin, node_size);
         
-        // Calculate this node's layout
-        let node_rect = if let Some(flexbox) = &node.flexbox {
+        // Positioned nodes are the containing block for absolute descendants
+        let child_containing_block = match node.position {
+            Position::Static => containing_block,
+            _ => BoxModel { content: node_rect, ..model.clone() }.padding_box(),
+        };
+        
+        Ok(PlacedNode {
+            rect: node_rect,
+            outer_height: outer_size.height,
+            child_containing_block,
+        })
+    }
+    
+    /// Size a node's content box for the space available to it
+    ///
+    /// Replaced content is sized from its intrinsic size: a lone specified width or height
+    /// scales the other side to keep the aspect ratio.
+    fn calculate_node_size(&self, node: &LayoutNode, model: &BoxModel, available_size: Size) -> VeloraResult<Size> {
+        let lengths = &node.lengths;
+        let specified = |length: &Option<CssValue>, base: f32| length.as_ref().and_then(|length| resolve_length(length, base));
+        let width = specified(&lengths.width, available_size.width);
+        let height = specified(&lengths.height, available_size.height);
+        
+        if let Some(intrinsic) = node.intrinsic_size {
+            // Scale a length by numerator / denominator, keeping the intrinsic side if that is degenerate
+            let scale = |length: f32, numerator: f32, denominator: f32| {
+                if denominator > 0.0 { length * numerator / denominator } else { numerator }
+            };
+            return Ok(match (width, height) {
+                (Some(width), Some(height)) => Size::new(width, height),
+                (Some(width), None) => Size::new(width, scale(width, intrinsic.height, intrinsic.width)),
+                (None, Some(height)) => Size::new(scale(height, intrinsic.width, intrinsic.height), height),
+                (None, None) => intrinsic,
+            });
+        }
+        
+        let size = self.calculate_auto_size(node, model, available_size)?;
+        Ok(Size::new(width.unwrap_or(size.width), height.unwrap_or(size.height)))
+    }
+    
+    /// Size a node's content box from its layout mode, ignoring any specified width and height
+    fn calculate_auto_size(&self, node: &LayoutNode, model: &BoxModel, available_size: Size) -> VeloraResult<Size> {
+        if let Some(flexbox) = &node.flexbox {
             // Use flexbox layout
             let rects = flexbox.calculate_layout(available_size)?;
-            if !rects.is_empty() {
-                rects[0] // Take the first rect as the node's position
-            } else {
-                Rect::new(0.0, 0.0, available_size.width, available_size.height)
-            }
+            Ok(match rects.first() {
+                Some(rect) => Size::new(rect.width, rect.height), // Take the first rect as the node's size
+                None => available_size,
+            })
         } else if let Some(grid) = &node.grid {
             // Use grid layout
             let rects = grid.calculate_layout(available_size)?;
-            if !rects.is_empty() {
-                rects[0] // Take the first rect as the node's position
-            } else {
-                Rect::new(0.0, 0.0, available_size.width, available_size.height)
-            }
+            Ok(match rects.first() {
+                Some(rect) => Size::new(rect.width, rect.height), // Take the first rect as the node's size
+                None => available_size,
+            })
         } else {
             // Use box model layout
-            let content_size = node.box_model.calculate_with_sizing(
+            model.calculate_with_sizing(
                 super::BoxSizing::ContentBox,
                 available_size
-            )?;
-            Rect::new(0.0, 0.0, content_size.width, content_size.height)
-        };
+            )
+        }
+    }
+    
+    /// Find the topmost node under a viewport point, using the rects from `calculate_layout`
+    ///
+    /// `scroll_offset` is how far the page is scrolled, so the point is moved by it into
+    /// page coordinates. Siblings are tried in reverse paint order, positioned nodes by
+    /// z-index, and descendants of a clipping node are only hit inside its padding box.
+    pub fn hit_test(&self, layouts: &HashMap<NodeId, Rect>, point: Point, scroll_offset: Point) -> Option<NodeId> {
+        let point = point + scroll_offset;
+        self.hit_test_node(layouts, self.root_id?, point)
+    }
+    
+    /// Hit test a stacking context, returning the deepest, topmost node containing `point`
+    fn hit_test_node(&self, layouts: &HashMap<NodeId, Rect>, node_id: NodeId, point: Point) -> Option<NodeId> {
+        let node = self.get_node(node_id)?;
+        let rect = layouts.get(&node_id);
         
-        results.insert(node_id, node_rect);
+        if node.overflow.clips() {
+            let clip = rect.map(|&content| BoxModel { content, ..node.box_model.clone() }.padding_box());
+            if !clip.is_some_and(|clip| clip.contains(point)) {
+                return None;
+            }
+        }
         
-        // Calculate children layouts
+        self.paint_order(node_id).into_iter().rev()
+            .find_map(|layer| match layer {
+                PaintLayer::Node(id) => layouts.get(&id).is_some_and(|rect| rect.contains(point)).then_some(id),
+                PaintLayer::Context(id) => self.hit_test_node(layouts, id, point),
+            })
+            .or_else(|| rect.is_some_and(|rect| rect.contains(point)).then_some(node_id))
+    }
+    
+    /// The descendants of a stacking context in the order they paint, after the node itself
+    ///
+    /// Descendants that do not form a context of their own are flattened in, so a positioned
+    /// grandchild stacks against the context's children. Layers are sorted by z-index, with
+    /// auto and static at level 0, and keep document order among equal levels.
+    pub fn paint_order(&self, node_id: NodeId) -> Vec<PaintLayer> {
+        let mut layers = Vec::new();
+        if let Some(node) = self.get_node(node_id) {
+            self.collect_layers(node, &mut layers);
+        }
+        // The sort is stable, so equal levels keep document order
+        layers.sort_by_key(|(z_index, _)| *z_index);
+        layers.into_iter().map(|(_, layer)| layer).collect()
+    }
+    
+    /// Collect the layers of a node's descendants, stopping at nested stacking contexts
+    fn collect_layers(&self, node: &LayoutNode, layers: &mut Vec<(i32, PaintLayer)>) {
         for &child_id in &node.child_ids {
-            let child_size = Size::new(node_rect.width, node_rect.height);
-            self.calculate_node_layout(child_id, child_size, results)?;
+            let Some(child) = self.get_node(child_id) else {
+                continue;
+            };
+            
+            match child.stack_level() {
+                Some(z_index) => layers.push((z_index, PaintLayer::Context(child_id))),
+                // Clipping nodes paint their subtree as a unit so the clip covers all of it
+                None if child.overflow.clips() => layers.push((0, PaintLayer::Context(child_id))),
+                None => {
+                    layers.push((0, PaintLayer::Node(child_id)));
+                    self.collect_layers(child, layers);
+                }
+            }
         }
-        
-        Ok(())
+    }
+}
+
+impl Default for LayoutTree {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -180,6 +672,12 @@ mod tests {
             grid: None,
             parent_id: None,
             child_ids: vec![],
+            position: Position::Static,
+            offsets: BoxOffsets::default(),
+            z_index: None,
+            overflow: Overflow::Visible,
+            lengths: SpecifiedLengths::default(),
+            intrinsic_size: None,
         };
         
         let result = tree.add_node(node);
@@ -201,6 +699,12 @@ mod tests {
             grid: None,
             parent_id: None,
             child_ids: vec![],
+            position: Position::Static,
+            offsets: BoxOffsets::default(),
+            z_index: None,
+            overflow: Overflow::Visible,
+            lengths: SpecifiedLengths::default(),
+            intrinsic_size: None,
         };
         
         tree.add_node(node.clone()).unwrap();
@@ -222,6 +726,12 @@ mod tests {
             grid: None,
             parent_id: None,
             child_ids: vec![],
+            position: Position::Static,
+            offsets: BoxOffsets::default(),
+            z_index: None,
+            overflow: Overflow::Visible,
+            lengths: SpecifiedLengths::default(),
+            intrinsic_size: None,
         };
         
         tree.add_node(node).unwrap();
@@ -243,6 +753,12 @@ mod tests {
             grid: None,
             parent_id: None,
             child_ids: vec![],
+            position: Position::Static,
+            offsets: BoxOffsets::default(),
+            z_index: None,
+            overflow: Overflow::Visible,
+            lengths: SpecifiedLengths::default(),
+            intrinsic_size: None,
         };
         
         tree.add_node(node).unwrap();
@@ -265,6 +781,12 @@ mod tests {
             grid: None,
             parent_id: None,
             child_ids: vec![],
+            position: Position::Static,
+            offsets: BoxOffsets::default(),
+            z_index: None,
+            overflow: Overflow::Visible,
+            lengths: SpecifiedLengths::default(),
+            intrinsic_size: None,
         };
         
         tree.add_node(node).unwrap();
@@ -277,10 +799,344 @@ mod tests {
         assert_eq!(layouts.len(), 1);
         assert!(layouts.contains_key(&NodeId(1)));
     }
-}
-
-impl Default for LayoutTree {
-    fn default() -> Self {
-        Self::new()
+    
+    /// A node with a fixed content size that children can flow inside
+    fn sized_node(id: u64, parent_id: Option<NodeId>, child_ids: Vec<NodeId>, position: Position, offsets: BoxOffsets) -> LayoutNode {
+        LayoutNode {
+            node_id: NodeId(id),
+            box_model: BoxModel::new(Rect::zero()),
+            flexbox: None,
+            grid: None,
+            parent_id,
+            child_ids,
+            position,
+            offsets,
+            z_index: None,
+            overflow: Overflow::Visible,
+            lengths: SpecifiedLengths::default(),
+            intrinsic_size: None,
+        }
+    }
+    
+    #[test]
+    fn test_percentage_width_resolves_against_parent() {
+        let mut tree = LayoutTree::new();
+        let mut parent = sized_node(1, None, vec![NodeId(2)], Position::Static, BoxOffsets::default());
+        parent.lengths.width = Some(CssValue::Length(400.0, CssUnit::Px));
+        tree.add_node(parent).unwrap();
+        let mut child = sized_node(2, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default());
+        child.lengths.width = Some(CssValue::Percentage(50.0));
+        child.lengths.height = Some(CssValue::Percentage(25.0));
+        tree.add_node(child).unwrap();
+        
+        let layouts = tree.calculate_layout(Size::new(1000.0, 800.0)).unwrap();
+        assert_eq!(layouts[&NodeId(1)].width, 400.0);
+        assert_eq!(layouts[&NodeId(2)], Rect::new(0.0, 0.0, 200.0, 200.0));
+    }
+    
+    #[test]
+    fn test_percentage_padding_resolves_against_width() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(sized_node(1, None, vec![NodeId(2)], Position::Static, BoxOffsets::default())).unwrap();
+        let mut child = sized_node(2, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default());
+        let ten_percent = CssValue::Percentage(10.0);
+        child.lengths.padding = Some([ten_percent.clone(), CssValue::Length(5.0, CssUnit::Px), ten_percent, CssValue::Percentage(0.0)]);
+        tree.add_node(child).unwrap();
+        
+        // Vertical padding is 10% of the 400px width, not of the 100px height
+        let layouts = tree.calculate_layout(Size::new(400.0, 100.0)).unwrap();
+        assert_eq!(layouts[&NodeId(2)], Rect::new(0.0, 40.0, 395.0, 20.0));
+    }
+    
+    #[test]
+    fn test_replaced_content_keeps_aspect_ratio() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(sized_node(1, None, vec![NodeId(2), NodeId(3), NodeId(4)], Position::Static, BoxOffsets::default())).unwrap();
+        let image = |id: u64, width: Option<f32>, height: Option<f32>| {
+            let mut node = sized_node(id, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default());
+            node.intrinsic_size = Some(Size::new(200.0, 100.0));
+            node.lengths.width = width.map(|width| CssValue::Length(width, CssUnit::Px));
+            node.lengths.height = height.map(|height| CssValue::Length(height, CssUnit::Px));
+            node
+        };
+        tree.add_node(image(2, Some(100.0), None)).unwrap();
+        tree.add_node(image(3, None, Some(30.0))).unwrap();
+        tree.add_node(image(4, None, None)).unwrap();
+        
+        let layouts = tree.calculate_layout(Size::new(800.0, 600.0)).unwrap();
+        assert_eq!(layouts[&NodeId(2)], Rect::new(0.0, 0.0, 100.0, 50.0));
+        assert_eq!(layouts[&NodeId(3)], Rect::new(0.0, 50.0, 60.0, 30.0));
+        assert_eq!(layouts[&NodeId(4)], Rect::new(0.0, 80.0, 200.0, 100.0));
+    }
+    
+    #[test]
+    fn test_specified_lengths_from_properties() {
+        let properties = HashMap::from([
+            ("width".to_string(), CssValue::Percentage(50.0)),
+            ("height".to_string(), CssValue::Keyword("auto".to_string())),
+            ("padding".to_string(), CssValue::Length(4.0, CssUnit::Px)),
+            ("padding-left".to_string(), CssValue::Percentage(10.0)),
+        ]);
+        let lengths = SpecifiedLengths::from_properties(&properties);
+        assert_eq!(lengths.width, Some(CssValue::Percentage(50.0)));
+        assert_eq!(lengths.height, None);
+        assert_eq!(lengths.padding, Some([
+            CssValue::Length(4.0, CssUnit::Px),
+            CssValue::Length(4.0, CssUnit::Px),
+            CssValue::Length(4.0, CssUnit::Px),
+            CssValue::Percentage(10.0),
+        ]));
+        assert_eq!(SpecifiedLengths::from_properties(&HashMap::new()), SpecifiedLengths::default());
+    }
+    
+    #[test]
+    fn test_block_flow_stacks_children() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(sized_node(1, None, vec![NodeId(2), NodeId(3)], Position::Static, BoxOffsets::default())).unwrap();
+        let mut first = sized_node(2, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default());
+        first.box_model.set_margin(Rect::new(0.0, 0.0, 0.0, 60.0));
+        tree.add_node(first).unwrap();
+        tree.add_node(sized_node(3, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default())).unwrap();
+        
+        let layouts = tree.calculate_layout(Size::new(200.0, 100.0)).unwrap();
+        assert_eq!(layouts[&NodeId(2)], Rect::new(0.0, 0.0, 200.0, 40.0));
+        assert_eq!(layouts[&NodeId(3)].y, 100.0);
+    }
+    
+    #[test]
+    fn test_relative_offset() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(sized_node(1, None, vec![NodeId(2)], Position::Static, BoxOffsets::default())).unwrap();
+        let offsets = BoxOffsets { top: Some(10.0), left: Some(10.0), ..BoxOffsets::default() };
+        tree.add_node(sized_node(2, Some(NodeId(1)), vec![], Position::Relative, offsets)).unwrap();
+        
+        let layouts = tree.calculate_layout(Size::new(200.0, 100.0)).unwrap();
+        assert_eq!(layouts[&NodeId(2)], Rect::new(10.0, 10.0, 200.0, 100.0));
+    }
+    
+    #[test]
+    fn test_absolute_in_positioned_container() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(sized_node(1, None, vec![NodeId(2), NodeId(3)], Position::Static, BoxOffsets::default())).unwrap();
+        
+        // A spacer pushes the container down the flow
+        let mut spacer = sized_node(2, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default());
+        spacer.box_model.set_margin(Rect::new(0.0, 0.0, 0.0, 70.0));
+        tree.add_node(spacer).unwrap();
+        
+        let mut container = sized_node(3, Some(NodeId(1)), vec![NodeId(4)], Position::Relative, BoxOffsets::default());
+        container.box_model.set_padding(Rect::new(5.0, 5.0, 10.0, 10.0));
+        tree.add_node(container).unwrap();
+        
+        let origin = BoxOffsets { top: Some(0.0), left: Some(0.0), ..BoxOffsets::default() };
+        tree.add_node(sized_node(4, Some(NodeId(3)), vec![], Position::Absolute, origin)).unwrap();
+        
+        let layouts = tree.calculate_layout(Size::new(200.0, 100.0)).unwrap();
+        assert_eq!(layouts[&NodeId(3)], Rect::new(5.0, 105.0, 190.0, 90.0));
+        
+        // (0,0) of the container's padding box, sized against it
+        assert_eq!(layouts[&NodeId(4)], Rect::new(0.0, 100.0, 200.0, 100.0));
+    }
+    
+    #[test]
+    fn test_relative_shift_prefers_left_and_top() {
+        let offsets = BoxOffsets { top: Some(3.0), right: Some(4.0), bottom: Some(5.0), left: None };
+        assert_eq!(offsets.relative_shift(), Point::new(-4.0, 3.0));
+    }
+    
+    #[test]
+    fn test_hit_test_finds_deepest_node() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(sized_node(1, None, vec![NodeId(2), NodeId(3)], Position::Static, BoxOffsets::default())).unwrap();
+        let mut first = sized_node(2, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default());
+        first.box_model.set_margin(Rect::new(0.0, 0.0, 0.0, 60.0));
+        tree.add_node(first).unwrap();
+        tree.add_node(sized_node(3, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default())).unwrap();
+        
+        let layouts = tree.calculate_layout(Size::new(200.0, 100.0)).unwrap();
+        assert_eq!(layouts[&NodeId(2)], Rect::new(0.0, 0.0, 200.0, 40.0));
+        assert_eq!(layouts[&NodeId(3)], Rect::new(0.0, 100.0, 200.0, 100.0));
+        
+        assert_eq!(tree.hit_test(&layouts, Point::new(10.0, 10.0), Point::zero()), Some(NodeId(2)));
+        assert_eq!(tree.hit_test(&layouts, Point::new(10.0, 150.0), Point::zero()), Some(NodeId(3)));
+        
+        // The first node's margin belongs to the root
+        assert_eq!(tree.hit_test(&layouts, Point::new(10.0, 70.0), Point::zero()), Some(NodeId(1)));
+        assert_eq!(tree.hit_test(&layouts, Point::new(10.0, 250.0), Point::zero()), None);
+        
+        // Scrolled down by 100, the top of the viewport shows the second node
+        assert_eq!(tree.hit_test(&layouts, Point::new(10.0, 10.0), Point::new(0.0, 100.0)), Some(NodeId(3)));
+    }
+    
+    #[test]
+    fn test_hit_test_prefers_higher_z_index() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(sized_node(1, None, vec![NodeId(2), NodeId(3)], Position::Static, BoxOffsets::default())).unwrap();
+        let origin = BoxOffsets { top: Some(0.0), left: Some(0.0), ..BoxOffsets::default() };
+        let mut raised = sized_node(2, Some(NodeId(1)), vec![], Position::Absolute, origin);
+        raised.z_index = Some(5);
+        tree.add_node(raised).unwrap();
+        tree.add_node(sized_node(3, Some(NodeId(1)), vec![], Position::Absolute, origin)).unwrap();
+        
+        let layouts = tree.calculate_layout(Size::new(200.0, 100.0)).unwrap();
+        assert_eq!(tree.hit_test(&layouts, Point::new(50.0, 50.0), Point::zero()), Some(NodeId(2)));
+    }
+    
+    #[test]
+    fn test_hit_test_stacks_positioned_grandchild_over_later_sibling() {
+        // Node 3 sits inside a static parent but its z-index lifts it above node 4
+        let mut tree = LayoutTree::new();
+        tree.add_node(sized_node(1, None, vec![NodeId(2), NodeId(4)], Position::Relative, BoxOffsets::default())).unwrap();
+        tree.add_node(sized_node(2, Some(NodeId(1)), vec![NodeId(3)], Position::Static, BoxOffsets::default())).unwrap();
+        let origin = BoxOffsets { top: Some(0.0), left: Some(0.0), ..BoxOffsets::default() };
+        let mut raised = sized_node(3, Some(NodeId(2)), vec![], Position::Absolute, origin);
+        raised.z_index = Some(1);
+        tree.add_node(raised).unwrap();
+        tree.add_node(sized_node(4, Some(NodeId(1)), vec![], Position::Absolute, origin)).unwrap();
+        
+        assert_eq!(tree.paint_order(NodeId(1)), vec![PaintLayer::Node(NodeId(2)), PaintLayer::Node(NodeId(4)), PaintLayer::Context(NodeId(3))]);
+        let layouts = tree.calculate_layout(Size::new(200.0, 100.0)).unwrap();
+        assert_eq!(tree.hit_test(&layouts, Point::new(50.0, 50.0), Point::zero()), Some(NodeId(3)));
+    }
+    
+    #[test]
+    fn test_hit_test_respects_overflow_clip() {
+        let mut tree = LayoutTree::new();
+        let mut root = sized_node(1, None, vec![NodeId(2)], Position::Static, BoxOffsets::default());
+        root.box_model.content = Rect::new(0.0, 0.0, 100.0, 100.0);
+        root.overflow = Overflow::Hidden;
+        tree.add_node(root).unwrap();
+        let offsets = BoxOffsets { top: Some(80.0), ..BoxOffsets::default() };
+        tree.add_node(sized_node(2, Some(NodeId(1)), vec![], Position::Relative, offsets)).unwrap();
+        
+        let layouts = tree.calculate_layout(Size::new(100.0, 100.0)).unwrap();
+        assert_eq!(tree.hit_test(&layouts, Point::new(50.0, 90.0), Point::zero()), Some(NodeId(2)));
+        
+        // The child sticks out below the root, but that part is clipped away
+        assert_eq!(tree.hit_test(&layouts, Point::new(50.0, 150.0), Point::zero()), None);
+    }
+    
+    #[test]
+    fn test_incremental_layout_recomputes_only_dirty_leaf() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(sized_node(1, None, vec![NodeId(2), NodeId(3)], Position::Static, BoxOffsets::default())).unwrap();
+        let mut first = sized_node(2, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default());
+        first.box_model.set_margin(Rect::new(0.0, 0.0, 0.0, 60.0));
+        tree.add_node(first).unwrap();
+        tree.add_node(sized_node(3, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default())).unwrap();
+        
+        let size = Size::new(200.0, 100.0);
+        let mut previous = tree.calculate_layout(size).unwrap();
+        
+        // Narrow the last child, then plant a marker to show the earlier sibling is not redone
+        tree.get_node_mut(NodeId(3)).unwrap().box_model.set_margin(Rect::new(0.0, 0.0, 50.0, 0.0));
+        let marker = Rect::new(7.0, 0.0, 200.0, 40.0);
+        previous.insert(NodeId(2), marker);
+        
+        let layouts = tree.calculate_layout_incremental(size, &[NodeId(3)], &previous).unwrap();
+        let full = tree.calculate_layout(size).unwrap();
+        assert_eq!(layouts[&NodeId(2)], marker);
+        assert_eq!(layouts[&NodeId(3)], full[&NodeId(3)]);
+        assert_eq!(layouts[&NodeId(3)].width, 150.0);
+        assert_eq!(layouts[&NodeId(1)], full[&NodeId(1)]);
+    }
+    
+    #[test]
+    fn test_incremental_layout_redoes_dirty_subtree_and_following_siblings() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(sized_node(1, None, vec![NodeId(2), NodeId(3), NodeId(4)], Position::Static, BoxOffsets::default())).unwrap();
+        tree.add_node(sized_node(2, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default())).unwrap();
+        tree.add_node(sized_node(3, Some(NodeId(1)), vec![NodeId(5)], Position::Static, BoxOffsets::default())).unwrap();
+        tree.add_node(sized_node(4, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default())).unwrap();
+        tree.add_node(sized_node(5, Some(NodeId(3)), vec![], Position::Static, BoxOffsets::default())).unwrap();
+        
+        let size = Size::new(200.0, 100.0);
+        let mut previous = tree.calculate_layout(size).unwrap();
+        tree.get_node_mut(NodeId(3)).unwrap().box_model.set_margin(Rect::new(0.0, 0.0, 80.0, 0.0));
+        
+        // Stale rects after the dirty node must all be replaced
+        for id in [4, 5] {
+            previous.insert(NodeId(id), Rect::zero());
+        }
+        
+        let layouts = tree.calculate_layout_incremental(size, &[NodeId(3)], &previous).unwrap();
+        assert_eq!(layouts, tree.calculate_layout(size).unwrap());
+        assert_eq!(layouts[&NodeId(5)].width, 120.0);
+    }
+    
+    #[test]
+    fn test_incremental_layout_reuses_percentage_padding() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(sized_node(1, None, vec![NodeId(2), NodeId(3)], Position::Static, BoxOffsets::default())).unwrap();
+        let mut padded = sized_node(2, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default());
+        padded.lengths.padding = Some([CssValue::Percentage(10.0), CssValue::Percentage(0.0), CssValue::Percentage(10.0), CssValue::Percentage(0.0)]);
+        tree.add_node(padded).unwrap();
+        tree.add_node(sized_node(3, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default())).unwrap();
+        
+        let size = Size::new(200.0, 100.0);
+        let previous = tree.calculate_layout(size).unwrap();
+        tree.get_node_mut(NodeId(3)).unwrap().box_model.set_margin(Rect::new(0.0, 0.0, 50.0, 0.0));
+        
+        // The reused sibling still takes 10% of 200px above and below its content
+        let layouts = tree.calculate_layout_incremental(size, &[NodeId(3)], &previous).unwrap();
+        assert_eq!(layouts, tree.calculate_layout(size).unwrap());
+        assert_eq!(layouts[&NodeId(3)].y, layouts[&NodeId(2)].height + 40.0);
+    }
+    
+    #[test]
+    fn test_layout_cache_skips_unchanged_nodes() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(sized_node(1, None, vec![NodeId(2), NodeId(3)], Position::Static, BoxOffsets::default())).unwrap();
+        tree.add_node(sized_node(2, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default())).unwrap();
+        tree.add_node(sized_node(3, Some(NodeId(1)), vec![], Position::Static, BoxOffsets::default())).unwrap();
+        
+        let size = Size::new(200.0, 100.0);
+        let first = tree.calculate_layout(size).unwrap();
+        assert_eq!(tree.layout_computations(), 3);
+        
+        // Identical inputs are answered from the cache
+        assert_eq!(tree.calculate_layout(size).unwrap(), first);
+        assert_eq!(tree.layout_computations(), 3);
+        
+        // A new available size, a changed node, or a cleared cache means computing again
+        tree.calculate_layout(Size::new(300.0, 100.0)).unwrap();
+        assert_eq!(tree.layout_computations(), 6);
+        
+        tree.get_node_mut(NodeId(3)).unwrap().box_model.set_margin(Rect::new(0.0, 0.0, 50.0, 0.0));
+        let changed = tree.calculate_layout(size).unwrap();
+        assert_eq!(tree.layout_computations(), 7);
+        assert_eq!(changed[&NodeId(3)].width, 150.0);
+        
+        tree.clear_cache();
+        tree.calculate_layout(size).unwrap();
+        assert_eq!(tree.layout_computations(), 10);
+    }
+    
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_layout_matches_serial() {
+        let mut tree = LayoutTree::new();
+        let child_ids: Vec<NodeId> = (2..=9).map(NodeId).collect();
+        tree.add_node(sized_node(1, None, child_ids.clone(), Position::Relative, BoxOffsets::default())).unwrap();
+        
+        for &child_id in &child_ids {
+            let grandchild = NodeId(child_id.0 * 10);
+            let position = if child_id.0 == 5 { Position::Absolute } else { Position::Static };
+            let offsets = BoxOffsets { top: Some(5.0), left: Some(child_id.0 as f32), ..BoxOffsets::default() };
+            let mut child = sized_node(child_id.0, Some(NodeId(1)), vec![grandchild], position, offsets);
+            child.box_model.set_margin(Rect::new(0.0, 0.0, 10.0, 90.0));
+            tree.add_node(child).unwrap();
+            tree.add_node(sized_node(grandchild.0, Some(child_id), vec![], Position::Relative, offsets)).unwrap();
+        }
+        
+        let size = Size::new(400.0, 300.0);
+        let serial = tree.calculate_layout(size).unwrap();
+        let parallel = tree.calculate_layout_parallel(size).unwrap();
+        assert_eq!(parallel.len(), 17);
+        assert_eq!(parallel, serial);
+        
+        // Run again from a cold cache so nothing is shared with the serial pass
+        tree.clear_cache();
+        assert_eq!(tree.calculate_layout_parallel(size).unwrap(), serial);
     }
 }
@@ -6,17 +6,20 @@
 pub mod box_model;
 pub mod flexbox;
 pub mod grid;
+pub mod inline;
 pub mod layout_tree;
 
 pub use box_model::{BoxModel, BoxSizing};
 pub use flexbox::{FlexContainer, FlexItem, FlexboxLayout};
 pub use grid::{GridContainer, GridItem, GridLayout};
-pub use layout_tree::{LayoutTree, LayoutNode};
+pub use inline::{InlineLayout, LineBox};
+pub use layout_tree::{LayoutTree, LayoutNode, BoxOffsets, SpecifiedLengths, PaintLayer};
 
 /// Re-export commonly used items for convenience
 pub mod prelude {
     pub use super::box_model::{BoxModel, BoxSizing};
     pub use super::flexbox::{FlexContainer, FlexItem, FlexboxLayout};
     pub use super::grid::{GridContainer, GridItem, GridLayout};
-    pub use super::layout_tree::{LayoutTree, LayoutNode};
+    pub use super::inline::{InlineLayout, LineBox};
+    pub use super::layout_tree::{LayoutTree, LayoutNode, BoxOffsets, SpecifiedLengths, PaintLayer};
 }
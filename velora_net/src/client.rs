@@ -1,18 +1,86 @@
 //! HTTP client for the Velora web engine
 
 use velora_core::{VeloraResult, HttpStatus, VeloraError};
+use velora_core::error::NetworkError;
 use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
 use reqwest::Client;
+use crate::cookies::CookieJar;
+use crate::data_url::DataUrl;
+use crate::file_url::load_file_url;
+use crate::network_log::{NetworkLog, NetworkLogEntry};
 use url::Url;
 use log::info;
+use tokio::io::AsyncWriteExt;
+
+/// Content codings the client can decode, sent as Accept-Encoding
+const ACCEPT_ENCODING: &str = "gzip, deflate, br";
+
+/// Largest body a response may decompress to, guarding against compression bombs
+const MAX_DECODED_BODY_SIZE: u64 = 256 * 1024 * 1024;
 
 /// HTTP client for making network requests
-#[derive(Debug)]
+///
+/// Clones share the configuration and connection pool.
+#[derive(Debug, Clone)]
 pub struct HttpClient {
     /// Client configuration
-    config: HttpClientConfig,
+    config: Arc<HttpClientConfig>,
     /// Internal reqwest client
-    client: Client,
+    client: Arc<Client>,
+    /// Cookies kept across requests, if enabled
+    cookie_jar: Option<Arc<CookieJar>>,
+    /// Finished requests, if logging is enabled
+    network_log: Option<Arc<NetworkLog>>,
+}
+
+/// Progress reported by `HttpClient::download`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadEvent {
+    /// The response arrived; `total` is its Content-Length, if it sent one
+    Started { total: Option<u64> },
+    
+    /// A chunk of this many bytes was written to disk
+    Received(u64),
+}
+
+/// Browser identities to send as the User-Agent, for sites that sniff it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserAgentPreset {
+    /// Chrome on desktop Linux, the default
+    DesktopChrome,
+    /// Safari on an iPhone
+    MobileSafari,
+}
+
+impl UserAgentPreset {
+    /// Every preset, in menu order
+    pub const ALL: [Self; 2] = [Self::DesktopChrome, Self::MobileSafari];
+    
+    /// Name shown to users
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::DesktopChrome => "Desktop Chrome",
+            Self::MobileSafari => "Mobile Safari",
+        }
+    }
+    
+    /// The User-Agent header value
+    pub fn user_agent(&self) -> &'static str {
+        match self {
+            Self::DesktopChrome => "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+            Self::MobileSafari => "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1",
+        }
+    }
+    
+    /// Whether sites will treat this browser as a phone
+    pub fn is_mobile(&self) -> bool {
+        matches!(self, Self::MobileSafari)
+    }
 }
 
 /// HTTP client configuration
@@ -26,14 +94,26 @@ pub struct HttpClientConfig {
     
     /// Maximum redirects
     pub max_redirects: u32,
+    
+    /// Whether to store cookies and send them back on later requests
+    pub cookies: bool,
+    
+    /// Directory that `file:` URLs must stay inside, or None to allow any file
+    pub file_root: Option<PathBuf>,
+    
+    /// Whether to record every request in a network log
+    pub network_log: bool,
 }
 
 impl Default for HttpClientConfig {
     fn default() -> Self {
         Self {
-            user_agent: "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36".to_string(),
+            user_agent: UserAgentPreset::DesktopChrome.user_agent().to_string(),
             timeout: 30,
             max_redirects: 10,
+            cookies: true,
+            file_root: None,
+            network_log: false,
         }
     }
 }
@@ -41,66 +121,152 @@ impl Default for HttpClientConfig {
 impl HttpClient {
     /// Create a new HTTP client
     pub fn new() -> VeloraResult<Self> {
-        let config = HttpClientConfig::default();
-        let client = Client::builder()
-            .user_agent(&config.user_agent)
-            .timeout(std::time::Duration::from_secs(config.timeout))
-            .build()
-            .map_err(|e| VeloraError::Network(velora_core::error::NetworkError::RequestFailed(e.to_string())))?;
-        
-        Ok(Self { config, client })
+        Self::with_config(HttpClientConfig::default())
     }
     
     /// Create a new HTTP client with custom configuration
     pub fn with_config(config: HttpClientConfig) -> VeloraResult<Self> {
-        let client = Client::builder()
+        let cookie_jar = config.cookies.then(|| Arc::new(CookieJar::new()));
+        let network_log = config.network_log.then(|| Arc::new(NetworkLog::new()));
+        
+        let mut builder = Client::builder()
             .user_agent(&config.user_agent)
-            .timeout(std::time::Duration::from_secs(config.timeout))
+            .timeout(std::time::Duration::from_secs(config.timeout));
+        if let Some(jar) = &cookie_jar {
+            builder = builder.cookie_provider(Arc::clone(jar));
+        }
+        let client = builder
             .build()
             .map_err(|e| VeloraError::Network(velora_core::error::NetworkError::RequestFailed(e.to_string())))?;
         
-        Ok(Self { config, client })
+        Ok(Self {
+            config: Arc::new(config),
+            client: Arc::new(client),
+            cookie_jar,
+            network_log,
+        })
+    }
+    
+    /// Store a cookie as if `url` had sent it in a `Set-Cookie` header
+    pub fn set_cookie(&self, url: &str, header: &str) -> VeloraResult<()> {
+        let url = Url::parse(url)
+            .map_err(|e| VeloraError::InvalidUrl(e.to_string()))?;
+        let jar = self.cookie_jar.as_ref()
+            .ok_or_else(|| VeloraError::InvalidState("Cookies are disabled for this client".to_string()))?;
+        
+        jar.set_cookie(&url, header);
+        Ok(())
+    }
+    
+    /// Get the cookie jar, if cookies are enabled
+    pub fn cookie_jar(&self) -> Option<&CookieJar> {
+        self.cookie_jar.as_deref()
+    }
+    
+    /// Get the request log, if logging is enabled
+    pub fn network_log(&self) -> Option<&NetworkLog> {
+        self.network_log.as_deref()
+    }
+    
+    /// Forget all cookies this client has stored
+    pub fn clear_cookies(&self) {
+        if let Some(jar) = &self.cookie_jar {
+            jar.clear();
+        }
     }
     
     /// Make a GET request
     pub async fn get(&self, url: &str) -> VeloraResult<HttpResponse> {
+        self.get_with_user_agent(url, None).await
+    }
+    
+    /// Make a GET request, sending `user_agent` instead of the configured one if given
+    pub async fn get_with_user_agent(&self, url: &str, user_agent: Option<&str>) -> VeloraResult<HttpResponse> {
+        self.open(url, user_agent).await?.read().await
+    }
+    
+    /// Make a GET request and wait only for the response headers
+    ///
+    /// The body is left unread so the caller can decide, from the headers, whether to read it
+    /// into memory with `PendingResponse::read` or stream it to disk with `PendingResponse::save`.
+    /// The request is logged once its body has been consumed, or here if it fails first.
+    pub async fn open(&self, url: &str, user_agent: Option<&str>) -> VeloraResult<PendingResponse> {
+        let log = self.network_log.clone().map(|log| RequestLog::start(log, url));
+        match self.send(url, user_agent).await {
+            Ok(response) => Ok(PendingResponse { log, ..response }),
+            Err(e) => {
+                if let Some(log) = log {
+                    log.finish(None, 0);
+                }
+                Err(e)
+            }
+        }
+    }
+    
+    /// Load a URL of any supported scheme, up to the end of its headers
+    async fn send(&self, url: &str, user_agent: Option<&str>) -> VeloraResult<PendingResponse> {
         info!("Making GET request to: {}", url);
         
+        // Inline data needs no network round-trip
+        if DataUrl::is_data_url(url) {
+            return Ok(DataUrl::parse(url)?.into_response().into());
+        }
+        
         // Validate URL
         let url = Url::parse(url)
             .map_err(|e| VeloraError::InvalidUrl(e.to_string()))?;
         
+        // Local pages are read straight from disk
+        if url.scheme() == "file" {
+            return load_file_url(&url, self.config.file_root.as_deref()).await.map(PendingResponse::from);
+        }
+        
         // Make the request
-        let response = self.client
-            .get(url.clone())
+        let response = self.get_request(url, user_agent)
             .send()
             .await
-            .map_err(|e| VeloraError::Network(velora_core::error::NetworkError::RequestFailed(e.to_string())))?;
-        
-        let status = response.status();
-        let headers = response.headers().clone();
-        let body = response.bytes().await
-            .map_err(|e| VeloraError::Network(velora_core::error::NetworkError::RequestFailed(e.to_string())))?
-            .to_vec();
+            .map_err(|e| VeloraError::Network(NetworkError::RequestFailed(e.to_string())))?;
         
         // Convert headers to our format
         let mut header_map = HashMap::new();
-        for (key, value) in headers.iter() {
+        for (key, value) in response.headers().iter() {
             if let Ok(value_str) = value.to_str() {
                 header_map.insert(key.as_str().to_string(), value_str.to_string());
             }
         }
         
-        // Create our response type
+        let status = response.status();
         let http_status = HttpStatus::new(
             status.as_u16(),
             status.canonical_reason().unwrap_or("Unknown").to_string()
         );
         
-        info!("Response: {} {} ({} bytes)", 
-              http_status.code, http_status.reason, body.len());
-        
-        Ok(HttpResponse::new(http_status, header_map, body))
+        Ok(PendingResponse {
+            status: http_status,
+            headers: header_map,
+            buffered: Vec::new(),
+            stream: Some(response),
+            log: None,
+        })
+    }
+    
+    /// Build an HTTP GET request with the client's standard headers
+    fn get_request(&self, url: Url, user_agent: Option<&str>) -> reqwest::RequestBuilder {
+        self.client
+            .get(url)
+            .header(reqwest::header::ACCEPT_ENCODING, ACCEPT_ENCODING)
+            .header(reqwest::header::USER_AGENT, user_agent.unwrap_or(&self.config.user_agent))
+    }
+    
+    /// Stream a GET response body into a file, returning the number of bytes written
+    ///
+    /// Unlike `get`, the body is written as it arrives instead of being held in memory.
+    /// `data:` and `file:` URLs are written in one chunk.
+    pub async fn download(&self, url: &str, path: &Path, progress: impl FnMut(DownloadEvent)) -> VeloraResult<u64> {
+        info!("Downloading {} to {}", url, path.display());
+        let response = self.open(url, None).await?.error_for_status()?;
+        let file = tokio::fs::File::create(path).await?;
+        response.save(file, progress).await
     }
     
     /// Make a POST request
@@ -114,6 +280,173 @@ impl HttpClient {
     }
 }
 
+/// A response whose headers have arrived but whose body has not been read yet
+#[derive(Debug)]
+pub struct PendingResponse {
+    /// Response status
+    pub status: HttpStatus,
+    
+    /// Response headers
+    pub headers: HashMap<String, String>,
+    
+    /// Body received so far, still encoded
+    buffered: Vec<u8>,
+    
+    /// Connection the rest of the body arrives on, or None once it has all been received
+    stream: Option<reqwest::Response>,
+    
+    /// Log entry to finish once the body has been consumed
+    log: Option<RequestLog>,
+}
+
+impl PendingResponse {
+    /// Get the Content-Type header, if the server sent one
+    pub fn content_type(&self) -> Option<&str> {
+        self.headers.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.as_str())
+    }
+    
+    /// Turn an error status into an error, so the body is only consumed for successful responses
+    pub fn error_for_status(self) -> VeloraResult<Self> {
+        if !self.status.is_success() {
+            return Err(VeloraError::Network(NetworkError::RequestFailed(format!(
+                "{} {}", self.status.code, self.status.reason
+            ))));
+        }
+        Ok(self)
+    }
+    
+    /// Read the whole body into memory, undoing its Content-Encoding
+    pub async fn read(mut self) -> VeloraResult<HttpResponse> {
+        let log = self.log.take();
+        let result = match self.receive_all().await {
+            Ok(()) => HttpResponse::decoded(self.status, self.headers, self.buffered),
+            Err(e) => Err(e),
+        };
+        
+        if let Ok(response) = &result {
+            info!("Response: {} {} ({} bytes)",
+                  response.status.code, response.status.reason, response.body.len());
+        }
+        if let Some(log) = log {
+            let finished = result.as_ref().ok();
+            log.finish(finished.map(|response| response.status.code), finished.map_or(0, |response| response.body.len()));
+        }
+        result
+    }
+    
+    /// Write the body into a file, returning the number of bytes written
+    ///
+    /// The body is written as it arrives. One sent with a Content-Encoding is decoded first,
+    /// and so is held in memory like a body from `read`.
+    pub async fn save(mut self, file: tokio::fs::File, mut progress: impl FnMut(DownloadEvent)) -> VeloraResult<u64> {
+        let log = self.log.take();
+        let status = self.status.code;
+        let result = self.write_to(file, &mut progress).await;
+        
+        if let Some(log) = log {
+            log.finish(result.as_ref().ok().map(|_| status), *result.as_ref().unwrap_or(&0) as usize);
+        }
+        result
+    }
+    
+    /// Write the body into a file, reporting progress per chunk
+    async fn write_to(&mut self, mut file: tokio::fs::File, progress: &mut impl FnMut(DownloadEvent)) -> VeloraResult<u64> {
+        let encoded = self.headers.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-encoding"))
+            .map(|(_, value)| value.clone())
+            .filter(|encoding| !encoding.trim().eq_ignore_ascii_case("identity"));
+        if let Some(encoding) = encoded {
+            self.receive_all().await?;
+            self.buffered = decode_body(&encoding, std::mem::take(&mut self.buffered), MAX_DECODED_BODY_SIZE)?;
+        }
+        
+        let total = match &self.stream {
+            Some(stream) => stream.content_length().map(|length| length + self.buffered.len() as u64),
+            None => Some(self.buffered.len() as u64),
+        };
+        progress(DownloadEvent::Started { total });
+        
+        let mut written = 0;
+        if self.stream.is_none() || !self.buffered.is_empty() {
+            file.write_all(&self.buffered).await?;
+            written += self.buffered.len() as u64;
+            progress(DownloadEvent::Received(self.buffered.len() as u64));
+        }
+        if let Some(stream) = &mut self.stream {
+            while let Some(chunk) = stream.chunk().await
+                .map_err(|e| VeloraError::Network(NetworkError::RequestFailed(e.to_string())))?
+            {
+                file.write_all(&chunk).await?;
+                written += chunk.len() as u64;
+                progress(DownloadEvent::Received(chunk.len() as u64));
+            }
+        }
+        file.flush().await?;
+        
+        Ok(written)
+    }
+    
+    /// Receive the rest of the body into the buffer
+    async fn receive_all(&mut self) -> VeloraResult<()> {
+        if let Some(mut stream) = self.stream.take() {
+            while let Some(chunk) = stream.chunk().await
+                .map_err(|e| VeloraError::Network(NetworkError::RequestFailed(e.to_string())))?
+            {
+                self.buffered.extend_from_slice(&chunk);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<HttpResponse> for PendingResponse {
+    /// Wrap a response whose body is already in memory
+    fn from(response: HttpResponse) -> Self {
+        Self {
+            status: response.status,
+            headers: response.headers,
+            buffered: response.body,
+            stream: None,
+            log: None,
+        }
+    }
+}
+
+/// A request in progress, recorded in the network log when it finishes
+#[derive(Debug)]
+struct RequestLog {
+    log: Arc<NetworkLog>,
+    url: String,
+    started: SystemTime,
+    timer: Instant,
+}
+
+impl RequestLog {
+    /// Start timing a request
+    fn start(log: Arc<NetworkLog>, url: &str) -> Self {
+        Self {
+            log,
+            url: url.to_string(),
+            started: SystemTime::now(),
+            timer: Instant::now(),
+        }
+    }
+    
+    /// Record the request with its status, or None if it failed, and body size
+    fn finish(self, status: Option<u16>, bytes: usize) {
+        self.log.record(NetworkLogEntry {
+            method: "GET".to_string(),
+            url: self.url,
+            status,
+            bytes,
+            started: self.started,
+            duration: self.timer.elapsed(),
+        });
+    }
+}
+
 /// HTTP response
 #[derive(Debug)]
 pub struct HttpResponse {
@@ -123,8 +456,11 @@ pub struct HttpResponse {
     /// Response headers
     pub headers: HashMap<String, String>,
     
-    /// Response body
+    /// Response body, already decompressed
     pub body: Vec<u8>,
+    
+    /// Content-Encoding the body was transferred with, if any
+    pub content_encoding: Option<String>,
 }
 
 impl HttpResponse {
@@ -134,20 +470,110 @@ impl HttpResponse {
             status,
             headers,
             body,
+            content_encoding: None,
         }
     }
     
+    /// Create a response from a raw body, undoing its Content-Encoding
+    pub fn decoded(status: HttpStatus, headers: HashMap<String, String>, body: Vec<u8>) -> VeloraResult<Self> {
+        let content_encoding = headers.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-encoding"))
+            .map(|(_, value)| value.clone());
+        
+        let body = match &content_encoding {
+            Some(encoding) => decode_body(encoding, body, MAX_DECODED_BODY_SIZE)?,
+            None => body,
+        };
+        
+        Ok(Self {
+            status,
+            headers,
+            body,
+            content_encoding,
+        })
+    }
+    
     /// Get a header value
     pub fn get_header(&self, name: &str) -> Option<&str> {
         self.headers.get(name).map(|s| s.as_str())
     }
     
+    /// Get the Content-Type, sniffing the body when the header is missing
+    pub fn content_type(&self) -> String {
+        self.headers.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.clone())
+            .unwrap_or_else(|| sniff_content_type(&self.body).to_string())
+    }
+    
     /// Get the response body as text
     pub fn text(&self) -> Result<String, std::string::FromUtf8Error> {
         String::from_utf8(self.body.clone())
     }
 }
 
+/// Guess the type of a body that arrived without a Content-Type header
+pub fn sniff_content_type(body: &[u8]) -> &'static str {
+    let start = body.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(body);
+    let start = &start[start.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(start.len())..];
+    let prefix = &start[..start.len().min(9)];
+    
+    if prefix.eq_ignore_ascii_case(b"<!doctype") || prefix.get(..5).is_some_and(|p| p.eq_ignore_ascii_case(b"<html")) {
+        "text/html"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// Undo a Content-Encoding; codings are removed in reverse of the order they were applied
+///
+/// An empty body, as sent with 204 and 304 responses, is returned as is. Decoding to more
+/// than `limit` bytes is an error.
+///
+/// This is done here instead of through reqwest's `gzip`, `deflate` and `brotli` features:
+/// reqwest removes the Content-Encoding header from any response it decodes, before the
+/// response is handed back, so `HttpResponse::content_encoding` could never be filled in.
+/// Its decoders also undo only a single coding and have no size limit.
+fn decode_body(encoding: &str, body: Vec<u8>, limit: u64) -> VeloraResult<Vec<u8>> {
+    if body.is_empty() {
+        return Ok(body);
+    }
+    
+    encoding.split(',')
+        .map(str::trim)
+        .filter(|coding| !coding.is_empty())
+        .rev()
+        .try_fold(body, |data, coding| {
+            let mut decoded = Vec::new();
+            // Read one byte past the limit to tell a body of exactly `limit` bytes from a larger one
+            let result = match coding.to_ascii_lowercase().as_str() {
+                "identity" => return Ok(data),
+                "gzip" | "x-gzip" => GzDecoder::new(&data[..]).take(limit + 1).read_to_end(&mut decoded),
+                // Servers disagree on whether deflate means zlib-wrapped or raw
+                "deflate" => ZlibDecoder::new(&data[..]).take(limit + 1).read_to_end(&mut decoded).or_else(|_| {
+                    decoded.clear();
+                    DeflateDecoder::new(&data[..]).take(limit + 1).read_to_end(&mut decoded)
+                }),
+                "br" => brotli::Decompressor::new(&data[..], 4096).take(limit + 1).read_to_end(&mut decoded),
+                other => {
+                    return Err(VeloraError::Network(NetworkError::InvalidResponse(
+                        format!("Unsupported content encoding: {}", other)
+                    )));
+                }
+            };
+            
+            result.map_err(|e| VeloraError::Network(NetworkError::InvalidResponse(
+                format!("Failed to decode {} body: {}", coding, e)
+            )))?;
+            if decoded.len() as u64 > limit {
+                return Err(VeloraError::Network(NetworkError::InvalidResponse(
+                    format!("Decoded {} body is larger than {} bytes", coding, limit)
+                )));
+            }
+            Ok(decoded)
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -170,6 +596,9 @@ mod tests {
             user_agent: "Custom Agent/2.0".to_string(),
             timeout: 60,
             max_redirects: 5,
+            cookies: false,
+            file_root: None,
+            network_log: false,
         };
         
         let client = HttpClient::with_config(config);
@@ -179,6 +608,170 @@ mod tests {
         assert_eq!(client.config.user_agent, "Custom Agent/2.0");
         assert_eq!(client.config.timeout, 60);
         assert_eq!(client.config.max_redirects, 5);
+        assert!(client.cookie_jar().is_none());
+        assert!(client.set_cookie("https://example.com", "a=1").is_err());
+        assert!(client.network_log().is_none());
+    }
+    
+    #[test]
+    fn test_http_client_cookies() {
+        let client = HttpClient::new().unwrap();
+        client.set_cookie("https://example.com/", "session=abc123").unwrap();
+        
+        // Clones share the jar, so a session survives across navigations
+        let copy = client.clone();
+        let url = Url::parse("https://example.com/page").unwrap();
+        assert_eq!(copy.cookie_jar().unwrap().cookie_header(&url), Some("session=abc123".to_string()));
+        
+        copy.clear_cookies();
+        assert_eq!(client.cookie_jar().unwrap().cookie_header(&url), None);
+    }
+    
+    #[tokio::test]
+    async fn test_requests_are_logged() {
+        let client = HttpClient::with_config(HttpClientConfig {
+            network_log: true,
+            ..HttpClientConfig::default()
+        }).unwrap();
+        
+        client.get("data:text/plain,Hello%20log").await.unwrap();
+        assert!(client.get("data:text/plain;base64,!!").await.is_err());
+        
+        // Clones log into the same place
+        let entries = client.clone().network_log().unwrap().entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].method, "GET");
+        assert_eq!(entries[0].url, "data:text/plain,Hello%20log");
+        assert_eq!(entries[0].status, Some(200));
+        assert_eq!(entries[0].bytes, 9);
+        assert!(entries[0].duration > std::time::Duration::ZERO);
+        assert_eq!((entries[1].status, entries[1].bytes), (None, 0));
+    }
+    
+    #[test]
+    fn test_user_agent_override() {
+        let client = HttpClient::with_config(HttpClientConfig {
+            user_agent: "Default Agent/1.0".to_string(),
+            ..HttpClientConfig::default()
+        }).unwrap();
+        let url = Url::parse("https://example.com/").unwrap();
+        let user_agent = |request: reqwest::Request| request.headers()[reqwest::header::USER_AGENT].to_str().unwrap().to_string();
+        
+        let mobile = UserAgentPreset::MobileSafari.user_agent();
+        let overridden = client.get_request(url.clone(), Some(mobile)).build().unwrap();
+        assert_eq!(user_agent(overridden), mobile);
+        
+        // The override only applies to the request it was given for
+        let default = client.get_request(url, None).build().unwrap();
+        assert_eq!(user_agent(default), "Default Agent/1.0");
+        
+        assert!(UserAgentPreset::MobileSafari.is_mobile());
+        assert!(!UserAgentPreset::DesktopChrome.is_mobile());
+    }
+    
+    #[test]
+    fn test_http_client_clone_shares_client() {
+        let client = HttpClient::with_config(HttpClientConfig {
+            user_agent: "Shared Agent/1.0".to_string(),
+            ..HttpClientConfig::default()
+        }).unwrap();
+        let copy = client.clone();
+        
+        assert!(Arc::ptr_eq(&client.client, &copy.client));
+        assert!(Arc::ptr_eq(&client.config, &copy.config));
+        assert_eq!(copy.config.user_agent, "Shared Agent/1.0");
+    }
+    
+    #[tokio::test]
+    async fn test_get_data_url() {
+        let client = HttpClient::new().unwrap();
+        let response = client.get("data:text/html,%3Cp%3EInline%3C%2Fp%3E").await.unwrap();
+        
+        assert!(response.status.is_success());
+        assert_eq!(response.content_type(), "text/html");
+        assert_eq!(response.text().unwrap(), "<p>Inline</p>");
+    }
+    
+    #[tokio::test]
+    async fn test_download_data_url() {
+        let client = HttpClient::new().unwrap();
+        let path = std::env::temp_dir().join(format!("velora_download_test_{}.txt", std::process::id()));
+        
+        let mut events = Vec::new();
+        let written = client.download("data:text/plain,hello%20world", &path, |event| events.push(event)).await;
+        let contents = std::fs::read_to_string(&path);
+        std::fs::remove_file(&path).unwrap();
+        
+        assert_eq!(written.unwrap(), 11);
+        assert_eq!(contents.unwrap(), "hello world");
+        assert_eq!(events, vec![DownloadEvent::Started { total: Some(11) }, DownloadEvent::Received(11)]);
+    }
+    
+    #[tokio::test]
+    async fn test_open_then_save_uses_one_request() {
+        // A server that answers every connection with the same small body
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/report.pdf", listener.local_addr().unwrap());
+        let connections = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = connections.clone();
+        tokio::spawn(async move {
+            use tokio::io::AsyncReadExt;
+            while let Ok((mut socket, _)) = listener.accept().await {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let mut request = [0; 1024];
+                let _ = socket.read(&mut request).await;
+                let _ = socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: application/pdf\r\nContent-Length: 5\r\n\r\n%PDF-").await;
+            }
+        });
+        
+        let client = HttpClient::with_config(HttpClientConfig { network_log: true, ..HttpClientConfig::default() }).unwrap();
+        let response = client.open(&url, None).await.unwrap();
+        assert_eq!(response.content_type(), Some("application/pdf"));
+        assert!(client.network_log().unwrap().entries().is_empty());
+        
+        let path = std::env::temp_dir().join(format!("velora_save_test_{}.pdf", std::process::id()));
+        let file = tokio::fs::File::create(&path).await.unwrap();
+        let mut events = Vec::new();
+        let written = response.save(file, |event| events.push(event)).await;
+        let contents = std::fs::read(&path);
+        std::fs::remove_file(&path).unwrap();
+        
+        assert_eq!(written.unwrap(), 5);
+        assert_eq!(contents.unwrap(), b"%PDF-");
+        assert_eq!(events, vec![DownloadEvent::Started { total: Some(5) }, DownloadEvent::Received(5)]);
+        assert_eq!(connections.load(std::sync::atomic::Ordering::SeqCst), 1);
+        
+        let entries = client.network_log().unwrap().entries();
+        assert_eq!((entries.len(), entries[0].status, entries[0].bytes), (1, Some(200), 5));
+    }
+    
+    #[tokio::test]
+    async fn test_compressed_response_keeps_encoding() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+        
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"<p>Hello, gzip!</p>").unwrap();
+        let compressed = encoder.finish().unwrap();
+        
+        // A server that records the request and answers with a gzip body
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            use tokio::io::AsyncReadExt;
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let read = socket.read(&mut request).await.unwrap();
+            let head = format!("HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nContent-Length: {}\r\n\r\n", compressed.len());
+            socket.write_all(head.as_bytes()).await.unwrap();
+            socket.write_all(&compressed).await.unwrap();
+            String::from_utf8_lossy(&request[..read]).to_ascii_lowercase()
+        });
+        
+        let response = HttpClient::new().unwrap().get(&url).await.unwrap();
+        assert_eq!(response.text().unwrap(), "<p>Hello, gzip!</p>");
+        assert_eq!(response.content_encoding.as_deref(), Some("gzip"));
+        assert!(server.await.unwrap().contains("accept-encoding: gzip, deflate, br"));
     }
     
     #[tokio::test]
@@ -223,4 +816,92 @@ mod tests {
         assert!(text.is_ok());
         assert_eq!(text.unwrap(), "Hello, World!");
     }
+    
+    #[test]
+    fn test_content_type_sniffing() {
+        assert_eq!(sniff_content_type(b"  <!DOCTYPE html><html></html>"), "text/html");
+        assert_eq!(sniff_content_type(b"\xEF\xBB\xBF<HTML><body></body>"), "text/html");
+        assert_eq!(sniff_content_type(b"%PDF-1.7"), "application/octet-stream");
+        assert_eq!(sniff_content_type(b""), "application/octet-stream");
+        
+        let mut headers = HashMap::new();
+        headers.insert("Content-Type".to_string(), "application/pdf".to_string());
+        let response = HttpResponse::new(HttpStatus::ok(), headers, b"<html>".to_vec());
+        assert_eq!(response.content_type(), "application/pdf");
+    }
+    
+    fn encoded_response(encoding: &str, body: Vec<u8>) -> VeloraResult<HttpResponse> {
+        let mut headers = HashMap::new();
+        headers.insert("content-encoding".to_string(), encoding.to_string());
+        HttpResponse::decoded(HttpStatus::ok(), headers, body)
+    }
+    
+    #[test]
+    fn test_gzip_response() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+        
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"<p>Hello, gzip!</p>").unwrap();
+        
+        let response = encoded_response("gzip", encoder.finish().unwrap()).unwrap();
+        assert_eq!(response.text().unwrap(), "<p>Hello, gzip!</p>");
+        assert_eq!(response.content_encoding.as_deref(), Some("gzip"));
+    }
+    
+    #[test]
+    fn test_deflate_response() {
+        use flate2::{write::{DeflateEncoder, ZlibEncoder}, Compression};
+        use std::io::Write;
+        
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(b"zlib wrapped").unwrap();
+        let response = encoded_response("deflate", zlib.finish().unwrap()).unwrap();
+        assert_eq!(response.text().unwrap(), "zlib wrapped");
+        
+        let mut raw = DeflateEncoder::new(Vec::new(), Compression::default());
+        raw.write_all(b"raw deflate").unwrap();
+        let response = encoded_response("deflate", raw.finish().unwrap()).unwrap();
+        assert_eq!(response.text().unwrap(), "raw deflate");
+    }
+    
+    #[test]
+    fn test_brotli_response() {
+        use std::io::Write;
+        
+        let mut compressed = Vec::new();
+        {
+            let mut encoder = brotli::CompressorWriter::new(&mut compressed, 4096, 5, 22);
+            encoder.write_all(b"Hello, brotli!").unwrap();
+        }
+        
+        let response = encoded_response("br", compressed).unwrap();
+        assert_eq!(response.text().unwrap(), "Hello, brotli!");
+    }
+    
+    #[test]
+    fn test_empty_encoded_body() {
+        for encoding in ["gzip", "br", "deflate", "gzip, br"] {
+            assert!(encoded_response(encoding, Vec::new()).unwrap().body.is_empty());
+        }
+    }
+    
+    #[test]
+    fn test_decoded_size_limit() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+        
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&[0; 1000]).unwrap();
+        let compressed = encoder.finish().unwrap();
+        
+        assert_eq!(decode_body("gzip", compressed.clone(), 1000).unwrap().len(), 1000);
+        assert!(decode_body("gzip", compressed, 999).is_err());
+    }
+    
+    #[test]
+    fn test_unsupported_encoding() {
+        assert!(encoded_response("compress", b"data".to_vec()).is_err());
+        assert_eq!(encoded_response("identity", b"data".to_vec()).unwrap().body, b"data");
+    }
 }
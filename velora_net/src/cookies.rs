@@ -0,0 +1,92 @@
+//! Cookie storage for the Velora web engine
+
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::HeaderValue;
+use std::sync::RwLock;
+use url::Url;
+
+/// Cookie store shared between an HttpClient and its requests
+///
+/// Wraps reqwest's jar so that it can be cleared without rebuilding the client.
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    /// Cookies received so far
+    jar: RwLock<Jar>,
+}
+
+impl CookieJar {
+    /// Create an empty cookie jar
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Store a cookie as if it arrived in a `Set-Cookie` header from `url`
+    pub fn set_cookie(&self, url: &Url, header: &str) {
+        if let Ok(jar) = self.jar.read() {
+            jar.add_cookie_str(header, url);
+        }
+    }
+    
+    /// Get the `Cookie` header value that would be sent to `url`
+    pub fn cookie_header(&self, url: &Url) -> Option<String> {
+        self.cookies(url)
+            .and_then(|value| value.to_str().ok().map(str::to_string))
+    }
+    
+    /// Forget every stored cookie
+    pub fn clear(&self) {
+        if let Ok(mut jar) = self.jar.write() {
+            *jar = Jar::default();
+        }
+    }
+}
+
+impl CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        if let Ok(jar) = self.jar.read() {
+            jar.set_cookies(cookie_headers, url);
+        }
+    }
+    
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        self.jar.read().ok()?.cookies(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+    
+    #[test]
+    fn test_cookie_sent_back_to_same_domain() {
+        let jar = CookieJar::new();
+        jar.set_cookie(&url("https://example.com/login"), "session=abc123; Path=/");
+        
+        assert_eq!(jar.cookie_header(&url("https://example.com/account")), Some("session=abc123".to_string()));
+        assert_eq!(jar.cookie_header(&url("https://other.org/")), None);
+    }
+    
+    #[test]
+    fn test_set_cookie_headers_are_captured() {
+        let jar = CookieJar::new();
+        let headers = [HeaderValue::from_static("a=1"), HeaderValue::from_static("b=2")];
+        jar.set_cookies(&mut headers.iter(), &url("https://example.com/"));
+        
+        let header = jar.cookie_header(&url("https://example.com/")).unwrap();
+        assert!(header.contains("a=1"));
+        assert!(header.contains("b=2"));
+    }
+    
+    #[test]
+    fn test_clear_cookies() {
+        let jar = CookieJar::new();
+        jar.set_cookie(&url("https://example.com/"), "session=abc123");
+        jar.clear();
+        
+        assert_eq!(jar.cookie_header(&url("https://example.com/")), None);
+    }
+}
@@ -0,0 +1,112 @@
+//! `data:` URL decoding for the Velora web engine
+
+use velora_core::{VeloraResult, VeloraError, HttpStatus};
+use super::client::HttpResponse;
+use base64::Engine;
+use std::collections::HashMap;
+
+/// Media type assumed when a data URL doesn't name one
+const DEFAULT_MEDIA_TYPE: &str = "text/plain;charset=US-ASCII";
+
+/// The contents of a `data:[<mediatype>][;base64],<data>` URL
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataUrl {
+    /// Media type, including any parameters such as charset
+    pub media_type: String,
+    
+    /// Decoded payload
+    pub data: Vec<u8>,
+}
+
+impl DataUrl {
+    /// Whether a URL uses the `data:` scheme
+    pub fn is_data_url(url: &str) -> bool {
+        url.get(..5).is_some_and(|scheme| scheme.eq_ignore_ascii_case("data:"))
+    }
+    
+    /// Decode a data URL with a base64 or percent-encoded payload
+    pub fn parse(url: &str) -> VeloraResult<Self> {
+        if !Self::is_data_url(url) {
+            return Err(VeloraError::InvalidUrl(format!("Not a data URL: {}", url)));
+        }
+        
+        let (header, payload) = url[5..].split_once(',')
+            .ok_or_else(|| VeloraError::InvalidUrl("Data URL has no ',' before its payload".to_string()))?;
+        
+        let (media_type, is_base64) = match header.trim().strip_suffix(";base64") {
+            Some(media_type) => (media_type.trim(), true),
+            None => (header.trim(), false),
+        };
+        let media_type = if media_type.is_empty() {
+            DEFAULT_MEDIA_TYPE.to_string()
+        } else if media_type.starts_with(';') {
+            // Parameters without a type, e.g. "data:;charset=utf-8,..."
+            format!("text/plain{}", media_type)
+        } else {
+            media_type.to_string()
+        };
+        
+        let bytes = urlencoding::decode_binary(payload.as_bytes()).into_owned();
+        let data = if is_base64 {
+            let compact: Vec<u8> = bytes.into_iter().filter(|b| !b.is_ascii_whitespace()).collect();
+            base64::engine::general_purpose::STANDARD.decode(compact)
+                .map_err(|e| VeloraError::InvalidUrl(format!("Invalid base64 in data URL: {}", e)))?
+        } else {
+            bytes
+        };
+        
+        Ok(Self { media_type, data })
+    }
+    
+    /// Present the decoded payload as a successful response
+    pub fn into_response(self) -> HttpResponse {
+        let mut headers = HashMap::new();
+        headers.insert("content-type".to_string(), self.media_type);
+        HttpResponse::new(HttpStatus::ok(), headers, self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// A 1x1 transparent PNG
+    const PNG_BASE64: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==";
+    
+    #[test]
+    fn test_base64_png() {
+        let data_url = DataUrl::parse(&format!("data:image/png;base64,{}", PNG_BASE64)).unwrap();
+        
+        assert_eq!(data_url.media_type, "image/png");
+        assert_eq!(&data_url.data[..8], b"\x89PNG\r\n\x1a\n");
+    }
+    
+    #[test]
+    fn test_plain_text() {
+        let data_url = DataUrl::parse("data:text/plain,Hello").unwrap();
+        assert_eq!(data_url.media_type, "text/plain");
+        assert_eq!(data_url.data, b"Hello");
+        
+        let response = data_url.into_response();
+        assert_eq!(response.text().unwrap(), "Hello");
+        assert_eq!(response.content_type(), "text/plain");
+    }
+    
+    #[test]
+    fn test_percent_encoded_and_default_type() {
+        let data_url = DataUrl::parse("data:,Hello%2C%20World%21").unwrap();
+        assert_eq!(data_url.media_type, "text/plain;charset=US-ASCII");
+        assert_eq!(data_url.data, b"Hello, World!");
+        
+        let data_url = DataUrl::parse("data:;charset=utf-8,%E2%9C%93").unwrap();
+        assert_eq!(data_url.media_type, "text/plain;charset=utf-8");
+        assert_eq!(String::from_utf8(data_url.data).unwrap(), "✓");
+    }
+    
+    #[test]
+    fn test_invalid_data_urls() {
+        assert!(DataUrl::parse("data:text/plain").is_err());
+        assert!(DataUrl::parse("data:;base64,not base64!").is_err());
+        assert!(DataUrl::parse("https://example.com").is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! `file:` URL loading for the Velora web engine
+
+use velora_core::{VeloraResult, VeloraError, HttpStatus};
+use super::client::HttpResponse;
+use std::collections::HashMap;
+use std::path::Path;
+use url::Url;
+
+/// Guess a Content-Type from a file extension
+pub fn content_type_for_path(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    let content_type = match extension.as_str() {
+        "html" | "htm" => "text/html",
+        "xhtml" => "application/xhtml+xml",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "txt" => "text/plain",
+        "json" => "application/json",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        _ => return None,
+    };
+    Some(content_type)
+}
+
+/// Read the file a `file:` URL points to, optionally confined to a root directory
+///
+/// Files without a known extension get no Content-Type, so the response sniffs one.
+pub async fn load_file_url(url: &Url, root: Option<&Path>) -> VeloraResult<HttpResponse> {
+    let path = url.to_file_path()
+        .map_err(|_| VeloraError::InvalidUrl(format!("Not a local file URL: {}", url)))?;
+    
+    // Resolve symlinks and ".." before comparing against the root
+    let path = tokio::fs::canonicalize(&path).await
+        .map_err(|_| VeloraError::ResourceNotFound(path.display().to_string()))?;
+    if let Some(root) = root {
+        let root = tokio::fs::canonicalize(root).await?;
+        if !path.starts_with(&root) {
+            return Err(VeloraError::InvalidUrl(
+                format!("{} is outside the allowed root {}", path.display(), root.display())
+            ));
+        }
+    }
+    
+    let body = tokio::fs::read(&path).await?;
+    
+    let mut headers = HashMap::new();
+    if let Some(content_type) = content_type_for_path(&path) {
+        headers.insert("content-type".to_string(), content_type.to_string());
+    }
+    Ok(HttpResponse::new(HttpStatus::ok(), headers, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    
+    /// A fresh directory under the system temp dir
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("velora_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    
+    #[test]
+    fn test_content_type_for_path() {
+        assert_eq!(content_type_for_path(Path::new("index.HTML")), Some("text/html"));
+        assert_eq!(content_type_for_path(Path::new("logo.png")), Some("image/png"));
+        assert_eq!(content_type_for_path(Path::new("README")), None);
+    }
+    
+    #[tokio::test]
+    async fn test_load_html_file() {
+        let dir = temp_dir("file_url");
+        let path = dir.join("page.html");
+        std::fs::write(&path, "<p>Local</p>").unwrap();
+        
+        let url = Url::from_file_path(&path).unwrap();
+        let response = load_file_url(&url, Some(&dir)).await.unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        
+        assert_eq!(response.content_type(), "text/html");
+        assert_eq!(response.text().unwrap(), "<p>Local</p>");
+    }
+    
+    #[tokio::test]
+    async fn test_reject_outside_root() {
+        let root = temp_dir("file_root");
+        let outside = temp_dir("file_outside");
+        let path = outside.join("secret.txt");
+        std::fs::write(&path, "secret").unwrap();
+        
+        // URL parsing already collapses "..", so this lands outside the root
+        let root_url = Url::from_directory_path(&root).unwrap();
+        let url = root_url.join(&format!("../{}/secret.txt", outside.file_name().unwrap().to_str().unwrap())).unwrap();
+        let result = load_file_url(&url, Some(&root)).await;
+        let unrestricted = load_file_url(&url, None).await;
+        
+        std::fs::remove_dir_all(&root).unwrap();
+        std::fs::remove_dir_all(&outside).unwrap();
+        assert!(matches!(result, Err(VeloraError::InvalidUrl(_))));
+        assert_eq!(unrestricted.unwrap().text().unwrap(), "secret");
+    }
+    
+    #[tokio::test]
+    async fn test_missing_file() {
+        let url = Url::parse("file:///velora/does/not/exist.html").unwrap();
+        let result = load_file_url(&url, None).await;
+        assert!(matches!(result, Err(VeloraError::ResourceNotFound(_))));
+    }
+}
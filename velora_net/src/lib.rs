@@ -6,14 +6,24 @@
 pub mod client;
 pub mod resource;
 pub mod cache;
+pub mod cookies;
+pub mod data_url;
+pub mod file_url;
+pub mod network_log;
 
-pub use client::HttpClient;
-pub use resource::ResourceLoader;
+pub use client::{HttpClient, DownloadEvent, PendingResponse, UserAgentPreset};
+pub use resource::{ResourceLoader, ResourceKind};
 pub use cache::ResourceCache;
+pub use cookies::CookieJar;
+pub use data_url::DataUrl;
+pub use network_log::{NetworkLog, NetworkLogEntry, DEFAULT_LOG_CAPACITY};
 
 /// Re-export commonly used items for convenience
 pub mod prelude {
-    pub use super::client::HttpClient;
-    pub use super::resource::ResourceLoader;
+    pub use super::client::{HttpClient, DownloadEvent, UserAgentPreset};
+    pub use super::resource::{ResourceLoader, ResourceKind};
     pub use super::cache::ResourceCache;
+    pub use super::cookies::CookieJar;
+    pub use super::data_url::DataUrl;
+    pub use super::network_log::{NetworkLog, NetworkLogEntry};
 }
@@ -0,0 +1,146 @@
+//! Request logging for the Velora web engine
+
+use std::collections::VecDeque;
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
+
+/// Requests a log keeps by default before dropping the oldest
+pub const DEFAULT_LOG_CAPACITY: usize = 500;
+
+/// One finished request, as shown in a network panel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkLogEntry {
+    /// HTTP method
+    pub method: String,
+    
+    /// Requested URL
+    pub url: String,
+    
+    /// Response status code, or None if the request failed
+    pub status: Option<u16>,
+    
+    /// Size of the response body
+    pub bytes: usize,
+    
+    /// When the request started
+    pub started: SystemTime,
+    
+    /// Time from starting the request to having the whole body
+    pub duration: Duration,
+}
+
+/// Request log shared between an HttpClient and its clones
+///
+/// Only the most recent requests are kept, so a long session cannot grow it without bound.
+#[derive(Debug)]
+pub struct NetworkLog {
+    /// Finished requests, oldest first
+    entries: RwLock<VecDeque<NetworkLogEntry>>,
+    
+    /// Most requests kept at once
+    capacity: usize,
+}
+
+impl Default for NetworkLog {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_LOG_CAPACITY)
+    }
+}
+
+impl NetworkLog {
+    /// Create an empty log
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Create an empty log that keeps at most `capacity` requests
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: RwLock::new(VecDeque::with_capacity(capacity.min(DEFAULT_LOG_CAPACITY))),
+            capacity: capacity.max(1),
+        }
+    }
+    
+    /// Add a finished request, dropping the oldest if the log is full
+    pub fn record(&self, entry: NetworkLogEntry) {
+        if let Ok(mut entries) = self.entries.write() {
+            if entries.len() >= self.capacity {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        }
+    }
+    
+    /// Get a copy of the logged requests, oldest first
+    pub fn entries(&self) -> Vec<NetworkLogEntry> {
+        self.with_entries(|entries| entries.iter().cloned().collect())
+    }
+    
+    /// Look at the logged requests, oldest first, without copying them
+    ///
+    /// New requests wait to be recorded until `f` returns, so keep it short.
+    pub fn with_entries<R>(&self, f: impl FnOnce(&VecDeque<NetworkLogEntry>) -> R) -> R {
+        match self.entries.read() {
+            Ok(entries) => f(&entries),
+            Err(_) => f(&VecDeque::new()),
+        }
+    }
+    
+    /// Get the number of logged requests
+    pub fn len(&self) -> usize {
+        self.with_entries(VecDeque::len)
+    }
+    
+    /// Whether no requests are logged
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// Forget every logged request
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.write() {
+            entries.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn entry(url: &str) -> NetworkLogEntry {
+        NetworkLogEntry {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            status: Some(200),
+            bytes: 10,
+            started: SystemTime::now(),
+            duration: Duration::from_millis(5),
+        }
+    }
+    
+    #[test]
+    fn test_record_keeps_order_until_cleared() {
+        let log = NetworkLog::new();
+        log.record(entry("https://example.com/"));
+        log.record(entry("https://example.com/style.css"));
+        
+        let urls: Vec<String> = log.entries().into_iter().map(|entry| entry.url).collect();
+        assert_eq!(urls, vec!["https://example.com/", "https://example.com/style.css"]);
+        
+        log.clear();
+        assert!(log.entries().is_empty());
+    }
+    
+    #[test]
+    fn test_full_log_drops_oldest() {
+        let log = NetworkLog::with_capacity(2);
+        for url in ["https://example.com/a", "https://example.com/b", "https://example.com/c"] {
+            log.record(entry(url));
+        }
+        
+        assert_eq!(log.len(), 2);
+        let urls: Vec<String> = log.with_entries(|entries| entries.iter().map(|entry| entry.url.clone()).collect());
+        assert_eq!(urls, vec!["https://example.com/b", "https://example.com/c"]);
+    }
+}
@@ -1,7 +1,39 @@
 //! Resource loading for the Velora web engine
 
-use velora_core::VeloraResult;
-use super::client::HttpClient;
+use velora_core::{VeloraResult, VeloraError};
+use velora_core::error::NetworkError;
+use super::client::{HttpClient, HttpResponse};
+
+/// Broad kind of a resource, judged from its Content-Type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceKind {
+    Html,
+    Text,
+    Stylesheet,
+    Script,
+    Image,
+    Other,
+}
+
+impl ResourceKind {
+    /// Classify a Content-Type value, ignoring parameters such as charset
+    pub fn from_content_type(content_type: &str) -> Self {
+        let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "text/html" | "application/xhtml+xml" => ResourceKind::Html,
+            "text/css" => ResourceKind::Stylesheet,
+            "text/javascript" | "application/javascript" | "application/ecmascript" => ResourceKind::Script,
+            "text/plain" => ResourceKind::Text,
+            _ if mime.starts_with("image/") => ResourceKind::Image,
+            _ => ResourceKind::Other,
+        }
+    }
+    
+    /// Whether a page can be rendered from this kind of resource
+    pub fn is_document(&self) -> bool {
+        matches!(self, ResourceKind::Html | ResourceKind::Text)
+    }
+}
 
 /// Resource loader for fetching web resources
 #[derive(Debug)]
@@ -11,6 +43,9 @@ pub struct ResourceLoader {
     
     /// Resource cache
     cache: std::collections::HashMap<String, CachedResource>,
+    
+    /// User-Agent sent instead of the client's own, if set
+    user_agent: Option<String>,
 }
 
 /// Cached resource
@@ -26,39 +61,83 @@ pub struct CachedResource {
     pub timestamp: std::time::SystemTime,
 }
 
+impl CachedResource {
+    /// Create a resource from a response, sniffing the type if no header was sent
+    pub fn from_response(response: HttpResponse) -> Self {
+        Self {
+            content_type: response.content_type(),
+            data: response.body,
+            timestamp: std::time::SystemTime::now(),
+        }
+    }
+    
+    /// Get the kind of content this resource holds
+    pub fn kind(&self) -> ResourceKind {
+        ResourceKind::from_content_type(&self.content_type)
+    }
+    
+    /// Check that the resource can be rendered as a page
+    pub fn expect_document(self, url: &str) -> VeloraResult<Self> {
+        if !self.kind().is_document() {
+            return Err(VeloraError::Network(NetworkError::UnexpectedContentType(
+                format!("{} is {}, not a document", url, self.content_type)
+            )));
+        }
+        Ok(self)
+    }
+}
+
 impl ResourceLoader {
     /// Create a new resource loader
     pub fn new() -> VeloraResult<Self> {
         Ok(Self {
             http_client: HttpClient::new()?,
             cache: std::collections::HashMap::new(),
+            user_agent: None,
         })
     }
     
+    /// Create a resource loader that shares an existing client's connections
+    pub fn with_client(http_client: HttpClient) -> Self {
+        Self {
+            http_client,
+            cache: std::collections::HashMap::new(),
+            user_agent: None,
+        }
+    }
+    
+    /// Send `user_agent` instead of the client's configured one, so subresources are
+    /// requested as the page that needs them was
+    pub fn set_user_agent(&mut self, user_agent: Option<&str>) {
+        self.user_agent = user_agent.map(str::to_string);
+    }
+    
     /// Load a resource from a URL
     pub async fn load_resource(&mut self, url: &str) -> VeloraResult<CachedResource> {
-        // TODO: Implement actual resource loading
-        // For now, create a mock resource and use the fields to avoid warnings
-        
         // Check cache first
         if let Some(cached) = self.cache.get(url) {
             return Ok(cached.clone());
         }
         
-        // Mock HTTP request using the client
-        let _response = self.http_client.get(url).await?;
+        let response = self.http_client.get_with_user_agent(url, self.user_agent.as_deref()).await?;
+        if !response.status.is_success() {
+            return Err(VeloraError::Network(NetworkError::RequestFailed(
+                format!("{} returned {} {}", url, response.status.code, response.status.reason)
+            )));
+        }
         
-        let resource = CachedResource {
-            data: Vec::new(),
-            content_type: "text/plain".to_string(),
-            timestamp: std::time::SystemTime::now(),
-        };
+        let resource = CachedResource::from_response(response);
         
         // Store in cache
         self.cache.insert(url.to_string(), resource.clone());
         
         Ok(resource)
     }
+    
+    /// Load a resource that is expected to be a page, rejecting other content types
+    pub async fn load_document(&mut self, url: &str) -> VeloraResult<CachedResource> {
+        self.load_resource(url).await?.expect_document(url)
+    }
 }
 
 #[cfg(test)]
@@ -78,8 +157,42 @@ mod tests {
         assert!(resource.is_ok());
         
         let resource = resource.unwrap();
-        assert_eq!(resource.content_type, "text/plain");
-        assert!(resource.data.is_empty());
+        assert!(resource.content_type.starts_with("text/html"));
+        assert!(!resource.data.is_empty());
+    }
+    
+    fn response(content_type: Option<&str>, body: &[u8]) -> HttpResponse {
+        let mut headers = std::collections::HashMap::new();
+        if let Some(content_type) = content_type {
+            headers.insert("content-type".to_string(), content_type.to_string());
+        }
+        HttpResponse::new(velora_core::HttpStatus::ok(), headers, body.to_vec())
+    }
+    
+    #[test]
+    fn test_resource_kind_classification() {
+        assert_eq!(ResourceKind::from_content_type("text/html; charset=utf-8"), ResourceKind::Html);
+        assert_eq!(ResourceKind::from_content_type("TEXT/CSS"), ResourceKind::Stylesheet);
+        assert_eq!(ResourceKind::from_content_type("image/png"), ResourceKind::Image);
+        assert_eq!(ResourceKind::from_content_type("application/pdf"), ResourceKind::Other);
+    }
+    
+    #[test]
+    fn test_pdf_rejected_for_document() {
+        let resource = CachedResource::from_response(response(Some("application/pdf"), b"%PDF-1.7"));
+        let result = resource.expect_document("https://example.com/file.pdf");
+        
+        assert!(matches!(result, Err(VeloraError::Network(NetworkError::UnexpectedContentType(_)))));
+    }
+    
+    #[test]
+    fn test_html_sniffed_without_header() {
+        let resource = CachedResource::from_response(response(None, b"<!DOCTYPE html><html></html>"));
+        assert_eq!(resource.content_type, "text/html");
+        assert!(resource.expect_document("https://example.com/").is_ok());
+        
+        let resource = CachedResource::from_response(response(None, b"\x89PNG\r\n"));
+        assert!(resource.expect_document("https://example.com/").is_err());
     }
     
     #[test]
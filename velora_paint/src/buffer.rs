@@ -0,0 +1,173 @@
+//! RGBA pixel buffers for the Velora web engine
+
+use velora_core::{Color, Rect, VeloraResult, VeloraError};
+use velora_core::error::PaintError;
+use std::io::Cursor;
+use std::ops::Range;
+
+/// A software framebuffer of 8-bit RGBA pixels in row-major order
+#[derive(Debug, Clone, PartialEq)]
+pub struct PixelBuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl PixelBuffer {
+    /// Create a buffer filled with transparent pixels
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+    
+    /// Get the buffer width in pixels
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    
+    /// Get the buffer height in pixels
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    
+    /// Get the raw RGBA bytes
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+    
+    /// Get the color of a pixel, or None if it is out of bounds
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<Color> {
+        let offset = self.offset(x, y)?;
+        let p = &self.pixels[offset..offset + 4];
+        Some(Color::rgba(p[0], p[1], p[2], p[3]))
+    }
+    
+    /// Overwrite a pixel; out-of-bounds writes are ignored
+    pub fn set_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if let Some(offset) = self.offset(x, y) {
+            self.pixels[offset..offset + 4].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+    
+    /// Composite a color over a pixel using source-over blending
+    pub fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if let Some(dst) = self.get_pixel(x, y) {
+            self.set_pixel(x, y, color.blend_over(dst));
+        }
+    }
+    
+    /// Blend a color over every pixel whose centre lies inside the rect
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let (xs, ys) = self.covered(rect);
+        for y in ys {
+            for x in xs.clone() {
+                self.blend_pixel(x, y, color);
+            }
+        }
+    }
+    
+    /// Overwrite every pixel whose centre lies inside the rect
+    pub fn clear_rect(&mut self, rect: Rect, color: Color) {
+        let (xs, ys) = self.covered(rect);
+        for y in ys {
+            for x in xs.clone() {
+                self.set_pixel(x, y, color);
+            }
+        }
+    }
+    
+    /// Get the pixels packed as 0xAARRGGBB, row by row
+    pub fn to_argb(&self) -> Vec<u32> {
+        self.pixels.chunks_exact(4)
+            .map(|p| Color::rgba(p[0], p[1], p[2], p[3]).to_u32())
+            .collect()
+    }
+    
+    /// Encode the buffer as a PNG file
+    pub fn encode_png(&self) -> VeloraResult<Vec<u8>> {
+        let image = image::RgbaImage::from_raw(self.width, self.height, self.pixels.clone())
+            .ok_or_else(|| VeloraError::Paint(PaintError::InvalidOperation("Pixel buffer has the wrong length".to_string())))?;
+        
+        let mut png = Cursor::new(Vec::new());
+        image.write_to(&mut png, image::ImageFormat::Png)
+            .map_err(|e| VeloraError::Paint(PaintError::InvalidOperation(format!("PNG encoding failed: {}", e))))?;
+        Ok(png.into_inner())
+    }
+    
+    /// Overwrite every pixel with a color
+    pub fn clear(&mut self, color: Color) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+    
+    /// Columns and rows of the pixels whose centre lies inside the rect, clamped to the buffer
+    fn covered(&self, rect: Rect) -> (Range<u32>, Range<u32>) {
+        let x_start = rect.x.max(0.0).round() as u32;
+        let y_start = rect.y.max(0.0).round() as u32;
+        let x_end = (rect.x + rect.width).min(self.width as f32).round().max(0.0) as u32;
+        let y_end = (rect.y + rect.height).min(self.height as f32).round().max(0.0) as u32;
+        (x_start..x_end, y_start..y_end)
+    }
+    
+    /// Byte offset of a pixel, or None if it is out of bounds
+    fn offset(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| (y as usize * self.width as usize + x as usize) * 4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_pixel_access() {
+        let mut buffer = PixelBuffer::new(4, 3);
+        assert_eq!(buffer.pixels().len(), 4 * 3 * 4);
+        assert_eq!(buffer.get_pixel(0, 0), Some(Color::transparent()));
+        
+        buffer.set_pixel(3, 2, Color::rgb(1, 2, 3));
+        assert_eq!(buffer.get_pixel(3, 2), Some(Color::rgb(1, 2, 3)));
+        
+        // Out of bounds access is ignored
+        buffer.set_pixel(4, 0, Color::white());
+        assert_eq!(buffer.get_pixel(4, 0), None);
+    }
+    
+    #[test]
+    fn test_blend_pixel() {
+        let mut buffer = PixelBuffer::new(1, 1);
+        buffer.clear(Color::white());
+        buffer.blend_pixel(0, 0, Color::rgba(0, 0, 0, 128));
+        
+        let blended = buffer.get_pixel(0, 0).unwrap();
+        assert_eq!(blended.a, 255);
+        assert!((126..=128).contains(&blended.r));
+    }
+    
+    #[test]
+    fn test_fill_rect_clamps_to_buffer() {
+        let mut buffer = PixelBuffer::new(4, 4);
+        buffer.fill_rect(Rect::new(2.0, -1.0, 10.0, 2.0), Color::black());
+        
+        assert_eq!(buffer.get_pixel(2, 0), Some(Color::black()));
+        assert_eq!(buffer.get_pixel(3, 0), Some(Color::black()));
+        assert_eq!(buffer.get_pixel(1, 0), Some(Color::transparent()));
+        assert_eq!(buffer.get_pixel(2, 1), Some(Color::transparent()));
+    }
+    
+    #[test]
+    fn test_argb_and_png_export() {
+        let mut buffer = PixelBuffer::new(2, 1);
+        buffer.set_pixel(1, 0, Color::rgba(0x11, 0x22, 0x33, 0x44));
+        assert_eq!(buffer.to_argb(), vec![0, 0x44112233]);
+        
+        let png = buffer.encode_png().unwrap();
+        let decoded = crate::ImageRenderer::new().unwrap().decode_image(&png).unwrap();
+        assert_eq!((decoded.width, decoded.height), (2, 1));
+        assert_eq!(decoded.pixels, buffer.pixels());
+    }
+}
@@ -0,0 +1,66 @@
+//! Paint invalidation for the Velora web engine
+
+use velora_core::Rect;
+
+/// Area of the page that needs repainting, accumulated between frames
+///
+/// Every invalidated rect is merged into one bounding rect, so a frame repaints a single
+/// area no matter how many changes caused it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DirtyRegion {
+    /// Union of the rects added since the last `take`
+    bounds: Option<Rect>,
+}
+
+impl DirtyRegion {
+    /// Create an empty region
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Mark a rect as needing a repaint; empty rects are ignored
+    pub fn add(&mut self, rect: Rect) {
+        if rect.width <= 0.0 || rect.height <= 0.0 {
+            return;
+        }
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => bounds.union(&rect),
+            None => rect,
+        });
+    }
+    
+    /// Bounding rect of everything marked so far
+    pub fn bounds(&self) -> Option<Rect> {
+        self.bounds
+    }
+    
+    /// Whether nothing needs repainting
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_none()
+    }
+    
+    /// Get the bounding dirty rect and reset the region
+    pub fn take(&mut self) -> Option<Rect> {
+        self.bounds.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_add_unions_and_take_clears() {
+        let mut region = DirtyRegion::new();
+        assert!(region.is_empty());
+        
+        region.add(Rect::new(10.0, 10.0, 20.0, 5.0));
+        region.add(Rect::new(50.0, 40.0, 10.0, 10.0));
+        region.add(Rect::new(0.0, 0.0, 0.0, 100.0));
+        assert_eq!(region.bounds(), Some(Rect::new(10.0, 10.0, 50.0, 40.0)));
+        
+        assert_eq!(region.take(), Some(Rect::new(10.0, 10.0, 50.0, 40.0)));
+        assert!(region.is_empty());
+        assert_eq!(region.take(), None);
+    }
+}
@@ -0,0 +1,302 @@
+//! Display lists for the Velora web engine
+
+use std::collections::HashMap;
+use velora_core::{NodeId, Rect, Color};
+use velora_layout::{LayoutTree, BoxModel, PaintLayer};
+use crate::PixelBuffer;
+
+/// A single paint operation, in the order it must be drawn
+#[derive(Debug, Clone, PartialEq)]
+pub enum DrawCommand {
+    /// Fill a rectangle with a solid color
+    FillRect { node_id: NodeId, rect: Rect, color: Color },
+    /// Restrict the following commands to a rect, intersected with any active clip
+    PushClip(Rect),
+    /// Restore the clip that was active before the matching `PushClip`
+    PopClip,
+}
+
+/// An ordered list of draw commands for one frame
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayList {
+    commands: Vec<DrawCommand>,
+}
+
+impl DisplayList {
+    /// Create an empty display list
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Append a command to the end of the list
+    pub fn push(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+    
+    /// Get the commands in paint order
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+    
+    /// Get the number of commands
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+    
+    /// Check if the list has no commands
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+    
+    /// Draw the commands into a buffer, honoring the clip stack
+    pub fn rasterize(&self, buffer: &mut PixelBuffer) {
+        // None means an earlier clip left nothing visible
+        let mut clips: Vec<Option<Rect>> = Vec::new();
+        
+        for command in &self.commands {
+            let clip = clips.last().copied();
+            match command {
+                DrawCommand::FillRect { rect, color, .. } => {
+                    let visible = match clip {
+                        Some(clip) => clip.and_then(|clip| clip.intersection(rect)),
+                        None => Some(*rect),
+                    };
+                    if let Some(visible) = visible {
+                        buffer.fill_rect(visible, *color);
+                    }
+                }
+                DrawCommand::PushClip(rect) => {
+                    let nested = match clip {
+                        Some(clip) => clip.and_then(|clip| clip.intersection(rect)),
+                        None => Some(*rect),
+                    };
+                    clips.push(nested);
+                }
+                DrawCommand::PopClip => {
+                    clips.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Builds a display list from a laid out tree, ordering paint by stacking context
+#[derive(Debug, Default)]
+pub struct DisplayListBuilder {
+    /// Background colors of the nodes that paint one
+    backgrounds: HashMap<NodeId, Color>,
+}
+
+impl DisplayListBuilder {
+    /// Create a new display list builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Set the background color painted behind a node's content
+    pub fn set_background(&mut self, node_id: NodeId, color: Color) {
+        self.backgrounds.insert(node_id, color);
+    }
+    
+    /// Build the display list for a tree using the rects from `LayoutTree::calculate_layout`
+    pub fn build(&self, tree: &LayoutTree, layouts: &HashMap<NodeId, Rect>) -> DisplayList {
+        let mut list = DisplayList::new();
+        if let Some(root_id) = tree.get_root() {
+            list.commands = self.paint_stacking_context(tree, layouts, root_id);
+        }
+        list
+    }
+    
+    /// Paint a node that establishes a stacking context, along with everything it contains
+    fn paint_stacking_context(&self, tree: &LayoutTree, layouts: &HashMap<NodeId, Rect>, node_id: NodeId) -> Vec<DrawCommand> {
+        let mut commands = Vec::new();
+        self.paint_node(layouts, node_id, &mut commands);
+        
+        let Some(node) = tree.get_node(node_id) else {
+            return commands;
+        };
+        
+        let clip = node.overflow.clips().then(|| layouts.get(&node_id)).flatten()
+            .map(|&content| BoxModel { content, ..node.box_model.clone() }.padding_box());
+        if let Some(clip) = clip {
+            commands.push(DrawCommand::PushClip(clip));
+        }
+        
+        // Hit testing walks the same order backwards, so clicks land on what is drawn on top
+        for layer in tree.paint_order(node_id) {
+            match layer {
+                PaintLayer::Node(id) => self.paint_node(layouts, id, &mut commands),
+                PaintLayer::Context(id) => commands.extend(self.paint_stacking_context(tree, layouts, id)),
+            }
+        }
+        
+        if clip.is_some() {
+            commands.push(DrawCommand::PopClip);
+        }
+        commands
+    }
+    
+    /// Emit the commands for a single node's own content
+    fn paint_node(&self, layouts: &HashMap<NodeId, Rect>, node_id: NodeId, commands: &mut Vec<DrawCommand>) {
+        if let (Some(&color), Some(&rect)) = (self.backgrounds.get(&node_id), layouts.get(&node_id)) {
+            commands.push(DrawCommand::FillRect { node_id, rect, color });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use velora_core::{Size, Overflow, Position};
+    use velora_layout::{LayoutNode, BoxModel, BoxOffsets, SpecifiedLengths};
+    
+    fn node(id: u64, parent_id: Option<NodeId>, child_ids: Vec<NodeId>, position: Position, z_index: Option<i32>) -> LayoutNode {
+        LayoutNode {
+            node_id: NodeId(id),
+            box_model: BoxModel::new(Rect::zero()),
+            flexbox: None,
+            grid: None,
+            parent_id,
+            child_ids,
+            position,
+            offsets: BoxOffsets { top: Some(0.0), left: Some(0.0), ..BoxOffsets::default() },
+            z_index,
+            overflow: Overflow::Visible,
+            lengths: SpecifiedLengths::default(),
+            intrinsic_size: None,
+        }
+    }
+    
+    fn paint_order(list: &DisplayList) -> Vec<u64> {
+        list.commands().iter().filter_map(|command| match command {
+            DrawCommand::FillRect { node_id, .. } => Some(node_id.0),
+            _ => None,
+        }).collect()
+    }
+    
+    fn build(tree: &LayoutTree) -> DisplayList {
+        let layouts = tree.calculate_layout(Size::new(100.0, 100.0)).unwrap();
+        let mut builder = DisplayListBuilder::new();
+        for id in 1..=4 {
+            builder.set_background(NodeId(id), Color::rgb(id as u8, 0, 0));
+        }
+        builder.build(tree, &layouts)
+    }
+    
+    #[test]
+    fn test_higher_z_index_paints_later() {
+        // Node 2 comes first in the DOM but sits above node 3
+        let mut tree = LayoutTree::new();
+        tree.add_node(node(1, None, vec![NodeId(2), NodeId(3)], Position::Relative, None)).unwrap();
+        tree.add_node(node(2, Some(NodeId(1)), vec![], Position::Absolute, Some(2))).unwrap();
+        tree.add_node(node(3, Some(NodeId(1)), vec![], Position::Absolute, Some(1))).unwrap();
+        
+        assert_eq!(paint_order(&build(&tree)), vec![1, 3, 2]);
+    }
+    
+    #[test]
+    fn test_auto_z_index_uses_document_order() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(node(1, None, vec![NodeId(2), NodeId(3)], Position::Relative, None)).unwrap();
+        tree.add_node(node(2, Some(NodeId(1)), vec![], Position::Absolute, None)).unwrap();
+        tree.add_node(node(3, Some(NodeId(1)), vec![], Position::Absolute, None)).unwrap();
+        
+        assert_eq!(paint_order(&build(&tree)), vec![1, 2, 3]);
+    }
+    
+    #[test]
+    fn test_negative_z_index_paints_below_flow() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(node(1, None, vec![NodeId(2), NodeId(3)], Position::Relative, None)).unwrap();
+        tree.add_node(node(2, Some(NodeId(1)), vec![], Position::Static, None)).unwrap();
+        tree.add_node(node(3, Some(NodeId(1)), vec![], Position::Absolute, Some(-1))).unwrap();
+        
+        assert_eq!(paint_order(&build(&tree)), vec![1, 3, 2]);
+    }
+    
+    #[test]
+    fn test_stacking_context_paints_as_a_unit() {
+        // Node 4 has a huge z-index but is confined to its parent's context
+        let mut tree = LayoutTree::new();
+        tree.add_node(node(1, None, vec![NodeId(2), NodeId(3)], Position::Relative, None)).unwrap();
+        tree.add_node(node(2, Some(NodeId(1)), vec![NodeId(4)], Position::Absolute, Some(1))).unwrap();
+        tree.add_node(node(3, Some(NodeId(1)), vec![], Position::Absolute, Some(2))).unwrap();
+        tree.add_node(node(4, Some(NodeId(2)), vec![], Position::Absolute, Some(100))).unwrap();
+        
+        assert_eq!(paint_order(&build(&tree)), vec![1, 2, 4, 3]);
+    }
+    
+    #[test]
+    fn test_z_index_ignored_on_static_nodes() {
+        let mut tree = LayoutTree::new();
+        tree.add_node(node(1, None, vec![NodeId(2), NodeId(3)], Position::Relative, None)).unwrap();
+        tree.add_node(node(2, Some(NodeId(1)), vec![], Position::Static, Some(5))).unwrap();
+        tree.add_node(node(3, Some(NodeId(1)), vec![], Position::Static, None)).unwrap();
+        
+        assert_eq!(paint_order(&build(&tree)), vec![1, 2, 3]);
+    }
+    
+    #[test]
+    fn test_overflow_hidden_emits_clip() {
+        let mut tree = LayoutTree::new();
+        let mut root = node(1, None, vec![NodeId(2)], Position::Static, None);
+        root.overflow = Overflow::Hidden;
+        root.box_model.set_padding(Rect::new(5.0, 5.0, 10.0, 10.0));
+        tree.add_node(root).unwrap();
+        tree.add_node(node(2, Some(NodeId(1)), vec![], Position::Static, None)).unwrap();
+        
+        let list = build(&tree);
+        assert_eq!(paint_order(&list), vec![1, 2]);
+        assert_eq!(list.commands()[1], DrawCommand::PushClip(Rect::new(0.0, 0.0, 100.0, 100.0)));
+        assert_eq!(list.commands()[3], DrawCommand::PopClip);
+    }
+    
+    #[test]
+    fn test_rasterize_respects_clip() {
+        let mut list = DisplayList::new();
+        list.push(DrawCommand::PushClip(Rect::new(2.0, 2.0, 4.0, 4.0)));
+        list.push(DrawCommand::FillRect { node_id: NodeId(1), rect: Rect::new(0.0, 0.0, 8.0, 8.0), color: Color::black() });
+        list.push(DrawCommand::PopClip);
+        
+        let mut buffer = PixelBuffer::new(8, 8);
+        list.rasterize(&mut buffer);
+        
+        assert_eq!(buffer.get_pixel(2, 2), Some(Color::black()));
+        assert_eq!(buffer.get_pixel(5, 5), Some(Color::black()));
+        assert_eq!(buffer.get_pixel(1, 1), Some(Color::transparent()));
+        assert_eq!(buffer.get_pixel(6, 3), Some(Color::transparent()));
+    }
+    
+    #[test]
+    fn test_nested_clips_intersect() {
+        let mut list = DisplayList::new();
+        list.push(DrawCommand::PushClip(Rect::new(0.0, 0.0, 4.0, 4.0)));
+        list.push(DrawCommand::PushClip(Rect::new(2.0, 2.0, 4.0, 4.0)));
+        list.push(DrawCommand::FillRect { node_id: NodeId(1), rect: Rect::new(0.0, 0.0, 8.0, 8.0), color: Color::black() });
+        list.push(DrawCommand::PopClip);
+        // After popping, only the outer clip applies
+        list.push(DrawCommand::FillRect { node_id: NodeId(2), rect: Rect::new(0.0, 0.0, 1.0, 8.0), color: Color::white() });
+        list.push(DrawCommand::PopClip);
+        
+        let mut buffer = PixelBuffer::new(8, 8);
+        list.rasterize(&mut buffer);
+        
+        assert_eq!(buffer.get_pixel(3, 3), Some(Color::black()));
+        assert_eq!(buffer.get_pixel(1, 1), Some(Color::transparent()));
+        assert_eq!(buffer.get_pixel(5, 5), Some(Color::transparent()));
+        assert_eq!(buffer.get_pixel(0, 3), Some(Color::white()));
+        assert_eq!(buffer.get_pixel(0, 4), Some(Color::transparent()));
+    }
+    
+    #[test]
+    fn test_disjoint_nested_clips_hide_everything() {
+        let mut list = DisplayList::new();
+        list.push(DrawCommand::PushClip(Rect::new(0.0, 0.0, 2.0, 2.0)));
+        list.push(DrawCommand::PushClip(Rect::new(4.0, 4.0, 2.0, 2.0)));
+        list.push(DrawCommand::FillRect { node_id: NodeId(1), rect: Rect::new(0.0, 0.0, 8.0, 8.0), color: Color::black() });
+        
+        let mut buffer = PixelBuffer::new(8, 8);
+        list.rasterize(&mut buffer);
+        assert!(buffer.pixels().iter().all(|&byte| byte == 0));
+    }
+}
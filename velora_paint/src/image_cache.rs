@@ -0,0 +1,306 @@
+//! Decoded image cache for the Velora web engine
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use velora_core::VeloraResult;
+use crate::DecodedImage;
+
+/// Bytes of decoded pixels kept by default
+pub const DEFAULT_IMAGE_BUDGET: usize = 64 * 1024 * 1024;
+
+/// Bytes of encoded image data kept by default
+pub const DEFAULT_SOURCE_BUDGET: usize = 32 * 1024 * 1024;
+
+/// Encoded data of an image, kept so it can be decoded again after eviction
+#[derive(Debug, Clone)]
+struct ImageSource {
+    data: Arc<[u8]>,
+    width: u32,
+    height: u32,
+    last_used: u64,
+}
+
+/// A decoded image and when it was last used
+#[derive(Debug, Clone)]
+struct CachedImage {
+    image: Arc<DecodedImage>,
+    last_used: u64,
+}
+
+/// Decoded images by URL, limited to a byte budget
+///
+/// Decoded RGBA pixels count against the budget and the least recently used are dropped
+/// when it is exceeded, to be decoded again on the next `get`. Encoded data has a budget of
+/// its own; an image whose data is dropped is forgotten entirely and has to be inserted again.
+#[derive(Debug, Clone)]
+pub struct ImageCache {
+    /// Most bytes of decoded pixels to keep
+    budget: usize,
+    /// Bytes of decoded pixels currently kept
+    used: usize,
+    /// Most bytes of encoded data to keep
+    source_budget: usize,
+    /// Bytes of encoded data currently kept
+    source_used: usize,
+    /// Use counter, increasing with every insert and hit
+    tick: u64,
+    sources: HashMap<String, ImageSource>,
+    decoded: HashMap<String, CachedImage>,
+    /// Number of images decoded because they were not cached
+    decodes: usize,
+}
+
+impl Default for ImageCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_IMAGE_BUDGET)
+    }
+}
+
+impl ImageCache {
+    /// Create an empty cache keeping at most `budget` bytes of decoded pixels
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            used: 0,
+            source_budget: DEFAULT_SOURCE_BUDGET,
+            source_used: 0,
+            tick: 0,
+            sources: HashMap::new(),
+            decoded: HashMap::new(),
+            decodes: 0,
+        }
+    }
+    
+    /// Store an image's encoded data together with its decoded pixels
+    ///
+    /// Data larger than the whole source budget is not kept, and neither are the pixels.
+    pub fn insert(&mut self, url: &str, data: Vec<u8>, image: DecodedImage) -> Arc<DecodedImage> {
+        self.tick += 1;
+        let source = ImageSource {
+            data: data.into(),
+            width: image.width,
+            height: image.height,
+            last_used: self.tick,
+        };
+        if let Some(previous) = self.sources.remove(url) {
+            self.source_used -= previous.data.len();
+        }
+        if source.data.len() > self.source_budget {
+            self.forget_decoded(url);
+            return Arc::new(image);
+        }
+        
+        self.source_used += source.data.len();
+        self.sources.insert(url.to_string(), source);
+        self.evict_sources(Some(url));
+        self.keep_decoded(url, Arc::new(image))
+    }
+    
+    /// Get an image's decoded pixels, decoding its data again if they were evicted
+    ///
+    /// Returns None for a URL that was never inserted.
+    pub fn get(&mut self, url: &str) -> VeloraResult<Option<Arc<DecodedImage>>> {
+        self.tick += 1;
+        let Some(source) = self.sources.get_mut(url) else {
+            return Ok(None);
+        };
+        source.last_used = self.tick;
+        if let Some(cached) = self.decoded.get_mut(url) {
+            cached.last_used = self.tick;
+            return Ok(Some(cached.image.clone()));
+        }
+        
+        let image = DecodedImage::decode(&source.data)?;
+        self.decodes += 1;
+        Ok(Some(self.keep_decoded(url, Arc::new(image))))
+    }
+    
+    /// Natural size of an image, known without decoding it
+    pub fn dimensions(&self, url: &str) -> Option<(u32, u32)> {
+        self.sources.get(url).map(|source| (source.width, source.height))
+    }
+    
+    /// Whether an image's encoded data is kept, so `get` can return it
+    pub fn contains(&self, url: &str) -> bool {
+        self.sources.contains_key(url)
+    }
+    
+    /// Whether an image's decoded pixels are currently kept
+    pub fn is_decoded(&self, url: &str) -> bool {
+        self.decoded.contains_key(url)
+    }
+    
+    /// Change the budget, evicting images until the cache fits
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict(None);
+    }
+    
+    /// Change the budget for encoded data, forgetting images until the cache fits
+    pub fn set_source_budget(&mut self, budget: usize) {
+        self.source_budget = budget;
+        self.evict_sources(None);
+    }
+    
+    /// Bytes of decoded pixels currently kept
+    pub fn memory_usage(&self) -> usize {
+        self.used
+    }
+    
+    /// Bytes of encoded data currently kept
+    pub fn source_usage(&self) -> usize {
+        self.source_used
+    }
+    
+    /// Number of images decoded again after being evicted
+    pub fn decode_count(&self) -> usize {
+        self.decodes
+    }
+    
+    /// Keep decoded pixels as the most recently used, evicting others to fit the budget
+    ///
+    /// An image larger than the whole budget is returned without being kept.
+    fn keep_decoded(&mut self, url: &str, image: Arc<DecodedImage>) -> Arc<DecodedImage> {
+        if let Some(previous) = self.decoded.remove(url) {
+            self.used -= previous.image.pixels.len();
+        }
+        if image.pixels.len() > self.budget {
+            return image;
+        }
+        
+        self.tick += 1;
+        self.used += image.pixels.len();
+        self.decoded.insert(url.to_string(), CachedImage { image: image.clone(), last_used: self.tick });
+        self.evict(Some(url));
+        image
+    }
+    
+    /// Drop an image's decoded pixels, if they are kept
+    fn forget_decoded(&mut self, url: &str) {
+        if let Some(evicted) = self.decoded.remove(url) {
+            self.used -= evicted.image.pixels.len();
+        }
+    }
+    
+    /// Forget the least recently used images until their encoded data fits the source budget, sparing `keep`
+    fn evict_sources(&mut self, keep: Option<&str>) {
+        while self.source_used > self.source_budget {
+            let oldest = self.sources.iter()
+                .filter(|(url, _)| Some(url.as_str()) != keep)
+                .min_by_key(|(_, source)| source.last_used)
+                .map(|(url, _)| url.clone());
+            let Some(url) = oldest else {
+                break;
+            };
+            if let Some(evicted) = self.sources.remove(&url) {
+                self.source_used -= evicted.data.len();
+            }
+            self.forget_decoded(&url);
+        }
+    }
+    
+    /// Drop the least recently used decoded images until the budget is met, sparing `keep`
+    fn evict(&mut self, keep: Option<&str>) {
+        while self.used > self.budget {
+            let oldest = self.decoded.iter()
+                .filter(|(url, _)| Some(url.as_str()) != keep)
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(url, _)| url.clone());
+            let Some(url) = oldest else {
+                break;
+            };
+            self.forget_decoded(&url);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use velora_core::Color;
+    use crate::PixelBuffer;
+    
+    /// A solid PNG and its decoded pixels, which take width * height * 4 bytes
+    fn image(width: u32, height: u32) -> (Vec<u8>, DecodedImage) {
+        let mut buffer = PixelBuffer::new(width, height);
+        buffer.clear(Color::rgb(255, 0, 0));
+        let data = buffer.encode_png().unwrap();
+        let decoded = DecodedImage::decode(&data).unwrap();
+        (data, decoded)
+    }
+    
+    #[test]
+    fn test_evicts_least_recently_used() {
+        // Room for two 10x10 images of 400 bytes each
+        let mut cache = ImageCache::new(800);
+        for url in ["a", "b"] {
+            let (data, decoded) = image(10, 10);
+            cache.insert(url, data, decoded);
+        }
+        assert_eq!(cache.memory_usage(), 800);
+        
+        // Using "a" makes "b" the one to go
+        cache.get("a").unwrap();
+        let (data, decoded) = image(10, 10);
+        cache.insert("c", data, decoded);
+        assert!(cache.is_decoded("a") && cache.is_decoded("c"));
+        assert!(!cache.is_decoded("b"));
+        assert_eq!(cache.memory_usage(), 800);
+        
+        // Evicted images are decoded again on demand
+        assert_eq!(cache.dimensions("b"), Some((10, 10)));
+        let reloaded = cache.get("b").unwrap().unwrap();
+        assert_eq!(reloaded.pixels.len(), 400);
+        assert_eq!(cache.decode_count(), 1);
+        assert!(!cache.is_decoded("a"));
+        
+        assert!(cache.get("missing").unwrap().is_none());
+    }
+    
+    #[test]
+    fn test_hit_avoids_decoding() {
+        let mut cache = ImageCache::new(1000);
+        let (data, decoded) = image(5, 5);
+        let inserted = cache.insert("a", data, decoded);
+        
+        let hit = cache.get("a").unwrap().unwrap();
+        assert!(Arc::ptr_eq(&inserted, &hit));
+        cache.get("a").unwrap();
+        assert_eq!(cache.decode_count(), 0);
+        
+        // Shrinking the budget evicts, so the next get decodes
+        cache.set_budget(50);
+        assert_eq!(cache.memory_usage(), 0);
+        let decoded = cache.get("a").unwrap().unwrap();
+        assert_eq!(decoded.pixels.len(), 100);
+        assert_eq!(cache.decode_count(), 1);
+        assert!(!cache.is_decoded("a"));
+    }
+    
+    #[test]
+    fn test_source_budget_forgets_least_recently_used() {
+        let mut cache = ImageCache::new(DEFAULT_IMAGE_BUDGET);
+        let (data, decoded) = image(10, 10);
+        let size = data.len();
+        cache.set_source_budget(size * 2);
+        
+        for url in ["a", "b"] {
+            cache.insert(url, data.clone(), decoded.clone());
+        }
+        assert_eq!(cache.source_usage(), size * 2);
+        
+        // Using "a" makes "b" the one to go, pixels and all
+        cache.get("a").unwrap();
+        cache.insert("c", data.clone(), decoded.clone());
+        assert!(cache.contains("a") && cache.contains("c"));
+        assert!(!cache.contains("b") && !cache.is_decoded("b"));
+        assert!(cache.get("b").unwrap().is_none());
+        assert_eq!(cache.dimensions("b"), None);
+        assert_eq!(cache.source_usage(), size * 2);
+        assert_eq!(cache.memory_usage(), 800);
+        
+        cache.set_source_budget(size);
+        assert_eq!(cache.source_usage(), size);
+        assert_eq!(cache.memory_usage(), 400);
+    }
+}
@@ -1,20 +1,129 @@
 //! Image rendering for the Velora web engine
 
-use velora_core::{VeloraResult, Rect};
+use std::sync::Arc;
+use velora_core::{VeloraResult, VeloraError, Rect, Size};
+use velora_core::error::PaintError;
+use crate::ImageCache;
+
+/// Size of the box shown for an image that is not loaded, unless configured otherwise
+pub const DEFAULT_PLACEHOLDER_SIZE: Size = Size { width: 24.0, height: 24.0 };
+
+/// A decoded image with 8-bit RGBA pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedImage {
+    /// Width in pixels
+    pub width: u32,
+    
+    /// Height in pixels
+    pub height: u32,
+    
+    /// Pixel data, four bytes per pixel in row-major order
+    pub pixels: Vec<u8>,
+}
+
+impl DecodedImage {
+    /// Decode encoded image data (PNG or ICO) into RGBA pixels
+    pub fn decode(data: &[u8]) -> VeloraResult<Self> {
+        let image = image::load_from_memory(data)
+            .map_err(|e| VeloraError::Paint(PaintError::InvalidOperation(format!("Image decoding failed: {}", e))))?
+            .into_rgba8();
+        
+        Ok(Self {
+            width: image.width(),
+            height: image.height(),
+            pixels: image.into_raw(),
+        })
+    }
+    
+    /// Scale the image down so neither side exceeds `max_size`, keeping the aspect ratio
+    ///
+    /// Fails if `pixels` holds fewer than `width * height` RGBA pixels.
+    pub fn thumbnail(&self, max_size: u32) -> VeloraResult<Self> {
+        let too_short = || VeloraError::Paint(PaintError::InvalidOperation(format!(
+            "{} bytes of pixels are too few for a {}x{} image", self.pixels.len(), self.width, self.height
+        )));
+        if self.pixels.len() < self.width as usize * self.height as usize * 4 {
+            return Err(too_short());
+        }
+        if self.width <= max_size && self.height <= max_size {
+            return Ok(self.clone());
+        }
+        
+        let buffer = image::RgbaImage::from_raw(self.width, self.height, self.pixels.clone()).ok_or_else(too_short)?;
+        let scale = max_size as f32 / self.width.max(self.height) as f32;
+        let width = ((self.width as f32 * scale).round() as u32).max(1);
+        let height = ((self.height as f32 * scale).round() as u32).max(1);
+        let scaled = image::imageops::thumbnail(&buffer, width, height);
+        
+        Ok(Self {
+            width: scaled.width(),
+            height: scaled.height(),
+            pixels: scaled.into_raw(),
+        })
+    }
+}
 
 /// Image renderer for displaying images
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ImageRenderer {
-    /// Image cache
-    _images: std::collections::HashMap<String, ()>,
+    /// Decoded images by source URL
+    images: ImageCache,
+    
+    /// Size laid out for images that are not loaded
+    placeholder_size: Size,
+}
+
+impl Default for ImageRenderer {
+    fn default() -> Self {
+        Self {
+            images: ImageCache::default(),
+            placeholder_size: DEFAULT_PLACEHOLDER_SIZE,
+        }
+    }
 }
 
 impl ImageRenderer {
     /// Create a new image renderer
     pub fn new() -> VeloraResult<Self> {
-        Ok(Self {
-            _images: std::collections::HashMap::new(),
-        })
+        Ok(Self::default())
+    }
+    
+    /// Keep an image's encoded data and its decoded pixels under its source URL
+    pub fn insert_image(&mut self, src: &str, data: Vec<u8>, image: DecodedImage) {
+        self.images.insert(src, data, image);
+    }
+    
+    /// Get a decoded image by its source URL, decoding it again if it was evicted
+    pub fn get_image(&mut self, src: &str) -> VeloraResult<Option<Arc<DecodedImage>>> {
+        self.images.get(src)
+    }
+    
+    /// Whether an image is stored under a source URL
+    pub fn contains_image(&self, src: &str) -> bool {
+        self.images.contains(src)
+    }
+    
+    /// Limit the bytes of decoded pixels kept, evicting the least recently used images
+    pub fn set_memory_budget(&mut self, bytes: usize) {
+        self.images.set_budget(bytes);
+    }
+    
+    /// Limit the bytes of encoded image data kept, forgetting the least recently used images
+    pub fn set_source_budget(&mut self, bytes: usize) {
+        self.images.set_source_budget(bytes);
+    }
+    
+    /// Set the size laid out for images that are not loaded
+    pub fn set_placeholder_size(&mut self, size: Size) {
+        self.placeholder_size = size;
+    }
+    
+    /// Natural size of an image, or the placeholder size if it is not loaded
+    pub fn intrinsic_size(&self, src: &str) -> Size {
+        match self.images.dimensions(src) {
+            Some((width, height)) => Size::new(width as f32, height as f32),
+            None => self.placeholder_size,
+        }
     }
     
     /// Load an image from a path
@@ -23,6 +132,11 @@ impl ImageRenderer {
         Ok(())
     }
     
+    /// Decode encoded image data (PNG or ICO) into RGBA pixels
+    pub fn decode_image(&self, data: &[u8]) -> VeloraResult<DecodedImage> {
+        DecodedImage::decode(data)
+    }
+    
     /// Draw an image at a specific position
     pub fn draw_image(&self, _image_id: &str, _rect: Rect) -> VeloraResult<()> {
         // TODO: Implement image drawing
@@ -47,6 +161,63 @@ mod tests {
         assert!(result.is_ok());
     }
     
+    fn encode_png(width: u32, height: u32) -> Vec<u8> {
+        let image = image::RgbaImage::from_pixel(width, height, image::Rgba([255, 0, 0, 255]));
+        let mut data = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut data, image::ImageFormat::Png).unwrap();
+        data.into_inner()
+    }
+    
+    #[test]
+    fn test_decode_image() {
+        let renderer = ImageRenderer::new().unwrap();
+        let decoded = renderer.decode_image(&encode_png(4, 2)).unwrap();
+        
+        assert_eq!((decoded.width, decoded.height), (4, 2));
+        assert_eq!(decoded.pixels.len(), 4 * 2 * 4);
+        assert_eq!(&decoded.pixels[0..4], &[255, 0, 0, 255]);
+        
+        assert!(renderer.decode_image(b"not an image").is_err());
+    }
+    
+    #[test]
+    fn test_decoded_image_thumbnail() {
+        let renderer = ImageRenderer::new().unwrap();
+        let decoded = renderer.decode_image(&encode_png(64, 32)).unwrap();
+        
+        let thumbnail = decoded.thumbnail(16).unwrap();
+        assert_eq!((thumbnail.width, thumbnail.height), (16, 8));
+        assert_eq!(thumbnail.pixels.len(), 16 * 8 * 4);
+        
+        // Images already small enough are left untouched
+        assert_eq!(thumbnail.thumbnail(16).unwrap(), thumbnail);
+        
+        // Pixels that do not match the dimensions are an error, not a panic
+        let mismatched = DecodedImage { width: 64, height: 32, pixels: vec![0; 10] };
+        assert!(mismatched.thumbnail(16).is_err());
+        assert!(DecodedImage { width: 4, pixels: vec![0; 10], ..mismatched }.thumbnail(16).is_err());
+    }
+    
+    #[test]
+    fn test_intrinsic_size() {
+        let mut renderer = ImageRenderer::new().unwrap();
+        let data = encode_png(200, 100);
+        let decoded = renderer.decode_image(&data).unwrap();
+        renderer.insert_image("https://example.com/a.png", data, decoded);
+        
+        assert_eq!(renderer.intrinsic_size("https://example.com/a.png"), Size::new(200.0, 100.0));
+        
+        // Evicted pixels come back from the encoded data; the size never needs them
+        renderer.set_memory_budget(0);
+        assert_eq!(renderer.intrinsic_size("https://example.com/a.png"), Size::new(200.0, 100.0));
+        let image = renderer.get_image("https://example.com/a.png").unwrap().unwrap();
+        assert_eq!((image.width, image.height), (200, 100));
+        assert_eq!(renderer.intrinsic_size("missing.png"), DEFAULT_PLACEHOLDER_SIZE);
+        
+        renderer.set_placeholder_size(Size::new(10.0, 5.0));
+        assert_eq!(renderer.intrinsic_size("missing.png"), Size::new(10.0, 5.0));
+    }
+    
     #[test]
     fn test_image_drawing() {
         let renderer = ImageRenderer::new().unwrap();
@@ -3,20 +3,32 @@
 //! This crate provides software rendering capabilities,
 //! including text rendering, shapes, and images.
 
+pub mod buffer;
 pub mod renderer;
 pub mod text;
 pub mod shapes;
 pub mod images;
+pub mod display_list;
+pub mod dirty_region;
+pub mod image_cache;
 
+pub use buffer::PixelBuffer;
 pub use renderer::Renderer;
 pub use text::TextRenderer;
 pub use shapes::ShapeRenderer;
-pub use images::ImageRenderer;
+pub use images::{ImageRenderer, DecodedImage};
+pub use display_list::{DisplayList, DisplayListBuilder, DrawCommand};
+pub use dirty_region::DirtyRegion;
+pub use image_cache::ImageCache;
 
 /// Re-export commonly used items for convenience
 pub mod prelude {
+    pub use super::buffer::PixelBuffer;
     pub use super::renderer::Renderer;
     pub use super::text::TextRenderer;
     pub use super::shapes::ShapeRenderer;
-    pub use super::images::ImageRenderer;
+    pub use super::images::{ImageRenderer, DecodedImage};
+    pub use super::display_list::{DisplayList, DisplayListBuilder, DrawCommand};
+    pub use super::dirty_region::DirtyRegion;
+    pub use super::image_cache::ImageCache;
 }
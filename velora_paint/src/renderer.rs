@@ -1,33 +1,113 @@
 //! Software renderer for the Velora web engine
 
-use velora_core::{VeloraResult, Size};
+use velora_core::{VeloraResult, Rect, Size, Color};
+use crate::{DirtyRegion, PixelBuffer};
 
 /// Software renderer for the Velora web engine
 #[derive(Debug)]
 pub struct Renderer {
-    /// Renderer state
-    _state: Option<()>,
+    /// Last presented frame, allocated by `initialize`
+    front: Option<PixelBuffer>,
+    
+    /// Frame being drawn, presented by `swap`
+    back: Option<PixelBuffer>,
+    
+    /// Color each frame starts from
+    background: Color,
+    
+    /// Area to repaint on the next frame
+    dirty: DirtyRegion,
+    
+    /// Whether the framebuffer holds a complete frame
+    painted: bool,
 }
 
 impl Renderer {
     /// Create a new renderer
     pub fn new() -> VeloraResult<Self> {
         Ok(Self {
-            _state: None,
+            front: None,
+            back: None,
+            background: Color::white(),
+            dirty: DirtyRegion::new(),
+            painted: false,
         })
     }
     
-    /// Initialize the renderer
-    pub fn initialize(&mut self, _size: Size) -> VeloraResult<()> {
-        // TODO: Initialize software renderer
+    /// Initialize the renderer with offscreen front and back buffers of the given size
+    pub fn initialize(&mut self, size: Size) -> VeloraResult<()> {
+        let (width, height) = Self::pixel_size(size);
+        self.front = Some(PixelBuffer::new(width, height));
+        self.back = Some(PixelBuffer::new(width, height));
+        self.painted = false;
         Ok(())
     }
     
-    /// Render a frame
+    /// Follow a window resize, reallocating both buffers if the pixel size changed
+    ///
+    /// The next frame after a reallocation is painted in full.
+    pub fn resize(&mut self, size: Size) -> VeloraResult<()> {
+        let current = self.back.as_ref().map(|back| (back.width(), back.height()));
+        if current == Some(Self::pixel_size(size)) {
+            return Ok(());
+        }
+        self.initialize(size)
+    }
+    
+    /// Set the color each frame is cleared to
+    ///
+    /// Already painted areas keep the old color until they are invalidated.
+    pub fn set_background(&mut self, color: Color) {
+        self.background = color;
+    }
+    
+    /// Mark an area of the framebuffer for repainting on the next frame
+    pub fn invalidate(&mut self, rect: Rect) {
+        self.dirty.add(rect);
+    }
+    
+    /// Render a frame into the back buffer
+    ///
+    /// The first frame after `initialize` paints everything; later frames repaint only the
+    /// bounding rect of the invalidated areas, and nothing if none were invalidated.
     pub fn render(&mut self) -> VeloraResult<()> {
-        // TODO: Implement software frame rendering
+        let dirty = self.dirty.take();
+        if let Some(target) = &mut self.back {
+            match dirty {
+                _ if !self.painted => target.clear(self.background),
+                Some(rect) => target.clear_rect(rect, self.background),
+                None => {}
+            }
+            self.painted = true;
+        }
         Ok(())
     }
+    
+    /// Present the back buffer, making it the front buffer
+    ///
+    /// The new back buffer starts as a copy of the presented frame, so the next frame only
+    /// needs to repaint what changed.
+    pub fn swap(&mut self) {
+        std::mem::swap(&mut self.front, &mut self.back);
+        if let (Some(front), Some(back)) = (&self.front, &mut self.back) {
+            back.clone_from(front);
+        }
+    }
+    
+    /// Get the last presented frame, if the renderer has been initialized
+    pub fn target(&self) -> Option<&PixelBuffer> {
+        self.front.as_ref()
+    }
+    
+    /// Get the frame being drawn, if the renderer has been initialized
+    pub fn back_buffer_mut(&mut self) -> Option<&mut PixelBuffer> {
+        self.back.as_mut()
+    }
+    
+    /// Whole pixels needed to cover a size
+    fn pixel_size(size: Size) -> (u32, u32) {
+        (size.width.max(0.0).ceil() as u32, size.height.max(0.0).ceil() as u32)
+    }
 }
 
 #[cfg(test)]
@@ -54,4 +134,83 @@ mod tests {
         let result = renderer.render();
         assert!(result.is_ok());
     }
+    
+    #[test]
+    fn test_renderer_offscreen_frame() {
+        let mut renderer = Renderer::new().unwrap();
+        renderer.initialize(Size::new(4.0, 2.5)).unwrap();
+        renderer.set_background(Color::rgb(10, 20, 30));
+        renderer.render().unwrap();
+        renderer.swap();
+        
+        let target = renderer.target().unwrap();
+        assert_eq!((target.width(), target.height()), (4, 3));
+        assert_eq!(target.get_pixel(3, 2), Some(Color::rgb(10, 20, 30)));
+    }
+    
+    #[test]
+    fn test_renderer_repaints_dirty_bounds_only() {
+        let mut renderer = Renderer::new().unwrap();
+        renderer.initialize(Size::new(8.0, 8.0)).unwrap();
+        renderer.render().unwrap();
+        renderer.swap();
+        
+        renderer.set_background(Color::black());
+        renderer.invalidate(Rect::new(1.0, 1.0, 1.0, 1.0));
+        renderer.invalidate(Rect::new(3.0, 2.0, 2.0, 2.0));
+        renderer.render().unwrap();
+        renderer.swap();
+        
+        let target = renderer.target().unwrap();
+        for (x, y) in [(1, 1), (4, 3), (2, 2)] {
+            assert_eq!(target.get_pixel(x, y), Some(Color::black()));
+        }
+        for (x, y) in [(0, 0), (5, 3), (1, 4)] {
+            assert_eq!(target.get_pixel(x, y), Some(Color::white()));
+        }
+        
+        // Nothing invalidated, nothing repainted
+        renderer.set_background(Color::rgb(10, 20, 30));
+        renderer.render().unwrap();
+        renderer.swap();
+        assert_eq!(renderer.target().unwrap().get_pixel(2, 2), Some(Color::black()));
+    }
+    
+    #[test]
+    fn test_back_buffer_hidden_until_swap() {
+        let mut renderer = Renderer::new().unwrap();
+        renderer.initialize(Size::new(4.0, 4.0)).unwrap();
+        renderer.set_background(Color::black());
+        renderer.render().unwrap();
+        
+        // The rendered frame stays in the back buffer
+        assert_eq!(renderer.target().unwrap().get_pixel(0, 0), Some(Color::transparent()));
+        renderer.swap();
+        assert_eq!(renderer.target().unwrap().get_pixel(0, 0), Some(Color::black()));
+        
+        renderer.back_buffer_mut().unwrap().set_pixel(1, 1, Color::white());
+        assert_eq!(renderer.target().unwrap().get_pixel(1, 1), Some(Color::black()));
+        renderer.swap();
+        assert_eq!(renderer.target().unwrap().get_pixel(1, 1), Some(Color::white()));
+        assert_eq!(renderer.target().unwrap().get_pixel(0, 0), Some(Color::black()));
+    }
+    
+    #[test]
+    fn test_resize_reallocates_buffers() {
+        let mut renderer = Renderer::new().unwrap();
+        renderer.initialize(Size::new(4.0, 4.0)).unwrap();
+        renderer.render().unwrap();
+        renderer.swap();
+        
+        // Same pixel size keeps the presented frame
+        renderer.resize(Size::new(3.5, 4.0)).unwrap();
+        assert_eq!(renderer.target().unwrap().get_pixel(0, 0), Some(Color::white()));
+        
+        renderer.resize(Size::new(6.0, 2.0)).unwrap();
+        renderer.render().unwrap();
+        renderer.swap();
+        let target = renderer.target().unwrap();
+        assert_eq!((target.width(), target.height()), (6, 2));
+        assert_eq!(target.get_pixel(5, 1), Some(Color::white()));
+    }
 }
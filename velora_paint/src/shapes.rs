@@ -1,6 +1,7 @@
 //! Shape rendering for the Velora web engine
 
-use velora_core::{VeloraResult, Point, Color, Rect};
+use velora_core::{VeloraResult, Point, Color, Rect, Gradient};
+use crate::buffer::PixelBuffer;
 
 /// Shape renderer for drawing geometric shapes
 #[derive(Debug)]
@@ -28,6 +29,40 @@ impl ShapeRenderer {
         Ok(())
     }
     
+    /// Fill a rectangle of the buffer with a linear gradient, blending over existing pixels
+    pub fn fill_gradient(&self, buffer: &mut PixelBuffer, rect: Rect, gradient: &Gradient) -> VeloraResult<()> {
+        let stops = resolve_stop_positions(gradient);
+        if stops.is_empty() {
+            return Ok(());
+        }
+        
+        // Gradient line through the rect centre, long enough for the corners to hit 0% and 100%
+        let angle = gradient.angle.to_radians();
+        let (dx, dy) = (angle.sin(), -angle.cos());
+        let length = (rect.width * dx).abs() + (rect.height * dy).abs();
+        let center = Point::new(rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+        
+        let x_start = rect.x.max(0.0).floor() as u32;
+        let y_start = rect.y.max(0.0).floor() as u32;
+        let x_end = (rect.x + rect.width).min(buffer.width() as f32).ceil().max(0.0) as u32;
+        let y_end = (rect.y + rect.height).min(buffer.height() as f32).ceil().max(0.0) as u32;
+        
+        for y in y_start..y_end {
+            for x in x_start..x_end {
+                let color = if stops.len() == 1 || length <= 0.0 {
+                    stops[0].1
+                } else {
+                    let px = x as f32 + 0.5 - center.x;
+                    let py = y as f32 + 0.5 - center.y;
+                    color_at(&stops, (px * dx + py * dy) / length + 0.5)
+                };
+                buffer.blend_pixel(x, y, color);
+            }
+        }
+        
+        Ok(())
+    }
+    
     /// Draw a circle
     pub fn draw_circle(&self, _center: Point, _radius: f32) -> VeloraResult<()> {
         // TODO: Implement circle drawing
@@ -35,9 +70,137 @@ impl ShapeRenderer {
     }
 }
 
+/// Fill in missing stop positions and clamp them to a non-decreasing sequence in [0, 1]
+fn resolve_stop_positions(gradient: &Gradient) -> Vec<(f32, Color)> {
+    let count = gradient.stops.len();
+    let mut positions: Vec<Option<f32>> = gradient.stops.iter().map(|stop| stop.position).collect();
+    
+    if count > 1 {
+        positions[0] = positions[0].or(Some(0.0));
+        positions[count - 1] = positions[count - 1].or(Some(1.0));
+    }
+    
+    let mut previous = 0.0f32;
+    let mut index = 0;
+    while index < count {
+        match positions[index] {
+            Some(position) => {
+                previous = position.clamp(previous, 1.0);
+                positions[index] = Some(previous);
+                index += 1;
+            }
+            None => {
+                // Spread a run of unpositioned stops evenly up to the next positioned one
+                let run_end = (index..count).find(|&i| positions[i].is_some()).unwrap_or(count);
+                let next = positions.get(run_end).copied().flatten().unwrap_or(1.0).clamp(previous, 1.0);
+                let steps = (run_end - index + 1) as f32;
+                for (offset, position) in positions[index..run_end].iter_mut().enumerate() {
+                    *position = Some(previous + (next - previous) * (offset + 1) as f32 / steps);
+                }
+                index = run_end;
+            }
+        }
+    }
+    
+    positions
+        .into_iter()
+        .zip(&gradient.stops)
+        .map(|(position, stop)| (position.unwrap_or(0.0), stop.color))
+        .collect()
+}
+
+/// Interpolate the gradient color at position `t` along the gradient line
+fn color_at(stops: &[(f32, Color)], t: f32) -> Color {
+    let first = stops[0];
+    let last = stops[stops.len() - 1];
+    if t <= first.0 {
+        return first.1;
+    }
+    if t >= last.0 {
+        return last.1;
+    }
+    
+    for pair in stops.windows(2) {
+        let ((start, from), (end, to)) = (pair[0], pair[1]);
+        if t <= end {
+            let span = end - start;
+            let f = if span <= 0.0 { 1.0 } else { (t - start) / span };
+            let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * f).round() as u8;
+            return Color::rgba(lerp(from.r, to.r), lerp(from.g, to.g), lerp(from.b, to.b), lerp(from.a, to.a));
+        }
+    }
+    
+    last.1
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use velora_core::ColorStop;
+    
+    fn stop(color: Color, position: Option<f32>) -> ColorStop {
+        ColorStop { color, position }
+    }
+    
+    fn assert_close(actual: Color, expected: Color) {
+        let close = |a: u8, b: u8| (a as i32 - b as i32).abs() <= 2;
+        assert!(
+            close(actual.r, expected.r) && close(actual.g, expected.g) && close(actual.b, expected.b) && close(actual.a, expected.a),
+            "{:?} is not close to {:?}", actual, expected
+        );
+    }
+    
+    #[test]
+    fn test_fill_gradient_horizontal_midpoint() {
+        let renderer = ShapeRenderer::new().unwrap();
+        let mut buffer = PixelBuffer::new(100, 10);
+        let gradient = Gradient {
+            angle: 90.0,
+            stops: vec![stop(Color::rgb(255, 0, 0), None), stop(Color::rgb(0, 0, 255), None)],
+        };
+        
+        renderer.fill_gradient(&mut buffer, Rect::new(0.0, 0.0, 100.0, 10.0), &gradient).unwrap();
+        
+        assert_close(buffer.get_pixel(0, 5).unwrap(), Color::rgb(254, 0, 1));
+        assert_close(buffer.get_pixel(50, 5).unwrap(), Color::rgb(127, 0, 128));
+        assert_close(buffer.get_pixel(99, 5).unwrap(), Color::rgb(1, 0, 254));
+    }
+    
+    #[test]
+    fn test_fill_gradient_with_stop_positions() {
+        let renderer = ShapeRenderer::new().unwrap();
+        let mut buffer = PixelBuffer::new(10, 100);
+        // Top to bottom: solid white until 50%, then fade to black by 100%
+        let gradient = Gradient {
+            angle: 180.0,
+            stops: vec![
+                stop(Color::white(), Some(0.0)),
+                stop(Color::white(), Some(0.5)),
+                stop(Color::black(), Some(1.5)),
+            ],
+        };
+        
+        renderer.fill_gradient(&mut buffer, Rect::new(0.0, 0.0, 10.0, 100.0), &gradient).unwrap();
+        
+        assert_close(buffer.get_pixel(5, 25).unwrap(), Color::white());
+        // The out-of-range 150% stop is clamped to 100%
+        assert_close(buffer.get_pixel(5, 75).unwrap(), Color::rgb(127, 127, 127));
+    }
+    
+    #[test]
+    fn test_fill_gradient_single_stop_is_solid() {
+        let renderer = ShapeRenderer::new().unwrap();
+        let mut buffer = PixelBuffer::new(8, 8);
+        let gradient = Gradient { angle: 45.0, stops: vec![stop(Color::rgb(10, 20, 30), Some(0.7))] };
+        
+        renderer.fill_gradient(&mut buffer, Rect::new(2.0, 2.0, 4.0, 4.0), &gradient).unwrap();
+        
+        assert_eq!(buffer.get_pixel(2, 2), Some(Color::rgb(10, 20, 30)));
+        assert_eq!(buffer.get_pixel(5, 5), Some(Color::rgb(10, 20, 30)));
+        // Pixels outside the rect are untouched
+        assert_eq!(buffer.get_pixel(1, 1), Some(Color::transparent()));
+        assert_eq!(buffer.get_pixel(6, 6), Some(Color::transparent()));
+    }
     
     #[test]
     fn test_shape_renderer_creation() {
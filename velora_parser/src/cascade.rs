@@ -0,0 +1,183 @@
+//! Cascade resolution for the Velora web engine
+
+use std::collections::HashMap;
+use velora_core::NodeId;
+use velora_dom::DomTree;
+use crate::css::{CascadeOrigin, CssParser, CssProperty, CssRule, SelectorSpecificity};
+use crate::shorthand;
+
+/// The browser's built-in stylesheet, applied beneath every page's own styles
+pub const USER_AGENT_STYLESHEET: &str = "
+    html, address, article, aside, blockquote, body, dd, div, dl, dt, fieldset, figcaption,
+    figure, footer, form, h1, h2, h3, h4, h5, h6, header, hr, li, main, nav, ol, p, pre,
+    section, table, ul { display: block }
+    head, script, style, title { display: none }
+    body { margin: 8px }
+";
+
+/// Cascade precedence of a declaration; greater ranks win
+type CascadeRank = (bool, CascadeOrigin, SelectorSpecificity, usize);
+
+/// Parse the built-in stylesheet
+pub fn user_agent_rules() -> Vec<CssRule> {
+    CssParser::new()
+        .parse_css_with_origin(USER_AGENT_STYLESHEET, CascadeOrigin::UserAgent)
+        .unwrap_or_default()
+}
+
+/// Pick the winning declaration for each property name
+///
+/// Each declaration carries the specificity of the selector it came from and its source
+/// order. `!important` declarations beat normal ones, then higher specificity wins, and
+/// among equal specificity the later declaration wins.
+pub fn resolve(declarations: Vec<(SelectorSpecificity, usize, CssProperty)>) -> HashMap<String, CssProperty> {
+    resolve_with_origins(
+        declarations
+            .into_iter()
+            .map(|(specificity, order, property)| (CascadeOrigin::Author, specificity, order, property))
+            .collect(),
+    )
+}
+
+/// Pick the winning declaration for each property name, taking stylesheet origins into account
+///
+/// Like `resolve`, except that before specificity is compared, author declarations beat
+/// user declarations, which beat user-agent ones. `!important` does not yet reverse the
+/// origin order as it does in browsers.
+pub fn resolve_with_origins(declarations: Vec<(CascadeOrigin, SelectorSpecificity, usize, CssProperty)>) -> HashMap<String, CssProperty> {
+    let mut winners: HashMap<String, (CascadeRank, CssProperty)> = HashMap::new();
+    
+    for (origin, specificity, order, property) in declarations {
+        let rank = (property.important, origin, specificity, order);
+        let beats_current = winners
+            .get(&property.name)
+            .is_none_or(|(current, _)| rank > *current);
+        
+        if beats_current {
+            winners.insert(property.name.clone(), (rank, property));
+        }
+    }
+    
+    winners
+        .into_iter()
+        .map(|(name, (_, property))| (name, property))
+        .collect()
+}
+
+/// Cascade the rules that match a node, returning its declared longhand properties
+///
+/// A rule applies with the specificity of its most specific matching selector; rules
+/// later in `rules` count as later in source order.
+pub fn cascade_rules(rules: &[CssRule], tree: &DomTree, node_id: NodeId) -> HashMap<String, CssProperty> {
+    let mut declarations = Vec::new();
+    
+    for rule in rules {
+        let specificity = rule.selectors
+            .iter()
+            .filter(|selector| selector.matches(tree, node_id))
+            .map(|selector| selector.specificity.clone())
+            .max();
+        let Some(specificity) = specificity else {
+            continue;
+        };
+        
+        for property in shorthand::expand_all(rule.properties.clone()) {
+            let order = declarations.len();
+            declarations.push((rule.origin, specificity.clone(), order, property));
+        }
+    }
+    
+    resolve_with_origins(declarations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn declaration(a: u32, b: u32, c: u32, order: usize, name: &str, value: &str, important: bool) -> (SelectorSpecificity, usize, CssProperty) {
+        let property = CssProperty {
+            name: name.to_string(),
+            value: value.to_string(),
+            important,
+            source_position: None,
+        };
+        (SelectorSpecificity { a, b, c }, order, property)
+    }
+    
+    #[test]
+    fn test_equal_specificity_uses_source_order() {
+        let resolved = resolve(vec![
+            declaration(0, 1, 0, 2, "color", "blue", false),
+            declaration(0, 1, 0, 1, "color", "red", false),
+            declaration(0, 1, 0, 0, "margin", "0", false),
+        ]);
+        
+        assert_eq!(resolved["color"].value, "blue");
+        assert_eq!(resolved["margin"].value, "0");
+        assert_eq!(resolved.len(), 2);
+    }
+    
+    #[test]
+    fn test_specificity_beats_source_order() {
+        let resolved = resolve(vec![
+            declaration(1, 0, 0, 0, "color", "red", false),
+            declaration(0, 2, 1, 1, "color", "blue", false),
+        ]);
+        
+        assert_eq!(resolved["color"].value, "red");
+    }
+    
+    fn declared_for(rules: &[CssRule], html: &str, tag: &str) -> HashMap<String, CssProperty> {
+        let document = crate::html::HtmlParser::new().parse_html(html).unwrap();
+        let node_id = document.get_elements_by_tag_name(tag)[0].id;
+        cascade_rules(rules, document.get_dom_tree(), node_id)
+    }
+    
+    #[test]
+    fn test_user_agent_defaults_apply_without_author_rules() {
+        let declared = declared_for(&user_agent_rules(), "<p>Hello</p>", "body");
+        
+        assert_eq!(declared["margin"].value, "8px");
+        assert_eq!(declared["display"].value, "block");
+    }
+    
+    #[test]
+    fn test_author_rules_override_user_agent_defaults() {
+        // A plain type selector in the page beats the UA rule even though it comes first
+        let mut rules = CssParser::new().parse_css("body { margin: 0 }").unwrap();
+        rules.extend(user_agent_rules());
+        let declared = declared_for(&rules, "<p>Hello</p>", "body");
+        
+        assert_eq!(declared["margin"].value, "0");
+        assert_eq!(declared["display"].value, "block");
+    }
+    
+    #[test]
+    fn test_descendant_rule_cascades_onto_descendants_only() {
+        let mut rules = CssParser::new().parse_css(".hero h1 { margin: 0; color: red } h1 { color: blue }").unwrap();
+        rules.extend(user_agent_rules());
+        let html = r#"<div class="hero"><h1>Title</h1></div>"#;
+        
+        // The descendant rule is more specific than the plain type selector
+        let heading = declared_for(&rules, html, "h1");
+        assert_eq!(heading["margin"].value, "0");
+        assert_eq!(heading["color"].value, "red");
+        
+        // The hero itself only gets the UA defaults
+        let hero = declared_for(&rules, html, "div");
+        assert!(!hero.contains_key("margin"));
+        assert!(!hero.contains_key("color"));
+        assert_eq!(hero["display"].value, "block");
+    }
+    
+    #[test]
+    fn test_important_overrides_specificity() {
+        let resolved = resolve(vec![
+            declaration(0, 0, 1, 0, "color", "green", true),
+            declaration(1, 0, 0, 1, "color", "red", false),
+        ]);
+        
+        assert_eq!(resolved["color"].value, "green");
+        assert!(resolved["color"].important);
+    }
+}
@@ -1,6 +1,9 @@
 //! CSS parser for the Velora web engine
 
-use velora_core::VeloraResult;
+use velora_core::{VeloraResult, VeloraError};
+use velora_core::error::ParserError;
+pub use velora_core::error::CssSourcePosition;
+use log::debug;
 
 /// CSS parser that converts CSS text into structured rules
 pub struct CssParser {
@@ -41,6 +44,23 @@ pub struct CssRule {
     
     /// Source position
     pub source_position: Option<CssSourcePosition>,
+    
+    /// Stylesheet the rule came from
+    pub origin: CascadeOrigin,
+}
+
+/// Where a stylesheet came from, in increasing order of cascade precedence
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CascadeOrigin {
+    /// The browser's built-in defaults
+    UserAgent,
+    
+    /// Styles configured by the user
+    User,
+    
+    /// The page's own stylesheets
+    #[default]
+    Author,
 }
 
 /// Types of CSS rules
@@ -91,6 +111,9 @@ pub enum SelectorPart {
     /// Pseudo-class (e.g., ":hover")
     PseudoClass(String),
     
+    /// Structural pseudo-class (e.g., ":nth-child(2n+1)")
+    NthChild(NthIndex),
+    
     /// Pseudo-element (e.g., "::before")
     PseudoElement(String),
     
@@ -101,6 +124,62 @@ pub enum SelectorPart {
     Combinator(CombinatorType),
 }
 
+/// The `an+b` argument of `:nth-child`, matching every 1-based index `a*n + b` for n >= 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NthIndex {
+    pub a: i32,
+    pub b: i32,
+}
+
+impl NthIndex {
+    /// Parse the `an+b` microsyntax, including the `even` and `odd` keywords
+    pub fn parse(input: &str) -> Option<Self> {
+        let input: String = input.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_ascii_lowercase();
+        
+        match input.as_str() {
+            "even" => return Some(Self { a: 2, b: 0 }),
+            "odd" => return Some(Self { a: 2, b: 1 }),
+            _ => {}
+        }
+        
+        let Some((a, b)) = input.split_once('n') else {
+            return Some(Self { a: 0, b: parse_signed(&input)? });
+        };
+        
+        let a = match a {
+            "" | "+" => 1,
+            "-" => -1,
+            _ => parse_signed(a)?,
+        };
+        let b = match b {
+            "" => 0,
+            // The sign is required between the two terms
+            _ if b.starts_with(['+', '-']) => parse_signed(b)?,
+            _ => return None,
+        };
+        
+        Some(Self { a, b })
+    }
+    
+    /// Check if a 1-based sibling index is selected
+    pub fn matches(&self, index: usize) -> bool {
+        let offset = index as i64 - self.b as i64;
+        match self.a {
+            0 => offset == 0,
+            a => offset % a as i64 == 0 && offset / a as i64 >= 0,
+        }
+    }
+}
+
+/// Parse an integer with an optional sign, rejecting anything else
+fn parse_signed(input: &str) -> Option<i32> {
+    let digits = input.strip_prefix(['+', '-']).unwrap_or(input);
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    input.parse().ok()
+}
+
 /// Attribute selector operators
 #[derive(Debug, Clone, PartialEq)]
 pub enum AttributeOperator {
@@ -137,19 +216,6 @@ pub struct CssProperty {
     pub source_position: Option<CssSourcePosition>,
 }
 
-/// Source position information
-#[derive(Debug, Clone)]
-pub struct CssSourcePosition {
-    /// Line number (1-based)
-    pub line: u32,
-    
-    /// Column number (1-based)
-    pub column: u32,
-    
-    /// Source file name
-    pub file: Option<String>,
-}
-
 impl CssParser {
     /// Create a new CSS parser with default options
     pub fn new() -> Self {
@@ -164,54 +230,165 @@ impl CssParser {
     }
     
     /// Parse CSS text into a list of rules
+    ///
+    /// In strict mode malformed rules fail with a `ParserError::Syntax` pointing at the
+    /// problem; otherwise they are skipped like browsers do.
     pub fn parse_css(&self, css: &str) -> VeloraResult<Vec<CssRule>> {
-        // This is a simplified implementation
-        // In a real implementation, you would use cssparser to tokenize
-        // and parse the CSS according to the CSS specification
-        
         let mut rules = Vec::new();
+        let mut index = 0;
         
-        // For now, we'll create a simple rule to demonstrate the structure
-        if css.contains("body") {
-            let rule = CssRule {
-                rule_type: CssRuleType::Style,
-                selectors: vec![CssSelector {
-                    specificity: SelectorSpecificity { a: 0, b: 0, c: 1 },
-                    parts: vec![SelectorPart::Element("body".to_string())],
-                }],
-                properties: vec![CssProperty {
-                    name: "background-color".to_string(),
-                    value: "#ffffff".to_string(),
-                    important: false,
-                    source_position: None,
-                }],
-                source_position: None,
+        while let Some(start) = next_significant(css, index) {
+            let position = CssSourcePosition::from_offset(css, start);
+            
+            // At-rules are not supported yet; skip them whole
+            if css[start..].starts_with('@') {
+                index = skip_at_rule(css, start);
+                debug!("Skipping unsupported at-rule at {}", position);
+                continue;
+            }
+            
+            let Some(open) = css[start..].find(['{', '}']).map(|offset| start + offset) else {
+                self.syntax_error(position, "expected '{' after selector")?;
+                break;
+            };
+            
+            if css.as_bytes()[open] == b'}' {
+                self.syntax_error(CssSourcePosition::from_offset(css, open), "unexpected '}'")?;
+                index = open + 1;
+                continue;
+            }
+            
+            let (body_end, next_index) = match css[open + 1..].find('}') {
+                Some(offset) => (open + 1 + offset, open + 2 + offset),
+                None => {
+                    self.syntax_error(CssSourcePosition::from_offset(css, open), "unclosed '{'")?;
+                    (css.len(), css.len())
+                }
             };
-            rules.push(rule);
+            index = next_index;
+            
+            let selector_text = css[start..open].trim();
+            if selector_text.is_empty() {
+                self.syntax_error(position.clone(), "missing selector")?;
+                continue;
+            }
+            
+            // A rule with any selector we cannot parse is dropped whole, like browsers do
+            let selectors = match split_top_level(selector_text, ',')
+                .into_iter()
+                .map(|selector| self.parse_selector(selector.trim()))
+                .collect::<VeloraResult<Vec<_>>>()
+            {
+                Ok(selectors) => selectors,
+                Err(error) => {
+                    self.syntax_error(position, &error.to_string())?;
+                    continue;
+                }
+            };
+            
+            rules.push(CssRule {
+                rule_type: CssRuleType::Style,
+                selectors,
+                properties: self.parse_declarations(&css[open + 1..body_end]),
+                source_position: Some(position),
+                origin: CascadeOrigin::Author,
+            });
         }
         
-        // Use options to avoid dead code warning
         if self.options.strict_mode && rules.is_empty() {
-            return Err(velora_core::VeloraError::Parser(
-                velora_core::error::ParserError::CssParsing("No valid CSS rules found".to_string())
-            ));
+            return Err(VeloraError::Parser(ParserError::CssParsing("No valid CSS rules found".to_string())));
         }
         
         Ok(rules)
     }
     
+    /// Parse CSS text from a stylesheet of the given origin
+    pub fn parse_css_with_origin(&self, css: &str, origin: CascadeOrigin) -> VeloraResult<Vec<CssRule>> {
+        let mut rules = self.parse_css(css)?;
+        for rule in &mut rules {
+            rule.origin = origin;
+        }
+        Ok(rules)
+    }
+    
+    /// Report a syntax error in strict mode, or log it and carry on
+    fn syntax_error(&self, position: CssSourcePosition, message: &str) -> VeloraResult<()> {
+        if self.options.strict_mode {
+            return Err(VeloraError::Parser(ParserError::Syntax {
+                position,
+                message: message.to_string(),
+            }));
+        }
+        
+        debug!("Ignoring CSS syntax error at {}: {}", position, message);
+        Ok(())
+    }
+    
+    /// Parse a declaration block body such as `color: red; margin: 0 auto`
+    ///
+    /// Malformed declarations are skipped, as in browsers.
+    pub fn parse_declarations(&self, block: &str) -> Vec<CssProperty> {
+        split_top_level(block, ';')
+            .into_iter()
+            .filter_map(|declaration| {
+                let (name, value) = declaration.split_once(':')?;
+                let name = name.trim().to_ascii_lowercase();
+                let mut value = value.trim();
+                
+                let mut important = false;
+                if let Some(index) = value.rfind('!') {
+                    if value[index + 1..].trim().eq_ignore_ascii_case("important") {
+                        important = true;
+                        value = value[..index].trim_end();
+                    }
+                }
+                
+                if name.is_empty() || value.is_empty() {
+                    return None;
+                }
+                
+                Some(CssProperty {
+                    name,
+                    value: value.to_string(),
+                    important,
+                    source_position: None,
+                })
+            })
+            .collect()
+    }
+    
     /// Parse a CSS selector string
+    ///
+    /// Compound selectors are separated in `parts` by `SelectorPart::Combinator`, left to
+    /// right as written. A malformed `:nth-child()` argument or a dangling combinator is an error.
     pub fn parse_selector(&self, selector: &str) -> VeloraResult<CssSelector> {
-        // Simplified selector parsing
-        let parts = if let Some(stripped) = selector.strip_prefix('#') {
-            vec![SelectorPart::Id(stripped.to_string())]
-        } else if let Some(stripped) = selector.strip_prefix('.') {
-            vec![SelectorPart::Class(stripped.to_string())]
-        } else if selector == "*" {
-            vec![SelectorPart::Universal]
-        } else {
-            vec![SelectorPart::Element(selector.to_string())]
-        };
+        let selector = selector.trim();
+        let mut parts = Vec::new();
+        let mut rest = selector;
+        
+        loop {
+            let (compound, tail) = parse_compound_selector(rest, selector)?;
+            if compound.is_empty() {
+                return Err(VeloraError::Parser(ParserError::CssParsing(format!("Missing compound selector in '{}'", selector))));
+            }
+            parts.extend(compound);
+            
+            let trimmed = tail.trim_start();
+            if trimmed.is_empty() {
+                break;
+            }
+            let combinator = match trimmed.chars().next() {
+                Some('>') => CombinatorType::Child,
+                Some('+') => CombinatorType::Adjacent,
+                Some('~') => CombinatorType::Sibling,
+                _ => CombinatorType::Descendant,
+            };
+            rest = match combinator {
+                CombinatorType::Descendant => trimmed,
+                _ => trimmed[1..].trim_start(),
+            };
+            parts.push(SelectorPart::Combinator(combinator));
+        }
         
         let specificity = self.calculate_specificity(&parts);
         
@@ -230,7 +407,7 @@ impl CssParser {
         for part in parts {
             match part {
                 SelectorPart::Id(_) => a += 1,
-                SelectorPart::Class(_) | SelectorPart::Attribute(_, _, _) | SelectorPart::PseudoClass(_) => b += 1,
+                SelectorPart::Class(_) | SelectorPart::Attribute(_, _, _) | SelectorPart::PseudoClass(_) | SelectorPart::NthChild(_) => b += 1,
                 SelectorPart::Element(_) | SelectorPart::PseudoElement(_) => c += 1,
                 _ => {}
             }
@@ -246,6 +423,182 @@ impl Default for CssParser {
     }
 }
 
+/// Parse a compound selector such as `li.item:nth-child(2n)` at the start of `input`,
+/// returning its parts and the text from the first combinator on
+fn parse_compound_selector<'a>(input: &'a str, selector: &str) -> VeloraResult<(Vec<SelectorPart>, &'a str)> {
+    let mut parts = Vec::new();
+    let mut rest = input;
+    
+    while let Some(first) = rest.chars().next() {
+        match first {
+            '#' => {
+                let (name, tail) = take_ident(&rest[1..]);
+                parts.push(SelectorPart::Id(name.to_string()));
+                rest = tail;
+            }
+            '.' => {
+                let (name, tail) = take_ident(&rest[1..]);
+                parts.push(SelectorPart::Class(name.to_string()));
+                rest = tail;
+            }
+            '*' => {
+                parts.push(SelectorPart::Universal);
+                rest = &rest[1..];
+            }
+            '[' => {
+                let close = rest.find(']').ok_or_else(|| {
+                    VeloraError::Parser(ParserError::CssParsing(format!("Unclosed attribute selector in '{}'", selector)))
+                })?;
+                parts.push(parse_attribute_selector(&rest[1..close]));
+                rest = &rest[close + 1..];
+            }
+            ':' if rest.starts_with("::") => {
+                let (name, tail) = take_ident(&rest[2..]);
+                parts.push(SelectorPart::PseudoElement(name.to_ascii_lowercase()));
+                rest = tail;
+            }
+            ':' => {
+                let (name, tail) = take_ident(&rest[1..]);
+                let name = name.to_ascii_lowercase();
+                rest = tail;
+                
+                let Some(arguments) = rest.strip_prefix('(') else {
+                    parts.push(SelectorPart::PseudoClass(name));
+                    continue;
+                };
+                let close = closing_paren(arguments).ok_or_else(|| {
+                    VeloraError::Parser(ParserError::CssParsing(format!("Unclosed ':{}(' in '{}'", name, selector)))
+                })?;
+                let argument = &arguments[..close];
+                rest = &arguments[close + 1..];
+                
+                if name == "nth-child" {
+                    let index = NthIndex::parse(argument).ok_or_else(|| {
+                        VeloraError::Parser(ParserError::CssParsing(format!("Invalid :nth-child argument '{}'", argument)))
+                    })?;
+                    parts.push(SelectorPart::NthChild(index));
+                } else {
+                    parts.push(SelectorPart::PseudoClass(format!("{}({})", name, argument.trim())));
+                }
+            }
+            c if c.is_whitespace() || matches!(c, '>' | '+' | '~') => break,
+            _ => {
+                let (name, tail) = take_ident(rest);
+                if name.is_empty() {
+                    return Err(VeloraError::Parser(ParserError::CssParsing(format!("Unexpected '{}' in selector '{}'", first, selector))));
+                }
+                parts.push(SelectorPart::Element(name.to_string()));
+                rest = tail;
+            }
+        }
+    }
+    
+    Ok((parts, rest))
+}
+
+/// Offset of the `)` that closes an already opened parenthesis, skipping nested pairs
+fn closing_paren(input: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (index, ch) in input.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(index),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split off a leading CSS identifier
+fn take_ident(input: &str) -> (&str, &str) {
+    let end = input
+        .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+        .unwrap_or(input.len());
+    input.split_at(end)
+}
+
+/// Parse the inside of an attribute selector such as `type="text"` or `lang|=en`
+fn parse_attribute_selector(inner: &str) -> SelectorPart {
+    let Some(equals) = inner.find('=') else {
+        return SelectorPart::Attribute(inner.trim().to_string(), None, None);
+    };
+    
+    let (name, operator) = match inner[..equals].chars().last() {
+        Some('*') => (&inner[..equals - 1], AttributeOperator::Contains),
+        Some('^') => (&inner[..equals - 1], AttributeOperator::StartsWith),
+        Some('$') => (&inner[..equals - 1], AttributeOperator::EndsWith),
+        Some('~') => (&inner[..equals - 1], AttributeOperator::ContainsWord),
+        Some('|') => (&inner[..equals - 1], AttributeOperator::ContainsPrefix),
+        _ => (&inner[..equals], AttributeOperator::Equals),
+    };
+    let value = inner[equals + 1..].trim().trim_matches(['"', '\'']);
+    
+    SelectorPart::Attribute(name.trim().to_string(), Some(value.to_string()), Some(operator))
+}
+
+/// Offset of the next character that is not whitespace or inside a comment
+fn next_significant(css: &str, mut index: usize) -> Option<usize> {
+    loop {
+        let rest = &css[index..];
+        let trimmed = rest.trim_start();
+        if trimmed.is_empty() {
+            return None;
+        }
+        index += rest.len() - trimmed.len();
+        
+        if !trimmed.starts_with("/*") {
+            return Some(index);
+        }
+        index = trimmed[2..].find("*/").map_or(css.len(), |end| index + 2 + end + 2);
+    }
+}
+
+/// Offset just past an at-rule, which ends at `;` or at its matching block
+fn skip_at_rule(css: &str, start: usize) -> usize {
+    let mut depth = 0usize;
+    for (offset, ch) in css[start..].char_indices() {
+        match ch {
+            ';' if depth == 0 => return start + offset + 1,
+            '{' => depth += 1,
+            '}' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    return start + offset + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    css.len()
+}
+
+/// Split on a separator that is not inside quotes or parentheses
+pub(crate) fn split_top_level(input: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+    
+    for (index, ch) in input.char_indices() {
+        match (quote, ch) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, c) if c == separator && depth == 0 => {
+                parts.push(&input[start..index]);
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    
+    parts
+}
+
 
 
 #[cfg(test)]
@@ -276,6 +629,72 @@ mod tests {
         assert_eq!(rule.properties.len(), 1);
     }
     
+    #[test]
+    fn test_parse_multiple_rules() {
+        let parser = CssParser::new();
+        let css = "/* reset */\n@import url(base.css);\nh1, .title { margin: 0 }\n@media print { p { color: black } }\n#main { color: red; padding: 1px 2px }";
+        
+        let rules = parser.parse_css(css).unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].selectors.len(), 2);
+        assert_eq!(rules[0].selectors[1].specificity, SelectorSpecificity { a: 0, b: 1, c: 0 });
+        assert_eq!(rules[1].properties.len(), 2);
+        
+        let position = rules[1].source_position.as_ref().unwrap();
+        assert_eq!((position.line, position.column), (5, 1));
+    }
+    
+    #[test]
+    fn test_malformed_rule_reports_line() {
+        let strict = CssParser::with_options(CssParserOptions { strict_mode: true, ..Default::default() });
+        let css = "a { color: red }\n\n  b { color: blue";
+        
+        match strict.parse_css(css) {
+            Err(VeloraError::Parser(ParserError::Syntax { position, message })) => {
+                assert_eq!(position, CssSourcePosition::new(3, 5));
+                assert_eq!(message, "unclosed '{'");
+            }
+            other => panic!("expected a syntax error, got {:?}", other),
+        }
+        
+        let error = strict.parse_css("a { color: red }\n}").unwrap_err();
+        assert_eq!(error.to_string(), "Parser error: Syntax error at line 2, column 1: unexpected '}'");
+        
+        // Lenient parsing keeps the well-formed rules
+        let rules = CssParser::new().parse_css("a { color: red }\n}\nb { color: blue").unwrap();
+        assert_eq!(rules.len(), 2);
+    }
+    
+    #[test]
+    fn test_bad_selector_skips_only_its_rule() {
+        let css = "a { color: red }\n*|* { color: green }\n.a\\:b { color: blue }\nli:not(:nth-child(2)) { color: gray }";
+        let rules = CssParser::new().parse_css(css).unwrap();
+        
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].properties[0].value, "red");
+        assert!(matches!(&rules[1].selectors[0].parts[1], SelectorPart::PseudoClass(name) if name == "not(:nth-child(2))"));
+        
+        // Strict parsing points at the rule instead
+        let strict = CssParser::with_options(CssParserOptions { strict_mode: true, ..Default::default() });
+        match strict.parse_css(css) {
+            Err(VeloraError::Parser(ParserError::Syntax { position, .. })) => assert_eq!(position, CssSourcePosition::new(2, 1)),
+            other => panic!("expected a syntax error, got {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_parse_declarations() {
+        let parser = CssParser::new();
+        let properties = parser.parse_declarations("COLOR: red ; background: url(\"a;b.png\") !important; bogus; margin:");
+        
+        assert_eq!(properties.len(), 2);
+        assert_eq!(properties[0].name, "color");
+        assert_eq!(properties[0].value, "red");
+        assert!(!properties[0].important);
+        assert_eq!(properties[1].value, "url(\"a;b.png\")");
+        assert!(properties[1].important);
+    }
+    
     #[test]
     fn test_selector_parsing() {
         let parser = CssParser::new();
@@ -303,14 +722,70 @@ mod tests {
     fn test_specificity_calculation() {
         let parser = CssParser::new();
         
-        // Test complex selector
-        let selector = "#main .header div:hover";
-        let parsed = parser.parse_selector(selector).unwrap();
+        // Every compound contributes, combinators do not
+        let parsed = parser.parse_selector("#main .header > div:hover").unwrap();
+        assert_eq!(parsed.specificity, SelectorSpecificity { a: 1, b: 2, c: 1 });
+    }
+    
+    #[test]
+    fn test_combinator_parsing() {
+        let parser = CssParser::new();
+        let selector = parser.parse_selector("ul > li + li ~ .last a").unwrap();
+        
+        assert!(matches!(selector.parts[..], [
+            SelectorPart::Element(_),
+            SelectorPart::Combinator(CombinatorType::Child),
+            SelectorPart::Element(_),
+            SelectorPart::Combinator(CombinatorType::Adjacent),
+            SelectorPart::Element(_),
+            SelectorPart::Combinator(CombinatorType::Sibling),
+            SelectorPart::Class(_),
+            SelectorPart::Combinator(CombinatorType::Descendant),
+            SelectorPart::Element(_),
+        ]));
+        
+        assert!(parser.parse_selector("ul >").is_err());
+        assert!(parser.parse_selector("> li").is_err());
+    }
+    
+    #[test]
+    fn test_nth_index_parsing() {
+        assert_eq!(NthIndex::parse("2n"), Some(NthIndex { a: 2, b: 0 }));
+        assert_eq!(NthIndex::parse("even"), Some(NthIndex { a: 2, b: 0 }));
+        assert_eq!(NthIndex::parse("ODD"), Some(NthIndex { a: 2, b: 1 }));
+        assert_eq!(NthIndex::parse(" 3n + 1 "), Some(NthIndex { a: 3, b: 1 }));
+        assert_eq!(NthIndex::parse("-n+3"), Some(NthIndex { a: -1, b: 3 }));
+        assert_eq!(NthIndex::parse("n"), Some(NthIndex { a: 1, b: 0 }));
+        assert_eq!(NthIndex::parse("5"), Some(NthIndex { a: 0, b: 5 }));
+        assert_eq!(NthIndex::parse("2n1"), None);
+        assert_eq!(NthIndex::parse("x"), None);
+        assert_eq!(NthIndex::parse(""), None);
+    }
+    
+    #[test]
+    fn test_nth_index_matching() {
+        let even = NthIndex { a: 2, b: 0 };
+        assert!(even.matches(2) && even.matches(4) && !even.matches(3));
+        
+        let first_three = NthIndex { a: -1, b: 3 };
+        assert!(first_three.matches(1) && first_three.matches(3) && !first_three.matches(4));
+        
+        let fifth = NthIndex { a: 0, b: 5 };
+        assert!(fifth.matches(5) && !fifth.matches(10));
+    }
+    
+    #[test]
+    fn test_structural_pseudo_class_parsing() {
+        let parser = CssParser::new();
+        let selector = parser.parse_selector("li.item:nth-child(2n+1)").unwrap();
+        
+        assert!(matches!(selector.parts[..], [
+            SelectorPart::Element(_),
+            SelectorPart::Class(_),
+            SelectorPart::NthChild(NthIndex { a: 2, b: 1 }),
+        ]));
+        assert_eq!(selector.specificity, SelectorSpecificity { a: 0, b: 2, c: 1 });
         
-        // This is simplified - in reality, we'd parse the full selector
-        // For now, we just test the basic functionality
-        assert!(parsed.specificity.a == 1); // Simplified parsing only gets first part (#main)
-        assert!(parsed.specificity.b == 0);
-        assert!(parsed.specificity.c == 0);
+        assert!(parser.parse_selector("li:nth-child(bogus)").is_err());
     }
 }
@@ -3,11 +3,37 @@
 //! This is a basic HTML parser that can handle simple HTML documents.
 //! TODO: Implement full HTML5 parsing using html5ever
 
-use velora_core::{VeloraResult, VeloraError, NodeId, ElementId};
+use velora_core::{VeloraResult, VeloraError, NodeId};
 use velora_core::error::ParserError;
-use velora_dom::{Document, Element, Node};
+use velora_dom::{Doctype, Document, DomTree, Element, Node, SVG_NAMESPACE};
+use crate::tokenizer::{Token, Tokenizer};
 use log::debug;
 
+/// Elements that go in `<head>` when they come before any body content
+const HEAD_ELEMENTS: &[&str] = &["base", "link", "meta", "noscript", "script", "style", "template", "title"];
+
+/// Mixed-case SVG attribute names, restored on SVG elements after the tokenizer lowercases them
+const SVG_ATTRIBUTE_NAMES: &[&str] = &[
+    "attributeName", "attributeType", "baseFrequency", "baseProfile", "calcMode", "clipPathUnits",
+    "diffuseConstant", "edgeMode", "filterUnits", "glyphRef", "gradientTransform", "gradientUnits",
+    "kernelMatrix", "kernelUnitLength", "keyPoints", "keySplines", "keyTimes", "lengthAdjust",
+    "limitingConeAngle", "markerHeight", "markerUnits", "markerWidth", "maskContentUnits", "maskUnits",
+    "numOctaves", "pathLength", "patternContentUnits", "patternTransform", "patternUnits", "pointsAtX",
+    "pointsAtY", "pointsAtZ", "preserveAlpha", "preserveAspectRatio", "primitiveUnits", "refX", "refY",
+    "repeatCount", "repeatDur", "requiredExtensions", "requiredFeatures", "specularConstant",
+    "specularExponent", "spreadMethod", "startOffset", "stdDeviation", "stitchTiles", "surfaceScale",
+    "systemLanguage", "tableValues", "targetX", "targetY", "textLength", "viewBox", "viewTarget",
+    "xChannelSelector", "yChannelSelector", "zoomAndPan",
+];
+
+/// The implied `<html>`, `<head>` and `<body>` of a parsed document
+#[derive(Debug, Clone, Copy)]
+struct DocumentParts {
+    html: NodeId,
+    head: NodeId,
+    body: NodeId,
+}
+
 /// HTML parser that converts HTML markup into DOM structures
 pub struct HtmlParser {
     /// Whether the parser is ready
@@ -24,49 +50,25 @@ impl HtmlParser {
     pub fn parse_html(&self, html: &str) -> VeloraResult<Document> {
         debug!("Parsing HTML document of {} bytes", html.len());
         
-        // Create a new document with a new NodeId
-        let document_id = NodeId(velora_core::next_id());
-        let mut document = Document::new(document_id);
+        // IDs are allocated by the document itself, so parsing is deterministic
+        let mut document = Document::default();
         
-        // Simple HTML parsing - split by tags and create basic structure
-        let html_trimmed = html.trim();
+        // A missing DOCTYPE puts the document in quirks mode
+        document.set_doctype(self.parse_doctype(html.trim_start()));
         
-        // Check if it starts with DOCTYPE
-        if html_trimmed.starts_with("<!DOCTYPE") {
-            // Skip DOCTYPE for now
-            let html_start = html_trimmed.find("<html").unwrap_or(0);
-            let html_content = &html_trimmed[html_start..];
-            
-            // Find the body content
-            if let Some(body_start) = html_content.find("<body>") {
-                let body_end = html_content.find("</body>").unwrap_or(html_content.len());
-                let body_content = &html_content[body_start + 6..body_end];
-                
-                // Parse body content
-                let body_element = self.parse_element("body", body_content)?;
-                // Add body to DOM tree
-                let dom_tree = document.get_dom_tree_mut();
-                dom_tree.add_node(body_element);
-            }
-            
-            // Find the head content
-            if let Some(head_start) = html_content.find("<head>") {
-                let head_end = html_content.find("</head>").unwrap_or(html_content.len());
-                let head_content = &html_content[head_start + 6..head_end];
-                
-                // Parse head content
-                let head_element = self.parse_element("head", head_content)?;
-                // Add head to DOM tree
-                let dom_tree = document.get_dom_tree_mut();
-                dom_tree.add_node(head_element);
-            }
-        } else {
-            // Simple content without DOCTYPE - treat as body content
-            let body_element = self.parse_element("body", html_trimmed)?;
-            // Add body to DOM tree
-            let dom_tree = document.get_dom_tree_mut();
-            dom_tree.add_node(body_element);
-        }
+        // Every document has html, head and body elements, whether or not the markup spells them out
+        let dom_tree = document.get_dom_tree_mut();
+        let html_element = dom_tree.create_element("html")?;
+        let parts = DocumentParts {
+            html: html_element,
+            head: self.insert_element(dom_tree, html_element, "head")?,
+            body: self.insert_element(dom_tree, html_element, "body")?,
+        };
+        self.build_tree(dom_tree, parts.body, html, Some(parts))?;
+        
+        // The UI decides what to show for documents without a title
+        let title = self.extract_title(&document);
+        document.set_title(title);
         
         debug!("HTML parsed successfully into document");
         Ok(document)
@@ -76,9 +78,16 @@ impl HtmlParser {
     pub fn parse_fragment(&self, html: &str, _context_element: &str) -> VeloraResult<Vec<Node>> {
         debug!("Parsing HTML fragment: {} bytes", html.len());
         
-        // For fragments, just parse as elements
-        let element = self.parse_element("div", html)?;
-        Ok(vec![element])
+        // Parse into a scratch tree and hand back its nodes in document order
+        let mut dom_tree = DomTree::new();
+        self.parse_element(&mut dom_tree, "div", html)?;
+        
+        let mut nodes = Vec::new();
+        dom_tree.traverse_dfs(|node| {
+            nodes.push(node.clone());
+            Ok(true)
+        })?;
+        Ok(nodes)
     }
     
     /// Parse HTML from a file
@@ -105,61 +114,476 @@ impl HtmlParser {
         self.parse_html(&html_string)
     }
     
-    /// Parse a simple HTML element
-    fn parse_element(&self, tag_name: &str, content: &str) -> VeloraResult<Node> {
-        let element_id = ElementId(velora_core::next_id());
-        let node_id = NodeId(velora_core::next_id());
+    /// Text of the first `<title>` element in the parsed tree, or an empty string
+    fn extract_title(&self, document: &Document) -> String {
+        let Some(title) = document.get_elements_by_tag_name("title").into_iter().next() else {
+            return String::new();
+        };
+        let dom_tree = document.get_dom_tree();
+        let text: String = dom_tree.descendants(title.id)
+            .filter(|node| node.is_text())
+            .filter_map(|node| node.node_value.as_deref())
+            .collect();
+        
+        // Collapse runs of whitespace like browsers do for document.title
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+    
+    /// Read a leading `<!DOCTYPE name [PUBLIC "public-id"] ["system-id"]>` declaration
+    fn parse_doctype(&self, html: &str) -> Option<Doctype> {
+        if !html.get(..9).is_some_and(|prefix| prefix.eq_ignore_ascii_case("<!DOCTYPE")) {
+            return None;
+        }
+        let end = html.find('>').unwrap_or(html.len());
+        let mut rest = html[9..end].trim_start();
         
-        let _element = Element::new(element_id, tag_name.to_string());
+        let name_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
         
-        // Simple text extraction - look for text between tags
-        let mut text_content = String::new();
-        let mut in_tag = false;
-        let mut current_tag = String::new();
+        // Identifiers are quoted with either kind of quote
+        let take_quoted = |rest: &mut &str| {
+            let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            let close = rest[1..].find(quote).map_or(rest.len(), |i| i + 1);
+            let value = rest[1..close].to_string();
+            *rest = rest.get(close + 1..).unwrap_or("").trim_start();
+            Some(value)
+        };
+        
+        let keyword = rest.get(..6).map(str::to_ascii_uppercase);
+        let (public_id, system_id) = match keyword.as_deref() {
+            Some("PUBLIC") => {
+                rest = rest[6..].trim_start();
+                let public_id = take_quoted(&mut rest);
+                (public_id, take_quoted(&mut rest))
+            }
+            Some("SYSTEM") => {
+                rest = rest[6..].trim_start();
+                (None, take_quoted(&mut rest))
+            }
+            _ => (None, None),
+        };
         
-        for ch in content.chars() {
-            match ch {
-                '<' => {
-                    in_tag = true;
-                    current_tag.clear();
+        Some(Doctype { name, public_id, system_id })
+    }
+    
+    /// Parse markup into a new element and its descendants, returning the element's node ID
+    fn parse_element(&self, dom_tree: &mut DomTree, tag_name: &str, content: &str) -> VeloraResult<NodeId> {
+        let root_id = dom_tree.create_element(&tag_name.to_ascii_lowercase())?;
+        self.build_tree(dom_tree, root_id, content, None)?;
+        Ok(root_id)
+    }
+    
+    /// Parse markup into the children of `root_id`
+    ///
+    /// For whole documents, `parts` holds the implied html, head and body elements. This is a
+    /// simplified form of the HTML insertion modes: metadata before any body content goes into
+    /// the head, and the first other tag or non-whitespace text starts the body. Explicit
+    /// `<html>`, `<head>` and `<body>` tags only contribute their attributes.
+    fn build_tree(&self, dom_tree: &mut DomTree, root_id: NodeId, content: &str, parts: Option<DocumentParts>) -> VeloraResult<()> {
+        // Stack of open elements; the last one receives new children
+        let mut open_elements = match parts {
+            Some(parts) => vec![parts.head],
+            None => vec![root_id],
+        };
+        let mut in_head = parts.is_some();
+        let mut current_element = None;
+        
+        let mut tokenizer = Tokenizer::new(content.to_string());
+        loop {
+            let token = tokenizer.next_token()?;
+            
+            if let Some(parts) = parts {
+                let structural = match &token {
+                    Token::TagStart(name) | Token::SelfClosingTag(name) => match name.as_str() {
+                        "html" => Some(Some(parts.html)),
+                        "head" => Some(in_head.then_some(parts.head)),
+                        "body" => Some(Some(parts.body)),
+                        _ => None,
+                    },
+                    Token::TagEnd(name) => matches!(name.as_str(), "html" | "head" | "body").then_some(None),
+                    _ => None,
+                };
+                if let Some(target) = structural {
+                    current_element = target;
+                    if target == Some(parts.body) && in_head {
+                        in_head = false;
+                        open_elements = vec![parts.body];
+                    }
+                    continue;
                 }
-                '>' => {
-                    in_tag = false;
-                    if !current_tag.starts_with('/') {
-                        // Opening tag - could add child elements here in the future
+                
+                // Body content ends the head, unless it is inside an open head element such as <title>
+                let starts_body = match &token {
+                    Token::TagStart(name) | Token::SelfClosingTag(name) => !HEAD_ELEMENTS.contains(&name.as_str()),
+                    Token::Text(text) => open_elements.len() == 1 && !text.trim().is_empty(),
+                    _ => false,
+                };
+                if in_head && starts_body {
+                    in_head = false;
+                    open_elements = vec![parts.body];
+                }
+            }
+            
+            let parent_id = *open_elements.last().unwrap_or(&root_id);
+            match token {
+                Token::Eof => break,
+                Token::TagStart(name) => {
+                    let node_id = self.insert_element(dom_tree, parent_id, &name)?;
+                    current_element = Some(node_id);
+                    
+                    // Void elements never have children, even without a self-closing slash
+                    let is_void = self.element_of(dom_tree, node_id)
+                        .is_some_and(|element| element.is_void_element());
+                    if !is_void {
+                        open_elements.push(node_id);
                     }
-                    current_tag.clear();
                 }
-                _ => {
-                    if in_tag {
-                        current_tag.push(ch);
-                    } else {
-                        text_content.push(ch);
+                Token::SelfClosingTag(name) => {
+                    current_element = Some(self.insert_element(dom_tree, parent_id, &name)?);
+                }
+                Token::Attribute(name, value) => {
+                    if let Some(node_id) = current_element {
+                        let node = dom_tree.get_node(node_id)?;
+                        if let Some(element_id) = node.element_id {
+                            let name = match node.namespace.as_deref() {
+                                Some(SVG_NAMESPACE) => svg_attribute_name(name),
+                                _ => name,
+                            };
+                            dom_tree.get_element_mut(element_id)?.set_attribute(name, value);
+                        }
+                    }
+                }
+                Token::TagEnd(name) => {
+                    current_element = None;
+                    
+                    // Close the nearest open element with this name; stray end tags are ignored
+                    let position = open_elements.iter().rposition(|&id| {
+                        dom_tree.get_node(id).is_ok_and(|node| node.node_name == name)
+                    });
+                    if let Some(position) = position.filter(|&position| position > 0) {
+                        open_elements.truncate(position);
                     }
                 }
+                Token::Comment(text) => {
+                    current_element = None;
+                    let comment_id = dom_tree.create_comment(&text)?;
+                    dom_tree.append_child(parent_id, comment_id)?;
+                }
+                Token::ProcessingInstruction(target, data) => {
+                    current_element = None;
+                    let instruction_id = dom_tree.create_processing_instruction(&target, &data)?;
+                    dom_tree.append_child(parent_id, instruction_id)?;
+                }
+                Token::Text(text) => {
+                    current_element = None;
+                    
+                    // Whitespace before the body is formatting; everywhere else it may be content
+                    if !(in_head && text.trim().is_empty()) {
+                        let text_id = dom_tree.create_text_node(&text)?;
+                        dom_tree.append_child(parent_id, text_id)?;
+                    }
+                }
+                _ => {
+                    current_element = None;
+                }
             }
         }
         
-        // Clean up text content
-        let text_content = text_content.trim();
-        
-        // Create the element node
-        let mut element_node = Node::new_element(node_id, tag_name.to_string());
-        element_node.element_id = Some(element_id);
-        
-        // Add text content if any
-        if !text_content.is_empty() {
-            let text_node_id = NodeId(velora_core::next_id());
-            let _text_node = Node::new_text(text_node_id, text_content.to_string());
-            // For now, just create the text node (in a real implementation, we'd add it to the DOM tree)
-        }
-        
-        Ok(element_node)
+        Ok(())
+    }
+    
+    /// Create an element and append it to the parent
+    ///
+    /// `<svg>` starts the SVG namespace and other elements inherit their parent's namespace.
+    fn insert_element(&self, dom_tree: &mut DomTree, parent_id: NodeId, tag_name: &str) -> VeloraResult<NodeId> {
+        let namespace = match tag_name {
+            "svg" => Some(SVG_NAMESPACE.to_string()),
+            _ => dom_tree.get_node(parent_id)?.namespace.clone(),
+        };
+        let node_id = dom_tree.create_element_ns(tag_name, namespace.as_deref())?;
+        dom_tree.append_child(parent_id, node_id)?;
+        Ok(node_id)
+    }
+    
+    /// Look up the element data for an element node
+    fn element_of<'a>(&self, dom_tree: &'a DomTree, node_id: NodeId) -> Option<&'a Element> {
+        let element_id = dom_tree.get_node(node_id).ok()?.element_id?;
+        dom_tree.get_element(element_id).ok()
     }
 }
 
+/// Restore the case of an SVG attribute name, as in the HTML spec's "adjust SVG attributes"
+fn svg_attribute_name(name: String) -> String {
+    SVG_ATTRIBUTE_NAMES.iter()
+        .find(|svg_name| svg_name.eq_ignore_ascii_case(&name))
+        .map_or(name, |svg_name| svg_name.to_string())
+}
+
 impl Default for HtmlParser {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use velora_dom::{NodeType, QuirksMode};
+    
+    #[test]
+    fn test_parse_is_deterministic() {
+        let parser = HtmlParser::new();
+        let html = "<div><p>One</p><p>Two</p></div>";
+        let first = parser.parse_html(html).unwrap();
+        let second = parser.parse_html(html).unwrap();
+        
+        let ids = |doc: &Document| doc.get_elements_by_tag_name("*").iter().map(|node| node.id).collect::<Vec<_>>();
+        assert_eq!(ids(&first), ids(&second));
+        assert_eq!(ids(&first)[0], NodeId(1));
+    }
+    
+    #[test]
+    fn test_parse_inline_svg_namespace() {
+        let parser = HtmlParser::new();
+        let document = parser.parse_html("<p>Icon: <svg><g><rect/></g><link></link></svg></p><link>").unwrap();
+        let tree = document.get_dom_tree();
+        let namespace_of = |tag: &str| {
+            document.get_elements_by_tag_name(tag).iter().map(|node| node.namespace.clone()).collect::<Vec<_>>()
+        };
+        
+        assert_eq!(namespace_of("p"), vec![None]);
+        assert_eq!(namespace_of("svg"), vec![Some(SVG_NAMESPACE.to_string())]);
+        assert_eq!(namespace_of("rect"), vec![Some(SVG_NAMESPACE.to_string())]);
+        
+        // The element data agrees with the node
+        let rect = document.get_elements_by_tag_name("rect")[0];
+        let element = tree.get_element(rect.element_id.unwrap()).unwrap();
+        assert_eq!(element.namespace.as_deref(), Some(SVG_NAMESPACE));
+        
+        // An SVG link is not an HTML void element, so it keeps its own end tag
+        let links = document.get_elements_by_tag_name("link");
+        assert_eq!(links.len(), 2);
+        let svg = document.get_elements_by_tag_name("svg")[0];
+        assert_eq!(links[0].parent_id, Some(svg.id));
+        assert_eq!(links[1].namespace, None);
+    }
+    
+    #[test]
+    fn test_parse_svg_attribute_case() {
+        let parser = HtmlParser::new();
+        let document = parser.parse_html(
+            r#"<svg viewBox="0 0 10 10" preserveAspectRatio="none"><rect pathlength="2" fill="red"/></svg><div viewBox="x"></div>"#
+        ).unwrap();
+        let tree = document.get_dom_tree();
+        let element = |tag: &str| {
+            let node = document.get_elements_by_tag_name(tag)[0];
+            tree.get_element(node.element_id.unwrap()).unwrap()
+        };
+        
+        let svg = element("svg");
+        assert_eq!(svg.get_attribute("viewBox"), Some("0 0 10 10"));
+        assert_eq!(svg.get_attribute("preserveAspectRatio"), Some("none"));
+        
+        // The table applies whatever case the source used; other attributes stay lowercase
+        let rect = element("rect");
+        assert_eq!(rect.get_attribute("pathLength"), Some("2"));
+        assert_eq!(rect.get_attribute("fill"), Some("red"));
+        
+        // HTML attributes are lowercased as before
+        assert_eq!(element("div").get_attribute("viewbox"), Some("x"));
+    }
+    
+    #[test]
+    fn test_doctype_selects_quirks_mode() {
+        let parser = HtmlParser::new();
+        
+        let document = parser.parse_html("<!DOCTYPE html><html><body><p>Hi</p></body></html>").unwrap();
+        assert_eq!(document.quirks_mode(), QuirksMode::NoQuirks);
+        assert_eq!(document.doctype(), Some(&Doctype { name: "html".to_string(), public_id: None, system_id: None }));
+        
+        let document = parser.parse_html("<p>Hi</p>").unwrap();
+        assert_eq!(document.quirks_mode(), QuirksMode::Quirks);
+        assert_eq!(document.doctype(), None);
+    }
+    
+    #[test]
+    fn test_legacy_doctype_identifiers() {
+        let parser = HtmlParser::new();
+        let transitional = "<!DOCTYPE HTML PUBLIC \"-//W3C//DTD HTML 4.01 Transitional//EN\"";
+        
+        let document = parser.parse_html(&format!("{}><html><body></body></html>", transitional)).unwrap();
+        let doctype = document.doctype().unwrap();
+        assert_eq!(doctype.public_id.as_deref(), Some("-//W3C//DTD HTML 4.01 Transitional//EN"));
+        assert_eq!(doctype.system_id, None);
+        assert_eq!(document.quirks_mode(), QuirksMode::Quirks);
+        
+        // The same public ID with a system ID only gets limited quirks
+        let html = format!("{} 'http://www.w3.org/TR/html4/loose.dtd'><html><body></body></html>", transitional);
+        let document = parser.parse_html(&html).unwrap();
+        assert_eq!(document.doctype().unwrap().system_id.as_deref(), Some("http://www.w3.org/TR/html4/loose.dtd"));
+        assert_eq!(document.quirks_mode(), QuirksMode::LimitedQuirks);
+    }
+    
+    #[test]
+    fn test_parse_keeps_comments_in_tree_order() {
+        let parser = HtmlParser::new();
+        let document = parser.parse_html("<div>Before <!-- a note --><span>After</span><?pi data?></div>").unwrap();
+        let tree = document.get_dom_tree();
+        
+        let div = document.get_elements_by_tag_name("div")[0];
+        let children: Vec<_> = div.child_ids.iter().map(|&id| tree.get_node(id).unwrap()).collect();
+        let names: Vec<&str> = children.iter().map(|node| node.node_name.as_str()).collect();
+        assert_eq!(names, vec!["#text", "#comment", "span", "pi"]);
+        
+        assert!(children[1].is_comment());
+        assert_eq!(children[1].node_value.as_deref(), Some(" a note "));
+        assert_eq!(children[3].node_type, NodeType::ProcessingInstruction);
+        assert_eq!(children[3].node_value.as_deref(), Some("data"));
+        
+        // Comments are not part of the rendered text
+        assert_eq!(children[0].node_value.as_deref(), Some("Before "));
+        assert_eq!(document.inner_text(), "Before After");
+    }
+    
+    #[test]
+    fn test_parse_keeps_whitespace_in_body_text() {
+        let parser = HtmlParser::new();
+        let document = parser.parse_html("<html>\n  <head>\n    <title>T</title>\n  </head>\n<p>Hello <b>world</b></p><pre>  a\n   b</pre>").unwrap();
+        let tree = document.get_dom_tree();
+        
+        // Formatting whitespace before the body is dropped
+        let head = document.head().unwrap();
+        assert_eq!(head.child_ids.len(), 1);
+        
+        let text_of = |tag: &str| {
+            let node = document.get_elements_by_tag_name(tag)[0];
+            tree.get_node(node.child_ids[0]).unwrap().node_value.clone()
+        };
+        assert_eq!(text_of("p").as_deref(), Some("Hello "));
+        assert_eq!(text_of("pre").as_deref(), Some("  a\n   b"));
+        assert_eq!(document.inner_text(), "Hello world\n  a\n   b");
+    }
+    
+    #[test]
+    fn test_bare_content_gets_implied_structure() {
+        let parser = HtmlParser::new();
+        let document = parser.parse_html("<p>Hello</p>").unwrap();
+        let tree = document.get_dom_tree();
+        
+        let root = tree.get_root().unwrap();
+        assert_eq!(root.node_name, "html");
+        let children: Vec<_> = root.child_ids.iter().map(|&id| tree.get_node(id).unwrap().node_name.clone()).collect();
+        assert_eq!(children, vec!["head", "body"]);
+        
+        let paragraph = document.get_elements_by_tag_name("p")[0];
+        assert_eq!(paragraph.parent_id, Some(document.body().unwrap().id));
+        assert!(document.head().unwrap().child_ids.is_empty());
+    }
+    
+    #[test]
+    fn test_metadata_goes_to_head() {
+        let parser = HtmlParser::new();
+        let html = "<title>Stray</title><meta charset=utf-8><html lang=en><p>Text</p><link rel=stylesheet href=a.css>";
+        let document = parser.parse_html(html).unwrap();
+        let parent_of = |tag: &str| {
+            let node = document.get_elements_by_tag_name(tag)[0];
+            document.get_dom_tree().get_node(node.parent_id.unwrap()).unwrap().node_name.clone()
+        };
+        
+        assert_eq!(parent_of("title"), "head");
+        assert_eq!(parent_of("meta"), "head");
+        assert_eq!(parent_of("p"), "body");
+        // Once the body has started, metadata stays where it appears
+        assert_eq!(parent_of("link"), "body");
+        
+        let html_element = document.document_element().unwrap().element_id.unwrap();
+        assert_eq!(document.get_dom_tree().get_element(html_element).unwrap().get_attribute("lang"), Some("en"));
+        assert_eq!(document.title(), Some("Stray"));
+    }
+    
+    #[test]
+    fn test_parse_title() {
+        let parser = HtmlParser::new();
+        let html = "<!DOCTYPE html><html><head><TITLE lang=\"en\">  Hello\n   World </TITLE></head><body><p>Hi</p></body></html>";
+        let document = parser.parse_html(html).unwrap();
+        
+        assert_eq!(document.title(), Some("Hello World"));
+    }
+    
+    #[test]
+    fn test_parse_without_title() {
+        let parser = HtmlParser::new();
+        let document = parser.parse_html("<!DOCTYPE html><html><head></head><body><h1>Heading</h1></body></html>").unwrap();
+        
+        assert_eq!(document.title(), Some(""));
+    }
+    
+    #[test]
+    fn test_title_comes_from_the_title_element() {
+        let parser = HtmlParser::new();
+        let html = "<!DOCTYPE html><head><!-- <title>Old</title> --><title>New</title></head>";
+        let document = parser.parse_html(html).unwrap();
+        
+        assert_eq!(document.title(), Some("New"));
+    }
+    
+    #[test]
+    fn test_parse_normalizes_tag_names() {
+        let parser = HtmlParser::new();
+        let document = parser.parse_html("<DIV Class=\"outer\"><Div>Nested</Div></DIV><span>Text</span>").unwrap();
+        
+        let divs = document.get_elements_by_tag_name("div");
+        assert_eq!(divs.len(), 2);
+        assert!(divs.iter().all(|node| node.node_name == "div"));
+        
+        // Lookup is case-insensitive and follows document order
+        assert_eq!(document.get_elements_by_tag_name("DIV").len(), 2);
+        let outer = divs[0];
+        assert_eq!(outer.child_ids, vec![divs[1].id]);
+        
+        let element = document.get_dom_tree().get_element(outer.element_id.unwrap()).unwrap();
+        assert_eq!(element.get_attribute("class"), Some("outer"));
+    }
+    
+    #[test]
+    fn test_parse_builds_document_tree() {
+        let parser = HtmlParser::new();
+        let html = "<!DOCTYPE html><html><head><link rel=icon href=\"/icon.png\"></head><body><p>One<br>Two</p></body></html>";
+        let document = parser.parse_html(html).unwrap();
+        
+        let root = document.get_dom_tree().get_root().unwrap();
+        assert_eq!(root.node_name, "html");
+        
+        let paragraph = document.get_elements_by_tag_name("p")[0];
+        let children: Vec<_> = paragraph.child_ids.iter()
+            .map(|&id| document.get_dom_tree().get_node(id).unwrap().node_name.clone())
+            .collect();
+        assert_eq!(children, vec!["#text", "br", "#text"]);
+        
+        assert_eq!(document.get_elements_by_tag_name("link").len(), 1);
+        assert_eq!(document.get_elements_by_tag_name("*").len(), 6);
+    }
+    
+    #[test]
+    fn test_parse_broken_tag_fails() {
+        let parser = HtmlParser::new();
+        let result = parser.parse_html("<p>Hello</p><img src=\"a.png\"");
+        
+        match result {
+            Err(VeloraError::Parser(ParserError::UnexpectedToken { found, position, .. })) => {
+                assert_eq!(found, "end of input");
+                assert_eq!((position.line, position.column), (1, 13));
+            }
+            other => panic!("expected an unexpected-token error, got {:?}", other.map(|_| ())),
+        }
+        
+        // Positions count from the start of the input, leading blank lines included
+        match parser.parse_html("\n\n  <p>Hello</p>\n  <img src=\"a.png\"") {
+            Err(VeloraError::Parser(ParserError::UnexpectedToken { position, .. })) => {
+                assert_eq!((position.line, position.column), (4, 3));
+            }
+            other => panic!("expected an unexpected-token error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Inline `style` attribute support for DOM elements
+
+use velora_dom::Element;
+use crate::css::{CssParser, CssProperty};
+
+/// Access to an element's inline style as parsed declarations
+///
+/// Lives in the parser crate because the DOM crate cannot depend on the CSS parser.
+pub trait InlineStyle {
+    /// Parse the `style` attribute into declarations, in source order
+    fn style_properties(&self) -> Vec<CssProperty>;
+    
+    /// Set one declaration, rewriting the `style` attribute; an empty value removes it
+    fn set_style_property(&mut self, name: &str, value: &str);
+}
+
+impl InlineStyle for Element {
+    fn style_properties(&self) -> Vec<CssProperty> {
+        self.get_style()
+            .map(|style| CssParser::new().parse_declarations(style))
+            .unwrap_or_default()
+    }
+    
+    fn set_style_property(&mut self, name: &str, value: &str) {
+        let name = name.trim().to_ascii_lowercase();
+        let value = value.trim();
+        
+        let mut properties = self.style_properties();
+        let existing = properties.iter().position(|property| property.name == name);
+        
+        match (existing, value.is_empty()) {
+            (Some(index), true) => {
+                properties.remove(index);
+            }
+            (Some(index), false) => {
+                properties[index].value = value.to_string();
+                properties[index].important = false;
+            }
+            (None, false) => properties.push(CssProperty {
+                name,
+                value: value.to_string(),
+                important: false,
+                source_position: None,
+            }),
+            (None, true) => {}
+        }
+        
+        if properties.is_empty() {
+            self.remove_attribute("style");
+            return;
+        }
+        
+        let style = properties
+            .iter()
+            .map(|property| {
+                let important = if property.important { " !important" } else { "" };
+                format!("{}: {}{}", property.name, property.value, important)
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        self.set_attribute("style".to_string(), style);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use velora_core::ElementId;
+    
+    fn styled_element(style: &str) -> Element {
+        let mut element = Element::new(ElementId(1), "div".to_string());
+        element.set_attribute("style".to_string(), style.to_string());
+        element
+    }
+    
+    #[test]
+    fn test_style_properties() {
+        let element = styled_element("color: red; margin: 0 auto");
+        let properties = element.style_properties();
+        
+        assert_eq!(properties.len(), 2);
+        assert_eq!((properties[0].name.as_str(), properties[0].value.as_str()), ("color", "red"));
+        assert_eq!((properties[1].name.as_str(), properties[1].value.as_str()), ("margin", "0 auto"));
+        
+        assert!(Element::new(ElementId(2), "p".to_string()).style_properties().is_empty());
+    }
+    
+    #[test]
+    fn test_set_style_property_round_trip() {
+        let mut element = styled_element("color: red; margin: 0 auto");
+        
+        element.set_style_property("color", "blue");
+        element.set_style_property("padding", "4px");
+        assert_eq!(element.get_style(), Some("color: blue; margin: 0 auto; padding: 4px"));
+        assert_eq!(element.get_attribute("style"), element.get_style());
+        
+        let reparsed = element.style_properties();
+        assert_eq!(reparsed.len(), 3);
+        assert_eq!(reparsed[0].value, "blue");
+        
+        element.set_style_property("margin", "");
+        assert_eq!(element.get_style(), Some("color: blue; padding: 4px"));
+        
+        element.set_style_property("color", "");
+        element.set_style_property("padding", "");
+        assert_eq!(element.get_style(), None);
+        assert!(!element.has_attribute("style"));
+    }
+}
@@ -5,15 +5,24 @@
 
 pub mod html;
 pub mod css;
+pub mod cascade;
+pub mod matching;
+pub mod inline_style;
+pub mod shorthand;
+pub mod style;
 pub mod tokenizer;
 
 pub use html::HtmlParser;
-pub use css::{CssParser, CssRule, CssSelector};
+pub use css::{CascadeOrigin, CssParser, CssRule, CssSelector, NthIndex};
 pub use tokenizer::Tokenizer;
+pub use inline_style::InlineStyle;
+pub use style::{ComputedStyle, StyleEngine};
 
 /// Re-export commonly used items for convenience
 pub mod prelude {
     pub use super::html::HtmlParser;
-    pub use super::css::{CssParser, CssRule, CssSelector};
+    pub use super::css::{CascadeOrigin, CssParser, CssRule, CssSelector, NthIndex};
     pub use super::tokenizer::Tokenizer;
+    pub use super::inline_style::InlineStyle;
+    pub use super::style::{ComputedStyle, StyleEngine};
 }
@@ -0,0 +1,231 @@
+//! Selector matching against the DOM tree
+//!
+//! Matches the selectors produced by `CssParser::parse_selector` right to left, walking
+//! ancestors for descendant and child combinators and earlier siblings for `+` and `~`.
+//! Structural pseudo-classes look at the node's position among its parent's element
+//! children, and `:hover`, `:focus` and `:active` consult an `InteractionState`.
+
+use velora_core::NodeId;
+use velora_dom::{DomTree, Element, InteractionState, Node};
+use crate::css::{AttributeOperator, CombinatorType, CssSelector, SelectorPart};
+
+impl CssSelector {
+    /// Check if the selector matches an element node of the tree, with nothing hovered or focused
+    pub fn matches(&self, tree: &DomTree, node_id: NodeId) -> bool {
+        self.matches_with_state(tree, node_id, &InteractionState::default())
+    }
+    
+    /// Check if the selector matches an element node, using the interaction state for dynamic pseudo-classes
+    pub fn matches_with_state(&self, tree: &DomTree, node_id: NodeId, state: &InteractionState) -> bool {
+        matches_from(&self.parts, tree, node_id, state)
+    }
+}
+
+/// Match `parts` with its last compound anchored at `node_id`, then the rest against
+/// the ancestors or earlier siblings its combinator selects
+fn matches_from(parts: &[SelectorPart], tree: &DomTree, node_id: NodeId, state: &InteractionState) -> bool {
+    let split = parts.iter().rposition(|part| matches!(part, SelectorPart::Combinator(_)));
+    let compound = &parts[split.map_or(0, |index| index + 1)..];
+    if !matches_compound(compound, tree, node_id, state) {
+        return false;
+    }
+    
+    let Some(index) = split else {
+        return true;
+    };
+    let SelectorPart::Combinator(combinator) = &parts[index] else {
+        return false;
+    };
+    let rest = &parts[..index];
+    
+    match combinator {
+        CombinatorType::Child => parent_element(tree, node_id).is_some_and(|parent| matches_from(rest, tree, parent, state)),
+        CombinatorType::Descendant => std::iter::successors(parent_element(tree, node_id), |&id| parent_element(tree, id))
+            .any(|ancestor| matches_from(rest, tree, ancestor, state)),
+        CombinatorType::Adjacent => previous_siblings(tree, node_id).into_iter().next().is_some_and(|sibling| matches_from(rest, tree, sibling, state)),
+        CombinatorType::Sibling => previous_siblings(tree, node_id).into_iter().any(|sibling| matches_from(rest, tree, sibling, state)),
+    }
+}
+
+/// Check every simple selector of one compound against an element node
+fn matches_compound(compound: &[SelectorPart], tree: &DomTree, node_id: NodeId, state: &InteractionState) -> bool {
+    let Ok(node) = tree.get_node(node_id) else {
+        return false;
+    };
+    let Some(element) = node.element_id.and_then(|id| tree.get_element(id).ok()) else {
+        return false;
+    };
+    
+    compound.iter().all(|part| matches_part(part, tree, node, element, state))
+}
+
+/// The parent of a node, if it is an element
+fn parent_element(tree: &DomTree, node_id: NodeId) -> Option<NodeId> {
+    let parent = tree.get_node(tree.get_node(node_id).ok()?.parent_id?).ok()?;
+    parent.is_element().then_some(parent.id)
+}
+
+/// The element siblings before a node, nearest first
+fn previous_siblings(tree: &DomTree, node_id: NodeId) -> Vec<NodeId> {
+    let Some(parent) = tree.get_node(node_id).ok().and_then(|node| tree.get_node(node.parent_id?).ok()) else {
+        return Vec::new();
+    };
+    
+    let position = parent.child_ids.iter().position(|&id| id == node_id).unwrap_or(0);
+    parent.child_ids[..position]
+        .iter()
+        .rev()
+        .copied()
+        .filter(|&id| tree.get_node(id).is_ok_and(|sibling| sibling.is_element()))
+        .collect()
+}
+
+/// Check a single simple selector
+fn matches_part(part: &SelectorPart, tree: &DomTree, node: &Node, element: &Element, state: &InteractionState) -> bool {
+    match part {
+        SelectorPart::Universal => true,
+        SelectorPart::Element(name) => node.node_name.eq_ignore_ascii_case(name),
+        SelectorPart::Id(id) => element.element_id.as_deref() == Some(id.as_str()),
+        SelectorPart::Class(class) => element.classes.iter().any(|c| c == class),
+        SelectorPart::Attribute(name, value, operator) => {
+            let Some(actual) = element.get_attribute(name) else {
+                return false;
+            };
+            match (value, operator) {
+                (Some(value), Some(operator)) => matches_attribute(actual, value, operator),
+                _ => true,
+            }
+        }
+        SelectorPart::NthChild(index) => sibling_position(tree, node).is_some_and(|(index_of, _)| index.matches(index_of)),
+        SelectorPart::PseudoClass(name) => match name.as_str() {
+            "first-child" => sibling_position(tree, node).is_some_and(|(index, _)| index == 1),
+            "last-child" => sibling_position(tree, node).is_some_and(|(index, count)| index == count),
+            "only-child" => sibling_position(tree, node).is_some_and(|(_, count)| count == 1),
+            "hover" => state.is_hovered(node.id),
+            "focus" => state.is_focused(node.id),
+            "active" => state.is_active(node.id),
+            _ => false,
+        },
+        SelectorPart::PseudoElement(_) | SelectorPart::Combinator(_) => false,
+    }
+}
+
+/// Compare an attribute value using a selector operator
+fn matches_attribute(actual: &str, value: &str, operator: &AttributeOperator) -> bool {
+    match operator {
+        AttributeOperator::Equals => actual == value,
+        AttributeOperator::Contains => !value.is_empty() && actual.contains(value),
+        AttributeOperator::StartsWith => !value.is_empty() && actual.starts_with(value),
+        AttributeOperator::EndsWith => !value.is_empty() && actual.ends_with(value),
+        AttributeOperator::ContainsWord => actual.split_whitespace().any(|word| word == value),
+        AttributeOperator::ContainsPrefix => actual == value || actual.starts_with(&format!("{}-", value)),
+    }
+}
+
+/// The node's 1-based index among its parent's element children, and how many there are
+fn sibling_position(tree: &DomTree, node: &Node) -> Option<(usize, usize)> {
+    let parent = tree.get_node(node.parent_id?).ok()?;
+    let siblings: Vec<NodeId> = parent.child_ids
+        .iter()
+        .copied()
+        .filter(|&id| tree.get_node(id).is_ok_and(|sibling| sibling.is_element()))
+        .collect();
+    
+    let index = siblings.iter().position(|&id| id == node.id)?;
+    Some((index + 1, siblings.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CssParser, HtmlParser};
+    use velora_dom::Document;
+    
+    fn matching_text(document: &Document, tag: &str, selector: &str) -> Vec<String> {
+        let selector = CssParser::new().parse_selector(selector).unwrap();
+        let tree = document.get_dom_tree();
+        document.get_elements_by_tag_name(tag)
+            .into_iter()
+            .filter(|node| selector.matches(tree, node.id))
+            .map(|node| tree.descendants(node.id).filter_map(|n| n.node_value.clone()).collect())
+            .collect()
+    }
+    
+    #[test]
+    fn test_nth_child_even() {
+        let document = HtmlParser::new()
+            .parse_html("<ul>text<li>1</li><!-- note --><li>2</li><li>3</li><li>4</li><li>5</li></ul>")
+            .unwrap();
+        
+        assert_eq!(matching_text(&document, "li", "li:nth-child(2n)"), vec!["2", "4"]);
+        assert_eq!(matching_text(&document, "li", "li:nth-child(odd)"), vec!["1", "3", "5"]);
+        assert_eq!(matching_text(&document, "li", ":nth-child(-n+2)"), vec!["1", "2"]);
+    }
+    
+    #[test]
+    fn test_first_and_last_child() {
+        let document = HtmlParser::new().parse_html("<ul><li>a</li><li>b</li><li>c</li></ul><p><em>only</em></p>").unwrap();
+        
+        assert_eq!(matching_text(&document, "li", "li:first-child"), vec!["a"]);
+        assert_eq!(matching_text(&document, "li", "li:last-child"), vec!["c"]);
+        assert_eq!(matching_text(&document, "em", "em:first-child:last-child"), vec!["only"]);
+    }
+    
+    #[test]
+    fn test_compound_selector_parts() {
+        let document = HtmlParser::new()
+            .parse_html("<li class=\"item odd\" id=\"first\">x</li><li class=\"item\" data-kind=\"even-row\">y</li>")
+            .unwrap();
+        
+        assert_eq!(matching_text(&document, "li", "li.item.odd"), vec!["x"]);
+        assert_eq!(matching_text(&document, "li", "#first"), vec!["x"]);
+        assert_eq!(matching_text(&document, "li", "[data-kind^=even]"), vec!["y"]);
+        assert_eq!(matching_text(&document, "li", "li:hover"), Vec::<String>::new());
+    }
+    
+    #[test]
+    fn test_hover_state_matches_pseudo_class() {
+        let document = HtmlParser::new()
+            .parse_html("<div class=\"feature-card\"><span>Fast</span></div><div class=\"feature-card\">Safe</div>")
+            .unwrap();
+        let tree = document.get_dom_tree();
+        let selector = CssParser::new().parse_selector(".feature-card:hover").unwrap();
+        let cards = document.get_elements_by_tag_name("div");
+        let span = document.get_elements_by_tag_name("span")[0].id;
+        
+        let mut state = InteractionState::new();
+        assert!(!selector.matches_with_state(tree, cards[0].id, &state));
+        
+        // Hovering the span inside the first card hovers the card too
+        state.set_hover_target(tree, Some(span));
+        assert!(selector.matches_with_state(tree, cards[0].id, &state));
+        assert!(!selector.matches_with_state(tree, cards[1].id, &state));
+        
+        state.set_hover_target(tree, None);
+        assert!(!selector.matches_with_state(tree, cards[0].id, &state));
+    }
+    
+    #[test]
+    fn test_descendant_and_child_combinators() {
+        let document = HtmlParser::new()
+            .parse_html("<div class=\"hero\"><h1>a</h1><section><h1>b</h1></section></div><h1>c</h1>")
+            .unwrap();
+        
+        assert_eq!(matching_text(&document, "h1", ".hero h1"), vec!["a", "b"]);
+        assert_eq!(matching_text(&document, "h1", ".hero > h1"), vec!["a"]);
+        assert_eq!(matching_text(&document, "h1", "div section h1"), vec!["b"]);
+        // The compound left of a combinator never matches the subject itself
+        assert_eq!(matching_text(&document, "div", ".hero h1"), Vec::<String>::new());
+    }
+    
+    #[test]
+    fn test_sibling_combinators() {
+        let document = HtmlParser::new()
+            .parse_html("<ul><li class=\"a\">1</li><!-- gap --><li>2</li><li>3</li></ul>")
+            .unwrap();
+        
+        assert_eq!(matching_text(&document, "li", ".a + li"), vec!["2"]);
+        assert_eq!(matching_text(&document, "li", ".a ~ li"), vec!["2", "3"]);
+        assert_eq!(matching_text(&document, "li", "li + li + li"), vec!["3"]);
+    }
+}
@@ -0,0 +1,302 @@
+//! Shorthand property expansion for the Velora web engine
+
+use velora_core::CssValue;
+use velora_core::utils::css;
+use crate::css::{split_top_level, CssProperty};
+
+/// Keywords accepted by `border-style`
+const BORDER_STYLES: &[&str] = &[
+    "none", "hidden", "dotted", "dashed", "solid", "double", "groove", "ridge", "inset", "outset",
+];
+
+/// Keywords accepted by `border-width`
+const BORDER_WIDTHS: &[&str] = &["thin", "medium", "thick"];
+
+/// Keywords accepted by `font-size`
+const FONT_SIZES: &[&str] = &[
+    "xx-small", "x-small", "small", "medium", "large", "x-large", "xx-large", "smaller", "larger",
+];
+
+/// Keywords accepted by `font-style`
+const FONT_STYLES: &[&str] = &["italic", "oblique"];
+
+/// Keywords accepted by `font-weight`
+const FONT_WEIGHTS: &[&str] = &["bold", "bolder", "lighter"];
+
+/// Replace a shorthand declaration with its longhands
+///
+/// Omitted components get their initial values. Other properties come back unchanged,
+/// and a shorthand that does not parse expands to nothing, so it is dropped like browsers do.
+pub fn expand(property: &CssProperty) -> Vec<CssProperty> {
+    let longhands = match property.name.as_str() {
+        "background" => expand_background(&property.value),
+        "border" => expand_border(&property.value),
+        "font" => expand_font(&property.value),
+        _ => return vec![property.clone()],
+    };
+    
+    longhands
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, value)| CssProperty {
+            name: name.to_string(),
+            value,
+            important: property.important,
+            source_position: property.source_position.clone(),
+        })
+        .collect()
+}
+
+/// Expand every shorthand in a declaration list, keeping declaration order
+pub fn expand_all(properties: Vec<CssProperty>) -> Vec<CssProperty> {
+    properties.iter().flat_map(expand).collect()
+}
+
+/// Take the color out of `background`
+///
+/// Images, positions and repeats are not supported and are ignored.
+fn expand_background(value: &str) -> Option<Vec<(&'static str, String)>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    
+    let color = components(value).into_iter().find(|token| is_background_color(token)).map(str::to_string);
+    Some(vec![("background-color", color.unwrap_or_else(|| "transparent".to_string()))])
+}
+
+/// Split `border: <width> || <style> || <color>`
+fn expand_border(value: &str) -> Option<Vec<(&'static str, String)>> {
+    let (mut width, mut style, mut color) = (None, None, None);
+    
+    for token in components(value) {
+        let lowered = token.to_ascii_lowercase();
+        let slot = if BORDER_WIDTHS.contains(&lowered.as_str()) || is_length(&lowered) {
+            &mut width
+        } else if BORDER_STYLES.contains(&lowered.as_str()) {
+            &mut style
+        } else if is_color(&lowered) {
+            &mut color
+        } else {
+            return None;
+        };
+        
+        // Each component may appear only once
+        if slot.replace(token.to_string()).is_some() {
+            return None;
+        }
+    }
+    
+    if width.is_none() && style.is_none() && color.is_none() {
+        return None;
+    }
+    
+    Some(vec![
+        ("border-width", width.unwrap_or_else(|| "medium".to_string())),
+        ("border-style", style.unwrap_or_else(|| "none".to_string())),
+        ("border-color", color.unwrap_or_else(|| "currentcolor".to_string())),
+    ])
+}
+
+/// Split `font: [<style> || <variant> || <weight>] <size>[/<line-height>] <family>`
+fn expand_font(value: &str) -> Option<Vec<(&'static str, String)>> {
+    let (mut style, mut variant, mut weight) = (None, None, None);
+    let mut rest = value.trim_start();
+    
+    // Optional keywords come first, then the size, which is required
+    let (size, line_height) = loop {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (token, remainder) = rest.split_at(end);
+        let lowered = token.to_ascii_lowercase();
+        rest = remainder.trim_start();
+        
+        if let Some((size, line_height)) = parse_font_size(token, &mut rest) {
+            break (size, line_height);
+        }
+        
+        let slot = match lowered.as_str() {
+            "normal" => continue,
+            keyword if FONT_STYLES.contains(&keyword) => &mut style,
+            "small-caps" => &mut variant,
+            keyword if FONT_WEIGHTS.contains(&keyword) || is_numeric_weight(keyword) => &mut weight,
+            _ => return None,
+        };
+        if slot.replace(token.to_string()).is_some() {
+            return None;
+        }
+    };
+    
+    let family = rest.trim();
+    if family.is_empty() {
+        return None;
+    }
+    
+    Some(vec![
+        ("font-style", style.unwrap_or_else(|| "normal".to_string())),
+        ("font-variant", variant.unwrap_or_else(|| "normal".to_string())),
+        ("font-weight", weight.unwrap_or_else(|| "normal".to_string())),
+        ("font-size", size),
+        ("line-height", line_height.unwrap_or_else(|| "normal".to_string())),
+        ("font-family", family.to_string()),
+    ])
+}
+
+/// Read `<size>[/<line-height>]` starting at `token`, consuming a spaced-out line height from `rest`
+fn parse_font_size(token: &str, rest: &mut &str) -> Option<(String, Option<String>)> {
+    let (size, attached) = match token.split_once('/') {
+        Some((size, line_height)) => (size, Some(line_height)),
+        None => (token, None),
+    };
+    let lowered = size.to_ascii_lowercase();
+    if !FONT_SIZES.contains(&lowered.as_str()) && !is_length(&lowered) {
+        return None;
+    }
+    
+    let line_height = match attached {
+        Some("") => take_token(rest),
+        Some(line_height) => Some(line_height.to_string()),
+        None => rest.strip_prefix('/').and_then(|after| {
+            *rest = after.trim_start();
+            take_token(rest)
+        }),
+    };
+    Some((size.to_string(), line_height))
+}
+
+/// Take the next whitespace-separated token from `rest`
+fn take_token(rest: &mut &str) -> Option<String> {
+    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let (token, remainder) = rest.split_at(end);
+    *rest = remainder.trim_start();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// Whether a token is a length or percentage (a bare number only if it is zero)
+fn is_length(token: &str) -> bool {
+    match css::parse_length(token) {
+        Some(CssValue::Length(..) | CssValue::Percentage(_)) => true,
+        Some(CssValue::Number(number)) => number == 0.0,
+        _ => false,
+    }
+}
+
+/// Split a shorthand value on spaces, keeping functions such as `rgb(0, 0, 0)` whole
+fn components(value: &str) -> Vec<&str> {
+    split_top_level(value, ' ')
+        .into_iter()
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+/// Whether a token is a numeric `font-weight` such as `700`
+fn is_numeric_weight(token: &str) -> bool {
+    token.parse::<u16>().is_ok_and(|weight| (1..=1000).contains(&weight))
+}
+
+/// Whether a `background` component is a color, unlike keywords such as `repeat` or `center`
+fn is_background_color(token: &str) -> bool {
+    matches!(css::parse_color(token), Some(CssValue::Color(_)))
+        || token.eq_ignore_ascii_case("transparent")
+        || token.eq_ignore_ascii_case("currentcolor")
+}
+
+/// Whether a token looks like a color: a parsable color or a color keyword
+fn is_color(token: &str) -> bool {
+    css::parse_color(token).is_some()
+        || token == "currentcolor"
+        || token.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    
+    fn longhands(name: &str, value: &str) -> HashMap<String, String> {
+        let property = CssProperty {
+            name: name.to_string(),
+            value: value.to_string(),
+            important: false,
+            source_position: None,
+        };
+        expand(&property).into_iter().map(|property| (property.name, property.value)).collect()
+    }
+    
+    fn map(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+    
+    #[test]
+    fn test_expand_border() {
+        assert_eq!(longhands("border", "1px solid red"), map(&[
+            ("border-width", "1px"),
+            ("border-style", "solid"),
+            ("border-color", "red"),
+        ]));
+        
+        // Components may come in any order, and omitted ones take their initial values
+        assert_eq!(longhands("border", "dashed #333"), map(&[
+            ("border-width", "medium"),
+            ("border-style", "dashed"),
+            ("border-color", "#333"),
+        ]));
+        
+        assert_eq!(longhands("border", "1px solid rgb(0, 0, 0)"), map(&[
+            ("border-width", "1px"),
+            ("border-style", "solid"),
+            ("border-color", "rgb(0, 0, 0)"),
+        ]));
+        assert_eq!(longhands("border", "hsl(120, 100%, 50%) thick")["border-color"], "hsl(120, 100%, 50%)");
+        
+        assert!(longhands("border", "1px 2px solid").is_empty());
+        assert!(longhands("border", "").is_empty());
+    }
+    
+    #[test]
+    fn test_expand_background() {
+        assert_eq!(longhands("background", "#ff0000"), map(&[("background-color", "#ff0000")]));
+        assert_eq!(longhands("background", "rgb(1, 2, 3)"), map(&[("background-color", "rgb(1, 2, 3)")]));
+        assert_eq!(longhands("background", "url(bg.png) no-repeat center blue"), map(&[("background-color", "blue")]));
+        assert_eq!(longhands("background", "url(\"a b.png\") rgba(0, 0, 0, 0.5)"), map(&[("background-color", "rgba(0, 0, 0, 0.5)")]));
+        assert_eq!(longhands("background", "none"), map(&[("background-color", "transparent")]));
+        assert!(longhands("background", " ").is_empty());
+    }
+    
+    #[test]
+    fn test_expand_font() {
+        assert_eq!(longhands("font", "16px/1.5 sans-serif"), map(&[
+            ("font-style", "normal"),
+            ("font-variant", "normal"),
+            ("font-weight", "normal"),
+            ("font-size", "16px"),
+            ("line-height", "1.5"),
+            ("font-family", "sans-serif"),
+        ]));
+        
+        assert_eq!(longhands("font", "italic bold 12px / 20px \"Helvetica Neue\", Arial"), map(&[
+            ("font-style", "italic"),
+            ("font-variant", "normal"),
+            ("font-weight", "bold"),
+            ("font-size", "12px"),
+            ("line-height", "20px"),
+            ("font-family", "\"Helvetica Neue\", Arial"),
+        ]));
+        
+        // Size and family are required
+        assert!(longhands("font", "bold sans-serif").is_empty());
+        assert!(longhands("font", "16px").is_empty());
+    }
+    
+    #[test]
+    fn test_other_properties_pass_through() {
+        let expanded = expand_all(vec![
+            CssProperty { name: "color".to_string(), value: "blue".to_string(), important: false, source_position: None },
+            CssProperty { name: "border".to_string(), value: "2px solid".to_string(), important: true, source_position: None },
+        ]);
+        
+        let names: Vec<&str> = expanded.iter().map(|property| property.name.as_str()).collect();
+        assert_eq!(names, vec!["color", "border-width", "border-style", "border-color"]);
+        assert!(expanded[1..].iter().all(|property| property.important));
+    }
+}
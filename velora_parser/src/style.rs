@@ -0,0 +1,374 @@
+//! Computed style resolution for the Velora web engine
+
+use std::collections::HashMap;
+use velora_core::{Color, CssUnit, NodeId, Size, CssValue, LengthContext};
+use velora_core::utils::css;
+use velora_dom::{Document, Node};
+use crate::{cascade, shorthand};
+use crate::css::{CssProperty, CssRule};
+use crate::inline_style::InlineStyle;
+
+/// Font size of the root element when nothing overrides it
+pub const DEFAULT_FONT_SIZE: f32 = 16.0;
+
+/// Properties whose value passes from parent to child unless the child declares its own
+pub const INHERITED_PROPERTIES: &[&str] = &[
+    "color", "cursor", "direction", "font-family", "font-size", "font-style", "font-variant",
+    "font-weight", "letter-spacing", "line-height", "list-style-type", "text-align",
+    "text-indent", "text-transform", "visibility", "white-space", "word-spacing",
+];
+
+/// Initial values of the inherited properties the engine knows about
+fn initial_properties() -> HashMap<String, CssValue> {
+    HashMap::from([
+        ("color".to_string(), CssValue::Color(Color::black())),
+        ("font-size".to_string(), CssValue::Length(DEFAULT_FONT_SIZE, CssUnit::Px)),
+    ])
+}
+
+/// Resolved style values for a single node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComputedStyle {
+    /// Font size in pixels
+    pub font_size: f32,
+}
+
+impl ComputedStyle {
+    /// Pick the resolved values out of a node's computed properties
+    pub fn from_properties(properties: &HashMap<String, CssValue>) -> Self {
+        let font_size = match properties.get("font-size") {
+            Some(CssValue::Length(size, CssUnit::Px)) => *size,
+            _ => DEFAULT_FONT_SIZE,
+        };
+        Self { font_size }
+    }
+}
+
+impl Default for ComputedStyle {
+    fn default() -> Self {
+        Self { font_size: DEFAULT_FONT_SIZE }
+    }
+}
+
+/// Computes styles for every node of a document
+#[derive(Debug, Clone)]
+pub struct StyleEngine {
+    /// Viewport size used for vw/vh units
+    viewport: Size,
+}
+
+impl StyleEngine {
+    /// Create a style engine for the given viewport
+    pub fn new(viewport: Size) -> Self {
+        Self { viewport }
+    }
+    
+    /// Compute styles for all nodes reachable from the document root
+    ///
+    /// Values come from `computed_properties_all`, so `rules` and inline styles cascade the
+    /// same way for both.
+    pub fn compute(&self, document: &Document, rules: &[CssRule]) -> HashMap<NodeId, ComputedStyle> {
+        self.computed_properties_all(document, rules)
+            .into_iter()
+            .map(|(node_id, properties)| (node_id, ComputedStyle::from_properties(&properties)))
+            .collect()
+    }
+    
+    /// Compute every declared or inherited property of a node, like `getComputedStyle`
+    ///
+    /// Declarations come from `rules` and the node's inline style, which beats normal rule
+    /// declarations. Inherited properties take the parent's value unless declared, and
+    /// `inherit` copies the parent's value for any property. Relative lengths resolve to
+    /// pixels and colors to `CssValue::Color`; other values are kept as keywords.
+    pub fn computed_properties(&self, document: &Document, rules: &[CssRule], node_id: NodeId) -> HashMap<String, CssValue> {
+        let tree = document.get_dom_tree();
+        let mut chain = Vec::new();
+        let mut current = tree.get_node(node_id).ok();
+        while let Some(node) = current {
+            chain.push(node);
+            current = node.parent_id.and_then(|parent_id| tree.get_node(parent_id).ok());
+        }
+        
+        let mut computed = initial_properties();
+        let mut root_font_size = None;
+        for node in chain.into_iter().rev() {
+            computed = self.compute_properties_of(document, rules, node, &computed, &mut root_font_size);
+        }
+        
+        computed
+    }
+    
+    /// Compute the properties of every node reachable from the document root
+    ///
+    /// Gives the same values as `computed_properties` for each node, but cascades every
+    /// node once, top-down, instead of once per descendant.
+    pub fn computed_properties_all(&self, document: &Document, rules: &[CssRule]) -> HashMap<NodeId, HashMap<String, CssValue>> {
+        let tree = document.get_dom_tree();
+        let mut computed = HashMap::new();
+        let Some(root) = tree.get_root() else {
+            return computed;
+        };
+        
+        let initial = initial_properties();
+        let mut root_font_size = None;
+        let mut stack = vec![(root, None)];
+        while let Some((node, parent_id)) = stack.pop() {
+            let parent = parent_id.and_then(|id| computed.get(&id)).unwrap_or(&initial);
+            let properties = self.compute_properties_of(document, rules, node, parent, &mut root_font_size);
+            computed.insert(node.id, properties);
+            
+            for &child_id in node.child_ids.iter().rev() {
+                if let Ok(child) = tree.get_node(child_id) {
+                    stack.push((child, Some(node.id)));
+                }
+            }
+        }
+        
+        computed
+    }
+    
+    /// Compute one node's properties from its declarations and its parent's computed properties
+    ///
+    /// `root_font_size` is filled in from the first node computed, which must be the root.
+    fn compute_properties_of(
+        &self,
+        document: &Document,
+        rules: &[CssRule],
+        node: &Node,
+        parent: &HashMap<String, CssValue>,
+        root_font_size: &mut Option<f32>,
+    ) -> HashMap<String, CssValue> {
+        let mut computed: HashMap<String, CssValue> = parent.iter()
+            .filter(|(name, _)| INHERITED_PROPERTIES.contains(&name.as_str()))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        
+        let parent_font_size = match parent.get("font-size") {
+            Some(CssValue::Length(size, CssUnit::Px)) => *size,
+            _ => DEFAULT_FONT_SIZE,
+        };
+        let declared = self.declared_properties(document, rules, node);
+        
+        let mut font_size = parent_font_size;
+        if let Some(property) = declared.get("font-size") {
+            let root = root_font_size.unwrap_or(DEFAULT_FONT_SIZE);
+            if let Some(size) = self.resolve_font_size(&property.value, parent_font_size, root) {
+                font_size = size;
+            }
+        }
+        computed.insert("font-size".to_string(), CssValue::Length(font_size, CssUnit::Px));
+        let root_font_size = *root_font_size.get_or_insert(font_size);
+        
+        for (name, property) in declared {
+            if name == "font-size" {
+                continue;
+            }
+            let value = property.value.trim();
+            match value.to_ascii_lowercase().as_str() {
+                "inherit" => match parent.get(&name) {
+                    Some(inherited) => computed.insert(name, inherited.clone()),
+                    None => computed.remove(&name),
+                },
+                "initial" => computed.remove(&name),
+                _ => computed.insert(name.clone(), self.computed_value(&name, value, font_size, root_font_size)),
+            };
+        }
+        
+        computed
+    }
+    
+    /// Cascade a node's matching rules and inline style into its declared properties
+    fn declared_properties(&self, document: &Document, rules: &[CssRule], node: &Node) -> HashMap<String, CssProperty> {
+        let tree = document.get_dom_tree();
+        let mut declared = cascade::cascade_rules(rules, tree, node.id);
+        
+        let element = node.element_id.and_then(|id| tree.get_element(id).ok());
+        if let Some(element) = element {
+            for property in shorthand::expand_all(element.style_properties()) {
+                // Only `!important` rules beat the inline style
+                let keep_rule = declared.get(&property.name)
+                    .is_some_and(|rule| rule.important && !property.important);
+                if !keep_rule {
+                    declared.insert(property.name.clone(), property);
+                }
+            }
+        }
+        
+        declared
+    }
+    
+    /// Resolve a declared value to its computed form
+    fn computed_value(&self, name: &str, value: &str, font_size: f32, root_font_size: f32) -> CssValue {
+        let color = || css::parse_color(value).filter(|parsed| matches!(parsed, CssValue::Color(_)));
+        if name.ends_with("color") {
+            if let Some(color) = color() {
+                return color;
+            }
+        }
+        
+        let context = LengthContext {
+            viewport: self.viewport,
+            font_size,
+            root_font_size,
+            percentage_base: None,
+        };
+        match css::parse_length(value) {
+            Some(length @ CssValue::Length(..)) => CssValue::Length(css::resolve_length(&length, &context), CssUnit::Px),
+            Some(number @ (CssValue::Number(_) | CssValue::Percentage(_))) => number,
+            _ => color().unwrap_or_else(|| CssValue::Keyword(value.to_string())),
+        }
+    }
+    
+    /// Resolve a `font-size` value; em and percentages are relative to the parent font size
+    fn resolve_font_size(&self, value: &str, parent_font_size: f32, root_font_size: f32) -> Option<f32> {
+        let value = value.trim().to_ascii_lowercase();
+        
+        let keyword = match value.as_str() {
+            "xx-small" => Some(DEFAULT_FONT_SIZE * 3.0 / 5.0),
+            "x-small" => Some(DEFAULT_FONT_SIZE * 3.0 / 4.0),
+            "small" => Some(DEFAULT_FONT_SIZE * 8.0 / 9.0),
+            "medium" => Some(DEFAULT_FONT_SIZE),
+            "large" => Some(DEFAULT_FONT_SIZE * 6.0 / 5.0),
+            "x-large" => Some(DEFAULT_FONT_SIZE * 3.0 / 2.0),
+            "xx-large" => Some(DEFAULT_FONT_SIZE * 2.0),
+            "smaller" => Some(parent_font_size / 1.2),
+            "larger" => Some(parent_font_size * 1.2),
+            "inherit" => Some(parent_font_size),
+            _ => None,
+        };
+        if keyword.is_some() {
+            return keyword;
+        }
+        
+        let length = css::parse_length(&value)?;
+        if matches!(length, CssValue::Keyword(_)) {
+            return None;
+        }
+        
+        let context = LengthContext {
+            viewport: self.viewport,
+            font_size: parent_font_size,
+            root_font_size,
+            percentage_base: Some(parent_font_size),
+        };
+        let size = css::resolve_length(&length, &context);
+        
+        // Negative font sizes are invalid
+        (size >= 0.0).then_some(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::html::HtmlParser;
+    
+    fn font_size_of(document: &Document, styles: &HashMap<NodeId, ComputedStyle>, tag: &str) -> f32 {
+        let node = document.get_elements_by_tag_name(tag)[0];
+        styles[&node.id].font_size
+    }
+    
+    #[test]
+    fn test_em_font_size_uses_parent() {
+        let html = r#"<div style="font-size: 20px"><p style="font-size: 2em">Big <span>text</span></p><em>Plain</em></div>"#;
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        let styles = StyleEngine::new(Size::new(800.0, 600.0)).compute(&document, &[]);
+        
+        // The body has no declaration and keeps the default size
+        assert_eq!(font_size_of(&document, &styles, "body"), DEFAULT_FONT_SIZE);
+        assert_eq!(font_size_of(&document, &styles, "div"), 20.0);
+        assert_eq!(font_size_of(&document, &styles, "p"), 40.0);
+        
+        // Children without declarations inherit
+        assert_eq!(font_size_of(&document, &styles, "span"), 40.0);
+        assert_eq!(font_size_of(&document, &styles, "em"), 20.0);
+    }
+    
+    #[test]
+    fn test_relative_font_size_units() {
+        let html = r#"<div style="font-size: 50%"><p style="font-size: 2rem"><span style="font-size: larger">x</span></p><b style="font-size: 5vw">y</b></div>"#;
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        let styles = StyleEngine::new(Size::new(400.0, 300.0)).compute(&document, &[]);
+        
+        assert_eq!(font_size_of(&document, &styles, "div"), 8.0);
+        assert_eq!(font_size_of(&document, &styles, "p"), 32.0);
+        assert!((font_size_of(&document, &styles, "span") - 38.4).abs() < 0.001);
+        assert_eq!(font_size_of(&document, &styles, "b"), 20.0);
+    }
+    
+    #[test]
+    fn test_computed_properties_inherit() {
+        let html = r#"<div style="color: #336699; margin: 2em; font-size: 10px"><p style="padding: 1rem">Hi</p></div>"#;
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        let rules = crate::css::CssParser::new().parse_css("p { color: red !important; width: 50% } div { color: blue }").unwrap();
+        let engine = StyleEngine::new(Size::new(800.0, 600.0));
+        let computed = |tag: &str, rules: &[CssRule]| {
+            engine.computed_properties(&document, rules, document.get_elements_by_tag_name(tag)[0].id)
+        };
+        
+        let div = computed("div", &rules);
+        assert_eq!(div["color"], CssValue::Color(Color::rgb(0x33, 0x66, 0x99)));
+        assert_eq!(div["margin"], CssValue::Length(20.0, CssUnit::Px));
+        
+        // Color and font size pass down; margin does not
+        let paragraph = computed("p", &[]);
+        assert_eq!(paragraph["color"], CssValue::Color(Color::rgb(0x33, 0x66, 0x99)));
+        assert_eq!(paragraph["font-size"], CssValue::Length(10.0, CssUnit::Px));
+        assert!(!paragraph.contains_key("margin"));
+        assert_eq!(paragraph["padding"], CssValue::Length(DEFAULT_FONT_SIZE, CssUnit::Px));
+        
+        // An important rule beats the inherited value, and percentages stay relative
+        let paragraph = computed("p", &rules);
+        assert_eq!(paragraph["color"], CssValue::Color(Color::rgb(255, 0, 0)));
+        assert_eq!(paragraph["width"], CssValue::Percentage(50.0));
+    }
+    
+    #[test]
+    fn test_computed_properties_all_matches_single_node() {
+        let html = r#"<div style="font-size: 10px; color: #336699"><p style="padding: 2em">Hi <em>there</em></p></div>"#;
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        let rules = crate::css::CssParser::new().parse_css("div p { margin: 1rem } em { color: inherit }").unwrap();
+        let engine = StyleEngine::new(Size::new(800.0, 600.0));
+        let all = engine.computed_properties_all(&document, &rules);
+        
+        for node in document.get_dom_tree().descendants(document.get_dom_tree().get_root().unwrap().id) {
+            assert_eq!(all[&node.id], engine.computed_properties(&document, &rules, node.id));
+        }
+        let paragraph = document.get_elements_by_tag_name("p")[0].id;
+        assert_eq!(all[&paragraph]["padding"], CssValue::Length(20.0, CssUnit::Px));
+    }
+    
+    #[test]
+    fn test_descendant_rule_does_not_style_ancestor() {
+        let html = r#"<div class="hero"><h1>Title</h1></div>"#;
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        let rules = crate::css::CssParser::new().parse_css(".hero h1 { color: red; margin: 4px }").unwrap();
+        let engine = StyleEngine::new(Size::new(800.0, 600.0));
+        let computed = |tag: &str| engine.computed_properties(&document, &rules, document.get_elements_by_tag_name(tag)[0].id);
+        
+        let hero = computed("div");
+        assert_eq!(hero["color"], CssValue::Color(Color::black()));
+        assert!(!hero.contains_key("margin"));
+        assert_eq!(computed("h1")["color"], CssValue::Color(Color::rgb(255, 0, 0)));
+    }
+    
+    #[test]
+    fn test_font_shorthand_sets_font_size() {
+        let html = r#"<div style="font: bold 24px/1.2 serif"><p style="font-size: 50%">x</p></div>"#;
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        let styles = StyleEngine::new(Size::new(800.0, 600.0)).compute(&document, &[]);
+        
+        assert_eq!(font_size_of(&document, &styles, "div"), 24.0);
+        assert_eq!(font_size_of(&document, &styles, "p"), 12.0);
+    }
+    
+    #[test]
+    fn test_stylesheet_font_size_applies() {
+        let html = r#"<div style="font-size: 10px"><p>Big</p></div>"#;
+        let document = HtmlParser::new().parse_html(html).unwrap();
+        let rules = crate::css::CssParser::new().parse_css("p { font-size: 2em }").unwrap();
+        let styles = StyleEngine::new(Size::new(800.0, 600.0)).compute(&document, &rules);
+        
+        assert_eq!(font_size_of(&document, &styles, "p"), 20.0);
+    }
+}
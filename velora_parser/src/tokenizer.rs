@@ -1,6 +1,11 @@
 //! Tokenizer for the Velora web engine
 
-use velora_core::VeloraResult;
+use velora_core::{VeloraResult, VeloraError};
+use velora_core::error::{CssSourcePosition, ParserError};
+use std::collections::VecDeque;
+
+/// Elements whose content is raw text rather than markup
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style", "textarea", "title"];
 
 /// Token types for HTML and CSS
 #[derive(Debug, Clone, PartialEq)]
@@ -23,6 +28,9 @@ pub enum Token {
     /// HTML comment
     Comment(String),
     
+    /// Processing instruction such as `<?xml version="1.0"?>`, as target and data
+    ProcessingInstruction(String, String),
+    
     /// CSS rule start
     CssRuleStart,
     
@@ -37,6 +45,10 @@ pub enum Token {
 }
 
 /// Tokenizer for parsing HTML and CSS
+///
+/// Tag and attribute names are normalized to ASCII lowercase; the HTML parser restores
+/// the case of SVG attributes such as `viewBox`. A `TagStart` or
+/// `SelfClosingTag` token is followed by one `Attribute` token per attribute.
 #[derive(Debug)]
 pub struct Tokenizer {
     /// Input source
@@ -44,6 +56,12 @@ pub struct Tokenizer {
     
     /// Current position
     position: usize,
+    
+    /// Tokens produced by the last tag but not yet returned
+    pending: VecDeque<Token>,
+    
+    /// Raw text element whose content is being read
+    raw_text_tag: Option<String>,
 }
 
 impl Tokenizer {
@@ -52,25 +70,266 @@ impl Tokenizer {
         Self {
             source,
             position: 0,
+            pending: VecDeque::new(),
+            raw_text_tag: None,
         }
     }
     
     /// Get the next token
     pub fn next_token(&mut self) -> VeloraResult<Token> {
-        // TODO: Implement actual tokenization
-        if self.position >= self.source.len() {
-            return Ok(Token::Eof);
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                return Ok(token);
+            }
+            
+            if self.position >= self.source.len() {
+                return Ok(Token::Eof);
+            }
+            
+            if let Some(token) = self.read_token()? {
+                return Ok(token);
+            }
         }
-        
-        // For now, return a simple text token
-        let token = Token::Text(self.source[self.position..].chars().next().unwrap().to_string());
-        self.position += 1;
-        
-        Ok(token)
     }
     
     /// Check if there are more tokens
     pub fn has_more(&self) -> bool {
-        self.position < self.source.len()
+        !self.pending.is_empty() || self.position < self.source.len()
+    }
+    
+    /// Read the token at the current position, or None if the markup there is skipped
+    fn read_token(&mut self) -> VeloraResult<Option<Token>> {
+        let rest = &self.source[self.position..];
+        
+        // Script and style content runs until the matching end tag
+        if let Some(tag) = self.raw_text_tag.take() {
+            let end = find_ascii_case_insensitive(rest, &format!("</{}", tag)).unwrap_or(rest.len());
+            self.position += end;
+            return Ok((end > 0).then(|| Token::Text(rest[..end].to_string())));
+        }
+        
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let (text, consumed) = match comment.find("-->") {
+                Some(end) => (&comment[..end], end + 7),
+                None => (comment, rest.len()),
+            };
+            self.position += consumed;
+            return Ok(Some(Token::Comment(text.to_string())));
+        }
+        
+        let bytes = rest.as_bytes();
+        let next = bytes.get(1).copied().unwrap_or(b' ');
+        
+        // DOCTYPE and other declarations are skipped
+        if bytes[0] == b'<' && next == b'!' {
+            self.position += rest.find('>').map_or(rest.len(), |end| end + 1);
+            return Ok(None);
+        }
+        
+        if let Some(instruction) = rest.strip_prefix("<?") {
+            let end = instruction.find('>').unwrap_or(instruction.len());
+            self.position += (end + 3).min(rest.len());
+            
+            let instruction = instruction[..end].strip_suffix('?').unwrap_or(&instruction[..end]);
+            let (target, data) = instruction.split_once(char::is_whitespace).unwrap_or((instruction, ""));
+            return Ok(Some(Token::ProcessingInstruction(target.to_string(), data.trim().to_string())));
+        }
+        
+        if bytes[0] == b'<' && next == b'/' {
+            let Some(end) = rest.find('>') else {
+                return Err(self.unexpected_eof("'>' to close the end tag", self.position));
+            };
+            self.position += end + 1;
+            let name = rest[2..end].trim().to_ascii_lowercase();
+            return Ok(Some(Token::TagEnd(name)));
+        }
+        
+        if bytes[0] == b'<' && next.is_ascii_alphabetic() {
+            return self.read_tag().map(Some);
+        }
+        
+        // Text runs until the next character that can start markup
+        let end = rest.char_indices()
+            .skip(1)
+            .find(|&(i, c)| c == '<' && rest[i + 1..].starts_with(|n: char| n.is_ascii_alphabetic() || matches!(n, '/' | '!' | '?')))
+            .map_or(rest.len(), |(i, _)| i);
+        self.position += end;
+        Ok(Some(Token::Text(rest[..end].to_string())))
+    }
+    
+    /// Read a start tag, queueing its attribute tokens
+    fn read_tag(&mut self) -> VeloraResult<Token> {
+        let rest = &self.source[self.position..];
+        let bytes = rest.as_bytes();
+        let mut index = 1;
+        
+        let name_end = rest[index..]
+            .find(|c: char| c.is_ascii_whitespace() || c == '/' || c == '>')
+            .map_or(rest.len(), |end| index + end);
+        let name = rest[index..name_end].to_ascii_lowercase();
+        index = name_end;
+        
+        let mut self_closing = false;
+        loop {
+            while index < bytes.len() && (bytes[index].is_ascii_whitespace() || bytes[index] == b'/') {
+                self_closing = bytes[index] == b'/';
+                index += 1;
+            }
+            if index >= bytes.len() {
+                return Err(self.unexpected_eof("'>' to close the tag", self.position));
+            }
+            if bytes[index] == b'>' {
+                index += 1;
+                break;
+            }
+            self_closing = false;
+            
+            let attr_end = rest[index..]
+                .find(|c: char| c.is_ascii_whitespace() || matches!(c, '=' | '>' | '/'))
+                .map_or(rest.len(), |end| index + end);
+            let attr_name = rest[index..attr_end].to_ascii_lowercase();
+            index = attr_end;
+            
+            while index < bytes.len() && bytes[index].is_ascii_whitespace() {
+                index += 1;
+            }
+            
+            let mut value = String::new();
+            if index < bytes.len() && bytes[index] == b'=' {
+                index += 1;
+                while index < bytes.len() && bytes[index].is_ascii_whitespace() {
+                    index += 1;
+                }
+                
+                if index < bytes.len() && (bytes[index] == b'"' || bytes[index] == b'\'') {
+                    let quote = bytes[index] as char;
+                    let Some(value_end) = rest[index + 1..].find(quote).map(|end| index + 1 + end) else {
+                        return Err(self.unexpected_eof(&format!("closing {} for the attribute value", quote), self.position + index));
+                    };
+                    value = rest[index + 1..value_end].to_string();
+                    index = (value_end + 1).min(rest.len());
+                } else {
+                    let value_end = rest[index..]
+                        .find(|c: char| c.is_ascii_whitespace() || c == '>')
+                        .map_or(rest.len(), |end| index + end);
+                    value = rest[index..value_end].to_string();
+                    index = value_end;
+                }
+            }
+            
+            self.pending.push_back(Token::Attribute(attr_name, value));
+        }
+        
+        self.position += index;
+        
+        if self_closing {
+            Ok(Token::SelfClosingTag(name))
+        } else {
+            if RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                self.raw_text_tag = Some(name.clone());
+            }
+            Ok(Token::TagStart(name))
+        }
+    }
+    
+    /// Error for input that ends in the middle of a construct starting at `offset`
+    fn unexpected_eof(&self, expected: &str, offset: usize) -> VeloraError {
+        VeloraError::Parser(ParserError::UnexpectedToken {
+            expected: expected.to_string(),
+            found: "end of input".to_string(),
+            position: CssSourcePosition::from_offset(&self.source, offset),
+        })
+    }
+}
+
+/// Find a needle in a haystack ignoring ASCII case
+fn find_ascii_case_insensitive(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.to_ascii_lowercase().find(&needle.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn tokenize(source: &str) -> Vec<Token> {
+        let mut tokenizer = Tokenizer::new(source.to_string());
+        let mut tokens = Vec::new();
+        loop {
+            match tokenizer.next_token().unwrap() {
+                Token::Eof => break,
+                token => tokens.push(token),
+            }
+        }
+        tokens
+    }
+    
+    #[test]
+    fn test_tokenize_tags_and_attributes() {
+        let tokens = tokenize("<!DOCTYPE html><DIV Class=\"a b\" id=main hidden>Hi<BR/></div><!-- note -->");
+        assert_eq!(tokens, vec![
+            Token::TagStart("div".to_string()),
+            Token::Attribute("class".to_string(), "a b".to_string()),
+            Token::Attribute("id".to_string(), "main".to_string()),
+            Token::Attribute("hidden".to_string(), String::new()),
+            Token::Text("Hi".to_string()),
+            Token::SelfClosingTag("br".to_string()),
+            Token::TagEnd("div".to_string()),
+            Token::Comment(" note ".to_string()),
+        ]);
+    }
+    
+    #[test]
+    fn test_tokenize_processing_instruction() {
+        let tokens = tokenize("<?xml version=\"1.0\"?><p>x</p><?php");
+        assert_eq!(tokens, vec![
+            Token::ProcessingInstruction("xml".to_string(), "version=\"1.0\"".to_string()),
+            Token::TagStart("p".to_string()),
+            Token::Text("x".to_string()),
+            Token::TagEnd("p".to_string()),
+            Token::ProcessingInstruction("php".to_string(), String::new()),
+        ]);
+    }
+    
+    #[test]
+    fn test_broken_tag_reports_position() {
+        let mut tokenizer = Tokenizer::new("<p>ok</p>\n  <div class=\"x".to_string());
+        let error = loop {
+            match tokenizer.next_token() {
+                Ok(Token::Eof) => panic!("expected an error"),
+                Ok(_) => continue,
+                Err(error) => break error,
+            }
+        };
+        
+        match &error {
+            VeloraError::Parser(ParserError::UnexpectedToken { expected, found, position }) => {
+                assert_eq!(expected, "closing \" for the attribute value");
+                assert_eq!(found, "end of input");
+                assert_eq!(*position, CssSourcePosition::new(2, 14));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert_eq!(
+            error.to_string(),
+            "Parser error: Unexpected end of input at line 2, column 14, expected closing \" for the attribute value"
+        );
+        
+        let mut tokenizer = Tokenizer::new("text <br".to_string());
+        assert_eq!(tokenizer.next_token().unwrap(), Token::Text("text ".to_string()));
+        assert!(matches!(
+            tokenizer.next_token(),
+            Err(VeloraError::Parser(ParserError::UnexpectedToken { position: CssSourcePosition { line: 1, column: 6, .. }, .. }))
+        ));
+    }
+    
+    #[test]
+    fn test_tokenize_raw_text() {
+        let tokens = tokenize("<script>if (a < b) { x(\"<p>\"); }</SCRIPT>1 < 2");
+        assert_eq!(tokens, vec![
+            Token::TagStart("script".to_string()),
+            Token::Text("if (a < b) { x(\"<p>\"); }".to_string()),
+            Token::TagEnd("script".to_string()),
+            Token::Text("1 < 2".to_string()),
+        ]);
     }
 }